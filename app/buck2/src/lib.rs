@@ -19,6 +19,7 @@ use buck2_client::commands::clean::CleanCommand;
 use buck2_client::commands::ctargets::ConfiguredTargetsCommand;
 use buck2_client::commands::expand_external_cell::ExpandExternalCellsCommand;
 use buck2_client::commands::explain::ExplainCommand;
+use buck2_client::commands::fetch::FetchCommand;
 use buck2_client::commands::help_env::HelpEnvCommand;
 use buck2_client::commands::init::InitCommand;
 use buck2_client::commands::install::InstallCommand;
@@ -36,6 +37,7 @@ use buck2_client::commands::status::StatusCommand;
 use buck2_client::commands::subscribe::SubscribeCommand;
 use buck2_client::commands::targets::TargetsCommand;
 use buck2_client::commands::test::TestCommand;
+use buck2_client::commands::ui::UiCommand;
 use buck2_client_ctx::argfiles::expand_argv;
 use buck2_client_ctx::client_ctx::BuckSubcommand;
 use buck2_client_ctx::client_ctx::ClientCommandContext;
@@ -343,6 +345,7 @@ pub(crate) enum CommandKind {
     Init(InitCommand),
     Explain(ExplainCommand),
     ExpandExternalCell(ExpandExternalCellsCommand),
+    Fetch(FetchCommand),
     Install(InstallCommand),
     Kill(KillCommand),
     Killall(KillallCommand),
@@ -374,6 +377,7 @@ pub(crate) enum CommandKind {
     Log(LogCommand),
     Lsp(LspCommand),
     Subscribe(SubscribeCommand),
+    Ui(UiCommand),
 }
 
 impl CommandKind {
@@ -539,6 +543,8 @@ impl CommandKind {
             CommandKind::Lsp(cmd) => command_ctx.exec(cmd, matches, events_ctx),
             CommandKind::Subscribe(cmd) => command_ctx.exec(cmd, matches, events_ctx),
             CommandKind::ExpandExternalCell(cmd) => command_ctx.exec(cmd, matches, events_ctx),
+            CommandKind::Fetch(cmd) => command_ctx.exec(cmd, matches, events_ctx),
+            CommandKind::Ui(cmd) => command_ctx.exec(cmd, matches, events_ctx),
         }
     }
 
@@ -583,6 +589,8 @@ impl CommandKind {
             CommandKind::Lsp(cmd) => cmd.logging_name(),
             CommandKind::Subscribe(cmd) => cmd.logging_name(),
             CommandKind::ExpandExternalCell(cmd) => cmd.logging_name(),
+            CommandKind::Fetch(cmd) => cmd.logging_name(),
+            CommandKind::Ui(cmd) => cmd.logging_name(),
         }
     }
 }
@@ -8,12 +8,16 @@
  * above-listed licenses.
  */
 
+pub(crate) mod archive;
 pub(crate) mod cas_artifact;
 pub(crate) mod copy;
 pub(crate) mod download_file;
+pub(crate) mod expand_template;
+pub(crate) mod extract;
 pub(crate) mod offline;
 pub(crate) mod run;
 pub(crate) mod symlinked_dir;
+pub(crate) mod util;
 pub(crate) mod write;
 pub(crate) mod write_json;
 pub(crate) mod write_macros;
@@ -0,0 +1,267 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use std::borrow::Cow;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use buck2_artifact::artifact::build_artifact::BuildArtifact;
+use buck2_build_api::actions::Action;
+use buck2_build_api::actions::ActionExecutionCtx;
+use buck2_build_api::actions::UnregisteredAction;
+use buck2_build_api::actions::box_slice_set::BoxSliceSet;
+use buck2_build_api::actions::execute::action_executor::ActionExecutionKind;
+use buck2_build_api::actions::execute::action_executor::ActionExecutionMetadata;
+use buck2_build_api::actions::execute::action_executor::ActionOutputs;
+use buck2_build_api::actions::execute::error::ExecuteError;
+use buck2_build_api::artifact_groups::ArtifactGroup;
+use buck2_build_api::interpreter::rule_defs::cmd_args::ArtifactPathMapper;
+use buck2_build_signals::env::WaitingData;
+use buck2_common::file_ops::metadata::FileDigestConfig;
+use buck2_core::category::CategoryRef;
+use buck2_core::cells::external::ArchiveFormat;
+use buck2_core::fs::buck_out_path::BuildArtifactPath;
+use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_error::BuckErrorContext;
+use buck2_execute::archive::ArchiveContents;
+use buck2_execute::artifact::fs::ExecutorFs;
+use buck2_execute::artifact_value::ArtifactValue;
+use buck2_execute::directory::INTERNER;
+use buck2_execute::entry::build_entry_from_disk;
+use buck2_execute::execute::blocking::IoRequest;
+use buck2_execute::execute::command_executor::ActionExecutionTimingData;
+use buck2_execute::materialize::materializer::DeclareArtifactPayload;
+use buck2_fs::paths::forward_rel_path::ForwardRelativePath;
+use dupe::Dupe;
+use gazebo::prelude::*;
+use indexmap::IndexMap;
+use indexmap::IndexSet;
+use starlark::values::OwnedFrozenValue;
+
+use crate::actions::impls::util::resolve_input;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(tag = Input)]
+pub(crate) enum ArchiveActionError {
+    #[error("Exactly one output must be specified for an {0} action, got {1}")]
+    WrongNumberOfOutputs(&'static str, usize),
+    #[error("Only artifact inputs are supported in archive actions, got {0}")]
+    UnsupportedInput(ArtifactGroup),
+    #[error("Output `{0}` of an {1} action cannot use a content-based path")]
+    ContentBasedPath(BuildArtifactPath, &'static str),
+}
+
+/// Checks the outputs of an `archive` or `extract` action, which write their single output in
+/// place and so cannot use content-based paths.
+pub(crate) fn single_output(
+    outputs: IndexSet<BuildArtifact>,
+    action: &'static str,
+) -> buck2_error::Result<BoxSliceSet<BuildArtifact>> {
+    if outputs.len() != 1 {
+        return Err(ArchiveActionError::WrongNumberOfOutputs(action, outputs.len()).into());
+    }
+    for output in &outputs {
+        if output.get_path().is_content_based_path() {
+            return Err(
+                ArchiveActionError::ContentBasedPath(output.get_path().dupe(), action).into(),
+            );
+        }
+    }
+    Ok(BoxSliceSet::from(outputs))
+}
+
+/// Hashes the output written to disk by `io` and declares it to the materializer.
+pub(crate) async fn write_output(
+    ctx: &mut dyn ActionExecutionCtx,
+    output: &BuildArtifact,
+    io: Box<dyn IoRequest>,
+) -> buck2_error::Result<ArtifactValue> {
+    ctx.blocking_executor()
+        .execute_io(io, ctx.cancellation_context())
+        .await?;
+
+    let path = ctx.fs().resolve_build(output.get_path(), None)?;
+    let (entry, _hashing_info) = build_entry_from_disk(
+        ctx.fs().fs().resolve(&path),
+        FileDigestConfig::build(ctx.digest_config().cas_digest_config()),
+        ctx.blocking_executor(),
+        ctx.fs().fs().root(),
+    )
+    .await?;
+    let entry = entry
+        .with_internal_error(|| format!("Action did not write its output `{path}`"))?
+        .map_dir(|dir| {
+            dir.fingerprint(ctx.digest_config().as_directory_serializer())
+                .shared(&*INTERNER)
+        });
+    let value = ArtifactValue::new(entry, None);
+
+    ctx.materializer()
+        .declare_existing(vec![DeclareArtifactPayload {
+            path,
+            artifact: value.dupe(),
+            persist_full_directory_structure: false,
+        }])
+        .await?;
+    Ok(value)
+}
+
+#[derive(Allocative)]
+pub(crate) struct UnregisteredArchiveAction {
+    srcs: Vec<(ArtifactGroup, Box<ForwardRelativePath>)>,
+    format: ArchiveFormat,
+}
+
+impl UnregisteredArchiveAction {
+    pub(crate) fn new(
+        srcs: Vec<(ArtifactGroup, Box<ForwardRelativePath>)>,
+        format: ArchiveFormat,
+    ) -> buck2_error::Result<Self> {
+        for (src, _) in &srcs {
+            match src {
+                ArtifactGroup::Artifact(..) | ArtifactGroup::Promise(..) => {}
+                ArtifactGroup::TransitiveSetProjection(..) => {
+                    return Err(ArchiveActionError::UnsupportedInput(src.dupe()).into());
+                }
+            }
+        }
+        Ok(Self { srcs, format })
+    }
+}
+
+impl UnregisteredAction for UnregisteredArchiveAction {
+    fn register(
+        self: Box<Self>,
+        outputs: IndexSet<BuildArtifact>,
+        _starlark_data: Option<OwnedFrozenValue>,
+        _error_handler: Option<OwnedFrozenValue>,
+    ) -> buck2_error::Result<Box<dyn Action>> {
+        Ok(Box::new(ArchiveAction {
+            inputs: BoxSliceSet::from(
+                self.srcs
+                    .iter()
+                    .map(|(src, _)| src.dupe())
+                    .collect::<IndexSet<_>>(),
+            ),
+            srcs: self.srcs,
+            format: self.format,
+            outputs: single_output(outputs, "archive")?,
+        }))
+    }
+}
+
+#[derive(Debug, Allocative)]
+struct ArchiveAction {
+    inputs: BoxSliceSet<ArtifactGroup>,
+    srcs: Vec<(ArtifactGroup, Box<ForwardRelativePath>)>,
+    format: ArchiveFormat,
+    outputs: BoxSliceSet<BuildArtifact>,
+}
+
+impl ArchiveAction {
+    fn output(&self) -> &BuildArtifact {
+        self.outputs
+            .iter()
+            .next()
+            .expect("a single artifact by construction")
+    }
+}
+
+struct ArchiveIoRequest {
+    srcs: Vec<(ProjectRelativePathBuf, Box<ForwardRelativePath>)>,
+    output: ProjectRelativePathBuf,
+    format: ArchiveFormat,
+}
+
+impl IoRequest for ArchiveIoRequest {
+    fn execute(self: Box<Self>, project_fs: &ProjectRoot) -> buck2_error::Result<()> {
+        let mut contents = ArchiveContents::new();
+        for (src, dest) in &self.srcs {
+            contents.add(&project_fs.resolve(src), dest)?;
+        }
+        contents.write(&project_fs.resolve(&self.output), self.format)
+    }
+}
+
+#[async_trait]
+impl Action for ArchiveAction {
+    fn kind(&self) -> buck2_data::ActionKind {
+        buck2_data::ActionKind::Archive
+    }
+
+    fn inputs(&self) -> buck2_error::Result<Cow<'_, [ArtifactGroup]>> {
+        Ok(Cow::Borrowed(self.inputs.as_slice()))
+    }
+
+    fn outputs(&self) -> Cow<'_, [BuildArtifact]> {
+        Cow::Borrowed(self.outputs.as_slice())
+    }
+
+    fn first_output(&self) -> &BuildArtifact {
+        self.output()
+    }
+
+    fn category(&self) -> CategoryRef<'_> {
+        CategoryRef::unchecked_new("archive")
+    }
+
+    fn identifier(&self) -> Option<&str> {
+        Some(self.output().get_path().path().as_str())
+    }
+
+    fn aquery_attributes(
+        &self,
+        _fs: &ExecutorFs,
+        _artifact_path_mapping: &dyn ArtifactPathMapper,
+    ) -> IndexMap<String, String> {
+        let mut attrs = IndexMap::new();
+        attrs.insert("format".to_owned(), self.format.to_string());
+        attrs
+    }
+
+    async fn execute(
+        &self,
+        ctx: &mut dyn ActionExecutionCtx,
+        waiting_data: WaitingData,
+    ) -> Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError> {
+        let srcs = self
+            .srcs
+            .iter()
+            .map(|(group, dest)| Ok((resolve_input(ctx, group)?, dest.clone())))
+            .collect::<buck2_error::Result<Vec<_>>>()?;
+        ctx.materializer()
+            .ensure_materialized(srcs.map(|(path, _)| path.clone()))
+            .await?;
+
+        ctx.cleanup_outputs().await?;
+        let output = ctx.fs().resolve_build(self.output().get_path(), None)?;
+        let value = write_output(
+            ctx,
+            self.output(),
+            Box::new(ArchiveIoRequest {
+                srcs,
+                output,
+                format: self.format,
+            }),
+        )
+        .await?;
+
+        Ok((
+            ActionOutputs::from_single(self.output().get_path().dupe(), value),
+            ActionExecutionMetadata {
+                execution_kind: ActionExecutionKind::Simple,
+                timing: ActionExecutionTimingData::default(),
+                input_files_bytes: None,
+                waiting_data,
+            },
+        ))
+    }
+}
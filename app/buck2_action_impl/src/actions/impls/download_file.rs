@@ -15,6 +15,7 @@ use allocative::Allocative;
 use async_trait::async_trait;
 use buck2_artifact::artifact::build_artifact::BuildArtifact;
 use buck2_build_api::actions::Action;
+use buck2_build_api::actions::ActionDownload;
 use buck2_build_api::actions::ActionExecutionCtx;
 use buck2_build_api::actions::UnregisteredAction;
 use buck2_build_api::actions::execute::action_executor::ActionExecutionKind;
@@ -253,6 +254,13 @@ impl Action for DownloadFileAction {
             .map(|o| o.get_path().path().as_str())
     }
 
+    fn download(&self) -> Option<ActionDownload<'_>> {
+        Some(ActionDownload {
+            url: &self.inner.url,
+            checksum: &self.inner.checksum,
+        })
+    }
+
     async fn execute(
         &self,
         ctx: &mut dyn ActionExecutionCtx,
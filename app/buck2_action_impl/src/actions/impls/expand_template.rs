@@ -0,0 +1,301 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use std::borrow::Cow;
+use std::slice;
+use std::time::Instant;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use buck2_artifact::artifact::build_artifact::BuildArtifact;
+use buck2_build_api::actions::Action;
+use buck2_build_api::actions::ActionExecutionCtx;
+use buck2_build_api::actions::UnregisteredAction;
+use buck2_build_api::actions::box_slice_set::BoxSliceSet;
+use buck2_build_api::actions::execute::action_executor::ActionExecutionKind;
+use buck2_build_api::actions::execute::action_executor::ActionExecutionMetadata;
+use buck2_build_api::actions::execute::action_executor::ActionOutputs;
+use buck2_build_api::actions::execute::error::ExecuteError;
+use buck2_build_api::artifact_groups::ArtifactGroup;
+use buck2_build_api::interpreter::rule_defs::cmd_args::ArtifactPathMapper;
+use buck2_build_signals::env::WaitingData;
+use buck2_common::file_ops::metadata::TrackedFileDigest;
+use buck2_core::category::CategoryRef;
+use buck2_core::content_hash::ContentBasedPathHash;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_error::BuckErrorContext;
+use buck2_execute::artifact::fs::ExecutorFs;
+use buck2_execute::execute::command_executor::ActionExecutionTimingData;
+use buck2_execute::materialize::materializer::WriteRequest;
+use buck2_fs::fs_util;
+use dupe::Dupe;
+use indexmap::IndexMap;
+use indexmap::IndexSet;
+use indexmap::indexmap;
+use starlark::values::OwnedFrozenValue;
+
+use crate::actions::impls::util::materialized_input;
+use crate::actions::impls::util::resolve_input;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(tag = Input)]
+enum ExpandTemplateError {
+    #[error("Exactly one output must be specified for an expand_template action, got {0}")]
+    WrongNumberOfOutputs(usize),
+    #[error("Only artifact inputs are supported in expand_template actions, got {0}")]
+    UnsupportedInput(ArtifactGroup),
+    #[error("Template `{0}` is not valid UTF-8")]
+    TemplateNotUtf8(ProjectRelativePathBuf),
+}
+
+/// The value a key in the template is replaced with.
+#[derive(Debug, Allocative)]
+pub(crate) enum Substitution {
+    Literal(String),
+    /// Replaced with the path of the artifact, relative to the project root.
+    Artifact(ArtifactGroup),
+}
+
+#[derive(Debug, Allocative)]
+pub(crate) struct UnregisteredExpandTemplateAction {
+    template: ArtifactGroup,
+    substitutions: Vec<(String, Substitution)>,
+    is_executable: bool,
+}
+
+impl UnregisteredExpandTemplateAction {
+    pub(crate) fn new(
+        template: ArtifactGroup,
+        substitutions: Vec<(String, Substitution)>,
+        is_executable: bool,
+    ) -> buck2_error::Result<Self> {
+        let artifacts = substitutions.iter().filter_map(|(_, s)| match s {
+            Substitution::Literal(_) => None,
+            Substitution::Artifact(a) => Some(a),
+        });
+        for input in std::iter::once(&template).chain(artifacts) {
+            match input {
+                ArtifactGroup::Artifact(..) | ArtifactGroup::Promise(..) => {}
+                ArtifactGroup::TransitiveSetProjection(..) => {
+                    return Err(ExpandTemplateError::UnsupportedInput(input.dupe()).into());
+                }
+            }
+        }
+        Ok(Self {
+            template,
+            substitutions,
+            is_executable,
+        })
+    }
+}
+
+impl UnregisteredAction for UnregisteredExpandTemplateAction {
+    fn register(
+        self: Box<Self>,
+        outputs: IndexSet<BuildArtifact>,
+        _starlark_data: Option<OwnedFrozenValue>,
+        _error_handler: Option<OwnedFrozenValue>,
+    ) -> buck2_error::Result<Box<dyn Action>> {
+        if outputs.len() != 1 {
+            return Err(ExpandTemplateError::WrongNumberOfOutputs(outputs.len()).into());
+        }
+        let output = outputs.into_iter().next().unwrap();
+
+        // The template comes first, followed by every artifact whose path is substituted.
+        let mut inputs = IndexSet::new();
+        inputs.insert(self.template.dupe());
+        for (_, substitution) in &self.substitutions {
+            if let Substitution::Artifact(artifact) = substitution {
+                inputs.insert(artifact.dupe());
+            }
+        }
+
+        Ok(Box::new(ExpandTemplateAction {
+            inputs: BoxSliceSet::from(inputs),
+            output,
+            inner: *self,
+        }))
+    }
+}
+
+#[derive(Debug, Allocative)]
+struct ExpandTemplateAction {
+    inputs: BoxSliceSet<ArtifactGroup>,
+    output: BuildArtifact,
+    inner: UnregisteredExpandTemplateAction,
+}
+
+/// Replaces the keys of `substitutions` in a single pass over `template`. Where several keys
+/// match at the same position, the first one in `substitutions` wins, and replaced text is never
+/// scanned again.
+fn expand(template: &str, substitutions: &[(&str, Cow<'_, str>)]) -> String {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+    'next: while let Some(c) = rest.chars().next() {
+        for (key, value) in substitutions {
+            if let Some(after) = rest.strip_prefix(key).filter(|_| !key.is_empty()) {
+                expanded.push_str(value);
+                rest = after;
+                continue 'next;
+            }
+        }
+        expanded.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    expanded
+}
+
+#[async_trait]
+impl Action for ExpandTemplateAction {
+    fn kind(&self) -> buck2_data::ActionKind {
+        buck2_data::ActionKind::ExpandTemplate
+    }
+
+    fn inputs(&self) -> buck2_error::Result<Cow<'_, [ArtifactGroup]>> {
+        Ok(Cow::Borrowed(self.inputs.as_slice()))
+    }
+
+    fn outputs(&self) -> Cow<'_, [BuildArtifact]> {
+        Cow::Borrowed(slice::from_ref(&self.output))
+    }
+
+    fn first_output(&self) -> &BuildArtifact {
+        &self.output
+    }
+
+    fn category(&self) -> CategoryRef<'_> {
+        CategoryRef::unchecked_new("expand_template")
+    }
+
+    fn identifier(&self) -> Option<&str> {
+        Some(self.output.get_path().path().as_str())
+    }
+
+    fn aquery_attributes(
+        &self,
+        _fs: &ExecutorFs,
+        _artifact_path_mapping: &dyn ArtifactPathMapper,
+    ) -> IndexMap<String, String> {
+        let substitutions = self
+            .inner
+            .substitutions
+            .iter()
+            .map(|(key, value)| match value {
+                Substitution::Literal(literal) => format!("{key}={literal}"),
+                Substitution::Artifact(artifact) => format!("{key}={artifact}"),
+            })
+            .collect::<Vec<_>>();
+        indexmap! {
+            "template".to_owned() => self.inner.template.to_string(),
+            "substitutions".to_owned() => substitutions.join("\n"),
+            "is_executable".to_owned() => self.inner.is_executable.to_string(),
+        }
+    }
+
+    async fn execute(
+        &self,
+        ctx: &mut dyn ActionExecutionCtx,
+        waiting_data: WaitingData,
+    ) -> Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError> {
+        let template = materialized_input(ctx, &self.inner.template).await?;
+        let substitutions = self
+            .inner
+            .substitutions
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    Substitution::Literal(literal) => Cow::Borrowed(literal.as_str()),
+                    Substitution::Artifact(artifact) => {
+                        Cow::Owned(resolve_input(&*ctx, artifact)?.to_string())
+                    }
+                };
+                buck2_error::Ok((key.as_str(), value))
+            })
+            .collect::<buck2_error::Result<Vec<_>>>()?;
+
+        let fs = ctx.fs();
+        let mut execution_start = None;
+
+        let value = ctx
+            .materializer()
+            .declare_write(Box::new(|| {
+                execution_start = Some(Instant::now());
+                let contents = fs_util::read(fs.fs().resolve(&template))?;
+                let contents = String::from_utf8(contents)
+                    .map_err(|_| ExpandTemplateError::TemplateNotUtf8(template.clone()))?;
+                let content = expand(&contents, &substitutions).into_bytes();
+                let path = fs.resolve_build(
+                    self.output.get_path(),
+                    if self.output.get_path().is_content_based_path() {
+                        let digest = TrackedFileDigest::from_content(
+                            &content,
+                            ctx.digest_config().cas_digest_config(),
+                        );
+                        Some(ContentBasedPathHash::new(digest.raw_digest().as_bytes())?)
+                    } else {
+                        None
+                    }
+                    .as_ref(),
+                )?;
+                Ok(vec![WriteRequest {
+                    path,
+                    content,
+                    is_executable: self.inner.is_executable,
+                }])
+            }))
+            .await?
+            .into_iter()
+            .next()
+            .buck_error_context("Write did not execute")?;
+
+        let wall_time = Instant::now()
+            - execution_start.buck_error_context("Action did not set execution_start")?;
+
+        Ok((
+            ActionOutputs::new(indexmap![self.output.get_path().dupe() => value]),
+            ActionExecutionMetadata {
+                execution_kind: ActionExecutionKind::Simple,
+                timing: ActionExecutionTimingData { wall_time },
+                input_files_bytes: None,
+                waiting_data,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::expand;
+
+    #[test]
+    fn expands_in_a_single_pass() {
+        let substitutions = [
+            ("{NAME}", Cow::Borrowed("{GREETING}, world")),
+            ("{GREETING}", Cow::Borrowed("hello")),
+            ("", Cow::Borrowed("ignored")),
+        ];
+        assert_eq!(
+            "say {GREETING}, world twice: {GREETING}, world",
+            expand("say {NAME} twice: {NAME}", &substitutions)
+        );
+        assert_eq!("hello", expand("{GREETING}", &substitutions));
+    }
+
+    #[test]
+    fn first_matching_key_wins() {
+        let short_first = [("{A}", Cow::Borrowed("x")), ("{A}{B}", Cow::Borrowed("y"))];
+        assert_eq!("x{B}", expand("{A}{B}", &short_first));
+        let long_first = [("{A}{B}", Cow::Borrowed("y")), ("{A}", Cow::Borrowed("x"))];
+        assert_eq!("yx", expand("{A}{B}{A}", &long_first));
+        assert_eq!("ünïcødé x", expand("ünïcødé {A}", &long_first));
+    }
+}
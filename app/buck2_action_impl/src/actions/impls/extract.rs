@@ -0,0 +1,220 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use buck2_artifact::artifact::build_artifact::BuildArtifact;
+use buck2_build_api::actions::Action;
+use buck2_build_api::actions::ActionExecutionCtx;
+use buck2_build_api::actions::UnregisteredAction;
+use buck2_build_api::actions::box_slice_set::BoxSliceSet;
+use buck2_build_api::actions::execute::action_executor::ActionExecutionKind;
+use buck2_build_api::actions::execute::action_executor::ActionExecutionMetadata;
+use buck2_build_api::actions::execute::action_executor::ActionOutputs;
+use buck2_build_api::actions::execute::error::ExecuteError;
+use buck2_build_api::artifact_groups::ArtifactGroup;
+use buck2_build_api::interpreter::rule_defs::cmd_args::ArtifactPathMapper;
+use buck2_build_signals::env::WaitingData;
+use buck2_core::category::CategoryRef;
+use buck2_core::cells::external::ArchiveFormat;
+use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_execute::archive::extract;
+use buck2_execute::artifact::fs::ExecutorFs;
+use buck2_execute::execute::blocking::IoRequest;
+use buck2_execute::execute::command_executor::ActionExecutionTimingData;
+use buck2_fs::fs_util;
+use dupe::Dupe;
+use indexmap::IndexMap;
+use indexmap::IndexSet;
+use indexmap::indexset;
+use starlark::values::OwnedFrozenValue;
+
+use crate::actions::impls::archive::ArchiveActionError;
+use crate::actions::impls::archive::single_output;
+use crate::actions::impls::archive::write_output;
+use crate::actions::impls::util::materialized_input;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(tag = Input)]
+enum ExtractActionError {
+    #[error("No entry in archive `{0}` is under `strip_prefix` `{1}`")]
+    StripPrefixNotFound(ProjectRelativePathBuf, Arc<str>),
+}
+
+#[derive(Allocative)]
+pub(crate) struct UnregisteredExtractAction {
+    archive: ArtifactGroup,
+    format: ArchiveFormat,
+    strip_prefix: Option<Arc<str>>,
+}
+
+impl UnregisteredExtractAction {
+    pub(crate) fn new(
+        archive: ArtifactGroup,
+        format: ArchiveFormat,
+        strip_prefix: Option<Arc<str>>,
+    ) -> buck2_error::Result<Self> {
+        match archive {
+            ArtifactGroup::Artifact(..) | ArtifactGroup::Promise(..) => {}
+            ArtifactGroup::TransitiveSetProjection(..) => {
+                return Err(ArchiveActionError::UnsupportedInput(archive.dupe()).into());
+            }
+        }
+        Ok(Self {
+            archive,
+            format,
+            strip_prefix,
+        })
+    }
+}
+
+impl UnregisteredAction for UnregisteredExtractAction {
+    fn register(
+        self: Box<Self>,
+        outputs: IndexSet<BuildArtifact>,
+        _starlark_data: Option<OwnedFrozenValue>,
+        _error_handler: Option<OwnedFrozenValue>,
+    ) -> buck2_error::Result<Box<dyn Action>> {
+        Ok(Box::new(ExtractAction {
+            inputs: BoxSliceSet::from(indexset![self.archive]),
+            format: self.format,
+            strip_prefix: self.strip_prefix,
+            outputs: single_output(outputs, "extract")?,
+        }))
+    }
+}
+
+#[derive(Debug, Allocative)]
+struct ExtractAction {
+    inputs: BoxSliceSet<ArtifactGroup>,
+    format: ArchiveFormat,
+    strip_prefix: Option<Arc<str>>,
+    outputs: BoxSliceSet<BuildArtifact>,
+}
+
+impl ExtractAction {
+    fn input(&self) -> &ArtifactGroup {
+        self.inputs
+            .iter()
+            .next()
+            .expect("a single input by construction")
+    }
+
+    fn output(&self) -> &BuildArtifact {
+        self.outputs
+            .iter()
+            .next()
+            .expect("a single artifact by construction")
+    }
+}
+
+struct ExtractIoRequest {
+    archive: ProjectRelativePathBuf,
+    output: ProjectRelativePathBuf,
+    format: ArchiveFormat,
+    strip_prefix: Option<Arc<str>>,
+}
+
+impl IoRequest for ExtractIoRequest {
+    fn execute(self: Box<Self>, project_fs: &ProjectRoot) -> buck2_error::Result<()> {
+        let dest = project_fs.resolve(&self.output);
+        fs_util::create_dir_all(&dest)?;
+        let extracted = extract(
+            &project_fs.resolve(&self.archive),
+            self.format,
+            &dest,
+            self.strip_prefix.as_deref(),
+        )?;
+        if let Some(strip_prefix) = self.strip_prefix {
+            if extracted == 0 {
+                return Err(
+                    ExtractActionError::StripPrefixNotFound(self.archive, strip_prefix).into(),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Action for ExtractAction {
+    fn kind(&self) -> buck2_data::ActionKind {
+        buck2_data::ActionKind::Extract
+    }
+
+    fn inputs(&self) -> buck2_error::Result<Cow<'_, [ArtifactGroup]>> {
+        Ok(Cow::Borrowed(self.inputs.as_slice()))
+    }
+
+    fn outputs(&self) -> Cow<'_, [BuildArtifact]> {
+        Cow::Borrowed(self.outputs.as_slice())
+    }
+
+    fn first_output(&self) -> &BuildArtifact {
+        self.output()
+    }
+
+    fn category(&self) -> CategoryRef<'_> {
+        CategoryRef::unchecked_new("extract")
+    }
+
+    fn identifier(&self) -> Option<&str> {
+        Some(self.output().get_path().path().as_str())
+    }
+
+    fn aquery_attributes(
+        &self,
+        _fs: &ExecutorFs,
+        _artifact_path_mapping: &dyn ArtifactPathMapper,
+    ) -> IndexMap<String, String> {
+        let mut attrs = IndexMap::new();
+        attrs.insert("format".to_owned(), self.format.to_string());
+        if let Some(strip_prefix) = &self.strip_prefix {
+            attrs.insert("strip_prefix".to_owned(), strip_prefix.to_string());
+        }
+        attrs
+    }
+
+    async fn execute(
+        &self,
+        ctx: &mut dyn ActionExecutionCtx,
+        waiting_data: WaitingData,
+    ) -> Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError> {
+        let archive = materialized_input(ctx, self.input()).await?;
+
+        ctx.cleanup_outputs().await?;
+        let output = ctx.fs().resolve_build(self.output().get_path(), None)?;
+        let value = write_output(
+            ctx,
+            self.output(),
+            Box::new(ExtractIoRequest {
+                archive,
+                output,
+                format: self.format,
+                strip_prefix: self.strip_prefix.dupe(),
+            }),
+        )
+        .await?;
+
+        Ok((
+            ActionOutputs::from_single(self.output().get_path().dupe(), value),
+            ActionExecutionMetadata {
+                execution_kind: ActionExecutionKind::Simple,
+                timing: ActionExecutionTimingData::default(),
+                input_files_bytes: None,
+                waiting_data,
+            },
+        ))
+    }
+}
@@ -251,6 +251,7 @@ pub(crate) struct UnregisteredRunAction {
     pub(crate) remote_execution_custom_image: Option<Box<RemoteExecutorCustomImage>>,
     pub(crate) meta_internal_extra_params: MetaInternalExtraParams,
     pub(crate) expected_eligible_for_dedupe: Option<bool>,
+    pub(crate) output_size_budget: Option<u64>,
 }
 
 impl UnregisteredAction for UnregisteredRunAction {
@@ -1388,6 +1389,10 @@ impl Action for RunAction {
         self.inner.expected_eligible_for_dedupe
     }
 
+    fn output_size_budget(&self) -> Option<u64> {
+        self.inner.output_size_budget
+    }
+
     fn aquery_attributes(
         &self,
         fs: &ExecutorFs,
@@ -0,0 +1,49 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use buck2_build_api::actions::ActionExecutionCtx;
+use buck2_build_api::artifact_groups::ArtifactGroup;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_error::BuckErrorContext;
+use buck2_execute::artifact::artifact_dyn::ArtifactDyn;
+use gazebo::prelude::*;
+
+/// Resolves the path of an input artifact of an action.
+pub(crate) fn resolve_input(
+    ctx: &dyn ActionExecutionCtx,
+    group: &ArtifactGroup,
+) -> buck2_error::Result<ProjectRelativePathBuf> {
+    let (input, value) = ctx
+        .artifact_values(group)
+        .iter()
+        .into_singleton()
+        .buck_error_context("Input did not dereference to exactly one artifact")?;
+    input.resolve_path(
+        ctx.fs(),
+        if input.has_content_based_path() {
+            Some(value.content_based_path_hash())
+        } else {
+            None
+        }
+        .as_ref(),
+    )
+}
+
+/// Resolves an input of an action that reads it from disk, making sure it is materialized.
+pub(crate) async fn materialized_input(
+    ctx: &dyn ActionExecutionCtx,
+    group: &ArtifactGroup,
+) -> buck2_error::Result<ProjectRelativePathBuf> {
+    let path = resolve_input(ctx, group)?;
+    ctx.materializer()
+        .ensure_materialized(vec![path.clone()])
+        .await?;
+    Ok(path)
+}
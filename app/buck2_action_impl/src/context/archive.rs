@@ -0,0 +1,144 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use std::sync::Arc;
+
+use buck2_build_api::artifact_groups::ArtifactGroup;
+use buck2_build_api::interpreter::rule_defs::artifact::associated::AssociatedArtifacts;
+use buck2_build_api::interpreter::rule_defs::artifact::output_artifact_like::OutputArtifactArg;
+use buck2_build_api::interpreter::rule_defs::artifact::starlark_artifact_like::ValueAsInputArtifactLike;
+use buck2_build_api::interpreter::rule_defs::artifact::starlark_declared_artifact::StarlarkDeclaredArtifact;
+use buck2_build_api::interpreter::rule_defs::context::AnalysisActions;
+use buck2_core::cells::external::ArchiveFormat;
+use buck2_error::BuckErrorContext;
+use buck2_execute::execute::request::OutputType;
+use buck2_fs::paths::forward_rel_path::ForwardRelativePathBuf;
+use indexmap::indexset;
+use starlark::environment::MethodsBuilder;
+use starlark::eval::Evaluator;
+use starlark::starlark_module;
+use starlark::values::ValueTyped;
+use starlark::values::dict::UnpackDictEntries;
+use starlark::values::none::NoneOr;
+
+use crate::actions::impls::archive::UnregisteredArchiveAction;
+use crate::actions::impls::extract::UnregisteredExtractAction;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(tag = Input)]
+enum ArchiveFormatError {
+    #[error(
+        "Cannot infer the archive format from `{0}`, pass `format` as one of `tar`, `tar.gz`, \
+        `tar.zst` or `zip`"
+    )]
+    CannotInfer(String),
+}
+
+fn infer_format(file_name: &str) -> buck2_error::Result<ArchiveFormat> {
+    ArchiveFormat::from_url(file_name)
+        .ok_or_else(|| ArchiveFormatError::CannotInfer(file_name.to_owned()).into())
+}
+
+#[starlark_module]
+pub(crate) fn analysis_actions_methods_archive(methods: &mut MethodsBuilder) {
+    /// Creates an archive at `output` containing `srcs`, a dictionary of path in the archive (as
+    /// string) to bound `artifact`. Directories are added recursively and symlinks are followed.
+    ///
+    /// The archive is written by Buck2 itself and is deterministic: entries are sorted,
+    /// timestamps and ownership are zeroed, and permissions are `0644`, or `0755` for directories
+    /// and executables.
+    ///
+    /// `format` is one of `tar`, `tar.gz`, `tar.zst` or `zip`, and is inferred from the extension
+    /// of `output` if omitted.
+    fn archive<'v>(
+        this: &AnalysisActions<'v>,
+        #[starlark(require = pos)] output: OutputArtifactArg<'v>,
+        #[starlark(require = pos)] srcs: UnpackDictEntries<&'v str, ValueAsInputArtifactLike<'v>>,
+        #[starlark(require = named, default = NoneOr::None)] format: NoneOr<&str>,
+        eval: &mut Evaluator<'v, '_, '_>,
+    ) -> starlark::Result<ValueTyped<'v, StarlarkDeclaredArtifact<'v>>> {
+        let srcs = srcs
+            .entries
+            .into_iter()
+            .map(|(path, src)| {
+                buck2_error::Ok((
+                    src.0.get_artifact_group()?,
+                    ForwardRelativePathBuf::try_from(path.to_owned())
+                        .buck_error_context("dict key must be a forward relative path")?
+                        .into_box(),
+                ))
+            })
+            .collect::<buck2_error::Result<Vec<_>>>()?;
+
+        let mut this = this.state()?;
+        let (declaration, output_artifact) =
+            this.get_or_declare_output(eval, output, OutputType::File, Some(false))?;
+        let format = match format.into_option() {
+            Some(format) => format.parse::<ArchiveFormat>()?,
+            None => output_artifact
+                .get_path()
+                .with_filename(|name| infer_format(name.as_str()))??,
+        };
+
+        this.register_action(
+            indexset![output_artifact],
+            UnregisteredArchiveAction::new(srcs, format)?,
+            None,
+            None,
+        )?;
+
+        Ok(declaration.into_declared_artifact(AssociatedArtifacts::new()))
+    }
+
+    /// Extracts the `archive` artifact into the directory `output`.
+    ///
+    /// `format` is one of `tar`, `tar.gz`, `tar.zst` or `zip`, and is inferred from the extension
+    /// of `archive` if omitted. With `strip_prefix`, only the entries under that directory of the
+    /// archive are extracted, relative to it.
+    fn extract<'v>(
+        this: &AnalysisActions<'v>,
+        #[starlark(require = pos)] output: OutputArtifactArg<'v>,
+        #[starlark(require = pos)] archive: ValueAsInputArtifactLike<'v>,
+        #[starlark(require = named, default = NoneOr::None)] format: NoneOr<&str>,
+        #[starlark(require = named, default = NoneOr::None)] strip_prefix: NoneOr<&str>,
+        eval: &mut Evaluator<'v, '_, '_>,
+    ) -> starlark::Result<ValueTyped<'v, StarlarkDeclaredArtifact<'v>>> {
+        let archive = archive.0.get_artifact_group()?;
+        let format = match (format.into_option(), &archive) {
+            (Some(format), _) => format.parse::<ArchiveFormat>()?,
+            (None, ArtifactGroup::Artifact(artifact)) => artifact
+                .get_path()
+                .with_filename(|name| infer_format(name.as_str()))??,
+            (None, other) => {
+                return Err(buck2_error::Error::from(ArchiveFormatError::CannotInfer(
+                    other.to_string(),
+                ))
+                .into());
+            }
+        };
+
+        let mut this = this.state()?;
+        let (declaration, output_artifact) =
+            this.get_or_declare_output(eval, output, OutputType::Directory, Some(false))?;
+
+        this.register_action(
+            indexset![output_artifact],
+            UnregisteredExtractAction::new(
+                archive,
+                format,
+                strip_prefix.into_option().map(Arc::from),
+            )?,
+            None,
+            None,
+        )?;
+
+        Ok(declaration.into_declared_artifact(AssociatedArtifacts::new()))
+    }
+}
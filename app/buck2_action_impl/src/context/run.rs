@@ -199,6 +199,10 @@ pub(crate) fn analysis_actions_methods_run(methods: &mut MethodsBuilder) {
     ///     * The output must also be declared as an output of the action
     ///     * The output artifact must be created if the action fails
     ///     * Nothing will be provided if left empty (Which is the default)
+    ///  * `output_size_budget`: the maximum total size, in bytes, of the outputs of this action.
+    ///    Overrides any budget configured for the action's category in `[output_size_budgets]`.
+    ///    Exceeding it is a soft error, or fails the action if `buck2.enforce_output_size_budgets`
+    ///    is set.
     ///
     /// When actions execute, they'll do so from the root of the repository. As they execute,
     /// actions have exclusive access to their output directory.
@@ -281,6 +285,7 @@ pub(crate) fn analysis_actions_methods_run(methods: &mut MethodsBuilder) {
         #[starlark(require = named, default = NoneOr::None)] expect_eligible_for_dedupe: NoneOr<
             bool,
         >,
+        #[starlark(require = named, default = NoneOr::None)] output_size_budget: NoneOr<u64>,
     ) -> starlark::Result<NoneType> {
         if incremental_remote_outputs && !no_outputs_cleanup {
             // Precaution to make sure content-based paths are not involved.
@@ -588,6 +593,7 @@ pub(crate) fn analysis_actions_methods_run(methods: &mut MethodsBuilder) {
             remote_execution_custom_image: re_custom_image,
             meta_internal_extra_params: extra_params,
             expected_eligible_for_dedupe: expect_eligible_for_dedupe.into_option(),
+            output_size_budget: output_size_budget.into_option(),
         };
 
         if expect_eligible_for_dedupe.into_option().unwrap_or(false) {
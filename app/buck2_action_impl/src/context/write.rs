@@ -14,6 +14,7 @@ use buck2_build_api::actions::impls::json::JsonUnpack;
 use buck2_build_api::artifact_groups::ArtifactGroup;
 use buck2_build_api::interpreter::rule_defs::artifact::associated::AssociatedArtifacts;
 use buck2_build_api::interpreter::rule_defs::artifact::output_artifact_like::OutputArtifactArg;
+use buck2_build_api::interpreter::rule_defs::artifact::starlark_artifact_like::ValueAsInputArtifactLike;
 use buck2_build_api::interpreter::rule_defs::artifact::starlark_declared_artifact::StarlarkDeclaredArtifact;
 use buck2_build_api::interpreter::rule_defs::artifact_tagging::ArtifactTag;
 use buck2_build_api::interpreter::rule_defs::cmd_args::ArtifactPathMapper;
@@ -41,10 +42,13 @@ use starlark::values::AllocValue;
 use starlark::values::UnpackValue;
 use starlark::values::ValueOf;
 use starlark::values::ValueTyped;
+use starlark::values::dict::UnpackDictEntries;
 use starlark::values::none::NoneOr;
 use starlark::values::type_repr::StarlarkTypeRepr;
 use starlark_map::small_set::SmallSet;
 
+use crate::actions::impls::expand_template::Substitution;
+use crate::actions::impls::expand_template::UnregisteredExpandTemplateAction;
 use crate::actions::impls::write::UnregisteredWriteAction;
 use crate::actions::impls::write_json::UnregisteredWriteJsonAction;
 use crate::actions::impls::write_macros::UnregisteredWriteMacrosToFileAction;
@@ -58,6 +62,12 @@ enum WriteActionError {
     ArgAttrsDetectedButNotAllowed,
 }
 
+#[derive(UnpackValue, StarlarkTypeRepr)]
+enum SubstitutionArg<'v> {
+    Str(&'v str),
+    Artifact(ValueAsInputArtifactLike<'v>),
+}
+
 #[derive(UnpackValue, StarlarkTypeRepr)]
 enum WriteContentArg<'v> {
     CommandLineArg(CommandLineArg<'v>),
@@ -109,6 +119,63 @@ impl<'v> CommandLineArtifactVisitor<'v> for CommandLineInputVisitor {
 
 #[starlark_module]
 pub(crate) fn analysis_actions_methods_write(methods: &mut MethodsBuilder) {
+    /// Returns an `artifact` whose contents are those of `template` with every occurrence of each
+    /// key of `substitutions` replaced by its value.
+    ///
+    /// * `output`: can be a string, or an existing artifact created with `declare_output`
+    /// * `template`: the `artifact` to read, which must be UTF-8 text
+    /// * `substitutions`: a dictionary from the string to replace to either a string or an
+    ///   `artifact`, which is replaced by its path relative to the project root. The template is
+    ///   scanned once: where several keys match at the same position, the first one in
+    ///   `substitutions` is replaced, and replaced text is not expanded again
+    /// * `is_executable` (optional): indicates whether the resulting file should be marked with
+    ///   executable permissions
+    ///
+    /// The substitution happens inside Buck2, without running a command.
+    fn expand_template<'v>(
+        this: &AnalysisActions<'v>,
+        #[starlark(require = pos)] output: OutputArtifactArg<'v>,
+        template: ValueAsInputArtifactLike<'v>,
+        substitutions: UnpackDictEntries<&'v str, SubstitutionArg<'v>>,
+        #[starlark(require = named, default = false)] is_executable: bool,
+        #[starlark(require = named, default = NoneOr::None)]
+        uses_experimental_content_based_path_hashing: NoneOr<bool>,
+        #[starlark(require = named, default = NoneOr::None)] has_content_based_path: NoneOr<bool>,
+        eval: &mut Evaluator<'v, '_, '_>,
+    ) -> starlark::Result<ValueTyped<'v, StarlarkDeclaredArtifact<'v>>> {
+        let substitutions = substitutions
+            .entries
+            .into_iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    SubstitutionArg::Str(s) => Substitution::Literal(s.to_owned()),
+                    SubstitutionArg::Artifact(a) => {
+                        Substitution::Artifact(a.0.get_artifact_group()?)
+                    }
+                };
+                buck2_error::Ok((key.to_owned(), value))
+            })
+            .collect::<buck2_error::Result<Vec<_>>>()?;
+        let action = UnregisteredExpandTemplateAction::new(
+            template.0.get_artifact_group()?,
+            substitutions,
+            is_executable,
+        )?;
+
+        let mut this = this.state()?;
+        let (declaration, output_artifact) = this.get_or_declare_output(
+            eval,
+            output,
+            OutputType::File,
+            uses_experimental_content_based_path_hashing
+                .into_option()
+                .or(has_content_based_path.into_option()),
+        )?;
+        this.register_action(indexset![output_artifact], action, None, None)?;
+
+        Ok(declaration.into_declared_artifact(AssociatedArtifacts::new()))
+    }
+
     /// Returns an `artifact` whose contents are `content` written as a JSON value.
     ///
     /// * `output`: can be a string, or an existing artifact created with `declare_output`
@@ -8,6 +8,7 @@
  * above-listed licenses.
  */
 
+pub mod aspect;
 pub mod calculation;
 pub mod env;
 mod plugins;
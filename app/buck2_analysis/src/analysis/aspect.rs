@@ -0,0 +1,312 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! Evaluation of `aspect()`s, which add providers to a target (and, transitively, to the deps it
+//! reaches through the aspect's `attr_aspects`) without rerunning its analysis.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use buck2_build_api::analysis::calculation::RuleAnalysisCalculation;
+use buck2_build_api::interpreter::rule_defs::provider::collection::FrozenProviderCollectionValue;
+use buck2_build_api::interpreter::rule_defs::provider::collection::ProviderCollection;
+use buck2_build_api::interpreter::rule_defs::provider::dependency::Dependency;
+use buck2_build_api::keep_going::KeepGoing;
+use buck2_core::provider::label::ConfiguredProvidersLabel;
+use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
+use buck2_error::BuckErrorContext;
+use buck2_events::dispatch::get_dispatcher;
+use buck2_interpreter::dice::starlark_provider::StarlarkEvalKind;
+use buck2_interpreter::factory::BuckStarlarkModule;
+use buck2_interpreter::factory::StarlarkEvaluatorProvider;
+use buck2_interpreter::load_module::InterpreterCalculation;
+use buck2_interpreter::print_handler::EventDispatcherPrintHandler;
+use buck2_interpreter::soft_error::Buck2StarlarkSoftErrorHandler;
+use buck2_interpreter::types::aspect::FROZEN_ASPECT_GET_ATTR_ASPECTS;
+use buck2_interpreter::types::aspect::FROZEN_ASPECT_GET_IMPL;
+use buck2_node::aspect::AspectId;
+use buck2_node::aspect::AspectIds;
+use buck2_node::attrs::configured_traversal::ConfiguredAttrTraversal;
+use buck2_node::attrs::inspect_options::AttrInspectOptions;
+use buck2_node::nodes::configured::ConfiguredTargetNodeRef;
+use buck2_node::nodes::configured_frontend::ConfiguredTargetNodeCalculation;
+use dice::CancellationContext;
+use dice::DiceComputations;
+use dice::Key;
+use dupe::Dupe;
+use futures::FutureExt;
+use starlark::values::list::AllocList;
+use strong_hash::StrongHash;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(tag = Input)]
+enum AspectEvalError {
+    #[error("Aspect `{0}` not found")]
+    NotFound(Arc<AspectId>),
+    #[error(
+        "Aspect `{0}` cannot propagate along attribute `{1}` to `{2}`, which is in another configuration"
+    )]
+    Transition(Arc<AspectId>, String, ConfiguredProvidersLabel),
+}
+
+/// The providers of `target` with `aspects` applied to it, in order.
+#[derive(
+    Clone,
+    Dupe,
+    derive_more::Display,
+    Debug,
+    Eq,
+    Hash,
+    PartialEq,
+    Allocative,
+    StrongHash
+)]
+#[display("{} ({})", target, aspects)]
+pub struct AspectKey {
+    pub aspects: AspectIds,
+    pub target: ConfiguredTargetLabel,
+}
+
+#[async_trait]
+impl Key for AspectKey {
+    type Value = buck2_error::Result<FrozenProviderCollectionValue>;
+
+    async fn compute(
+        &self,
+        ctx: &mut DiceComputations,
+        cancellation: &CancellationContext,
+    ) -> Self::Value {
+        compute_aspect(ctx, self, cancellation)
+            .await
+            .with_buck_error_context(|| {
+                format!(
+                    "Error applying aspects `{}` to `{}`",
+                    self.aspects, self.target
+                )
+            })
+    }
+
+    fn equality(_: &Self::Value, _: &Self::Value) -> bool {
+        // Like analysis results, provider collections are not comparable.
+        false
+    }
+}
+
+/// Returns the providers of `target` with `aspects` applied to it.
+pub async fn get_aspect_providers(
+    ctx: &mut DiceComputations<'_>,
+    aspects: &AspectIds,
+    target: &ConfiguredTargetLabel,
+) -> buck2_error::Result<FrozenProviderCollectionValue> {
+    ctx.compute(&AspectKey {
+        aspects: aspects.dupe(),
+        target: target.dupe(),
+    })
+    .await?
+}
+
+/// Computes the providers of every dep of `configured_node` which has aspects applied to it.
+pub async fn get_aspect_deps(
+    configured_node: ConfiguredTargetNodeRef<'_>,
+    ctx: &mut DiceComputations<'_>,
+) -> buck2_error::Result<HashMap<(ConfiguredTargetLabel, AspectIds), FrozenProviderCollectionValue>>
+{
+    let aspect_deps = configured_node
+        .aspect_deps()
+        .map(|(dep, aspects)| (dep.target().dupe(), aspects))
+        .collect::<Vec<_>>();
+    let results = KeepGoing::try_compute_join_all(ctx, aspect_deps, |ctx, (target, aspects)| {
+        async move {
+            let providers = get_aspect_providers(ctx, &aspects, &target).await?;
+            buck2_error::Ok(((target, aspects), providers))
+        }
+        .boxed()
+    })
+    .await?;
+    Ok(results.into_iter().collect())
+}
+
+/// The deps of `node` reached through the attributes named in `attrs`, including their subtargets.
+/// Attributes the node doesn't have are skipped, so an aspect can list attributes of several rules.
+///
+/// Aspects only propagate along deps in the configuration of `node`: the deps of exec, toolchain
+/// and transition attributes are rejected.
+fn deps_through_attrs(
+    node: ConfiguredTargetNodeRef<'_>,
+    aspect: &Arc<AspectId>,
+    attrs: &[String],
+) -> buck2_error::Result<Vec<ConfiguredProvidersLabel>> {
+    struct Traversal<'a> {
+        node: ConfiguredTargetNodeRef<'a>,
+        aspect: &'a Arc<AspectId>,
+        attr: &'a str,
+        deps: Vec<ConfiguredProvidersLabel>,
+    }
+
+    impl Traversal<'_> {
+        fn transition_error(&self, dep: &ConfiguredProvidersLabel) -> buck2_error::Error {
+            AspectEvalError::Transition(self.aspect.dupe(), self.attr.to_owned(), dep.dupe()).into()
+        }
+    }
+
+    impl ConfiguredAttrTraversal for Traversal<'_> {
+        fn dep(&mut self, dep: &ConfiguredProvidersLabel) -> buck2_error::Result<()> {
+            if dep.target().cfg() != self.node.label().cfg() {
+                return Err(self.transition_error(dep));
+            }
+            if !self.deps.contains(dep) {
+                self.deps.push(dep.dupe());
+            }
+            Ok(())
+        }
+
+        fn exec_dep(&mut self, dep: &ConfiguredProvidersLabel) -> buck2_error::Result<()> {
+            Err(self.transition_error(dep))
+        }
+
+        fn toolchain_dep(&mut self, dep: &ConfiguredProvidersLabel) -> buck2_error::Result<()> {
+            Err(self.transition_error(dep))
+        }
+    }
+
+    let mut traversal = Traversal {
+        node,
+        aspect,
+        attr: "",
+        deps: Vec::new(),
+    };
+    for attr in attrs {
+        if let Some(value) = node.get(attr, AttrInspectOptions::All) {
+            traversal.attr = attr;
+            value.traverse(node.label().pkg(), &mut traversal)?;
+        }
+    }
+    Ok(traversal.deps)
+}
+
+async fn compute_aspect(
+    ctx: &mut DiceComputations<'_>,
+    key: &AspectKey,
+    cancellation: &CancellationContext,
+) -> buck2_error::Result<FrozenProviderCollectionValue> {
+    let (aspect_id, applied_before) = key
+        .aspects
+        .aspects()
+        .split_last()
+        .internal_error("Aspect key with no aspects")?;
+
+    // The last aspect runs over the providers of the target with all the earlier aspects applied.
+    let base = if applied_before.is_empty() {
+        ctx.get_analysis_result(&key.target)
+            .await?
+            .require_compatible()?
+            .providers()?
+            .to_owned()
+    } else {
+        get_aspect_providers(ctx, &AspectIds::from(applied_before.to_vec()), &key.target).await?
+    };
+
+    let module = ctx
+        .get_loaded_module_from_import_path(&aspect_id.path)
+        .await?;
+    let aspect = module
+        .env()
+        // This is a hashmap lookup, so we are not caching the result in DICE.
+        .get_any_visibility(&aspect_id.name)
+        .map_err(|_| buck2_error::Error::from(AspectEvalError::NotFound(aspect_id.dupe())))?
+        .0;
+    // This is safe because only owned strings are taken from the aspect.
+    let attr_aspects =
+        (FROZEN_ASPECT_GET_ATTR_ASPECTS.get()?)(unsafe { aspect.unchecked_frozen_value() })?;
+
+    // The aspects are propagated along `attr_aspects`, so the deps are seen with them applied.
+    let node = ctx
+        .get_configured_target_node(&key.target)
+        .await?
+        .require_compatible()?;
+    let node = node.forward_target().unwrap_or(&node).as_ref();
+    let deps = deps_through_attrs(node, aspect_id, &attr_aspects)?;
+    let mut dep_targets = Vec::new();
+    for dep in &deps {
+        if !dep_targets.contains(dep.target()) {
+            dep_targets.push(dep.target().dupe());
+        }
+    }
+    let dep_providers: HashMap<_, _> =
+        KeepGoing::try_compute_join_all(ctx, dep_targets, |ctx, target| {
+            let aspects = key.aspects.dupe();
+            async move {
+                let providers = get_aspect_providers(ctx, &aspects, &target).await?;
+                buck2_error::Ok((target, providers))
+            }
+            .boxed()
+        })
+        .await?
+        .into_iter()
+        .collect();
+
+    let print = EventDispatcherPrintHandler(get_dispatcher());
+    let eval_kind = StarlarkEvalKind::Aspect(Arc::new(key.dupe()));
+    let provider = StarlarkEvaluatorProvider::new(ctx, eval_kind).await?;
+    BuckStarlarkModule::with_profiling(|env| {
+        let (finished_eval, ()) =
+            provider.with_evaluator(&env, cancellation.into(), |eval, _| {
+                eval.set_print_handler(&print);
+                eval.set_soft_error_handler(&Buck2StarlarkSoftErrorHandler);
+
+                // IMPORTANT: `owned_value` keeps the aspect module alive with the result.
+                let aspect = aspect
+                    .owned_value(eval.frozen_heap())
+                    .unpack_frozen()
+                    .internal_error("Must be frozen")?;
+                let implementation = (FROZEN_ASPECT_GET_IMPL.get()?)(aspect)?;
+
+                let base = base.add_heap_ref(env.heap());
+                let target = env.heap().alloc(Dependency::new(
+                    env.heap(),
+                    ConfiguredProvidersLabel::default_for(key.target.dupe()),
+                    base,
+                    None,
+                ));
+                // The aspect sees the providers of the subtargets the attributes refer to.
+                let deps = deps
+                    .iter()
+                    .map(|dep| {
+                        let providers = dep_providers
+                            .get(dep.target())
+                            .internal_error("Aspect providers of dep must be computed")?
+                            .lookup_inner(dep)?;
+                        buck2_error::Ok(Dependency::new(
+                            env.heap(),
+                            dep.dupe(),
+                            providers.add_heap_ref(env.heap()),
+                            None,
+                        ))
+                    })
+                    .collect::<buck2_error::Result<Vec<_>>>()?;
+                let deps = env.heap().alloc(AllocList(deps));
+
+                let list_res =
+                    eval.eval_function(implementation.to_value(), &[target, deps], &[])?;
+                let res = ProviderCollection::try_from_value_with_base(list_res, base)?;
+                env.set_extra_value(env.heap().alloc(res));
+                Ok(())
+            })?;
+        let (token, frozen_env, _) = finished_eval.freeze_and_finish(env)?;
+        let providers = FrozenProviderCollectionValue::try_from_value(
+            frozen_env
+                .owned_extra_value()
+                .internal_error("Aspect result must be set")?,
+        )?;
+        Ok((token, providers))
+    })
+}
@@ -61,6 +61,7 @@ use dupe::IterDupedExt;
 use futures::FutureExt;
 use smallvec::SmallVec;
 
+use crate::analysis::aspect::get_aspect_deps;
 use crate::analysis::env::RuleSpec;
 use crate::analysis::env::get_user_defined_rule_spec;
 use crate::analysis::env::run_analysis;
@@ -274,10 +275,11 @@ async fn get_analysis_result_inner(
 
     let ((res, now), spans): ((buck2_error::Result<_>, _), _) = match configured_node.rule_type() {
         RuleType::Starlark(func) => {
-            let (dep_analysis, query_results) = ctx
-                .try_compute2(
+            let (dep_analysis, query_results, aspect_results) = ctx
+                .try_compute3(
                     |ctx| get_dep_analysis(configured_node, ctx).boxed(),
                     |ctx| resolve_queries(ctx, configured_node).boxed(),
+                    |ctx| get_aspect_deps(configured_node, ctx).boxed(),
                 )
                 .await?;
 
@@ -305,6 +307,7 @@ async fn get_analysis_result_inner(
                                 ctx,
                                 target,
                                 dep_analysis,
+                                aspect_results,
                                 query_results,
                                 configured_node.execution_platform_resolution(),
                                 &rule_spec,
@@ -27,6 +27,7 @@ use buck2_build_api::validation::transitive_validations::TransitiveValidationsDa
 use buck2_core::deferred::base_deferred_key::BaseDeferredKey;
 use buck2_core::execution_types::execution::ExecutionPlatformResolution;
 use buck2_core::provider::label::ConfiguredProvidersLabel;
+use buck2_core::provider::label::ProvidersName;
 use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
 use buck2_core::unsafe_send_future::UnsafeSendFuture;
 use buck2_error::BuckErrorContext;
@@ -40,6 +41,7 @@ use buck2_interpreter::print_handler::EventDispatcherPrintHandler;
 use buck2_interpreter::soft_error::Buck2StarlarkSoftErrorHandler;
 use buck2_interpreter::types::rule::FROZEN_PROMISE_ARTIFACT_MAPPINGS_GET_IMPL;
 use buck2_interpreter::types::rule::FROZEN_RULE_GET_IMPL;
+use buck2_node::aspect::AspectIds;
 use buck2_node::nodes::configured::ConfiguredTargetNodeRef;
 use buck2_node::rule_type::StarlarkRuleType;
 use dice::CancellationContext;
@@ -72,11 +74,19 @@ enum AnalysisError {
     MissingDep(ConfiguredProvidersLabel),
 }
 
+#[derive(buck2_error::Error, Debug)]
+#[buck2(tag = Input)]
+enum AspectResolutionError {
+    #[error("Aspects `{1}` cannot be applied to subtarget `{0}`, only to a whole target")]
+    Subtarget(ConfiguredProvidersLabel, AspectIds),
+}
+
 // Contains a `module` that things must live on, and various `FrozenProviderCollectionValue`s
 // that are NOT tied to that module. Must claim ownership of them via `add_reference` before returning them.
 pub struct RuleAnalysisAttrResolutionContext<'a, 'v> {
     pub module: &'a Module<'v>,
     pub dep_analysis_results: HashMap<ConfiguredTargetLabel, FrozenProviderCollectionValue>,
+    pub aspect_results: HashMap<(ConfiguredTargetLabel, AspectIds), FrozenProviderCollectionValue>,
     pub query_results: HashMap<String, Arc<AnalysisQueryResult>>,
     pub execution_platform_resolution: ExecutionPlatformResolution,
 }
@@ -93,6 +103,14 @@ impl<'a, 'v> AttrResolutionContext<'v> for &'_ RuleAnalysisAttrResolutionContext
         get_dep(&self.dep_analysis_results, target, self.module)
     }
 
+    fn get_dep_with_aspects(
+        &mut self,
+        target: &ConfiguredProvidersLabel,
+        aspects: &AspectIds,
+    ) -> buck2_error::Result<FrozenValueTyped<'v, FrozenProviderCollection>> {
+        get_dep_with_aspects(&self.aspect_results, target, aspects, self.module)
+    }
+
     fn resolve_unkeyed_placeholder(
         &mut self,
         name: &str,
@@ -128,6 +146,22 @@ pub fn get_dep<'v>(
     }
 }
 
+pub fn get_dep_with_aspects<'v>(
+    aspect_results: &HashMap<(ConfiguredTargetLabel, AspectIds), FrozenProviderCollectionValue>,
+    target: &ConfiguredProvidersLabel,
+    aspects: &AspectIds,
+    module: &Module<'v>,
+) -> buck2_error::Result<FrozenValueTyped<'v, FrozenProviderCollection>> {
+    if !matches!(target.name(), ProvidersName::Default) {
+        return Err(AspectResolutionError::Subtarget(target.dupe(), aspects.dupe()).into());
+    }
+    match aspect_results.get(&(target.target().dupe(), aspects.dupe())) {
+        None => Err(AnalysisError::MissingDep(target.dupe()).into()),
+        // IMPORTANT: Anything given back to the user must be kept alive
+        Some(x) => Ok(x.add_heap_ref(module.heap())),
+    }
+}
+
 pub fn resolve_unkeyed_placeholder<'v>(
     dep_analysis_results: &HashMap<ConfiguredTargetLabel, FrozenProviderCollectionValue>,
     name: &str,
@@ -185,6 +219,7 @@ pub trait RuleSpec: Sync {
 struct AnalysisEnv<'a> {
     rule_spec: &'a dyn RuleSpec,
     deps: Vec<(&'a ConfiguredTargetLabel, AnalysisResult)>,
+    aspect_results: HashMap<(ConfiguredTargetLabel, AspectIds), FrozenProviderCollectionValue>,
     query_results: HashMap<String, Arc<AnalysisQueryResult>>,
     execution_platform: &'a ExecutionPlatformResolution,
     label: ConfiguredTargetLabel,
@@ -195,6 +230,7 @@ pub(crate) async fn run_analysis<'a>(
     dice: &'a mut DiceComputations<'_>,
     label: &ConfiguredTargetLabel,
     results: Vec<(&'a ConfiguredTargetLabel, AnalysisResult)>,
+    aspect_results: HashMap<(ConfiguredTargetLabel, AspectIds), FrozenProviderCollectionValue>,
     query_results: HashMap<String, Arc<AnalysisQueryResult>>,
     execution_platform: &'a ExecutionPlatformResolution,
     rule_spec: &'a dyn RuleSpec,
@@ -204,6 +240,7 @@ pub(crate) async fn run_analysis<'a>(
     let analysis_env = AnalysisEnv {
         rule_spec,
         deps: results,
+        aspect_results,
         query_results,
         execution_platform,
         label: label.dupe(),
@@ -259,6 +296,7 @@ async fn run_analysis_with_env_underlying(
             let resolution_ctx = RuleAnalysisAttrResolutionContext {
                 module: &env,
                 dep_analysis_results,
+                aspect_results: analysis_env.aspect_results,
                 query_results: analysis_env.query_results,
                 execution_platform_resolution: node.execution_platform_resolution().clone(),
             };
@@ -123,12 +123,29 @@ impl DepAttrTypeExt for DepAttrType {
         dep_attr: &DepAttr<ConfiguredProvidersLabel>,
     ) -> buck2_error::Result<Value<'v>> {
         let is_exec = dep_attr.attr_type.transition == DepAttrTransition::Exec;
-        Self::resolve_single_impl(
-            ctx,
-            &dep_attr.label,
+        if dep_attr.attr_type.aspects.is_empty() {
+            return Self::resolve_single_impl(
+                ctx,
+                &dep_attr.label,
+                &dep_attr.attr_type.required_providers,
+                is_exec,
+            );
+        }
+
+        // Aspects are only allowed on `attrs.dep()`, which never transitions to the exec platform.
+        let provider_collection =
+            ctx.get_dep_with_aspects(&dep_attr.label, &dep_attr.attr_type.aspects)?;
+        Self::check_providers(
             &dep_attr.attr_type.required_providers,
-            is_exec,
-        )
+            provider_collection.as_ref(),
+            &dep_attr.label,
+        )?;
+        Ok(Self::alloc_dependency(
+            ctx.starlark_module(),
+            &dep_attr.label,
+            provider_collection,
+            None,
+        ))
     }
 }
 
@@ -17,10 +17,19 @@ use buck2_build_api::interpreter::rule_defs::provider::collection::FrozenProvide
 use buck2_core::execution_types::execution::ExecutionPlatformResolution;
 use buck2_core::provider::label::ConfiguredProvidersLabel;
 use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
+use buck2_node::aspect::AspectIds;
+use dupe::Dupe;
 use starlark::environment::Module;
 use starlark::values::FrozenValueTyped;
 use starlark::values::Heap;
 
+#[derive(buck2_error::Error, Debug)]
+#[buck2(tag = Input)]
+enum AttrResolutionError {
+    #[error("Aspects `{1}` on dependency `{0}` cannot be applied in this context")]
+    AspectsNotSupported(ConfiguredProvidersLabel, AspectIds),
+}
+
 /// Result of query evaluation from queries referenced in target nodes.
 ///
 /// Queries are:
@@ -49,6 +58,15 @@ pub trait AttrResolutionContext<'v> {
         target: &ConfiguredProvidersLabel,
     ) -> buck2_error::Result<FrozenValueTyped<'v, FrozenProviderCollection>>;
 
+    /// Get the `ProviderCollection` for this label with `aspects` applied to it.
+    fn get_dep_with_aspects(
+        &mut self,
+        target: &ConfiguredProvidersLabel,
+        aspects: &AspectIds,
+    ) -> buck2_error::Result<FrozenValueTyped<'v, FrozenProviderCollection>> {
+        Err(AttrResolutionError::AspectsNotSupported(target.dupe(), aspects.dupe()).into())
+    }
+
     fn resolve_unkeyed_placeholder(
         &mut self,
         name: &str,
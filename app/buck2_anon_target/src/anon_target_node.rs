@@ -200,6 +200,7 @@ impl AnonTargetDyn for AnonTarget {
         let rule_analysis_attr_resolution_ctx = RuleAnalysisAttrResolutionContext {
             module: &env,
             dep_analysis_results,
+            aspect_results: HashMap::new(),
             query_results: HashMap::new(),
             execution_platform_resolution: exec_resolution,
         };
@@ -54,6 +54,7 @@ use buck2_execute::execute::prepared::PreparedAction;
 use buck2_execute::execute::request::CommandExecutionRequest;
 use buck2_execute::execute::request::ExecutorPreference;
 use buck2_execute::execute::result::CommandExecutionResult;
+use buck2_execute::materialize::http::Checksum;
 use buck2_execute::materialize::materializer::Materializer;
 use buck2_execute::re::manager::UnconfiguredRemoteExecutionClient;
 use buck2_execute::re::output_trees_download_config::OutputTreesDownloadConfig;
@@ -225,9 +226,27 @@ pub trait Action: Allocative + Debug + Send + Sync + 'static {
         None
     }
 
+    /// Maximum total size, in bytes, of the outputs of this action. Takes precedence over the
+    /// budgets configured in `[output_size_budgets]`.
+    fn output_size_budget(&self) -> Option<u64> {
+        None
+    }
+
+    /// The file this action downloads, for actions that download one. `buck2 fetch` pins these
+    /// in `buck2.lock`.
+    fn download(&self) -> Option<ActionDownload<'_>> {
+        None
+    }
+
     // TODO this probably wants more data for execution, like printing a short_name and the target
 }
 
+/// A file downloaded by an action, see `Action::download`.
+pub struct ActionDownload<'a> {
+    pub url: &'a str,
+    pub checksum: &'a Checksum,
+}
+
 /// The context for actions to use when executing
 #[async_trait]
 pub trait ActionExecutionCtx: Send + Sync {
@@ -19,6 +19,8 @@ use buck2_artifact::artifact::build_artifact::BuildArtifact;
 use buck2_build_signals::env::NodeDuration;
 use buck2_build_signals::env::WaitingData;
 use buck2_common::events::HasEvents;
+use buck2_common::lockfile::HasLockfile;
+use buck2_common::lockfile::LockedDownload;
 use buck2_core::deferred::base_deferred_key::BaseDeferredKey;
 use buck2_core::fs::artifact_path_resolver::ArtifactFs;
 use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
@@ -34,7 +36,6 @@ use buck2_events::dispatch::span_async;
 use buck2_events::span::SpanId;
 use buck2_execute::execute::result::CommandExecutionReport;
 use buck2_execute::execute::result::CommandExecutionStatus;
-use buck2_execute::output_size::OutputSize;
 use buck2_interpreter::print_handler::EventDispatcherPrintHandler;
 use buck2_interpreter::soft_error::Buck2StarlarkSoftErrorHandler;
 use buck2_node::nodes::configured_frontend::ConfiguredTargetNodeCalculation;
@@ -107,6 +108,19 @@ async fn build_action_no_redirect(
     cancellation: &CancellationContext,
     action: Arc<RegisteredAction>,
 ) -> buck2_error::Result<ActionOutputs> {
+    // Checked here rather than by the action itself, so that DICE reruns the check when the
+    // lockfile changes.
+    if let Some(download) = action.download() {
+        if let Some(lockfile) = ctx.get_lockfile().await? {
+            lockfile
+                .check_download(
+                    download.url,
+                    &LockedDownload::new(download.checksum.sha1(), download.checksum.sha256()),
+                )
+                .buck_error_context(format!("for action `{action}`"))?;
+        }
+    }
+
     let inputs = action.inputs()?;
     let waiting_data = WaitingData::new();
     let ensured_inputs = if inputs.is_empty() {
@@ -276,8 +290,8 @@ async fn build_action_inner(
     let mut incremental_kind = None;
     let mut waiting_data = None;
     let error_diagnostics = match execute_result {
-        Ok((outputs, meta)) => {
-            output_size = outputs.calc_output_count_and_bytes().bytes;
+        Ok((outputs, meta, size)) => {
+            output_size = size;
             action_result = Ok(outputs);
             execution_kind = Some(meta.execution_kind.as_enum());
             wall_time = Some(meta.timing.wall_time);
@@ -363,9 +377,23 @@ async fn build_action_inner(
         .map(|outputs| {
             outputs
                 .iter()
-                .filter_map(|(_artifact, value)| {
+                .filter_map(|(path, value)| {
                     Some(buck2_data::ActionOutput {
                         tiny_digest: value.digest()?.tiny_digest().to_string(),
+                        path: executor
+                            .fs()
+                            .resolve_build(path, Some(&value.content_based_path_hash()))
+                            .ok()
+                            .and_then(|resolved| {
+                                Some(
+                                    resolved
+                                        .strip_prefix_opt(
+                                            executor.fs().buck_out_path_resolver().root(),
+                                        )?
+                                        .to_string(),
+                                )
+                            })
+                            .unwrap_or_default(),
                     })
                 })
                 .collect()
@@ -30,6 +30,7 @@ use buck2_core::fs::buck_out_path::BuildArtifactPath;
 use buck2_data::SchedulingMode;
 use buck2_error::BuckErrorContext;
 use buck2_error::internal_error;
+use buck2_event_observer::humanized::HumanizedBytes;
 use buck2_events::dispatch::EventDispatcher;
 use buck2_execute::artifact::fs::ExecutorFs;
 use buck2_execute::artifact_value::ArtifactValue;
@@ -309,6 +310,18 @@ impl HasActionExecutor for DiceComputations<'_> {
     }
 }
 
+#[derive(Debug, buck2_error::Error)]
+#[buck2(tag = Input)]
+#[error(
+    "Action `{action}` of `{owner}` produced {size} of outputs, which exceeds its output size budget of {budget}"
+)]
+struct OutputSizeBudgetExceeded {
+    action: String,
+    owner: String,
+    size: String,
+    budget: String,
+}
+
 pub struct BuckActionExecutor {
     command_executor: CommandExecutor,
     blocking_executor: Arc<dyn BlockingExecutor>,
@@ -677,6 +690,7 @@ impl ActionExecutionCtx for BuckActionExecutionContext<'_> {
 }
 
 impl BuckActionExecutor {
+    /// Runs the action, returning its outputs along with their total size in bytes.
     pub(crate) async fn execute(
         &self,
         waiting_data: WaitingData,
@@ -684,7 +698,7 @@ impl BuckActionExecutor {
         action: &RegisteredAction,
         cancellations: &CancellationContext,
     ) -> (
-        Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError>,
+        Result<(ActionOutputs, ActionExecutionMetadata, u64), ExecuteError>,
         Vec<CommandExecutionReport>,
     ) {
         let mut command_reports = Vec::new();
@@ -782,7 +796,9 @@ impl BuckActionExecutor {
                     Err(ExecuteError::MismatchedOutputs { declared, real })
                 }
             } else {
-                Ok((result, metadata))
+                let output_size = result.calc_output_count_and_bytes().bytes;
+                self.check_output_size_budget(action, output_size)?;
+                Ok((result, metadata, output_size))
             }
         }
         .await;
@@ -790,9 +806,43 @@ impl BuckActionExecutor {
         (res, command_reports)
     }
 
+    fn check_output_size_budget(
+        &self,
+        action: &RegisteredAction,
+        size: u64,
+    ) -> buck2_error::Result<()> {
+        let budgets = &self.run_action_knobs.output_size_budgets;
+        let Some(budget) =
+            budgets.budget_for(action.category().as_str(), action.output_size_budget())
+        else {
+            return Ok(());
+        };
+        if size <= budget {
+            return Ok(());
+        }
+
+        let error = buck2_error::Error::from(OutputSizeBudgetExceeded {
+            action: action.name(),
+            owner: action.owner().to_string(),
+            size: HumanizedBytes::new(size).to_string(),
+            budget: HumanizedBytes::new(budget).to_string(),
+        });
+        if budgets.enforce {
+            Err(error)
+        } else {
+            // A user-configured budget, so warn the user rather than report a soft error.
+            self.events.console_warning(error.to_string());
+            Ok(())
+        }
+    }
+
     pub fn invalidation_tracking_enabled(&self) -> bool {
         self.invalidation_tracking_enabled
     }
+
+    pub fn fs(&self) -> &ArtifactFs {
+        self.command_executor.fs()
+    }
 }
 
 #[cfg(test)]
@@ -11,6 +11,7 @@
 use buck2_common::file_ops::metadata::TrackedFileDigest;
 use buck2_directory::directory::dashmap_directory_interner::DashMapDirectoryInterner;
 use buck2_execute::directory::ActionDirectoryMember;
+use buck2_execute::output_size::OutputSizeBudgets;
 use dice::UserComputationData;
 use dupe::Dupe;
 
@@ -34,6 +35,9 @@ pub struct RunActionKnobs {
     pub deduplicate_get_digests_ttl_calls: bool,
 
     pub re_outputs_required: bool,
+
+    /// Limits on the size of action outputs.
+    pub output_size_budgets: OutputSizeBudgets,
 }
 
 pub trait HasRunActionKnobs {
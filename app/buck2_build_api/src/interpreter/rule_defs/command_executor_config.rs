@@ -16,6 +16,7 @@ use buck2_core::execution_types::executor_config::CacheUploadBehavior;
 use buck2_core::execution_types::executor_config::CommandExecutorConfig;
 use buck2_core::execution_types::executor_config::CommandGenerationOptions;
 use buck2_core::execution_types::executor_config::Executor;
+use buck2_core::execution_types::executor_config::ExternalExecutorOptions;
 use buck2_core::execution_types::executor_config::HybridExecutionLevel;
 use buck2_core::execution_types::executor_config::ImagePackageIdentifier;
 use buck2_core::execution_types::executor_config::LocalExecutorOptions;
@@ -62,6 +63,8 @@ enum CommandExecutorConfigErrors {
     ReCafFbpkgsNotAList(String, String),
     #[error("expected an dict, got `{0}` (type `{1}`)")]
     ReCafFbpkgNotADict(String, String),
+    #[error("`external_executor` cannot be used with `local_enabled` or `remote_enabled`")]
+    ExternalExecutorWithLocalOrRemote,
 }
 
 #[derive(Debug, Display, NoSerialize, ProvidesStaticType, Allocative)]
@@ -104,6 +107,10 @@ pub fn register_command_executor_config(builder: &mut GlobalsBuilder) {
     /// * `remote_execution_gang_workers`: Gang workers for gang scheduling in remote execution
     /// * `remote_execution_custom_image`: Custom Tupperware image for remote execution for this platform
     /// * `meta_internal_extra_params`: Json dict of extra params to pass to RE related to Meta internal infra.
+    /// * `external_executor`: Path to a Unix socket on which an external executor listens.
+    /// Commands are forwarded to it using the protocol in `buck2_external_executor_proto` instead
+    /// of being run locally or on RE. Inputs are materialized and outputs are read back from the
+    /// local project. Requires `local_enabled` and `remote_enabled` to be `False`
     #[starlark(as_type = StarlarkCommandExecutorConfig)]
     fn CommandExecutorConfig<'v>(
         #[starlark(require = named)] local_enabled: bool,
@@ -139,6 +146,7 @@ pub fn register_command_executor_config(builder: &mut GlobalsBuilder) {
         #[starlark(default = NoneOr::None, require = named)] meta_internal_extra_params: NoneOr<
             DictRef<'v>,
         >,
+        #[starlark(default = NoneOr::None, require = named)] external_executor: NoneOr<&str>,
     ) -> starlark::Result<StarlarkCommandExecutorConfig> {
         let command_executor_config = {
             let remote_execution_max_input_files_mebibytes: Option<i32> =
@@ -328,6 +336,19 @@ pub fn register_command_executor_config(builder: &mut GlobalsBuilder) {
                 (None, None, _) => Executor::None,
             };
 
+            let executor = match external_executor.into_option() {
+                Some(_) if local_enabled || remote_enabled => {
+                    return Err(buck2_error::Error::from(
+                        CommandExecutorConfigErrors::ExternalExecutorWithLocalOrRemote,
+                    )
+                    .into());
+                }
+                Some(socket) => Executor::External(ExternalExecutorOptions {
+                    socket: socket.to_owned(),
+                }),
+                None => executor,
+            };
+
             let output_paths_behavior = remote_output_paths
                 .into_option()
                 .map(|s| s.parse())
@@ -107,6 +107,11 @@ pub struct ExternalRunnerTestInfoGen<V: ValueLifetimeless> {
     /// Configuration needed to spawn a new worker. This worker will be used to run every single
     /// command related to test execution, including listing.
     worker: ValueOfUncheckedGeneric<V, FrozenWorkerInfo>,
+
+    /// A command which merges the raw coverage data written by this test into an LCOV report,
+    /// used when running `buck2 test --coverage`. The test runner appends the directory holding
+    /// the raw coverage data and the path to write the report to.
+    coverage_merger: ValueOfUncheckedGeneric<V, Vec<Either<String, FrozenValue>>>,
 }
 
 // NOTE: All the methods here unwrap because we validate at freeze time.
@@ -116,7 +121,14 @@ impl FrozenExternalRunnerTestInfo {
     }
 
     pub fn command<'v>(&self) -> impl Iterator<Item = TestCommandMember<'v>> {
-        unwrap_all(iter_test_command(self.command.get().to_value()))
+        unwrap_all(iter_test_command(self.command.get().to_value(), "command"))
+    }
+
+    pub fn coverage_merger<'v>(&self) -> impl Iterator<Item = TestCommandMember<'v>> {
+        unwrap_all(iter_test_command(
+            self.coverage_merger.get().to_value(),
+            "coverage_merger",
+        ))
     }
 
     pub fn env<'v>(&self) -> impl Iterator<Item = (&'v str, &'v dyn CommandLineArgLike<'v>)> {
@@ -192,7 +204,7 @@ impl FrozenExternalRunnerTestInfo {
         &self,
         visitor: &mut dyn CommandLineArtifactVisitor<'_>,
     ) -> buck2_error::Result<()> {
-        for member in self.command() {
+        for member in self.command().chain(self.coverage_merger()) {
             match member {
                 TestCommandMember::Literal(..) => {}
                 TestCommandMember::Arglike(arglike) => {
@@ -243,6 +255,7 @@ fn iter_value<'v>(value: Value<'v>) -> buck2_error::Result<impl Iterator<Item =
 
 fn iter_test_command<'v>(
     command: Value<'v>,
+    name: &'static str,
 ) -> impl Iterator<Item = buck2_error::Result<TestCommandMember<'v>>> {
     if command.is_none() {
         return Either::Left(Either::Left(empty()));
@@ -251,11 +264,13 @@ fn iter_test_command<'v>(
     let iterable = match iter_value(command) {
         Ok(v) => v,
         Err(e) => {
-            return Either::Left(Either::Right(once(Err(e.context("Invalid `command`")))));
+            return Either::Left(Either::Right(once(Err(
+                e.context(format!("Invalid `{name}`"))
+            ))));
         }
     };
 
-    Either::Right(iterable.map(|item| {
+    Either::Right(iterable.map(move |item| {
         if let Some(s) = item.unpack_str() {
             return Ok(TestCommandMember::Literal(s));
         }
@@ -267,7 +282,7 @@ fn iter_test_command<'v>(
         }
 
         let arglike = ValueAsCommandLineLike::unpack_value_err(item)
-            .with_buck_error_context(|| format!("Invalid item in `command`: {item}"))?
+            .with_buck_error_context(|| format!("Invalid item in `{name}`: {item}"))?
             .0;
 
         Ok(TestCommandMember::Arglike(arglike))
@@ -459,7 +474,11 @@ fn validate_external_runner_test_info<'v, V>(
 where
     V: ValueLike<'v>,
 {
-    check_all(iter_test_command(info.command.get().to_value()))?;
+    check_all(iter_test_command(info.command.get().to_value(), "command"))?;
+    check_all(iter_test_command(
+        info.coverage_merger.get().to_value(),
+        "coverage_merger",
+    ))?;
     check_all(iter_test_env(info.env.get().to_value()))?;
     check_all(iter_opt_str_list(info.labels.get().to_value(), "labels"))?;
     check_all(iter_opt_str_list(
@@ -522,6 +541,7 @@ fn external_runner_test_info_creator(globals: &mut GlobalsBuilder) {
         #[starlark(default = NoneType)] local_resources: Value<'v>,
         #[starlark(default = NoneType)] required_local_resources: Value<'v>,
         #[starlark(default = NoneType)] worker: Value<'v>,
+        #[starlark(default = NoneType)] coverage_merger: Value<'v>,
     ) -> starlark::Result<ExternalRunnerTestInfo<'v>> {
         let res = ExternalRunnerTestInfo {
             test_type: ValueOfUnchecked::new(r#type),
@@ -536,6 +556,7 @@ fn external_runner_test_info_creator(globals: &mut GlobalsBuilder) {
             local_resources: ValueOfUnchecked::new(local_resources),
             required_local_resources: ValueOfUnchecked::new(required_local_resources),
             worker: ValueOfUnchecked::new(worker),
+            coverage_merger: ValueOfUnchecked::new(coverage_merger),
         };
         validate_external_runner_test_info(&res)?;
         Ok(res)
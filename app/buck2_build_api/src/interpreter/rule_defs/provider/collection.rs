@@ -97,6 +97,8 @@ enum ProviderCollectionError {
     },
     #[error("collection {repr} did not receive a `DefaultInfo` provider")]
     CollectionMissingDefaultInfo { repr: String },
+    #[error("aspect returned provider of type `{0}`, which the target already has")]
+    AspectProviderAlreadyPresent(String),
     #[error(
         "requested sub target named `{0}` of target `{1}` is not available. Available subtargets are: `{2:?}`"
     )]
@@ -317,6 +319,34 @@ impl<'v, V: ValueLike<'v>> ProviderCollectionGen<V> {
         Ok(ProviderCollection::<'v> { providers })
     }
 
+    /// Takes a value, e.g. a return from an `aspect()` implementation function, and builds a
+    /// `ProviderCollection` from it and the providers of `base`.
+    ///
+    /// An error is returned if:
+    ///  - `value` is not a list
+    ///  - Two instances of the same provider are provided
+    ///  - A provider is already in `base`
+    pub fn try_from_value_with_base(
+        value: Value<'v>,
+        base: FrozenValueTyped<'v, FrozenProviderCollection>,
+    ) -> buck2_error::Result<ProviderCollection<'v>> {
+        let added = Self::try_from_value_impl(value)?;
+
+        let mut providers = SmallMap::with_capacity(base.providers.len() + added.len());
+        for (id, value) in &base.providers {
+            providers.insert(id.dupe(), value.to_value());
+        }
+        for (id, value) in added {
+            if providers.contains_key(&id) {
+                return Err(
+                    ProviderCollectionError::AspectProviderAlreadyPresent(id.name.clone()).into(),
+                );
+            }
+            providers.insert(id, value);
+        }
+        Ok(ProviderCollection::<'v> { providers })
+    }
+
     /// Common implementation of `[]`, `in`, and `.get`.
     fn get_impl(
         &self,
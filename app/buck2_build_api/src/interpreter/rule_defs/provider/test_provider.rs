@@ -37,6 +37,7 @@ pub trait TestProvider {
         target: ConfiguredTarget,
         executor: Arc<dyn TestExecutor + 'exec>,
         working_dir_cell: CellName,
+        coverage: bool,
     ) -> BoxFuture<'exec, buck2_error::Result<()>>;
 }
 
@@ -57,23 +58,26 @@ impl TestProvider for FrozenExternalRunnerTestInfo {
         target: ConfiguredTarget,
         executor: Arc<dyn TestExecutor + 'exec>,
         working_dir_cell: CellName,
+        coverage: bool,
     ) -> BoxFuture<'exec, buck2_error::Result<()>> {
         let mut handle_index = 0;
 
-        let command = self
-            .command()
-            .map(|c| match c {
-                TestCommandMember::Literal(l) => ExternalRunnerSpecValue::Verbatim(l.to_owned()),
-                TestCommandMember::Arglike(_) => {
-                    // We assign indices to handles, which Tpx can use to reference them later.
-                    // We don't count literals in here since Tpx won't use handles to
-                    // communicate those (it would just use a literal instead).
-                    let handle = ExternalRunnerSpecValue::ArgHandle(handle_index.into());
-                    handle_index += 1;
-                    handle
-                }
-            })
-            .collect();
+        let mut to_spec_value = |c: TestCommandMember<'_>| match c {
+            TestCommandMember::Literal(l) => ExternalRunnerSpecValue::Verbatim(l.to_owned()),
+            TestCommandMember::Arglike(_) => {
+                // We assign indices to handles, which Tpx can use to reference them later.
+                // We don't count literals in here since Tpx won't use handles to
+                // communicate those (it would just use a literal instead).
+                let handle = ExternalRunnerSpecValue::ArgHandle(handle_index.into());
+                handle_index += 1;
+                handle
+            }
+        };
+
+        let command = self.command().map(&mut to_spec_value).collect();
+        // The merger's handles continue the numbering of the command's, matching the order in
+        // which the orchestrator resolves them.
+        let coverage_merger = self.coverage_merger().map(&mut to_spec_value).collect();
 
         let env = self
             .env()
@@ -100,6 +104,8 @@ impl TestProvider for FrozenExternalRunnerTestInfo {
                 .map(str::to_owned)
                 .or(package_oncall),
             working_dir_cell,
+            coverage,
+            coverage_merger,
         };
 
         async move { executor.external_runner_spec(spec).await }.boxed()
@@ -168,6 +168,7 @@ async fn test_analysis_calculation() -> buck2_error::Result<()> {
             None,
             false,
             false,
+            false,
             None,
             Arc::new(ConcurrentTargetLabelInterner::default()),
         )?,
@@ -37,6 +37,7 @@ fn test_construction() -> buck2_error::Result<()> {
             ExternalRunnerTestInfo(type = "foo", labels = ("foo",))
             ExternalRunnerTestInfo(type = "foo", use_project_relative_paths = True)
             ExternalRunnerTestInfo(type = "foo", run_from_project_root = True)
+            ExternalRunnerTestInfo(type = "foo", coverage_merger = ["merge", cmd_args()])
             ExternalRunnerTestInfo(type = "foo", local_resources = {"bar": None}, required_local_resources = [RequiredTestLocalResource("bar", listing=False)])
         "#
     );
@@ -88,6 +89,16 @@ fn test_validation() -> buck2_error::Result<()> {
         "`command`",
     );
 
+    tester.run_starlark_bzl_test_expecting_error(
+        indoc!(
+            r#"
+        def test():
+            ExternalRunnerTestInfo(type = "foo", coverage_merger = [123])
+        "#
+        ),
+        "`coverage_merger`",
+    );
+
     tester.run_starlark_bzl_test_expecting_error(
         indoc!(
             r#"
@@ -214,6 +214,8 @@ pub struct BuildSignalsContext {
     pub metadata: HashMap<String, String>,
     pub isolation_prefix: FileNameBuf,
     pub early_command_timing: EarlyCommandTiming,
+    /// Whether to include the whole graph in `BuildGraphExecutionInfo`.
+    pub log_graph: bool,
 }
 
 /// Created along with the BuildSignalsInstaller (ideally, BuildSignalsInstaller's definition would
@@ -268,10 +270,18 @@ where
     result
 }
 
+/// Whether the graph used to compute the critical path should be logged (`buck2.log_critical_path_graph`).
+#[derive(Copy, Clone, Dupe)]
+struct LogCriticalPathGraph(bool);
+
 pub trait HasCriticalPathBackend {
     fn set_critical_path_backend(&mut self, backend: CriticalPathBackendName);
 
     fn get_critical_path_backend(&self) -> CriticalPathBackendName;
+
+    fn set_log_critical_path_graph(&mut self, log_graph: bool);
+
+    fn get_log_critical_path_graph(&self) -> bool;
 }
 
 impl HasCriticalPathBackend for UserComputationData {
@@ -285,6 +295,14 @@ impl HasCriticalPathBackend for UserComputationData {
             .get::<CriticalPathBackendName>()
             .expect("CriticalPathBackendName should be set")
     }
+
+    fn set_log_critical_path_graph(&mut self, log_graph: bool) {
+        self.data.set(LogCriticalPathGraph(log_graph));
+    }
+
+    fn get_log_critical_path_graph(&self) -> bool {
+        self.data.get::<LogCriticalPathGraph>().is_ok_and(|v| v.0)
+    }
 }
 
 #[cfg(test)]
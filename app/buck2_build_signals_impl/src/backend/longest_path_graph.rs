@@ -43,6 +43,8 @@ use crate::backend::backend::BuildListenerBackend;
 pub(crate) struct LongestPathGraphBackend {
     builder: Result<GraphBuilder<NodeKey, NodeData>, CriticalPathError>,
     top_level_targets: Vec<TopLevelTarget>,
+    /// Whether to return the whole graph in the `BuildInfo`.
+    log_graph: bool,
 }
 
 /// Represents nodes that block us "seeing" other parts of the graph until they finish evaluating.
@@ -52,10 +54,11 @@ struct TopLevelTarget {
 }
 
 impl LongestPathGraphBackend {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(log_graph: bool) -> Self {
         Self {
             builder: Ok(GraphBuilder::new()),
             top_level_targets: Vec::new(),
+            log_graph,
         }
     }
 }
@@ -187,6 +190,12 @@ impl BuildListenerBackend for LongestPathGraphBackend {
                 .unwrap_or(u64::MAX)
         });
 
+        let graph_proto = if self.log_graph {
+            critical_path_graph_proto(&graph, &keys, &data, &durations)
+        } else {
+            None
+        };
+
         let (slowest_path, cp_res) = std::thread::scope(|s| {
             let cp = s.spawn(|| {
                 compute_critical_paths(&graph, &keys, &data, durations, &self.top_level_targets)
@@ -207,6 +216,7 @@ impl BuildListenerBackend for LongestPathGraphBackend {
             num_nodes: graph.vertices_count() as _,
             num_edges: graph.edges_count() as _,
             top_level_targets: critical_path_for_top_level_targets,
+            graph: graph_proto,
         })
     }
 
@@ -294,6 +304,46 @@ fn compute_critical_paths(
     ))
 }
 
+/// Serializes the graph so that the critical path can be recomputed offline (see
+/// `buck2 log critical-path --what-if`). Nodes are emitted in topological order. Returns `None` if
+/// the graph has a cycle, in which case we can't compute a critical path either.
+fn critical_path_graph_proto(
+    graph: &Graph,
+    keys: &VertexKeys<NodeKey>,
+    data: &VertexData<NodeData>,
+    durations: &VertexData<u64>,
+) -> Option<buck2_data::CriticalPathGraph> {
+    // `topo_sort` puts dependents before their dependencies, so we walk it backwards.
+    let topo_order = graph.topo_sort().ok()?;
+
+    let mut index = graph.allocate_vertex_data(0u32);
+    for (i, vertex_idx) in topo_order.iter().rev().enumerate() {
+        index[*vertex_idx] = i as u32;
+    }
+
+    let nodes = topo_order
+        .iter()
+        .rev()
+        .map(|vertex_idx| {
+            let vertex_idx = *vertex_idx;
+            buck2_data::CriticalPathGraphNode {
+                deps: graph.iter_edges(vertex_idx).map(|d| index[d]).collect(),
+                duration_us: durations[vertex_idx],
+                entry: Some(buck2_data::CriticalPathEntry2 {
+                    entry: Some(
+                        keys[vertex_idx]
+                            .dupe()
+                            .into_critical_path_entry_data(&data[vertex_idx].extra_data),
+                    ),
+                    ..Default::default()
+                }),
+            }
+        })
+        .collect();
+
+    Some(buck2_data::CriticalPathGraph { nodes })
+}
+
 /// Computes the "slowest path" where each node's predecessor is the dependency that finished last.
 /// This differs from critical path where predecessors have the greatest critical path length.
 /// The slowest path makes waiting time directly attributable to what a node is immediately waiting on.
@@ -486,7 +486,8 @@ impl DeferredBuildSignals for DeferredBuildSignalsImpl {
     ) -> Box<dyn FinishBuildSignals> {
         let handle = match backend {
             CriticalPathBackendName::LongestPathGraph => {
-                start_backend(events, self.receiver, LongestPathGraphBackend::new(), ctx)
+                let backend = LongestPathGraphBackend::new(ctx.log_graph);
+                start_backend(events, self.receiver, backend, ctx)
             }
             CriticalPathBackendName::Logging => start_backend(
                 events.dupe(),
@@ -582,6 +583,7 @@ where
             num_nodes,
             num_edges,
             top_level_targets,
+            graph,
         } = self.backend.finish()?;
 
         let critical_path2 = critical_path.into_critical_path_proto(&ctx.early_command_timing, now);
@@ -607,6 +609,7 @@ where
             num_edges,
             backend_name: Some(T::name().to_string()),
             top_level_targets,
+            graph,
         });
         Ok(())
     }
@@ -758,6 +761,8 @@ pub(crate) struct BuildInfo {
     num_edges: u64,
     /// Critical path for top level targets
     top_level_targets: Vec<(ConfiguredTargetLabel, Duration)>,
+    /// The whole graph, if requested via `buck2.log_critical_path_graph`.
+    graph: Option<buck2_data::CriticalPathGraph>,
 }
 
 /// Entry in a detailed critical path, including metadata about timing and dependencies.
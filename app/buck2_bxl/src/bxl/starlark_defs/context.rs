@@ -77,6 +77,7 @@ pub(crate) mod fs;
 pub(crate) mod lifetime_erase;
 pub(crate) mod methods;
 pub(crate) mod output;
+pub(crate) mod run_local;
 pub(crate) mod starlark_async;
 
 /// Errors that can occur when accessing some field of `BxlContext` for dynamic action or anon target.
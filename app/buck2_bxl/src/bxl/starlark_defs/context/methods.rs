@@ -11,6 +11,7 @@
 use std::iter;
 use std::sync::Arc;
 
+use buck2_build_api::interpreter::rule_defs::cmd_args::value_as::ValueAsCommandLineLike;
 use buck2_build_api::interpreter::rule_defs::context::AnalysisActions;
 use buck2_cli_proto::build_request::Materializations;
 use buck2_cli_proto::build_request::Uploads;
@@ -64,6 +65,7 @@ use crate::bxl::starlark_defs::context::anon_target::run_anon_target_promises;
 use crate::bxl::starlark_defs::context::build;
 use crate::bxl::starlark_defs::context::fs::BxlFilesystem;
 use crate::bxl::starlark_defs::context::output::StarlarkOutputStream;
+use crate::bxl::starlark_defs::context::run_local;
 use crate::bxl::starlark_defs::cquery::StarlarkCQueryCtx;
 use crate::bxl::starlark_defs::event::StarlarkUserEventParser;
 use crate::bxl::starlark_defs::lazy_ctx::StarlarkLazyCtx;
@@ -767,6 +769,43 @@ pub(crate) fn bxl_context_methods(builder: &mut MethodsBuilder) {
         })
     }
 
+    /// Runs a command on the local machine and returns its stdout as a string.
+    ///
+    /// `cmd` is a command line like the one accepted by `ctx.bxl_actions().actions.run()`: a string,
+    /// an artifact, a `cmd_args`, or a list of those. Artifacts in `cmd`, and in the optional
+    /// `inputs` (for files the command reads that are not on its command line), are built and
+    /// materialized before the command runs. `env` sets additional environment variables.
+    ///
+    /// The command runs from the project root, through the local executor, with only a minimal
+    /// set of environment variables (such as `PATH` and `HOME`) inherited from the daemon. Its
+    /// output is cached per BXL function: the command only runs again if its command line, `env`,
+    /// or the contents of one of its inputs change. A command that exits with a non-zero status fails the BXL
+    /// function.
+    ///
+    /// Artifacts declared by `ctx.bxl_actions()` in this BXL function cannot be used, since they
+    /// are only built after the function returns, and `cmd` cannot contain outputs (`as_output()`):
+    /// use `ctx.bxl_actions().actions.run()` for commands that produce artifacts.
+    ///
+    /// Sample usage:
+    /// ```python
+    /// def _impl_resource_dir(ctx):
+    ///     resource_dir = ctx.run_local(["clang", "-print-resource-dir"]).strip()
+    ///     ctx.output.print(resource_dir)
+    /// ```
+    fn run_local<'v>(
+        this: &'v BxlContext<'v>,
+        #[starlark(require = pos)] cmd: ValueAsCommandLineLike<'v>,
+        #[starlark(require = named, default = UnpackList::default())] inputs: UnpackList<
+            ValueAsCommandLineLike<'v>,
+        >,
+        #[starlark(require = named, default = SmallMap::new())] env: SmallMap<String, String>,
+        eval: &mut Evaluator<'v, '_, '_>,
+    ) -> starlark::Result<String> {
+        let inputs = inputs.items.into_iter().map(|input| input.0).collect();
+        let env = env.into_iter().collect();
+        Ok(run_local::run_local(this, cmd.0, inputs, env, eval)?.to_string())
+    }
+
     /// Emits a user-defined instant event, taking in a required string id and a metadata dictionary where the
     /// keys are strings, and values are either strings, bools, or ints. The id is user-supplied, and used to
     /// identify the instant events in the event logs more easily.
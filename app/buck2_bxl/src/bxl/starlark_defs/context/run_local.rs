@@ -0,0 +1,335 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! Implementation of `ctx.run_local()`: run a command on the local machine while the BXL
+//! function is being evaluated, and hand its stdout back to the script.
+
+use std::sync::Arc;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use buck2_build_api::actions::artifact::get_artifact_fs::GetArtifactFs;
+use buck2_build_api::actions::execute::dice_data::CommandExecutorResponse;
+use buck2_build_api::actions::execute::dice_data::DiceHasCommandExecutor;
+use buck2_build_api::actions::impls::run_action_knobs::HasRunActionKnobs;
+use buck2_build_api::artifact_groups::ArtifactGroup;
+use buck2_build_api::artifact_groups::calculation::ArtifactGroupCalculation;
+use buck2_build_api::bxl::types::BxlFunctionLabel;
+use buck2_build_api::interpreter::rule_defs::cmd_args::CommandLineArgLike;
+use buck2_build_api::interpreter::rule_defs::cmd_args::DefaultCommandLineContext;
+use buck2_build_api::interpreter::rule_defs::cmd_args::SimpleCommandLineArtifactVisitor;
+use buck2_build_signals::env::WaitingData;
+use buck2_common::events::HasEvents;
+use buck2_common::file_ops::metadata::FileDigest;
+use buck2_common::liveliness_observer::NoopLivelinessObserver;
+use buck2_core::execution_types::executor_config::CommandExecutorConfig;
+use buck2_core::execution_types::executor_config::CommandGenerationOptions;
+use buck2_core::execution_types::executor_config::Executor;
+use buck2_core::execution_types::executor_config::LocalExecutorOptions;
+use buck2_core::execution_types::executor_config::PathSeparatorKind;
+use buck2_data::ToProtoMessage;
+use buck2_execute::artifact::fs::ExecutorFs;
+use buck2_execute::digest_config::HasDigestConfig;
+use buck2_execute::execute::cache_uploader::NoOpCacheUploader;
+use buck2_execute::execute::claim::MutexClaimManager;
+use buck2_execute::execute::command_executor::CommandExecutor;
+use buck2_execute::execute::environment_inheritance::EnvironmentInheritance;
+use buck2_execute::execute::manager::CommandExecutionManager;
+use buck2_execute::execute::prepared::NoOpCommandOptionalExecutor;
+use buck2_execute::execute::prepared::PreparedCommand;
+use buck2_execute::execute::request::CommandExecutionInput;
+use buck2_execute::execute::request::CommandExecutionPaths;
+use buck2_execute::execute::request::CommandExecutionRequest;
+use buck2_execute::execute::result::CommandExecutionReport;
+use buck2_execute::execute::result::CommandExecutionResult;
+use buck2_execute::execute::result::CommandExecutionStatus;
+use buck2_execute::execute::target::CommandExecutionTarget;
+use dice::DiceComputations;
+use dice::Key;
+use dice_futures::cancellation::CancellationContext;
+use dupe::Dupe;
+use futures::FutureExt;
+use fxhash::FxHashMap;
+use indexmap::IndexSet;
+use indexmap::indexset;
+use itertools::Itertools;
+use sorted_vector_map::SortedVectorMap;
+use starlark::eval::Evaluator;
+
+use crate::bxl::starlark_defs::context::BxlContext;
+
+#[derive(buck2_error::Error, Debug)]
+#[buck2(tag = ActionCommandFailure)]
+enum RunLocalError {
+    #[error("`run_local` was called with an empty command line")]
+    #[buck2(tag = Input)]
+    EmptyCommand,
+    #[error(
+        "`run_local` commands can't have outputs, but the command line contains `{0}`. Use `ctx.bxl_actions().actions.run()` to run commands that produce artifacts"
+    )]
+    #[buck2(tag = Input)]
+    OutputInCommand(String),
+    #[error("Command `{0}` failed with exit code {1}, stdout:\n{2}\nstderr:\n{3}")]
+    Failed(String, i32, String, String),
+    #[error("Command `{0}` timed out after {1}s, stdout:\n{2}\nstderr:\n{3}")]
+    TimedOut(String, u64, String, String),
+    #[error("Command `{0}` was cancelled")]
+    #[buck2(tag = Tier0)]
+    Cancelled(String),
+    #[error("Output of command `{0}` is not valid UTF-8")]
+    #[buck2(tag = Input)]
+    NotUtf8(String),
+}
+
+/// A command to run locally, with its command line already expanded. Keyed on the inputs and their
+/// digests too, so that the command runs again whenever one of them (e.g. the tool binary) changes,
+/// even if the command line stays the same.
+#[derive(Clone, Debug, derive_more::Display, Eq, Hash, PartialEq, Allocative)]
+#[display("{}", cmd.iter().join(" "))]
+struct RunLocalKey {
+    /// The BXL function running the command, for attribution in events.
+    owner: BxlFunctionLabel,
+    cmd: Vec<String>,
+    env: SortedVectorMap<String, String>,
+    inputs: Vec<ArtifactGroup>,
+    /// The digests of the input artifacts, in the order they were built. `None` for symlinks.
+    input_digests: Vec<Option<FileDigest>>,
+}
+
+#[async_trait]
+impl Key for RunLocalKey {
+    type Value = buck2_error::Result<Arc<str>>;
+
+    async fn compute(
+        &self,
+        ctx: &mut DiceComputations,
+        cancellation: &CancellationContext,
+    ) -> Self::Value {
+        let inputs = ctx
+            .try_compute_join(&self.inputs, |ctx, group| {
+                async move { ctx.ensure_artifact_group(group).await }.boxed()
+            })
+            .await?
+            .into_iter()
+            .map(|values| CommandExecutionInput::Artifact(Box::new(values)))
+            .collect();
+
+        let artifact_fs = ctx.get_artifact_fs().await?;
+        let digest_config = ctx.global_data().get_digest_config();
+        let paths = CommandExecutionPaths::new(
+            inputs,
+            indexset![],
+            &artifact_fs,
+            digest_config,
+            ctx.per_transaction_data()
+                .get_run_action_knobs()
+                .action_paths_interner
+                .as_ref(),
+        )?;
+        let request =
+            CommandExecutionRequest::new(vec![], self.cmd.clone(), paths, self.env.clone())
+                .with_local_environment_inheritance(EnvironmentInheritance::run_local_allowlist());
+
+        let executor = local_executor(ctx, &artifact_fs).await?;
+        let prepared_action = executor.prepare_action(&request, digest_config, false)?;
+        let prepared_command = PreparedCommand {
+            target: self as _,
+            request: &request,
+            prepared_action: &prepared_action,
+            digest_config,
+        };
+        let manager = CommandExecutionManager::new(
+            Box::new(MutexClaimManager::new()),
+            ctx.per_transaction_data().get_dispatcher().dupe(),
+            NoopLivelinessObserver::create(),
+            WaitingData::new(),
+        );
+
+        let CommandExecutionResult {
+            report:
+                CommandExecutionReport {
+                    std_streams,
+                    exit_code,
+                    status,
+                    ..
+                },
+            ..
+        } = executor
+            .exec_cmd(manager, &prepared_command, cancellation)
+            .await;
+
+        let std_streams = std_streams.into_bytes().await?;
+        let stdout = || String::from_utf8_lossy(&std_streams.stdout).into_owned();
+        let stderr = || String::from_utf8_lossy(&std_streams.stderr).into_owned();
+        match status {
+            CommandExecutionStatus::Success { .. } => {}
+            CommandExecutionStatus::Failure { .. }
+            | CommandExecutionStatus::WorkerFailure { .. } => {
+                return Err(RunLocalError::Failed(
+                    self.to_string(),
+                    exit_code.unwrap_or(1),
+                    stdout(),
+                    stderr(),
+                )
+                .into());
+            }
+            CommandExecutionStatus::TimedOut { duration, .. } => {
+                return Err(RunLocalError::TimedOut(
+                    self.to_string(),
+                    duration.as_secs(),
+                    stdout(),
+                    stderr(),
+                )
+                .into());
+            }
+            CommandExecutionStatus::Error { error, .. } => return Err(error),
+            CommandExecutionStatus::Cancelled { .. } => {
+                return Err(RunLocalError::Cancelled(self.to_string()).into());
+            }
+        }
+
+        match String::from_utf8(std_streams.stdout) {
+            Ok(stdout) => Ok(Arc::from(stdout)),
+            Err(_) => Err(RunLocalError::NotUtf8(self.to_string()).into()),
+        }
+    }
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        match (x, y) {
+            (Ok(x), Ok(y)) => x == y,
+            _ => false,
+        }
+    }
+}
+
+impl CommandExecutionTarget for RunLocalKey {
+    fn re_action_key(&self) -> String {
+        String::new()
+    }
+
+    fn re_affinity_key(&self) -> String {
+        String::new()
+    }
+
+    fn as_proto_action_key(&self) -> buck2_data::ActionKey {
+        buck2_data::ActionKey {
+            id: Default::default(),
+            owner: Some(buck2_data::action_key::Owner::BxlKey(
+                buck2_data::BxlFunctionKey {
+                    label: Some(self.owner.as_proto()),
+                },
+            )),
+            key: self.to_string(),
+        }
+    }
+
+    fn as_proto_action_name(&self) -> buck2_data::ActionName {
+        buck2_data::ActionName {
+            category: "bxl_run_local".to_owned(),
+            identifier: self.cmd.first().cloned().unwrap_or_default(),
+        }
+    }
+}
+
+/// A local executor with no action cache: the result is cached by DICE instead, for as long as
+/// the inputs don't change.
+async fn local_executor(
+    ctx: &mut DiceComputations<'_>,
+    artifact_fs: &buck2_core::fs::artifact_path_resolver::ArtifactFs,
+) -> buck2_error::Result<CommandExecutor> {
+    let executor_config = CommandExecutorConfig {
+        executor: Executor::Local(LocalExecutorOptions::default()),
+        options: CommandGenerationOptions {
+            path_separator: PathSeparatorKind::system_default(),
+            output_paths_behavior: Default::default(),
+            use_bazel_protocol_remote_persistent_workers: false,
+        },
+    };
+    let CommandExecutorResponse {
+        executor, platform, ..
+    } = ctx.get_command_executor_from_dice(&executor_config).await?;
+    Ok(CommandExecutor::new(
+        executor,
+        Arc::new(NoOpCommandOptionalExecutor {}),
+        Arc::new(NoOpCommandOptionalExecutor {}),
+        Arc::new(NoOpCacheUploader {}),
+        artifact_fs.clone(),
+        executor_config.options,
+        platform,
+    ))
+}
+
+/// Builds the inputs of `cmd` and `inputs`, expands `cmd`, and runs it locally.
+pub(crate) fn run_local<'v>(
+    ctx: &'v BxlContext<'v>,
+    cmd: &'v dyn CommandLineArgLike<'v>,
+    inputs: Vec<&'v dyn CommandLineArgLike<'v>>,
+    env: SortedVectorMap<String, String>,
+    eval: &mut Evaluator<'v, '_, '_>,
+) -> buck2_error::Result<Arc<str>> {
+    let mut visitor = SimpleCommandLineArtifactVisitor::new();
+    cmd.visit_artifacts(&mut visitor)?;
+    if let Some(output) = visitor.declared_outputs.first() {
+        return Err(RunLocalError::OutputInCommand(output.to_string()).into());
+    }
+    if let Some(output) = visitor.frozen_outputs.first() {
+        return Err(RunLocalError::OutputInCommand(output.to_string()).into());
+    }
+    for input in inputs {
+        input.visit_artifacts(&mut visitor)?;
+    }
+    let inputs: IndexSet<ArtifactGroup> = visitor.inputs;
+
+    let ensured = ctx.via_dice(eval, |dice| {
+        dice.via(|dice| {
+            async move {
+                dice.try_compute_join(&inputs, |dice, group| {
+                    async move { dice.ensure_artifact_group(group).await }.boxed()
+                })
+                .await
+                .map(|values| (inputs, values))
+            }
+            .boxed_local()
+        })
+    });
+    let (inputs, ensured) = ensured?;
+
+    let artifact_path_mapping: FxHashMap<_, _> = ensured
+        .iter()
+        .flat_map(|v| v.iter())
+        .map(|(a, v)| (a, v.content_based_path_hash()))
+        .collect();
+    let input_digests = ensured
+        .iter()
+        .flat_map(|v| v.iter())
+        .map(|(_, v)| v.digest().cloned())
+        .collect();
+    let executor_fs = ExecutorFs::new(ctx.artifact_fs(), PathSeparatorKind::system_default());
+    let mut expanded = Vec::new();
+    cmd.add_to_command_line(
+        &mut expanded,
+        &mut DefaultCommandLineContext::new(&executor_fs),
+        &artifact_path_mapping,
+    )?;
+    if expanded.is_empty() {
+        return Err(RunLocalError::EmptyCommand.into());
+    }
+
+    let key = RunLocalKey {
+        owner: ctx.current_bxl().label().clone(),
+        cmd: expanded,
+        env,
+        inputs: inputs.into_iter().collect(),
+        input_digests,
+    };
+    ctx.via_dice(eval, |dice| {
+        dice.via(|dice| async move { dice.compute(&key).await? }.boxed_local())
+    })
+}
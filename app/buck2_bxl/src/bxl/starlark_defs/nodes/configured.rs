@@ -17,6 +17,7 @@ use std::fmt;
 use std::path::Path;
 
 use allocative::Allocative;
+use buck2_analysis::analysis::aspect::get_aspect_deps;
 use buck2_analysis::analysis::calculation::get_dep_analysis;
 use buck2_analysis::analysis::calculation::resolve_queries;
 use buck2_analysis::analysis::env::RuleAnalysisAttrResolutionContext;
@@ -525,9 +526,14 @@ fn configured_target_node_value_methods(builder: &mut MethodsBuilder) {
             ctx.via(|dice_ctx| resolve_queries(dice_ctx, configured_node).boxed_local())
         })?;
 
+        let aspect_results = ctx.via_dice(eval, |ctx| {
+            ctx.via(|dice_ctx| get_aspect_deps(configured_node, dice_ctx).boxed_local())
+        })?;
+
         let resolution_ctx = RuleAnalysisAttrResolutionContext {
             module: eval.module(),
             dep_analysis_results: get_deps_from_analysis_results(dep_analysis?)?,
+            aspect_results,
             query_results,
             execution_platform_resolution: configured_node.execution_platform_resolution().clone(),
         };
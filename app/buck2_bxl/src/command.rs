@@ -12,6 +12,7 @@ use std::collections::HashMap;
 use std::io::Write;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use buck2_build_api::actions::artifact::get_artifact_fs::GetArtifactFs;
@@ -43,21 +44,26 @@ use buck2_data::BxlEnsureArtifactsEnd;
 use buck2_data::BxlEnsureArtifactsStart;
 use buck2_error::BuckErrorContext;
 use buck2_events::dispatch::get_dispatcher;
+use buck2_events::dispatch::span_async;
 use buck2_interpreter::load_module::InterpreterCalculation;
 use buck2_interpreter::parse_import::ParseImportOptions;
 use buck2_interpreter::parse_import::RelativeImports;
 use buck2_interpreter::parse_import::parse_import_with_config;
 use buck2_interpreter::paths::module::StarlarkModulePath;
 use buck2_server_ctx::bxl::GetBxlStreamingTracker;
+use buck2_server_ctx::commands::command_end_ext;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::ctx::ServerCommandDiceContext;
 use buck2_server_ctx::global_cfg_options::global_cfg_options_from_client_context;
 use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
 use buck2_server_ctx::template::ServerCommandTemplate;
 use buck2_server_ctx::template::run_server_command;
 use dice::DiceComputations;
+use dice::DiceEquality;
 use dice::DiceTransaction;
 use dupe::Dupe;
 use dupe::IterDupedExt;
+use dupe::OptionDupedExt;
 use futures::FutureExt;
 use futures::StreamExt;
 use futures::stream::FuturesUnordered;
@@ -74,12 +80,60 @@ use crate::bxl::key::BxlKey;
 use crate::bxl::starlark_defs::cli_args::CliArgValue;
 use crate::bxl::streaming_output_writer::StreamingOutputWriter;
 
+/// How often `buck2 bxl --watch` checks for file changes.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
 pub(crate) async fn bxl_command(
     ctx: &dyn ServerCommandContextTrait,
     partial_result_dispatcher: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
     req: BxlRequest,
 ) -> buck2_error::Result<BxlResponse> {
-    run_server_command(BxlServerCommand { req }, ctx, partial_result_dispatcher).await
+    if req.watch {
+        bxl_watch_command(ctx, partial_result_dispatcher, req).await
+    } else {
+        run_server_command(BxlServerCommand { req }, ctx, partial_result_dispatcher).await
+    }
+}
+
+/// `buck2 bxl --watch`: like `run_server_command`, but every `WATCH_POLL_INTERVAL` a new DICE
+/// transaction is started (which syncs file changes from the file watcher), until the client
+/// disconnects. The BXL function is only evaluated again if the sync changed anything, DICE only
+/// recomputes it if a file it read or a node it requested was invalidated, and outputs are only
+/// emitted when it was recomputed.
+async fn bxl_watch_command(
+    server_ctx: &dyn ServerCommandContextTrait,
+    mut partial_result_dispatcher: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
+    req: BxlRequest,
+) -> buck2_error::Result<BxlResponse> {
+    let command = BxlServerCommand { req };
+    let start_event = server_ctx
+        .command_start_event(command.start_event().into())
+        .await?;
+
+    span_async(start_event, async {
+        let result = command
+            .watch(server_ctx, &mut partial_result_dispatcher.as_writer())
+            .await;
+        let end_event = command_end_ext(&result, command.end_event(&result), |_| None);
+        (result, end_event)
+    })
+    .await
+}
+
+/// What the previous `--watch` iteration produced.
+enum WatchState {
+    Initial,
+    Result(Arc<BxlResult>),
+    Error(String),
+}
+
+enum WatchIteration {
+    /// Neither the result nor the error changed since the previous iteration.
+    Unchanged,
+    /// The BXL function was evaluated again.
+    Changed(Arc<BxlResult>),
+    /// `--help` was passed to the BXL function, so there is nothing to watch.
+    Help(BxlResponse),
 }
 
 struct BxlServerCommand {
@@ -139,6 +193,116 @@ impl BxlServerCommand {
 
         let bxl_eval_result = self.eval_bxl(&bxl_cmd_ctx, &mut dice_ctx, bxl_args).await;
 
+        self.finish(server_ctx, &bxl_cmd_ctx, &mut dice_ctx, bxl_eval_result)
+            .await
+    }
+
+    /// Evaluate the BXL function whenever DICE invalidates it, until the client disconnects.
+    /// Errors are printed, and the next change evaluates the function again.
+    async fn watch(
+        &self,
+        server_ctx: &dyn ServerCommandContextTrait,
+        stdout: &mut (impl Write + Send),
+    ) -> buck2_error::Result<BxlResponse> {
+        let mut state = WatchState::Initial;
+        // The DICE version of the previous iteration. DICE only bumps the version when an
+        // injected key changed or a file was invalidated by the file watcher, so if it is the
+        // same there is nothing to evaluate again.
+        let mut evaluated_version: Option<DiceEquality> = None;
+        loop {
+            let iteration = server_ctx
+                .with_dice_ctx(|server_ctx, mut dice_ctx| {
+                    let state = &state;
+                    let evaluated_version = &mut evaluated_version;
+                    async move {
+                        let version = dice_ctx.equality_token();
+                        if *evaluated_version == Some(version) {
+                            return Ok(WatchIteration::Unchanged);
+                        }
+                        *evaluated_version = Some(version);
+                        self.watch_iteration(server_ctx, &mut dice_ctx, state).await
+                    }
+                })
+                .await;
+            match iteration {
+                Ok(WatchIteration::Unchanged) => {}
+                Ok(WatchIteration::Help(response)) => return Ok(response),
+                Ok(WatchIteration::Changed(bxl_result)) => {
+                    state = WatchState::Result(bxl_result);
+                }
+                Err(e) => {
+                    let message = format!("{e:?}");
+                    if !matches!(&state, WatchState::Error(previous) if *previous == message) {
+                        writeln!(server_ctx.stderr()?, "BXL FAILED\n{message}")?;
+                        state = WatchState::Error(message);
+                    }
+                }
+            }
+            stdout.flush()?;
+            tokio::time::sleep(WATCH_POLL_INTERVAL).await;
+        }
+    }
+
+    async fn watch_iteration(
+        &self,
+        server_ctx: &dyn ServerCommandContextTrait,
+        dice_ctx: &mut DiceTransaction,
+        state: &WatchState,
+    ) -> buck2_error::Result<WatchIteration> {
+        let bxl_cmd_ctx = self
+            .parse_and_validate_request(server_ctx, dice_ctx)
+            .await?;
+
+        let bxl_args = match self.resolve_cli_args(&bxl_cmd_ctx, dice_ctx).await? {
+            BxlResolvedCliArgs::Resolved(bxl_args) => Arc::new(bxl_args),
+            BxlResolvedCliArgs::Help => {
+                return Ok(WatchIteration::Help(BxlResponse {
+                    project_root: bxl_cmd_ctx.project_root,
+                    errors: Vec::new(),
+                    serialized_build_report: None,
+                }));
+            }
+        };
+
+        let bxl_eval_result = self.eval_bxl(&bxl_cmd_ctx, dice_ctx, bxl_args).await;
+        match (&bxl_eval_result, state) {
+            (Ok(bxl_result), WatchState::Result(previous)) if Arc::ptr_eq(bxl_result, previous) => {
+                return Ok(WatchIteration::Unchanged);
+            }
+            (Err(e), WatchState::Error(previous)) if format!("{:?}", e.error) == *previous => {
+                return Ok(WatchIteration::Unchanged);
+            }
+            _ => {}
+        }
+        if !matches!(state, WatchState::Initial) {
+            writeln!(server_ctx.stderr()?, "Files changed, BXL evaluated again")?;
+        }
+
+        let bxl_result = bxl_eval_result.as_ref().ok().duped();
+        // Fails if the evaluation failed.
+        let response = self
+            .finish(server_ctx, &bxl_cmd_ctx, dice_ctx, bxl_eval_result)
+            .await?;
+        let bxl_result = bxl_result.internal_error("BXL evaluation should have succeeded")?;
+        if response.errors.is_empty() {
+            writeln!(server_ctx.stderr()?, "BXL SUCCEEDED")?;
+        } else {
+            writeln!(server_ctx.stderr()?, "BXL FAILED")?;
+            for error in &response.errors {
+                writeln!(server_ctx.stderr()?, "{}", error.message)?;
+            }
+        }
+        Ok(WatchIteration::Changed(bxl_result))
+    }
+
+    /// Emit the outputs of an evaluated BXL function, and materialize its artifacts.
+    async fn finish(
+        &self,
+        server_ctx: &dyn ServerCommandContextTrait,
+        bxl_cmd_ctx: &BxlCommandContext<'_>,
+        dice_ctx: &mut DiceTransaction,
+        bxl_eval_result: bxl::eval::Result<Arc<BxlResult>>,
+    ) -> buck2_error::Result<BxlResponse> {
         // let per_transaction_data = dice_ctx.per_transaction_data();
         let dispatcher = dice_ctx.per_transaction_data().get_dispatcher().dupe();
         let mut streaming_output_writer = StreamingOutputWriter::new(dispatcher);
@@ -147,7 +311,7 @@ impl BxlServerCommand {
         let bxl_result = match bxl_eval_result {
             Ok(bxl_result) => {
                 self.emit_streaming_output(
-                    &mut dice_ctx,
+                    dice_ctx,
                     bxl_result.streaming(),
                     &mut streaming_output_writer,
                 )?;
@@ -156,7 +320,7 @@ impl BxlServerCommand {
             Err(e) => {
                 if let Some(output) = &e.output_stream_state {
                     self.emit_streaming_output(
-                        &mut dice_ctx,
+                        dice_ctx,
                         &output.streaming,
                         &mut streaming_output_writer,
                     )?;
@@ -166,11 +330,7 @@ impl BxlServerCommand {
         };
 
         let errors = self
-            .materialize_artifacts(
-                &mut dice_ctx,
-                bxl_result.dupe(),
-                &mut streaming_output_writer,
-            )
+            .materialize_artifacts(dice_ctx, bxl_result.dupe(), &mut streaming_output_writer)
             .await;
 
         self.emit_outputs(server_ctx, bxl_result, &mut streaming_output_writer)
@@ -183,11 +343,11 @@ impl BxlServerCommand {
             .collect();
 
         let serialized_build_report = self
-            .write_build_report(&bxl_cmd_ctx, &mut dice_ctx, server_ctx, errors)
+            .write_build_report(bxl_cmd_ctx, dice_ctx, server_ctx, errors)
             .await?;
 
         Ok(BxlResponse {
-            project_root: bxl_cmd_ctx.project_root,
+            project_root: bxl_cmd_ctx.project_root.clone(),
             errors: error_reports,
             serialized_build_report,
         })
@@ -25,6 +25,8 @@ pub enum NewGenericRequest {
     ExpandExternalCells(ExpandExternalCellsRequest),
     Complete(CompleteRequest),
     Docs(DocsRequest),
+    Ui(UiRequest),
+    Fetch(FetchRequest),
 }
 
 #[derive(Serialize, Deserialize)]
@@ -35,6 +37,8 @@ pub enum NewGenericResponse {
     ExpandExternalCells(ExpandExternalCellsResponse),
     Complete(CompleteResponse),
     Docs(DocsResponse),
+    Ui(UiResponse),
+    Fetch(FetchResponse),
 }
 
 #[derive(Serialize, Deserialize)]
@@ -122,3 +126,33 @@ pub struct DocsResponse {
     // Set when requested format is JSON.
     pub json_output: Option<String>,
 }
+
+#[derive(Serialize, Deserialize)]
+pub struct UiRequest {
+    /// Port to serve the UI on if it is not running yet. `0` picks a free port.
+    pub port: u16,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UiResponse {
+    /// URL of the UI, including the access token.
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FetchRequest {
+    pub target_patterns: Vec<String>,
+    pub target_cfg: TargetCfg,
+    /// Do not write the lockfile; fail if it is out of date instead.
+    pub check: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FetchResponse {
+    /// Absolute path of the lockfile.
+    pub lockfile: String,
+    pub external_cells: u64,
+    pub downloads: u64,
+    /// Whether the lockfile was rewritten.
+    pub changed: bool,
+}
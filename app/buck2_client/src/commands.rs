@@ -15,6 +15,7 @@ pub mod clean_stale;
 pub mod ctargets;
 pub mod expand_external_cell;
 pub mod explain;
+pub mod fetch;
 pub mod help_env;
 pub mod init;
 pub mod install;
@@ -30,3 +31,4 @@ pub mod status;
 pub mod subscribe;
 pub mod targets;
 pub mod test;
+pub mod ui;
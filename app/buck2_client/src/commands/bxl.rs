@@ -88,6 +88,11 @@ pub struct BxlCommandOptions {
     #[clap(value_name = "PATH", long = "user-event-log")]
     pub user_event_log: Option<PathArg>,
 
+    /// Keep running, and evaluate the BXL function again whenever a file it read or a target
+    /// it queried changes. Output is streamed for every evaluation. Stop with Ctrl-C.
+    #[clap(long)]
+    pub watch: bool,
+
     #[clap(flatten)]
     build_opts: CommonBuildOptions,
 }
@@ -117,6 +122,7 @@ impl StreamingCommand for BxlCommand {
                         as i32,
                     final_artifact_uploads: self.bxl_opts.upload_final_artifacts.to_proto() as i32,
                     print_stacktrace: ctx.verbosity.print_success_stderr(),
+                    watch: self.bxl_opts.watch,
                 },
                 events_ctx,
                 ctx.console_interaction_stream(&self.common_ops.console_opts),
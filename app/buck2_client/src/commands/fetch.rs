@@ -0,0 +1,116 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use buck2_cli_proto::new_generic::FetchRequest;
+use buck2_cli_proto::new_generic::NewGenericRequest;
+use buck2_cli_proto::new_generic::NewGenericResponse;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::common::BuckArgMatches;
+use buck2_client_ctx::common::CommonBuildConfigurationOptions;
+use buck2_client_ctx::common::CommonCommandOptions;
+use buck2_client_ctx::common::CommonEventLogOptions;
+use buck2_client_ctx::common::CommonStarlarkOptions;
+use buck2_client_ctx::common::target_cfg::TargetCfgOptions;
+use buck2_client_ctx::common::ui::CommonConsoleOptions;
+use buck2_client_ctx::daemon::client::BuckdClientConnector;
+use buck2_client_ctx::events_ctx::EventsCtx;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_client_ctx::streaming::StreamingCommand;
+use buck2_error::ErrorTag;
+use buck2_error::buck2_error;
+
+/// Download all external inputs of the given targets and pin them in `buck2.lock`.
+///
+/// This fetches every external cell and the output of every `download_file` action in the
+/// transitive dependencies of the targets, so that a later build does not need network access
+/// for them. The lockfile at the project root records the origin of each external cell and the
+/// digest of each downloaded URL.
+#[derive(Debug, clap::Parser)]
+#[clap(name = "fetch")]
+pub struct FetchCommand {
+    /// Do not write `buck2.lock`; fail if it is missing or out of date instead.
+    #[clap(long)]
+    check: bool,
+
+    /// Patterns to fetch the external inputs of.
+    #[clap(name = "TARGET_PATTERNS", value_hint = clap::ValueHint::Other)]
+    patterns: Vec<String>,
+
+    #[clap(flatten)]
+    target_cfg: TargetCfgOptions,
+
+    #[clap(flatten)]
+    common_opts: CommonCommandOptions,
+}
+
+#[async_trait::async_trait(?Send)]
+impl StreamingCommand for FetchCommand {
+    const COMMAND_NAME: &'static str = "fetch";
+
+    async fn exec_impl(
+        self,
+        buckd: &mut BuckdClientConnector,
+        matches: BuckArgMatches<'_>,
+        ctx: &mut ClientCommandContext<'_>,
+        events_ctx: &mut EventsCtx,
+    ) -> ExitResult {
+        let context = ctx.client_context(matches, &self)?;
+        let resp = buckd
+            .with_flushing()
+            .new_generic(
+                context,
+                NewGenericRequest::Fetch(FetchRequest {
+                    target_patterns: self.patterns,
+                    target_cfg: self.target_cfg.target_cfg(),
+                    check: self.check,
+                }),
+                events_ctx,
+                ctx.console_interaction_stream(&self.common_opts.console_opts),
+            )
+            .await??;
+        let NewGenericResponse::Fetch(resp) = resp else {
+            return buck2_error!(
+                ErrorTag::InvalidEvent,
+                "Unexpected response type from generic command"
+            )
+            .into();
+        };
+
+        let status = if resp.changed {
+            "updated"
+        } else {
+            "up to date"
+        };
+        buck2_client_ctx::eprintln!(
+            "Fetched {} external cells and {} downloads, {} is {}",
+            resp.external_cells,
+            resp.downloads,
+            resp.lockfile,
+            status
+        )?;
+        ExitResult::success()
+    }
+
+    fn console_opts(&self) -> &CommonConsoleOptions {
+        &self.common_opts.console_opts
+    }
+
+    fn event_log_opts(&self) -> &CommonEventLogOptions {
+        &self.common_opts.event_log_opts
+    }
+
+    fn build_config_opts(&self) -> &CommonBuildConfigurationOptions {
+        &self.common_opts.config_opts
+    }
+
+    fn starlark_opts(&self) -> &CommonStarlarkOptions {
+        &self.common_opts.starlark_opts
+    }
+}
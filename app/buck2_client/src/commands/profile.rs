@@ -223,6 +223,13 @@ impl StreamingCommand for ProfileSubcommand {
                     ))
                     .into();
                 }
+                if bxl.bxl_opts.watch {
+                    return Err::<(), _>(buck2_error!(
+                        buck2_error::ErrorTag::Input,
+                        "BXL profile does not support `--watch`"
+                    ))
+                    .into();
+                }
                 ProfileOpts::BxlProfile(BxlProfile {
                     bxl_label: bxl.bxl_opts.bxl_label.clone(),
                     bxl_args: bxl.bxl_opts.bxl_args.clone(),
@@ -51,6 +51,9 @@ pub struct SubscribeCommand {
     /// Whether to request command snapshots.
     #[clap(long)]
     active_commands: bool,
+    /// Whether to request action, test and progress events for all commands.
+    #[clap(long)]
+    command_events: bool,
 
     /// Whether to get output as JSON. The JSON format is deemed unstable so this should only be
     /// used for debugging.
@@ -108,15 +111,26 @@ impl StreamingCommand for SubscribeCommand {
             ok: true,
         };
 
-        let stream = if self.active_commands {
-            futures::stream::once(futures::future::ready(SubscriptionRequest {
+        let mut initial_requests = Vec::new();
+        if self.active_commands {
+            initial_requests.push(SubscriptionRequest {
                 request: Some(buck2_subscription_proto::SubscribeToActiveCommands {}.into()),
-            }))
-            .chain(stream)
-            .left_stream()
-        } else {
-            stream.right_stream()
-        };
+            });
+        }
+        if self.command_events {
+            initial_requests.push(SubscriptionRequest {
+                request: Some(
+                    buck2_subscription_proto::SubscribeToCommandEvents {
+                        actions: true,
+                        tests: true,
+                        progress: true,
+                        ..Default::default()
+                    }
+                    .into(),
+                ),
+            });
+        }
+        let stream = futures::stream::iter(initial_requests).chain(stream);
 
         let stream = stream.map(|request| buck2_cli_proto::SubscriptionRequestWrapper {
             request: Some(request),
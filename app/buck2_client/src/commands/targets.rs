@@ -32,7 +32,9 @@ use buck2_client_ctx::exit_result::ExitResult;
 use buck2_client_ctx::path_arg::PathArg;
 use buck2_client_ctx::query_args::CommonAttributeArgs;
 use buck2_client_ctx::streaming::StreamingCommand;
+use buck2_fs::fs_util;
 use buck2_fs::paths::abs_norm_path::AbsNormPath;
+use buck2_fs::paths::abs_path::AbsPath;
 use dupe::Dupe;
 use gazebo::prelude::*;
 
@@ -44,6 +46,10 @@ enum TargetsError {
     /// Clap should report it, but if we missed something, this is a fallback.
     #[error("Flags are mutually exclusive")]
     IncompatibleArguments,
+    #[error(
+        "`--write-target-hash-snapshot` and `--diff-target-hash-snapshot` require `--show-target-hash` or `--show-unconfigured-target-hash`"
+    )]
+    SnapshotRequiresTargetHash,
 }
 
 // Use non-camel case so the possible values match buck1's
@@ -88,6 +94,23 @@ enum TargetHashFunction {
     Strong,
 }
 
+#[derive(Debug, clap::ValueEnum, Clone, Dupe)]
+enum ChangedFilesGraphType {
+    Unconfigured,
+    Configured,
+}
+
+impl ChangedFilesGraphType {
+    fn to_proto(&self) -> targets_request::TargetHashGraphType {
+        match self {
+            ChangedFilesGraphType::Unconfigured => {
+                targets_request::TargetHashGraphType::Unconfigured
+            }
+            ChangedFilesGraphType::Configured => targets_request::TargetHashGraphType::Configured,
+        }
+    }
+}
+
 #[derive(Debug, clap::ValueEnum, Clone, Dupe)]
 enum Compression {
     None,
@@ -168,6 +191,45 @@ pub struct TargetsCommand {
     #[clap(long, action = clap::ArgAction::Set, default_value = "true", conflicts_with = "streaming")]
     target_hash_recursive: bool,
 
+    /// Write the target hashes, and what went into each of them, to this file. Compare a later
+    /// state against it with `--diff-target-hash-snapshot`.
+    #[clap(long, value_name = "PATH", conflicts_with = "streaming")]
+    write_target_hash_snapshot: Option<PathArg>,
+
+    /// Instead of the targets, print the targets whose hash differs from the one in this file
+    /// (written by `--write-target-hash-snapshot` with the same target hash flags), together with
+    /// the attributes, files and dependencies responsible for each change.
+    #[clap(long, value_name = "PATH", conflicts_with = "streaming")]
+    diff_target_hash_snapshot: Option<PathArg>,
+
+    /// Print the targets matching the patterns which are affected by changes to these files,
+    /// together with the reasons each target is affected. A target is affected if one of its
+    /// inputs changed, if its build file, a `PACKAGE` file above it or any file they load changed,
+    /// if a buckconfig of its cell or of the root cell changed, or if it depends on an affected
+    /// target.
+    ///
+    /// Buckconfig changes are not narrowed down to the keys that changed, so every target of the
+    /// cell is affected by them. Deleted files are only detected through the build files and
+    /// targets that reference them.
+    #[clap(
+        long,
+        num_args = 1..,
+        value_name = "PATH",
+        conflicts_with_all = ["resolve_alias", "streaming", "show_target_hash", "show_unconfigured_target_hash"]
+    )]
+    changed_files: Vec<PathArg>,
+
+    /// Dependency graph used to propagate `--changed-files` to dependents. The configured graph
+    /// follows the dependencies of the targets in the target platform, like `cquery rdeps()`.
+    /// The unconfigured graph also follows dependencies in every branch of a `select()`.
+    #[clap(
+        long,
+        value_enum,
+        default_value = "configured",
+        requires = "changed_files"
+    )]
+    changed_files_graph: ChangedFilesGraphType,
+
     #[clap(flatten)]
     attributes: CommonAttributeArgs,
 
@@ -321,6 +383,11 @@ impl StreamingCommand for TargetsCommand {
                 (false, true) => targets_request::TargetHashGraphType::Unconfigured as i32,
                 (false, false) => targets_request::TargetHashGraphType::None as i32,
             };
+        if (self.write_target_hash_snapshot.is_some() || self.diff_target_hash_snapshot.is_some())
+            && target_hash_graph_type == targets_request::TargetHashGraphType::None as i32
+        {
+            return ExitResult::err(TargetsError::SnapshotRequiresTargetHash.into());
+        }
 
         let output_format = self.output_format()?;
 
@@ -330,12 +397,33 @@ impl StreamingCommand for TargetsCommand {
             .target_hash_modified_paths
             .into_try_map(|path| path.resolve(&ctx.working_dir).into_string())?;
 
+        // Snapshot paths are relative to the client's working directory, so the client does the IO.
+        let write_target_hash_snapshot = self
+            .write_target_hash_snapshot
+            .map(|x| x.resolve(&ctx.working_dir));
+        let target_hash_snapshot_diff = self
+            .diff_target_hash_snapshot
+            .try_map(|x| fs_util::read_to_string(x.resolve(&ctx.working_dir)))?;
+
+        let changed_files = self
+            .changed_files
+            .into_try_map(|path| path.resolve(&ctx.working_dir).into_string())?;
+
+        if !changed_files.is_empty() && self.show_output.format().is_some() {
+            return ExitResult::err(TargetsError::IncompatibleArguments.into());
+        }
+
         let target_request = TargetsRequest {
             context,
             target_patterns: self.patterns,
             output_format: output_format as i32,
             targets: Some(if self.resolve_alias {
                 targets_request::Targets::ResolveAlias(targets_request::ResolveAlias {})
+            } else if !changed_files.is_empty() {
+                targets_request::Targets::Impact(targets_request::Impact {
+                    changed_files,
+                    graph_type: self.changed_files_graph.to_proto() as i32,
+                })
             } else {
                 targets_request::Targets::Other(targets_request::Other {
                     output_attributes,
@@ -350,6 +438,8 @@ impl StreamingCommand for TargetsCommand {
                     cached: !self.no_cache,
                     imports: self.imports,
                     package_values,
+                    target_hash_snapshot_write: write_target_hash_snapshot.is_some(),
+                    target_hash_snapshot_diff,
                 })
             }),
             target_cfg: Some(self.target_cfg.target_cfg()),
@@ -379,6 +469,7 @@ impl StreamingCommand for TargetsCommand {
                 buckd,
                 events_ctx,
                 target_request,
+                write_target_hash_snapshot.as_deref(),
             )
             .await
         }
@@ -439,6 +530,7 @@ async fn targets(
     buckd: &mut BuckdClientConnector,
     events_ctx: &mut EventsCtx,
     target_request: TargetsRequest,
+    write_target_hash_snapshot: Option<&AbsPath>,
 ) -> ExitResult {
     let response = buckd
         .with_flushing()
@@ -449,6 +541,11 @@ async fn targets(
             &mut StdoutPartialResultHandler,
         )
         .await??;
+    if let (Some(path), Some(snapshot)) =
+        (write_target_hash_snapshot, &response.target_hash_snapshot)
+    {
+        fs_util::write(path, snapshot)?;
+    }
     if !response.serialized_targets_output.is_empty() {
         buck2_client_ctx::print!("{}", response.serialized_targets_output)?;
     }
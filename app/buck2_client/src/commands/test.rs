@@ -134,6 +134,11 @@ If include patterns are present, regardless of whether exclude patterns are pres
     #[clap(long)]
     ignore_tests_attribute: bool,
 
+    /// Run tests with coverage enabled and merge the coverage they collect into a single LCOV
+    /// report. Merging requires the test rules to provide a `coverage_merger`.
+    #[clap(long)]
+    coverage: bool,
+
     /// Writes the test executor stderr to the provided path
     ///
     /// --test-executor-stderr=- will write to stderr
@@ -225,6 +230,7 @@ impl StreamingCommand for TestCommand {
                             || self.unstable_allow_all_tests_on_re,
                         force_use_project_relative_paths: self.unstable_allow_all_tests_on_re,
                         force_run_from_project_root: self.unstable_allow_all_tests_on_re,
+                        coverage: self.coverage,
                     }),
                     timeout: self.timeout_options.overall_timeout()?,
                     ignore_tests_attribute: self.ignore_tests_attribute,
@@ -320,6 +326,10 @@ impl StreamingCommand for TestCommand {
             console.print_stderr(message.as_str())?;
         }
 
+        if let Some(coverage_report) = &response.coverage_report {
+            console.print_stderr(&format!("Coverage report: {coverage_report}"))?;
+        }
+
         match self.test_executor_stderr {
             Some(OutputDestinationArg::Path(path)) => {
                 forward_output_to_path(&response.executor_stderr, &path, &ctx.working_dir)?;
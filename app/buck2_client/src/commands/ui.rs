@@ -0,0 +1,87 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use buck2_cli_proto::new_generic::NewGenericRequest;
+use buck2_cli_proto::new_generic::NewGenericResponse;
+use buck2_cli_proto::new_generic::UiRequest;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::common::BuckArgMatches;
+use buck2_client_ctx::common::CommonBuildConfigurationOptions;
+use buck2_client_ctx::common::CommonEventLogOptions;
+use buck2_client_ctx::common::CommonStarlarkOptions;
+use buck2_client_ctx::common::ui::CommonConsoleOptions;
+use buck2_client_ctx::daemon::client::BuckdClientConnector;
+use buck2_client_ctx::events_ctx::EventsCtx;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_client_ctx::streaming::StreamingCommand;
+use buck2_error::ErrorTag;
+use buck2_error::buck2_error;
+
+/// Start a local web UI showing running and recent commands.
+///
+/// The UI is served by the daemon on localhost and keeps running until the daemon exits. This
+/// command prints its URL, which includes an access token. If the UI is already running, its
+/// URL is printed, unless `--port` asks for a different port, which is an error.
+#[derive(Debug, clap::Parser)]
+#[clap(name = "ui")]
+pub struct UiCommand {
+    /// Port to serve the UI on. By default a free port is picked
+    #[clap(long, default_value = "0")]
+    port: u16,
+}
+
+#[async_trait::async_trait(?Send)]
+impl StreamingCommand for UiCommand {
+    const COMMAND_NAME: &'static str = "ui";
+
+    async fn exec_impl(
+        self,
+        buckd: &mut BuckdClientConnector,
+        matches: BuckArgMatches<'_>,
+        ctx: &mut ClientCommandContext<'_>,
+        events_ctx: &mut EventsCtx,
+    ) -> ExitResult {
+        let context = ctx.client_context(matches, &self)?;
+        let resp = buckd
+            .with_flushing()
+            .new_generic(
+                context,
+                NewGenericRequest::Ui(UiRequest { port: self.port }),
+                events_ctx,
+                None,
+            )
+            .await??;
+        let NewGenericResponse::Ui(resp) = resp else {
+            return buck2_error!(
+                ErrorTag::InvalidEvent,
+                "Unexpected response type from generic command"
+            )
+            .into();
+        };
+
+        ExitResult::success().with_stdout(format!("{}\n", resp.url).into_bytes())
+    }
+
+    fn console_opts(&self) -> &CommonConsoleOptions {
+        CommonConsoleOptions::default_ref()
+    }
+
+    fn event_log_opts(&self) -> &CommonEventLogOptions {
+        CommonEventLogOptions::default_ref()
+    }
+
+    fn build_config_opts(&self) -> &CommonBuildConfigurationOptions {
+        CommonBuildConfigurationOptions::default_ref()
+    }
+
+    fn starlark_opts(&self) -> &CommonStarlarkOptions {
+        CommonStarlarkOptions::default_ref()
+    }
+}
@@ -208,6 +208,7 @@ impl<'a> ClientCommandContext<'a> {
             disable_starlark_types: starlark_opts.disable_starlark_types,
             unstable_typecheck: starlark_opts.unstable_typecheck,
             skip_targets_with_duplicate_names: starlark_opts.skip_targets_with_duplicate_names,
+            strict_globs: starlark_opts.strict_globs,
             reuse_current_config: config_opts.reuse_current_config,
             sanitized_argv: cmd.sanitize_argv(self.argv.clone()).argv,
             preemptible: match config_opts.preemptible {
@@ -268,6 +269,7 @@ impl<'a> ClientCommandContext<'a> {
             unstable_typecheck: false,
             target_call_stacks: false,
             skip_targets_with_duplicate_names: false,
+            strict_globs: false,
             trace_id: format!("{}", self.trace_id),
             reuse_current_config: false,
             daemon_uuid: get_possibly_nested_invocation_daemon_uuid(),
@@ -163,6 +163,11 @@ pub struct CommonEventLogOptions {
     /// written to `buck-out/v2/<uuid>/command_report` even without this flag.
     #[clap(long, value_name = "PATH")]
     pub(crate) command_report_path: Option<PathArg>,
+
+    /// Stream command, analysis, action and RE spans as OpenTelemetry traces to this OTLP/HTTP
+    /// collector, for example `http://localhost:4318`. Defaults to `$BUCK2_OTLP_ENDPOINT`.
+    #[clap(long, value_name = "URL")]
+    pub(crate) otlp_endpoint: Option<String>,
 }
 
 impl CommonEventLogOptions {
@@ -173,6 +178,7 @@ impl CommonEventLogOptions {
             write_build_id: None,
             command_report_path: None,
             unstable_write_invocation_record: None,
+            otlp_endpoint: None,
         };
         &DEFAULT
     }
@@ -184,6 +190,7 @@ impl CommonEventLogOptions {
             write_build_id: None,
             command_report_path: None,
             unstable_write_invocation_record: None,
+            otlp_endpoint: None,
         };
         &NO_EVENT_LOG
     }
@@ -413,6 +420,10 @@ pub struct CommonStarlarkOptions {
     #[clap(long, hide = true)]
     pub(crate) skip_targets_with_duplicate_names: bool,
 
+    /// Make `glob()` calls which match no files an error rather than a warning.
+    #[clap(long)]
+    pub strict_globs: bool,
+
     /// Enables profiling for all evaluations whose evaluation identifier matches one of the provided patterns.
     ///
     /// Some examples identifiers:
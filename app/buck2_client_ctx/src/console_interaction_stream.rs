@@ -8,14 +8,22 @@
  * above-listed licenses.
  */
 
+use std::time::Duration;
+
 use strum::EnumIter;
+use superconsole::input::Key;
+use superconsole::input::KeyDecoder;
 use tokio::io::AsyncReadExt;
 
 use crate::stdin::Stdin;
 
+/// How long to wait for the rest of an escape sequence before deciding the user pressed `Esc`.
+const ESCAPE_TIMEOUT: Duration = Duration::from_millis(50);
+
 pub struct ConsoleInteractionStream<'a> {
     stdin: &'a mut Stdin,
     term: InteractiveTerminal,
+    keys: KeyDecoder,
 }
 
 impl<'a> ConsoleInteractionStream<'a> {
@@ -32,7 +40,11 @@ impl<'a> ConsoleInteractionStream<'a> {
             }
         };
 
-        Some(Self { stdin, term })
+        Some(Self {
+            stdin,
+            term,
+            keys: KeyDecoder::new(),
+        })
     }
 }
 
@@ -201,6 +213,10 @@ pub enum SuperConsoleToggle {
     IncreaseReplaySpeed,
     DecreaseReplaySpeed,
     PauseReplay,
+    SelectPrevious,
+    SelectNext,
+    Inspect,
+    CloseInspector,
     Help,
 }
 
@@ -220,26 +236,45 @@ impl SuperConsoleToggle {
             SuperConsoleToggle::IncreaseReplaySpeed => "increase replay speed",
             SuperConsoleToggle::DecreaseReplaySpeed => "decrease replay speed",
             SuperConsoleToggle::PauseReplay => "pause replay",
+            SuperConsoleToggle::SelectPrevious => "select previous action",
+            SuperConsoleToggle::SelectNext => "select next action",
+            SuperConsoleToggle::Inspect => "expand selected action",
+            SuperConsoleToggle::CloseInspector => "close action inspector",
             SuperConsoleToggle::Help => "help",
         }
     }
 
-    pub fn key(&self) -> char {
+    /// What the key does, for the help message.
+    pub fn help(&self) -> String {
+        match self {
+            SuperConsoleToggle::SelectPrevious
+            | SuperConsoleToggle::SelectNext
+            | SuperConsoleToggle::Inspect
+            | SuperConsoleToggle::CloseInspector => self.description().to_owned(),
+            _ => format!("toggle {}", self.description()),
+        }
+    }
+
+    pub fn key(&self) -> &'static str {
         match self {
-            SuperConsoleToggle::Dice => 'd',
-            SuperConsoleToggle::DebugEvents => 'e',
-            SuperConsoleToggle::TwoLinesMode => '2',
-            SuperConsoleToggle::DetailedRE => 'r',
-            SuperConsoleToggle::Io => 'i',
-            SuperConsoleToggle::TargetConfigurations => 'p',
-            SuperConsoleToggle::ExpandedProgress => 'x',
-            SuperConsoleToggle::Commands => 'c',
-            SuperConsoleToggle::IncrLines => '+',
-            SuperConsoleToggle::DecrLines => '-',
-            SuperConsoleToggle::IncreaseReplaySpeed => 'k',
-            SuperConsoleToggle::DecreaseReplaySpeed => 'j',
-            SuperConsoleToggle::PauseReplay => 'y',
-            SuperConsoleToggle::Help => '?',
+            SuperConsoleToggle::Dice => "d",
+            SuperConsoleToggle::DebugEvents => "e",
+            SuperConsoleToggle::TwoLinesMode => "2",
+            SuperConsoleToggle::DetailedRE => "r",
+            SuperConsoleToggle::Io => "i",
+            SuperConsoleToggle::TargetConfigurations => "p",
+            SuperConsoleToggle::ExpandedProgress => "x",
+            SuperConsoleToggle::Commands => "c",
+            SuperConsoleToggle::IncrLines => "+",
+            SuperConsoleToggle::DecrLines => "-",
+            SuperConsoleToggle::IncreaseReplaySpeed => "k",
+            SuperConsoleToggle::DecreaseReplaySpeed => "j",
+            SuperConsoleToggle::PauseReplay => "y",
+            SuperConsoleToggle::SelectPrevious => "up",
+            SuperConsoleToggle::SelectNext => "down",
+            SuperConsoleToggle::Inspect => "enter",
+            SuperConsoleToggle::CloseInspector => "esc",
+            SuperConsoleToggle::Help => "?",
         }
     }
 }
@@ -252,9 +287,17 @@ pub trait SuperConsoleInteraction: Send + Sync {
 #[async_trait::async_trait]
 impl SuperConsoleInteraction for ConsoleInteractionStream<'_> {
     async fn toggle(&mut self) -> buck2_error::Result<Option<SuperConsoleToggle>> {
-        match self.stdin.read_u8().await {
-            Ok(c) => {
-                let c: char = c.into();
+        let key = if self.keys.is_pending() {
+            match tokio::time::timeout(ESCAPE_TIMEOUT, self.stdin.read_u8()).await {
+                Ok(c) => c.map(|c| self.keys.push(c)),
+                Err(_) => Ok(self.keys.flush()),
+            }
+        } else {
+            self.stdin.read_u8().await.map(|c| self.keys.push(c))
+        };
+
+        match key {
+            Ok(Some(Key::Char(c))) => {
                 let console_toggle = match c {
                     'd' => Some(SuperConsoleToggle::Dice),
                     'e' => Some(SuperConsoleToggle::DebugEvents),
@@ -274,6 +317,11 @@ impl SuperConsoleInteraction for ConsoleInteractionStream<'_> {
                 };
                 Ok(console_toggle)
             }
+            Ok(Some(Key::Up)) => Ok(Some(SuperConsoleToggle::SelectPrevious)),
+            Ok(Some(Key::Down)) => Ok(Some(SuperConsoleToggle::SelectNext)),
+            Ok(Some(Key::Enter)) => Ok(Some(SuperConsoleToggle::Inspect)),
+            Ok(Some(Key::Escape)) => Ok(Some(SuperConsoleToggle::CloseInspector)),
+            Ok(_) => Ok(None),
             // NOTE: An EOF here would be reported as "unexpected" because we asked for a u8.
             Err(e)
                 if e.kind() == std::io::ErrorKind::UnexpectedEof
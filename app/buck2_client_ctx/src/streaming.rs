@@ -16,6 +16,7 @@ use buck2_common::argv::Argv;
 use buck2_common::argv::SanitizedArgv;
 use buck2_common::init::DEFAULT_RETAINED_EVENT_LOGS;
 use buck2_common::invocation_paths::InvocationPaths;
+use buck2_core::buck2_env;
 use buck2_error::ExitCode;
 use buck2_event_observer::span_tracker::EventTimestamp;
 use dupe::Dupe;
@@ -41,6 +42,7 @@ use crate::subscribers::build_graph_stats::BuildGraphStats;
 use crate::subscribers::build_id_writer::BuildIdWriter;
 use crate::subscribers::event_log::EventLog;
 use crate::subscribers::health_check_subscriber::HealthCheckSubscriber;
+use crate::subscribers::otlp::OtlpTraceExporter;
 use crate::subscribers::re_log::ReLog;
 use crate::subscribers::subscriber::EventSubscriber;
 use crate::subscribers::superconsole::timekeeper::RealtimeClock;
@@ -118,6 +120,9 @@ fn update_events_ctx<T: StreamingCommand>(
     if let Some(build_graph_stats) = get_build_graph_stats(cmd, ctx) {
         subscribers.push(build_graph_stats)
     }
+    if let Some(otlp_trace_exporter) = get_otlp_trace_exporter::<T>(event_log_opts) {
+        subscribers.push(otlp_trace_exporter)
+    }
     let representative_config_flags = if ctx.paths().is_ok() {
         matches.get_representative_config_flags()
     } else {
@@ -322,6 +327,26 @@ fn get_build_id_writer(
     }
 }
 
+fn get_otlp_trace_exporter<T: StreamingCommand>(
+    opts: &CommonEventLogOptions,
+) -> Option<Box<dyn EventSubscriber>> {
+    let endpoint = match &opts.otlp_endpoint {
+        Some(endpoint) => endpoint.as_str(),
+        None => match buck2_env!("BUCK2_OTLP_ENDPOINT") {
+            Ok(Some(endpoint)) => endpoint,
+            Ok(None) => return None,
+            Err(e) => {
+                tracing::warn!("Not exporting OpenTelemetry traces: {:#}", e);
+                return None;
+            }
+        },
+    };
+    Some(Box::new(OtlpTraceExporter::new(
+        endpoint,
+        T::COMMAND_NAME.to_owned(),
+    )))
+}
+
 fn get_build_graph_stats<T: StreamingCommand>(
     cmd: &T,
     ctx: &ClientCommandContext,
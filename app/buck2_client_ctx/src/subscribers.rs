@@ -16,6 +16,7 @@ pub(crate) mod errorconsole;
 pub mod event_log;
 pub(crate) mod health_check_subscriber;
 pub(crate) mod observer;
+pub(crate) mod otlp;
 pub mod re_log;
 pub mod recorder;
 pub(crate) mod simpleconsole;
@@ -0,0 +1,578 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! Streams command, analysis, action and RE spans to an OpenTelemetry collector, as OTLP traces
+//! over HTTP with JSON encoding.
+//!
+//! Spans we don't export are skipped over: an exported span's parent is its nearest exported
+//! ancestor.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use async_trait::async_trait;
+use buck2_data::span_end_event;
+use buck2_data::span_start_event;
+use buck2_event_observer::action_stats::is_cache_hit;
+use buck2_event_observer::display::TargetDisplayOptions;
+use buck2_event_observer::display::display_action_key;
+use buck2_event_observer::display::display_analysis_target;
+use buck2_event_observer::display::display_executor_stage;
+use buck2_events::BuckEvent;
+use buck2_events::span::SpanId;
+use buck2_http::HttpClient;
+use buck2_http::HttpClientBuilder;
+use bytes::Bytes;
+use dupe::Dupe;
+use serde_json::json;
+use tokio::task::JoinHandle;
+
+use crate::subscribers::subscriber::EventSubscriber;
+
+/// Finished spans are uploaded in batches of this size, and at the end of the command.
+const EXPORT_BATCH_SIZE: usize = 512;
+
+const TRACES_PATH: &str = "/v1/traces";
+
+/// `Status.code` in OTLP.
+const STATUS_CODE_ERROR: u32 = 2;
+
+/// `SpanKind` in OTLP.
+const SPAN_KIND_INTERNAL: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+enum AttributeValue {
+    String(String),
+    Int(i64),
+    Bool(bool),
+}
+
+impl AttributeValue {
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            AttributeValue::String(v) => json!({ "stringValue": v }),
+            // OTLP JSON encodes 64-bit integers as strings.
+            AttributeValue::Int(v) => json!({ "intValue": v.to_string() }),
+            AttributeValue::Bool(v) => json!({ "boolValue": v }),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct OtlpSpan {
+    span_id: SpanId,
+    parent: Option<SpanId>,
+    name: String,
+    start: SystemTime,
+    end: SystemTime,
+    attributes: Vec<(&'static str, AttributeValue)>,
+    error: bool,
+}
+
+impl OtlpSpan {
+    fn to_json(&self, trace_id: &str) -> serde_json::Value {
+        let mut span = json!({
+            "traceId": trace_id,
+            "spanId": format_span_id(self.span_id),
+            "name": self.name,
+            "kind": SPAN_KIND_INTERNAL,
+            "startTimeUnixNano": unix_nanos(self.start).to_string(),
+            "endTimeUnixNano": unix_nanos(self.end).to_string(),
+            "attributes": attributes_to_json(&self.attributes),
+        });
+        if let Some(parent) = self.parent {
+            span["parentSpanId"] = json!(format_span_id(parent));
+        }
+        if self.error {
+            span["status"] = json!({ "code": STATUS_CODE_ERROR });
+        }
+        span
+    }
+}
+
+/// A span that has started but not yet ended.
+struct OpenSpan {
+    /// The parent in the event stream, which may not be exported.
+    parent: Option<SpanId>,
+    /// Set if this span is exported.
+    exported: Option<OtlpSpan>,
+}
+
+pub struct OtlpTraceExporter {
+    endpoint: String,
+    command_name: String,
+    client: Option<HttpClient>,
+    /// Set when no HTTP client could be created, after which events are ignored.
+    disabled: bool,
+    trace_id: Option<String>,
+    open: HashMap<SpanId, OpenSpan>,
+    finished: Vec<OtlpSpan>,
+    uploads: Vec<JoinHandle<buck2_error::Result<()>>>,
+}
+
+impl OtlpTraceExporter {
+    /// `endpoint` is the collector's base URL (for example `http://localhost:4318`), or its full
+    /// traces URL.
+    pub fn new(endpoint: &str, command_name: String) -> Self {
+        let endpoint = endpoint.trim_end_matches('/');
+        let endpoint = if endpoint.ends_with(TRACES_PATH) {
+            endpoint.to_owned()
+        } else {
+            format!("{endpoint}{TRACES_PATH}")
+        };
+        Self {
+            endpoint,
+            command_name,
+            client: None,
+            disabled: false,
+            trace_id: None,
+            open: HashMap::new(),
+            finished: Vec::new(),
+            uploads: Vec::new(),
+        }
+    }
+
+    fn handle_event(&mut self, event: &BuckEvent) -> buck2_error::Result<()> {
+        if self.trace_id.is_none() {
+            self.trace_id = Some(event.trace_id()?.to_string().replace('-', ""));
+        }
+
+        if let Some(start) = event.span_start_event() {
+            let Some(span_id) = event.span_id() else {
+                return Ok(());
+            };
+            let exported = start
+                .data
+                .as_ref()
+                .and_then(|data| self.span_name_and_attributes(data))
+                .map(|(name, attributes)| OtlpSpan {
+                    span_id,
+                    parent: self.exported_ancestor(event.parent_id()),
+                    name,
+                    start: event.timestamp(),
+                    end: event.timestamp(),
+                    attributes,
+                    error: false,
+                });
+            self.open.insert(
+                span_id,
+                OpenSpan {
+                    parent: event.parent_id(),
+                    exported,
+                },
+            );
+        } else if let Some(end) = event.span_end_event() {
+            let Some(span_id) = event.span_id() else {
+                return Ok(());
+            };
+            if let Some(OpenSpan {
+                exported: Some(mut span),
+                ..
+            }) = self.open.remove(&span_id)
+            {
+                span.end = event.timestamp();
+                if let Some(data) = &end.data {
+                    add_end_attributes(&mut span, data);
+                }
+                self.finished.push(span);
+            }
+        }
+        Ok(())
+    }
+
+    /// The closest span, starting from `parent`, which is exported.
+    fn exported_ancestor(&self, mut parent: Option<SpanId>) -> Option<SpanId> {
+        while let Some(id) = parent {
+            let open = self.open.get(&id)?;
+            if open.exported.is_some() {
+                return Some(id);
+            }
+            parent = open.parent;
+        }
+        None
+    }
+
+    fn span_name_and_attributes(
+        &self,
+        data: &span_start_event::Data,
+    ) -> Option<(String, Vec<(&'static str, AttributeValue)>)> {
+        let opts = TargetDisplayOptions::for_log();
+        match data {
+            span_start_event::Data::Command(_) => Some((
+                format!("buck2 {}", self.command_name),
+                vec![(
+                    "buck2.command",
+                    AttributeValue::String(self.command_name.clone()),
+                )],
+            )),
+            span_start_event::Data::Analysis(analysis) => {
+                let mut attributes =
+                    vec![("buck2.rule", AttributeValue::String(analysis.rule.clone()))];
+                if let Some(target) = analysis
+                    .target
+                    .as_ref()
+                    .and_then(|t| display_analysis_target(t, opts).ok())
+                {
+                    attributes.push(("buck2.target", AttributeValue::String(target)));
+                }
+                Some(("analysis".to_owned(), attributes))
+            }
+            span_start_event::Data::ActionExecution(action) => {
+                let mut attributes = vec![(
+                    "buck2.action.kind",
+                    AttributeValue::String(action.kind().as_str_name().to_owned()),
+                )];
+                if let Some(target) = action
+                    .key
+                    .as_ref()
+                    .and_then(|k| display_action_key(k, opts).ok())
+                {
+                    attributes.push(("buck2.target", AttributeValue::String(target)));
+                }
+                if let Some(name) = &action.name {
+                    attributes.push((
+                        "buck2.action.category",
+                        AttributeValue::String(name.category.clone()),
+                    ));
+                    if !name.identifier.is_empty() {
+                        attributes.push((
+                            "buck2.action.identifier",
+                            AttributeValue::String(name.identifier.clone()),
+                        ));
+                    }
+                }
+                Some(("action".to_owned(), attributes))
+            }
+            span_start_event::Data::ExecutorStage(stage) => {
+                use buck2_data::executor_stage_start::Stage;
+
+                match stage.stage.as_ref()? {
+                    stage @ (Stage::Re(_) | Stage::CacheQuery(_) | Stage::CacheHit(_)) => {
+                        Some((display_executor_stage(stage)?.to_owned(), Vec::new()))
+                    }
+                    Stage::Local(_) | Stage::Prepare(_) => None,
+                }
+            }
+            span_start_event::Data::ReUpload(_) => Some(("re_upload".to_owned(), Vec::new())),
+            _ => None,
+        }
+    }
+
+    /// Uploads the finished spans in the background.
+    async fn export(&mut self) -> buck2_error::Result<()> {
+        if self.finished.is_empty() {
+            return Ok(());
+        }
+        let Some(trace_id) = &self.trace_id else {
+            return Ok(());
+        };
+        let spans: Vec<_> = self
+            .finished
+            .drain(..)
+            .map(|span| span.to_json(trace_id))
+            .collect();
+        let body = export_request(spans);
+
+        let client = match &self.client {
+            Some(client) => client.dupe(),
+            None => match HttpClientBuilder::oss().await {
+                Ok(builder) => self.client.insert(builder.build()).dupe(),
+                Err(e) => {
+                    // Tracing is best-effort, so don't fail the command over it.
+                    tracing::warn!(
+                        "Failed to create HTTP client, not exporting OpenTelemetry traces: {:#}",
+                        e
+                    );
+                    self.disabled = true;
+                    self.open.clear();
+                    return Ok(());
+                }
+            },
+        };
+        let endpoint = self.endpoint.clone();
+        self.uploads.push(tokio::spawn(async move {
+            let body = Bytes::from(serde_json::to_vec(&body)?);
+            client
+                .post(
+                    &endpoint,
+                    body,
+                    vec![("Content-Type".to_owned(), "application/json".to_owned())],
+                )
+                .await?;
+            Ok(())
+        }));
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for OtlpTraceExporter {
+    fn name(&self) -> &'static str {
+        "otlp trace exporter"
+    }
+
+    async fn handle_events(&mut self, events: &[Arc<BuckEvent>]) -> buck2_error::Result<()> {
+        if self.disabled {
+            return Ok(());
+        }
+        for event in events {
+            self.handle_event(event)?;
+        }
+        if self.finished.len() >= EXPORT_BATCH_SIZE {
+            self.export().await?;
+        }
+        Ok(())
+    }
+
+    async fn finalize(&mut self) -> buck2_error::Result<()> {
+        if !self.disabled {
+            self.export().await?;
+        }
+        let mut first_error = None;
+        for upload in self.uploads.drain(..) {
+            let result = match upload.await {
+                Ok(result) => result,
+                Err(e) => Err(buck2_error::buck2_error!(
+                    buck2_error::ErrorTag::Tier0,
+                    "OTLP upload task failed: {}",
+                    e
+                )),
+            };
+            if let Err(e) = result {
+                first_error.get_or_insert(e);
+            }
+        }
+        match first_error {
+            // Tracing is best-effort, so failures to export are reported but don't fail the
+            // command.
+            Some(e) => {
+                tracing::warn!("Failed to export OpenTelemetry traces: {:#}", e);
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+fn add_end_attributes(span: &mut OtlpSpan, data: &span_end_event::Data) {
+    match data {
+        span_end_event::Data::Command(command) => {
+            span.error = !command.is_success;
+        }
+        span_end_event::Data::ActionExecution(action) => {
+            let execution_kind = action.execution_kind();
+            span.attributes.push((
+                "buck2.action.execution_kind",
+                AttributeValue::String(execution_kind.as_str_name().to_owned()),
+            ));
+            span.attributes.push((
+                "buck2.action.cache_hit",
+                AttributeValue::Bool(is_cache_hit(execution_kind)),
+            ));
+            span.attributes.push((
+                "buck2.action.output_size",
+                AttributeValue::Int(action.output_size as i64),
+            ));
+            span.error = action.failed;
+        }
+        _ => {}
+    }
+}
+
+fn export_request(spans: Vec<serde_json::Value>) -> serde_json::Value {
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": attributes_to_json(&[(
+                    "service.name",
+                    AttributeValue::String("buck2".to_owned()),
+                )]),
+            },
+            "scopeSpans": [{
+                "scope": { "name": "buck2" },
+                "spans": spans,
+            }],
+        }],
+    })
+}
+
+fn attributes_to_json(attributes: &[(&'static str, AttributeValue)]) -> serde_json::Value {
+    attributes
+        .iter()
+        .map(|(key, value)| json!({ "key": key, "value": value.to_json() }))
+        .collect()
+}
+
+fn format_span_id(span_id: SpanId) -> String {
+    format!("{:016x}", u64::from(span_id))
+}
+
+fn unix_nanos(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buck2_wrapper_common::invocation_id::TraceId;
+
+    use super::*;
+
+    fn event(
+        trace_id: &TraceId,
+        seconds: u64,
+        span_id: u64,
+        parent_id: Option<u64>,
+        data: buck2_data::buck_event::Data,
+    ) -> BuckEvent {
+        BuckEvent::new(
+            UNIX_EPOCH + Duration::from_secs(seconds),
+            trace_id.dupe(),
+            SpanId::from_u64_opt(span_id),
+            parent_id.and_then(SpanId::from_u64_opt),
+            data,
+        )
+    }
+
+    fn start(data: span_start_event::Data) -> buck2_data::buck_event::Data {
+        buck2_data::buck_event::Data::SpanStart(buck2_data::SpanStartEvent { data: Some(data) })
+    }
+
+    fn end(data: span_end_event::Data) -> buck2_data::buck_event::Data {
+        buck2_data::buck_event::Data::SpanEnd(buck2_data::SpanEndEvent {
+            data: Some(data),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_endpoint() {
+        assert_eq!(
+            OtlpTraceExporter::new("http://localhost:4318", "build".to_owned()).endpoint,
+            "http://localhost:4318/v1/traces"
+        );
+        assert_eq!(
+            OtlpTraceExporter::new("http://localhost:4318/v1/traces/", "build".to_owned()).endpoint,
+            "http://localhost:4318/v1/traces"
+        );
+    }
+
+    #[test]
+    fn test_spans() -> buck2_error::Result<()> {
+        let trace_id = TraceId::new();
+        let mut exporter = OtlpTraceExporter::new("http://localhost:4318", "build".to_owned());
+
+        let events = [
+            event(
+                &trace_id,
+                1,
+                1,
+                None,
+                start(span_start_event::Data::Command(
+                    buck2_data::CommandStart::default(),
+                )),
+            ),
+            // Not exported, so the action's parent is the command.
+            event(
+                &trace_id,
+                2,
+                2,
+                Some(1),
+                start(span_start_event::Data::FileWatcher(
+                    buck2_data::FileWatcherStart::default(),
+                )),
+            ),
+            event(
+                &trace_id,
+                3,
+                3,
+                Some(2),
+                start(span_start_event::Data::ActionExecution(
+                    buck2_data::ActionExecutionStart {
+                        name: Some(buck2_data::ActionName {
+                            category: "cxx_compile".to_owned(),
+                            identifier: "main.cpp".to_owned(),
+                        }),
+                        ..Default::default()
+                    },
+                )),
+            ),
+            event(
+                &trace_id,
+                4,
+                3,
+                Some(2),
+                end(span_end_event::Data::ActionExecution(
+                    buck2_data::ActionExecutionEnd {
+                        execution_kind: buck2_data::ActionExecutionKind::ActionCache as i32,
+                        ..Default::default()
+                    },
+                )),
+            ),
+            event(
+                &trace_id,
+                5,
+                2,
+                Some(1),
+                end(span_end_event::Data::FileWatcher(
+                    buck2_data::FileWatcherEnd::default(),
+                )),
+            ),
+            event(
+                &trace_id,
+                6,
+                1,
+                None,
+                end(span_end_event::Data::Command(buck2_data::CommandEnd {
+                    is_success: false,
+                    ..Default::default()
+                })),
+            ),
+        ];
+        for event in &events {
+            exporter.handle_event(event)?;
+        }
+
+        assert!(exporter.open.is_empty());
+        let [action, command] = &exporter.finished[..] else {
+            panic!("Expected two spans, got {:?}", exporter.finished);
+        };
+
+        assert_eq!(command.name, "buck2 build");
+        assert_eq!(command.parent, None);
+        assert!(command.error);
+
+        assert_eq!(action.name, "action");
+        assert_eq!(action.parent, SpanId::from_u64_opt(1));
+        assert!(!action.error);
+        assert_eq!(action.start, UNIX_EPOCH + Duration::from_secs(3));
+        assert_eq!(action.end, UNIX_EPOCH + Duration::from_secs(4));
+        assert!(action.attributes.contains(&(
+            "buck2.action.category",
+            AttributeValue::String("cxx_compile".to_owned())
+        )));
+        assert!(
+            action
+                .attributes
+                .contains(&("buck2.action.cache_hit", AttributeValue::Bool(true)))
+        );
+
+        let trace_id = trace_id.to_string().replace('-', "");
+        let json = action.to_json(&trace_id);
+        assert_eq!(json["traceId"], trace_id);
+        assert_eq!(json["spanId"], "0000000000000003");
+        assert_eq!(json["parentSpanId"], "0000000000000001");
+        assert_eq!(json["startTimeUnixNano"], "3000000000");
+        Ok(())
+    }
+}
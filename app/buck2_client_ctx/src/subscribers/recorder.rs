@@ -24,6 +24,8 @@ use async_trait::async_trait;
 use buck2_cli_proto::command_result;
 use buck2_common::build_count::BuildCount;
 use buck2_common::build_count::BuildCountManager;
+use buck2_common::build_history::BuildHistoryEntry;
+use buck2_common::build_history::BuildHistoryManager;
 use buck2_common::convert::ProstDurationExt;
 use buck2_common::invocation_paths::InvocationPaths;
 use buck2_core::buck2_env;
@@ -107,6 +109,8 @@ pub struct InvocationRecorder {
     isolation_dir: Option<String>,
     start_time: SystemTime,
     build_count_manager: Option<BuildCountManager>,
+    build_history_manager: Option<BuildHistoryManager>,
+    build_history_entry: Option<BuildHistoryEntry>,
     trace_id: TraceId,
     command_end: Option<buck2_data::CommandEnd>,
     command_duration: Option<prost_types::Duration>,
@@ -313,6 +317,8 @@ impl InvocationRecorder {
             isolation_dir: None,
             start_time,
             build_count_manager: None,
+            build_history_manager: None,
+            build_history_entry: None,
             trace_id,
             command_end: None,
             command_duration: None,
@@ -563,10 +569,20 @@ impl InvocationRecorder {
             }
         });
 
+        let build_history =
+            paths.and_then(|p| match BuildHistoryManager::new(p.build_history_dir()) {
+                Ok(manager) => Some(manager),
+                Err(e) => {
+                    let _unused = soft_error!("build_history_init_failed", e);
+                    None
+                }
+            });
+
         self.cli_args = sanitized_argv;
         self.representative_config_flags = representative_config_flags;
         self.write_to_path = write_to_path;
         self.build_count_manager = build_count;
+        self.build_history_manager = build_history;
         self.filesystem = Some(filesystem);
         self.compressed_event_log_size_bytes = log_size_counter_bytes;
         self.health_check_tags_receiver = health_check_tags_receiver;
@@ -1190,6 +1206,11 @@ impl InvocationRecorder {
             ),
         };
 
+        // Only commands that ran on the daemon are interesting for trends.
+        if self.build_history_manager.is_some() && record.command_end.is_some() {
+            self.build_history_entry = Some(self.build_history_entry(&record));
+        }
+
         let event = BuckEvent::new(
             SystemTime::now(),
             self.trace_id.dupe(),
@@ -1222,6 +1243,46 @@ impl InvocationRecorder {
         event
     }
 
+    fn build_history_entry(&self, record: &buck2_data::InvocationRecord) -> BuildHistoryEntry {
+        let millis =
+            |d: &prost_types::Duration| d.try_into_duration().ok().and_then(duration_as_millis);
+        BuildHistoryEntry {
+            trace_id: self.trace_id.to_string(),
+            command: record.command_name.clone().unwrap_or_default(),
+            target_patterns: record
+                .parsed_target_patterns
+                .as_ref()
+                .map(|p| p.target_patterns.iter().map(|t| t.value.clone()).collect())
+                .unwrap_or_default(),
+            start_time_ms: self
+                .start_time
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .ok()
+                .and_then(duration_as_millis)
+                .unwrap_or_default(),
+            success: record.outcome == Some(InvocationOutcome::Success as i32),
+            duration_ms: record.command_duration.as_ref().and_then(millis),
+            critical_path_ms: record.critical_path_duration.as_ref().and_then(millis),
+            cache_hit_rate: record.cache_hit_rate,
+            actions_run: record.run_local_count + record.run_remote_count,
+            peak_memory_bytes: record.peak_process_memory_bytes,
+        }
+    }
+
+    /// Appends this invocation to the local build history used by `buck2 log trends`.
+    async fn append_build_history(&mut self) {
+        let (Some(manager), Some(entry)) =
+            (&self.build_history_manager, self.build_history_entry.take())
+        else {
+            return;
+        };
+        match manager.append(&entry).await {
+            Ok(true) => {}
+            Ok(false) => tracing::debug!("Build history is locked, not recording this command"),
+            Err(e) => tracing::warn!("Failed to append to build history: {:#}", e),
+        }
+    }
+
     fn try_read_health_check_tags(&mut self) {
         // The sender may have sent multiple tag messages since the recorder and health checker don't necessarily run at the same frequency.
         // We should not make assumptions about order of sender/receiver drop since the health checker is a BuckEvent subscriber as well.
@@ -2395,6 +2456,7 @@ impl EventSubscriber for InvocationRecorder {
         // Typically initialized already unless the command failed early.
         let fb = buck2_common::fbinit::get_or_init_fbcode_globals();
         let event = self.create_record_event();
+        self.append_build_history().await;
         if let Some(scribe_sink) = new_remote_event_sink_if_enabled(
             fb,
             ScribeConfig {
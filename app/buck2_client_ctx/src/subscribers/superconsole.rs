@@ -40,6 +40,7 @@ use superconsole::Lines;
 use superconsole::Span;
 pub(crate) use superconsole::SuperConsole;
 use superconsole::components::DrawVertical;
+use superconsole::input::Key;
 use superconsole::style::Attribute;
 use superconsole::style::Color;
 use superconsole::style::ContentStyle;
@@ -56,6 +57,8 @@ use crate::subscribers::superconsole::debug_events::DebugEventsComponent;
 use crate::subscribers::superconsole::debugger::StarlarkDebuggerComponent;
 use crate::subscribers::superconsole::dice::DiceComponent;
 use crate::subscribers::superconsole::header::TasksHeader;
+use crate::subscribers::superconsole::inspector::ActionInspector;
+use crate::subscribers::superconsole::inspector::ActionInspectorComponent;
 use crate::subscribers::superconsole::io::IoHeader;
 use crate::subscribers::superconsole::re::ReHeader;
 use crate::subscribers::superconsole::session_info::SessionInfoComponent;
@@ -72,6 +75,7 @@ pub(crate) mod debug_events;
 mod debugger;
 pub(crate) mod dice;
 mod header;
+mod inspector;
 pub(crate) mod io;
 mod message_renderer;
 mod re;
@@ -111,6 +115,7 @@ pub struct SuperConsoleState {
     simple_console: SimpleConsole<DebugEventObserverExtra>,
     config: SuperConsoleConfig,
     active_warnings: Option<Vec<DisplayReport>>,
+    inspector: ActionInspector,
 }
 
 impl SuperConsoleState {
@@ -247,7 +252,11 @@ impl Component for BuckRootComponent<'_> {
             mode,
         )?;
         draw.draw(&TasksHeader::new(&self.header, self.state), mode)?;
-        draw.draw(&TimedList::new(&CUTOFFS, self.state), mode)?;
+        if self.state.inspector.is_open() {
+            draw.draw(&ActionInspectorComponent { state: self.state }, mode)?;
+        } else {
+            draw.draw(&TimedList::new(&CUTOFFS, self.state), mode)?;
+        }
 
         Ok(draw.finish())
     }
@@ -386,6 +395,7 @@ impl SuperConsoleState {
             ),
             config,
             active_warnings: None,
+            inspector: ActionInspector::default(),
         })
     }
 
@@ -393,6 +403,7 @@ impl SuperConsoleState {
         &mut self,
         event: &Arc<BuckEvent>,
     ) -> buck2_error::Result<()> {
+        self.inspector.handle_event(event);
         self.simple_console.update_event_observer(event).await
     }
 
@@ -411,7 +422,7 @@ impl StatefulSuperConsoleImpl {
     async fn toggle(
         &mut self,
         what: &str,
-        key: char,
+        key: &str,
         var: impl FnOnce(&mut Self) -> &mut bool,
     ) -> buck2_error::Result<()> {
         let var = var(self);
@@ -758,9 +769,13 @@ impl StatefulSuperConsoleImpl {
                         self.handle_stderr(&message).await?;
                     }
                 }
+                SuperConsoleToggle::SelectPrevious => self.inspect(Key::Up),
+                SuperConsoleToggle::SelectNext => self.inspect(Key::Down),
+                SuperConsoleToggle::Inspect => self.inspect(Key::Enter),
+                SuperConsoleToggle::CloseInspector => self.inspect(Key::Escape),
                 SuperConsoleToggle::Help => {
                     let help_message = SuperConsoleToggle::iter()
-                        .map(|t| format!("`{}` = toggle {}", t.key(), t.description()))
+                        .map(|t| format!("`{}` = {}", t.key(), t.help()))
                         .collect::<Vec<_>>()
                         .join("\n");
                     self.handle_stderr(&format!(
@@ -776,6 +791,12 @@ impl StatefulSuperConsoleImpl {
         Ok(())
     }
 
+    fn inspect(&mut self, key: Key) {
+        self.state
+            .inspector
+            .handle_key(key, self.state.simple_console.observer.spans());
+    }
+
     fn try_update_active_warnings(&mut self) {
         let reports = self
             .state
@@ -788,6 +809,12 @@ impl StatefulSuperConsoleImpl {
     async fn tick(&mut self, tick: &Tick) -> buck2_error::Result<()> {
         self.state.timekeeper.tick(*tick);
         self.try_update_active_warnings();
+        self.state
+            .inspector
+            .refresh(self.state.simple_console.observer.spans());
+        self.state
+            .inspector
+            .sample_running(self.state.simple_console.observer.spans());
         self.super_console.render(&BuckRootComponent {
             header: &self.header,
             state: &self.state,
@@ -0,0 +1,645 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! The action inspector: the arrow keys select a running or recently failed action in place of
+//! the timed list, and `Enter` expands it to show its command line, executor, RE queue state and
+//! the end of its stderr. Above the actions, it shows how many are done and how many ran
+//! concurrently lately.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+
+use buck2_event_observer::display;
+use buck2_event_observer::display::TargetDisplayOptions;
+use buck2_event_observer::fmt_duration;
+use buck2_event_observer::span_tracker::BuckEventSpanHandle;
+use buck2_event_observer::span_tracker::BuckEventSpanInfo;
+use buck2_event_observer::span_tracker::BuckEventSpanTracker;
+use buck2_event_observer::what_ran::command_to_string;
+use buck2_event_observer::what_ran::worker_command_as_fallback_to_string;
+use buck2_events::BuckEvent;
+use buck2_events::span::SpanId;
+use superconsole::Component;
+use superconsole::Dimensions;
+use superconsole::DrawMode;
+use superconsole::Line;
+use superconsole::Lines;
+use superconsole::Span;
+use superconsole::components::ProgressBar;
+use superconsole::components::ScrollView;
+use superconsole::components::Sparkline;
+use superconsole::components::Table;
+use superconsole::components::scroll_view::ScrollPosition;
+use superconsole::components::table::Column;
+use superconsole::components::table::ColumnWidth;
+use superconsole::input::Interactive;
+use superconsole::input::Key;
+use superconsole::selection::Selection;
+use superconsole::style::Color;
+use superconsole::style::Stylize;
+
+use crate::subscribers::superconsole::SuperConsoleState;
+
+/// How many failed actions we keep around to inspect.
+const MAX_FAILED_ACTIONS: usize = 20;
+
+/// How much of an action's stderr we show.
+const STDERR_TAIL_LINES: usize = 10;
+
+/// How many samples of the number of running actions we keep, one per tick.
+const MAX_RUNNING_SAMPLES: usize = 200;
+
+/// Width of the labels of the progress lines.
+const LABEL_WIDTH: usize = 15;
+
+const DETAILS_INDENT: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InspectedAction {
+    /// A running action, by its span.
+    Running(SpanId),
+    /// A failed action, by its position in the sequence of failures.
+    Failed(u64),
+}
+
+struct FailedAction {
+    id: u64,
+    error: buck2_data::ActionError,
+}
+
+#[derive(Default)]
+pub(crate) struct ActionInspector {
+    /// What can be selected, in the order it is shown. Refreshed before drawing and on key
+    /// presses.
+    items: Vec<InspectedAction>,
+    selection: Selection,
+    /// The inspector is open while something is selected.
+    selected: Option<InspectedAction>,
+    expanded: bool,
+    failed: VecDeque<FailedAction>,
+    next_failed_id: u64,
+    /// The latest stderr of running local commands, by the span of the executor stage running
+    /// them.
+    stderr_tails: HashMap<SpanId, String>,
+    /// The number of running actions at each tick, oldest first.
+    running_samples: VecDeque<u64>,
+}
+
+impl ActionInspector {
+    pub(crate) fn is_open(&self) -> bool {
+        self.selected.is_some()
+    }
+
+    pub(crate) fn handle_event(&mut self, event: &BuckEvent) {
+        match event.data() {
+            buck2_data::buck_event::Data::SpanEnd(_) => {
+                if let Some(span_id) = event.span_id() {
+                    self.stderr_tails.remove(&span_id);
+                }
+            }
+            buck2_data::buck_event::Data::Instant(instant) => match &instant.data {
+                Some(buck2_data::instant_event::Data::CommandStderrTail(tail)) => {
+                    if let Some(parent) = event.parent_id() {
+                        self.stderr_tails.insert(parent, tail.stderr.clone());
+                    }
+                }
+                Some(buck2_data::instant_event::Data::ActionError(error)) => {
+                    let id = self.next_failed_id;
+                    self.next_failed_id += 1;
+
+                    // Action errors are emitted in the action's span: keep following the action
+                    // if it was selected while running.
+                    if let Some(parent) = event.parent_id() {
+                        if self.selected == Some(InspectedAction::Running(parent)) {
+                            self.selected = Some(InspectedAction::Failed(id));
+                        }
+                    }
+
+                    self.failed.push_back(FailedAction {
+                        id,
+                        error: error.clone(),
+                    });
+                    if self.failed.len() > MAX_FAILED_ACTIONS {
+                        self.failed.pop_front();
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    /// Catch up with actions starting and finishing. If the selected action went away, whatever
+    /// took its place in the list is selected instead.
+    pub(crate) fn refresh(&mut self, spans: &BuckEventSpanTracker) {
+        self.items = spans
+            .iter_roots()
+            .filter(|root| is_action(&root.info().event))
+            .filter_map(|root| root.info().event.span_id())
+            .map(InspectedAction::Running)
+            .chain(self.failed.iter().map(|f| InspectedAction::Failed(f.id)))
+            .collect();
+        self.selection.set_len(self.items.len());
+
+        if let Some(selected) = self.selected {
+            if let Some(index) = self.items.iter().position(|item| *item == selected) {
+                self.selection.select(Some(index));
+            }
+            self.selected = self.selection.selected().map(|i| self.items[i]);
+            if self.selected.is_none() {
+                self.expanded = false;
+            }
+        }
+    }
+
+    /// Record how many actions are running now. Called once per tick, whether or not the
+    /// inspector is open, so that there is some history to show when it is opened.
+    pub(crate) fn sample_running(&mut self, spans: &BuckEventSpanTracker) {
+        let running = spans
+            .iter_roots()
+            .filter(|root| is_action(&root.info().event))
+            .count();
+        self.running_samples.push_back(running as u64);
+        if self.running_samples.len() > MAX_RUNNING_SAMPLES {
+            self.running_samples.pop_front();
+        }
+    }
+
+    pub(crate) fn handle_key(&mut self, key: Key, spans: &BuckEventSpanTracker) {
+        self.refresh(spans);
+        match key {
+            Key::Escape => {
+                self.selected = None;
+                self.selection.select(None);
+                self.expanded = false;
+            }
+            Key::Enter => {
+                if self.selected.is_some() {
+                    self.expanded = !self.expanded;
+                }
+            }
+            key => {
+                if self.selection.handle_key(key) {
+                    self.selected = self.selection.selected().map(|i| self.items[i]);
+                }
+            }
+        }
+    }
+}
+
+fn is_action(event: &BuckEvent) -> bool {
+    matches!(
+        event.span_start_event().and_then(|s| s.data.as_ref()),
+        Some(buck2_data::span_start_event::Data::ActionExecution(..))
+    )
+}
+
+/// Draws the actions that can be inspected, in place of the timed list.
+pub(crate) struct ActionInspectorComponent<'s> {
+    pub(crate) state: &'s SuperConsoleState,
+}
+
+impl ActionInspectorComponent<'_> {
+    fn opts(&self) -> TargetDisplayOptions {
+        TargetDisplayOptions::for_console(self.state.config.display_platform)
+    }
+
+    /// The action and its status, as cells of the table of actions.
+    fn running_row(&self, root: &BuckEventSpanHandle) -> buck2_error::Result<(Line, Line)> {
+        let info = root.info();
+        let elapsed = self.state.timekeeper.duration_since(info.start);
+        Ok((
+            Line::sanitized(&display::display_event(&info.event, self.opts())?),
+            Line::sanitized(&fmt_duration::fmt_duration(elapsed)),
+        ))
+    }
+
+    fn failed_row(&self, failed: &FailedAction) -> buck2_error::Result<(Line, Line)> {
+        Ok((
+            Line::sanitized(&display::display_action_identity(
+                failed.error.key.as_ref(),
+                failed.error.name.as_ref(),
+                self.opts(),
+            )?),
+            Line::from_iter([Span::new_styled_lossy("failed".to_owned().with(Color::Red))]),
+        ))
+    }
+
+    /// Actions finished out of those started, and the number of running actions over time.
+    fn progress(&self, width: usize) -> Vec<Line> {
+        let actions = self.state.extra().progress_state().phase_stats().actions;
+        let Ok(Lines(mut lines)) = ProgressBar::new(actions.finished, actions.started)
+            .with_label(Line::sanitized(&format!(
+                "{:<width$}",
+                "Finished",
+                width = LABEL_WIDTH - 1
+            )))
+            .draw(Dimensions::new(width, 1), DrawMode::Normal);
+
+        let samples = &self.state.inspector.running_samples;
+        let mut running = Line::sanitized(&format!(
+            "{:<width$}",
+            format!("Running {}", samples.back().copied().unwrap_or(0)),
+            width = LABEL_WIDTH
+        ));
+        let Ok(Lines(chart)) = Sparkline::new(samples.iter().copied().collect()).draw(
+            Dimensions::new(width.saturating_sub(LABEL_WIDTH), 1),
+            DrawMode::Normal,
+        );
+        running.extend(chart.into_iter().flatten());
+        lines.push(running);
+        lines
+    }
+
+    fn running_details(&self, root: &BuckEventSpanHandle, width: usize) -> Lines {
+        use buck2_data::executor_stage_start::Stage;
+
+        let mut descendants = Vec::new();
+        collect_descendants(root, &mut descendants);
+
+        let mut executor = "not started yet".to_owned();
+        let mut command = None;
+        let mut queue = None;
+        let mut stderr = None;
+        for info in &descendants {
+            if let Some(tail) = info
+                .event
+                .span_id()
+                .and_then(|id| self.state.inspector.stderr_tails.get(&id))
+            {
+                stderr = Some(tail.as_str());
+            }
+
+            let Some(buck2_data::span_start_event::Data::ExecutorStage(stage)) =
+                info.event.span_start_event().and_then(|s| s.data.as_ref())
+            else {
+                continue;
+            };
+            let Some(stage) = stage.stage.as_ref() else {
+                continue;
+            };
+
+            match stage {
+                Stage::Local(local) => {
+                    use buck2_data::local_stage::Stage;
+
+                    match local.stage.as_ref() {
+                        Some(Stage::Execute(execute)) => {
+                            executor = "local".to_owned();
+                            command = execute.command.as_ref().map(command_to_string);
+                        }
+                        Some(Stage::WorkerExecute(execute)) => {
+                            executor = "local worker".to_owned();
+                            command = execute
+                                .command
+                                .as_ref()
+                                .map(worker_command_as_fallback_to_string);
+                        }
+                        Some(Stage::WorkerInit(init)) => {
+                            executor = "local worker (initializing)".to_owned();
+                            command = init.command.as_ref().map(command_to_string);
+                        }
+                        Some(_) => executor = "local".to_owned(),
+                        None => {}
+                    }
+                }
+                Stage::Re(re) => {
+                    use buck2_data::re_stage::Stage;
+
+                    executor = "remote".to_owned();
+                    match re.stage.as_ref() {
+                        Some(Stage::Execute(execute)) => {
+                            executor = format!("remote ({})", execute.use_case);
+                            command = Some(format!("action digest {}", execute.action_digest));
+                        }
+                        Some(
+                            Stage::Queue(..)
+                            | Stage::QueueOverQuota(..)
+                            | Stage::QueueAcquiringDependencies(..)
+                            | Stage::QueueNoWorkerAvailable(..),
+                        ) => {
+                            queue = Some(format!(
+                                "{} for {}",
+                                display::display_executor_stage(stage).unwrap_or("re_queued"),
+                                fmt_duration::fmt_duration(
+                                    self.state.timekeeper.duration_since(info.start)
+                                ),
+                            ));
+                        }
+                        _ => {}
+                    }
+                }
+                Stage::CacheQuery(..) => executor = "remote cache query".to_owned(),
+                Stage::CacheHit(..) => executor = "remote cache hit".to_owned(),
+                Stage::Prepare(..) => executor = "preparing".to_owned(),
+            }
+        }
+
+        let mut lines = Vec::new();
+        detail(&mut lines, "Executor", &executor, width);
+        if let Some(command) = &command {
+            detail(&mut lines, "Command", command, width);
+        }
+        if let Some(queue) = &queue {
+            detail(&mut lines, "RE queue", queue, width);
+        }
+        if let Some(stderr) = stderr {
+            stderr_tail(&mut lines, stderr, width);
+        }
+        Lines(lines)
+    }
+
+    fn failed_details(&self, failed: &FailedAction, width: usize) -> buck2_error::Result<Lines> {
+        use buck2_data::command_execution_kind::Command;
+
+        let error = display::display_action_error(&failed.error, self.opts())?;
+
+        let mut lines = Vec::new();
+        detail(&mut lines, "Reason", &error.reason, width);
+        if let Some(details) = error.command {
+            let (executor, command) = match details
+                .command_kind
+                .as_ref()
+                .and_then(|k| k.command.as_ref())
+            {
+                Some(Command::LocalCommand(local)) => ("local", Some(command_to_string(local))),
+                Some(Command::OmittedLocalCommand(..)) => ("local", None),
+                Some(Command::WorkerCommand(worker)) => (
+                    "local worker",
+                    Some(worker_command_as_fallback_to_string(worker)),
+                ),
+                Some(Command::WorkerInitCommand(init)) => {
+                    ("local worker (initializing)", Some(command_to_string(init)))
+                }
+                Some(Command::RemoteCommand(remote)) => (
+                    "remote",
+                    Some(format!("action digest {}", remote.action_digest)),
+                ),
+                None => ("unknown", None),
+            };
+            detail(&mut lines, "Executor", executor, width);
+            if let Some(command) = &command {
+                detail(&mut lines, "Command", command, width);
+            }
+            stderr_tail(&mut lines, &details.cmd_stderr, width);
+        }
+        Ok(Lines(lines))
+    }
+}
+
+impl Component for ActionInspectorComponent<'_> {
+    type Error = buck2_error::Error;
+
+    fn draw_unchecked(&self, dimensions: Dimensions, mode: DrawMode) -> buck2_error::Result<Lines> {
+        let inspector = &self.state.inspector;
+        if mode == DrawMode::Final || !inspector.is_open() {
+            return Ok(Lines::new());
+        }
+
+        let roots: HashMap<SpanId, BuckEventSpanHandle> = self
+            .state
+            .simple_console
+            .observer()
+            .spans()
+            .iter_roots()
+            .filter_map(|root| Some((root.info().event.span_id()?, root)))
+            .collect();
+
+        let mut lines = vec![Line::from_iter([Span::new_styled(
+            format!(
+                "{} actions: `↑`/`↓` to select, `enter` to expand, `esc` to close",
+                inspector.items.len()
+            )
+            .italic(),
+        )?])];
+        lines.extend(self.progress(dimensions.width));
+
+        let mut table = Table::new(vec![
+            Column::new(ColumnWidth::Fixed(1)),
+            Column::new(ColumnWidth::Fill),
+            Column::new(ColumnWidth::Fit).right_aligned(),
+        ]);
+        // The details of the expanded action go below its row.
+        let mut expanded = None;
+        for index in inspector
+            .selection
+            .visible_range(self.state.config.max_lines)
+        {
+            let item = inspector.items[index];
+            let selected = inspector.selection.selected() == Some(index);
+
+            let ((action, status), details) = match item {
+                InspectedAction::Running(span_id) => {
+                    // Actions which finished since the last refresh are skipped.
+                    let Some(root) = roots.get(&span_id) else {
+                        continue;
+                    };
+                    let details = (selected && inspector.expanded)
+                        .then(|| self.running_details(root, dimensions.width));
+                    (self.running_row(root)?, details)
+                }
+                InspectedAction::Failed(id) => {
+                    let Some(failed) = inspector.failed.iter().find(|f| f.id == id) else {
+                        continue;
+                    };
+                    let details = match selected && inspector.expanded {
+                        true => Some(self.failed_details(failed, dimensions.width)?),
+                        false => None,
+                    };
+                    (self.failed_row(failed)?, details)
+                }
+            };
+
+            let marker = match selected {
+                true => Line::from_iter([Span::new_styled_lossy(">".to_owned().bold())]),
+                false => Line::default(),
+            };
+            if let Some(details) = details {
+                expanded = Some((table.len(), details));
+            }
+            table.push_row(vec![marker, action, status]);
+        }
+
+        let Ok(Lines(mut rows)) = table.draw(Dimensions::new(dimensions.width, table.len()), mode);
+        if let Some((row, Lines(details))) = expanded {
+            rows.splice(row + 1..row + 1, details);
+        }
+        lines.extend(rows);
+
+        Ok(Lines(lines))
+    }
+}
+
+fn collect_descendants(span: &BuckEventSpanHandle, out: &mut Vec<BuckEventSpanInfo>) {
+    for child in span.children() {
+        out.push(child.info().clone());
+        collect_descendants(&child, out);
+    }
+}
+
+/// A `name: value` line, wrapped to `width` so that long command lines can be read in full.
+fn detail(lines: &mut Vec<Line>, name: &str, value: &str, width: usize) {
+    let prefix = format!("{}{}: ", " ".repeat(DETAILS_INDENT), name);
+    let width = width.saturating_sub(prefix.len()).max(1);
+    let chars: Vec<char> = value.chars().collect();
+    for (i, chunk) in chars.chunks(width).enumerate() {
+        let chunk: String = chunk.iter().collect();
+        let mut line = match i {
+            0 => Line::from_iter([Span::new_styled_lossy(prefix.clone().bold())]),
+            _ => {
+                let mut line = Line::default();
+                line.pad_left(prefix.len());
+                line
+            }
+        };
+        line.push(Span::new_unstyled_lossy(chunk));
+        lines.push(line);
+    }
+}
+
+/// Lines that are already laid out.
+struct Prerendered(Lines);
+
+impl Component for Prerendered {
+    type Error = Infallible;
+
+    fn draw_unchecked(
+        &self,
+        _dimensions: Dimensions,
+        _mode: DrawMode,
+    ) -> Result<Lines, Infallible> {
+        Ok(self.0.clone())
+    }
+}
+
+/// The last lines of `stderr`, saying how many more there are above them.
+fn stderr_tail(lines: &mut Vec<Line>, stderr: &str, width: usize) {
+    let stderr = stderr.trim_end();
+    if stderr.is_empty() {
+        return;
+    }
+
+    // Only the end is ever visible, so only that is parsed: earlier lines are just counted.
+    let hidden = stderr.lines().count().saturating_sub(STDERR_TAIL_LINES);
+    let tail = stderr.lines().skip(hidden).collect::<Vec<_>>().join("\n");
+    let mut content = vec![Line::default(); hidden];
+    content.extend(Lines::from_colored_multiline_string(&tail).0);
+
+    let content_height = content.len();
+    let Ok(Lines(tail)) = ScrollView::new(Prerendered(Lines(content)), content_height)
+        .with_position(ScrollPosition::Bottom)
+        .with_indicators()
+        .draw(
+            Dimensions::new(width.saturating_sub(DETAILS_INDENT), STDERR_TAIL_LINES),
+            DrawMode::Normal,
+        );
+
+    lines.push(Line::from_iter([Span::new_styled_lossy(
+        format!("{}stderr:", " ".repeat(DETAILS_INDENT)).bold(),
+    )]));
+    lines.extend(tail.into_iter().map(|mut line| {
+        line.pad_left(DETAILS_INDENT);
+        line
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::SystemTime;
+
+    use buck2_wrapper_common::invocation_id::TraceId;
+    use dupe::Dupe;
+
+    use super::*;
+
+    fn action_start(trace_id: &TraceId, span_id: SpanId) -> Arc<BuckEvent> {
+        Arc::new(BuckEvent::new(
+            SystemTime::now(),
+            trace_id.dupe(),
+            Some(span_id),
+            None,
+            buck2_data::buck_event::Data::SpanStart(buck2_data::SpanStartEvent {
+                data: Some(buck2_data::ActionExecutionStart::default().into()),
+            }),
+        ))
+    }
+
+    fn action_error(trace_id: &TraceId, parent: SpanId) -> Arc<BuckEvent> {
+        Arc::new(BuckEvent::new(
+            SystemTime::now(),
+            trace_id.dupe(),
+            None,
+            Some(parent),
+            buck2_data::buck_event::Data::Instant(buck2_data::InstantEvent {
+                data: Some(buck2_data::ActionError::default().into()),
+            }),
+        ))
+    }
+
+    #[test]
+    fn test_navigation() -> buck2_error::Result<()> {
+        let trace_id = TraceId::new();
+        let (a, b) = (SpanId::next(), SpanId::next());
+        let mut spans = BuckEventSpanTracker::new();
+        spans.handle_event(&action_start(&trace_id, a))?;
+        spans.handle_event(&action_start(&trace_id, b))?;
+
+        let mut inspector = ActionInspector::default();
+        inspector.refresh(&spans);
+        assert!(!inspector.is_open());
+
+        inspector.handle_key(Key::Down, &spans);
+        assert_eq!(inspector.selected, Some(InspectedAction::Running(a)));
+        inspector.handle_key(Key::Down, &spans);
+        assert_eq!(inspector.selected, Some(InspectedAction::Running(b)));
+        inspector.handle_key(Key::Enter, &spans);
+        assert!(inspector.expanded);
+
+        inspector.handle_key(Key::Escape, &spans);
+        assert!(!inspector.is_open());
+        assert!(!inspector.expanded);
+        Ok(())
+    }
+
+    #[test]
+    fn test_follows_failed_action() -> buck2_error::Result<()> {
+        let trace_id = TraceId::new();
+        let a = SpanId::next();
+        let start = action_start(&trace_id, a);
+        let mut spans = BuckEventSpanTracker::new();
+        spans.handle_event(&start)?;
+
+        let mut inspector = ActionInspector::default();
+        inspector.handle_key(Key::Down, &spans);
+        assert_eq!(inspector.selected, Some(InspectedAction::Running(a)));
+
+        inspector.handle_event(&action_error(&trace_id, a));
+        assert_eq!(inspector.selected, Some(InspectedAction::Failed(0)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_stderr_tail() {
+        let stderr = (0..20)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut lines = Vec::new();
+        stderr_tail(&mut lines, &stderr, 40);
+
+        let lines: Vec<String> = lines.iter().map(|l| l.to_unstyled()).collect();
+        assert_eq!(lines.len(), 1 + STDERR_TAIL_LINES);
+        assert_eq!(lines[1].trim(), "↑ 11 more lines");
+        assert_eq!(lines[2].trim(), "11");
+        assert_eq!(lines.last().unwrap().trim(), "19");
+    }
+}
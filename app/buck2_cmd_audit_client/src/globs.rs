@@ -0,0 +1,46 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use buck2_client_ctx::common::CommonCommandOptions;
+use buck2_client_ctx::common::target_cfg::TargetCfgUnusedOptions;
+
+use crate::AuditSubcommand;
+
+/// Inspect the `glob()` calls of packages.
+///
+/// Prints the include and exclude patterns of each `glob()` call made by the build file of
+/// the package, and the number of files it matched.
+#[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize)]
+#[clap(name = "globs")]
+pub struct AuditGlobsCommand {
+    /// Package names to inspect (like `//foo/bar`, no trailing colon).
+    pub packages: Vec<String>,
+
+    /// Print json representation of outputs
+    #[clap(long)]
+    pub json: bool,
+
+    /// Only print globs which matched no files.
+    #[clap(long)]
+    pub empty: bool,
+
+    /// Command doesn't need these flags, but they are used in mode files, so we need to keep them.
+    #[clap(flatten)]
+    _target_cfg: TargetCfgUnusedOptions,
+
+    #[clap(flatten)]
+    common_opts: CommonCommandOptions,
+}
+
+impl AuditSubcommand for AuditGlobsCommand {
+    fn common_opts(&self) -> &CommonCommandOptions {
+        &self.common_opts
+    }
+}
@@ -35,6 +35,7 @@ use crate::deferred_materializer::DeferredMaterializerCommand;
 use crate::dep_files::AuditDepFilesCommand;
 use crate::execution_platform_resolution::AuditExecutionPlatformResolutionCommand;
 use crate::file_package::AuditFilePackageCommand;
+use crate::globs::AuditGlobsCommand;
 use crate::includes::AuditIncludesCommand;
 use crate::output::command::AuditOutputCommand;
 use crate::output::parse::AuditParseCommand;
@@ -55,6 +56,7 @@ pub mod deferred_materializer;
 pub mod dep_files;
 pub mod execution_platform_resolution;
 pub mod file_package;
+pub mod globs;
 pub mod includes;
 pub mod output;
 pub mod package_values;
@@ -87,6 +89,7 @@ pub enum AuditCommand {
     Output(AuditOutputCommand),
     Parse(AuditParseCommand),
     PackageValues(PackageValuesCommand),
+    Globs(AuditGlobsCommand),
     #[clap(subcommand, hide = true)]
     Perf(AuditPerfCommand),
 }
@@ -125,6 +128,7 @@ impl AuditCommand {
             AuditCommand::Output(cmd) => cmd,
             AuditCommand::Parse(cmd) => cmd,
             AuditCommand::PackageValues(cmd) => cmd,
+            AuditCommand::Globs(cmd) => cmd,
             AuditCommand::Perf(cmd) => cmd,
         }
     }
@@ -37,6 +37,14 @@ pub struct AuditProvidersCommand {
     )]
     pub print_debug: bool,
 
+    #[clap(
+        long = "aspect",
+        value_name = "ASPECT",
+        help = "Apply an aspect to the targets before printing their providers, in the form \
+            `//path/to:defs.bzl%name`. May be repeated; aspects are applied in order"
+    )]
+    pub aspects: Vec<String>,
+
     #[clap(
         name = "TARGET_PATTERNS",
         help = "Patterns to analyze",
@@ -0,0 +1,107 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use std::io::Write;
+
+use async_trait::async_trait;
+use buck2_cmd_audit_client::globs::AuditGlobsCommand;
+use buck2_common::dice::cells::HasCellResolver;
+use buck2_core::pattern::parse_package::parse_package;
+use buck2_events::dispatch::console_message;
+use buck2_node::nodes::eval_result::GlobResult;
+use buck2_node::nodes::frontend::TargetGraphCalculation;
+use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::ctx::ServerCommandDiceContext;
+use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
+use dupe::Dupe;
+use futures::FutureExt;
+use gazebo::prelude::SliceExt;
+use starlark_map::small_map::SmallMap;
+
+use crate::ServerAuditSubcommand;
+
+fn glob_to_json(glob: &GlobResult) -> serde_json::Value {
+    serde_json::json!({
+        "include": glob.include,
+        "exclude": glob.exclude,
+        "matches": glob.match_count,
+    })
+}
+
+#[async_trait]
+impl ServerAuditSubcommand for AuditGlobsCommand {
+    async fn server_execute(
+        &self,
+        server_ctx: &dyn ServerCommandContextTrait,
+        mut stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
+        _client_server_ctx: buck2_cli_proto::ClientContext,
+    ) -> buck2_error::Result<()> {
+        if self.packages.is_empty() {
+            console_message("No packages specified".to_owned());
+        }
+
+        Ok(server_ctx
+            .with_dice_ctx(|server_ctx, mut dice_ctx| async move {
+                let cell_alias_resolver = dice_ctx
+                    .get_cell_alias_resolver_for_dir(server_ctx.working_dir())
+                    .await?;
+
+                let packages = self
+                    .packages
+                    .try_map(|package| parse_package(package.dupe(), &cell_alias_resolver))?;
+
+                let results = dice_ctx
+                    .try_compute_join(packages, |ctx, package| {
+                        async move {
+                            let result = ctx.get_interpreter_results(package.dupe()).await?;
+                            buck2_error::Ok((package, result))
+                        }
+                        .boxed()
+                    })
+                    .await?;
+
+                let selected = |glob: &&GlobResult| !self.empty || glob.match_count == 0;
+
+                let mut stdout = stdout.as_writer();
+                if self.json {
+                    let globs_by_package: SmallMap<String, Vec<serde_json::Value>> = results
+                        .iter()
+                        .map(|(package, result)| {
+                            (
+                                package.to_string(),
+                                result
+                                    .globs()
+                                    .iter()
+                                    .filter(selected)
+                                    .map(glob_to_json)
+                                    .collect(),
+                            )
+                        })
+                        .collect();
+                    serde_json::to_writer_pretty(&mut stdout, &globs_by_package)?;
+                    // Because serde does not write a trailing newline.
+                    writeln!(stdout)?;
+                } else {
+                    for (package, result) in &results {
+                        writeln!(stdout, "{package}:")?;
+                        for glob in result.globs().iter().filter(selected) {
+                            writeln!(
+                                stdout,
+                                "  glob({:?}, exclude = {:?}): {} matches",
+                                glob.include, glob.exclude, glob.match_count
+                            )?;
+                        }
+                    }
+                }
+                Ok(())
+            })
+            .await?)
+    }
+}
@@ -30,6 +30,7 @@ pub mod deferred_materializer;
 mod dep_files;
 mod execution_platform_resolution;
 mod file_package;
+mod globs;
 mod includes;
 pub mod output;
 mod package_values;
@@ -102,6 +103,7 @@ impl AuditCommandExt for AuditCommand {
             AuditCommand::Output(cmd) => cmd,
             AuditCommand::Parse(cmd) => cmd,
             AuditCommand::PackageValues(cmd) => cmd,
+            AuditCommand::Globs(cmd) => cmd,
             AuditCommand::Perf(cmd) => cmd,
         }
     }
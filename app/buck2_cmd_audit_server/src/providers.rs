@@ -9,12 +9,24 @@
  */
 
 use std::io::Write;
+use std::sync::Arc;
 
 use async_trait::async_trait;
+use buck2_analysis::analysis::aspect::get_aspect_providers;
 use buck2_build_api::analysis::calculation::RuleAnalysisCalculation;
 use buck2_build_api::interpreter::rule_defs::provider::collection::FrozenProviderCollectionValue;
 use buck2_cli_proto::ClientContext;
 use buck2_cmd_audit_client::providers::AuditProvidersCommand;
+use buck2_common::dice::cells::HasCellResolver;
+use buck2_core::cells::build_file_cell::BuildFileCell;
+use buck2_core::cells::cell_path_with_allowed_relative_dir::CellPathWithAllowedRelativeDir;
+use buck2_core::configuration::compatibility::MaybeCompatible;
+use buck2_core::provider::label::ProvidersName;
+use buck2_interpreter::parse_import::ParseImportOptions;
+use buck2_interpreter::parse_import::RelativeImports;
+use buck2_interpreter::parse_import::parse_bzl_path_with_config;
+use buck2_node::aspect::AspectId;
+use buck2_node::aspect::AspectIds;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
 use buck2_server_ctx::ctx::ServerCommandDiceContext;
 use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
@@ -22,6 +34,7 @@ use buck2_server_ctx::pattern_parse_and_resolve::parse_and_resolve_provider_labe
 use buck2_util::indent::indent;
 use dice::DiceComputations;
 use dice::DiceTransaction;
+use dupe::Dupe;
 use futures::FutureExt;
 use futures::StreamExt;
 use futures::stream::FuturesOrdered;
@@ -50,6 +63,52 @@ impl ServerAuditSubcommand for AuditProvidersCommand {
 enum AuditProvidersError {
     #[error("Evaluation of at least one target providers failed")]
     AtLeastOneFailed,
+    #[error("Expected an aspect in the form `//path/to:defs.bzl%name`, got `{0}`")]
+    InvalidAspect(String),
+    #[error("Aspects can only be applied to a whole target, not to subtarget `{0}`")]
+    AspectOnSubtarget(String),
+}
+
+async fn parse_aspects(
+    ctx: &mut DiceTransaction,
+    server_ctx: &dyn ServerCommandContextTrait,
+    aspects: &[String],
+) -> buck2_error::Result<AspectIds> {
+    if aspects.is_empty() {
+        return Ok(AspectIds::EMPTY);
+    }
+
+    let cell_resolver = ctx.get_cell_resolver().await?;
+    let current_cell_path = cell_resolver.get_cell_path(server_ctx.working_dir());
+    let current_cell = BuildFileCell::new(current_cell_path.cell());
+    let cell_alias_resolver = ctx
+        .get_cell_alias_resolver(current_cell_path.cell())
+        .await?;
+    let current_dir = CellPathWithAllowedRelativeDir::new(current_cell_path, None);
+
+    let mut ids = Vec::with_capacity(aspects.len());
+    for aspect in aspects {
+        let (path, name) = aspect
+            .rsplit_once('%')
+            .ok_or_else(|| AuditProvidersError::InvalidAspect(aspect.clone()))?;
+        let path = parse_bzl_path_with_config(
+            &cell_alias_resolver,
+            path,
+            &ParseImportOptions {
+                relative_import_option: RelativeImports::Allow {
+                    current_dir_with_allowed_relative: &current_dir,
+                },
+                // Otherwise `@arg` is expanded as mode file.
+                allow_missing_at_symbol: true,
+            },
+            current_cell,
+        )?;
+        ids.push(Arc::new(AspectId {
+            path,
+            name: name.to_owned(),
+        }));
+    }
+    Ok(AspectIds::from(ids))
 }
 
 async fn server_execute_with_dice(
@@ -69,15 +128,29 @@ async fn server_execute_with_dice(
         )
         .await?;
 
+    let aspects = parse_aspects(&mut ctx, server_ctx, &command.aspects).await?;
+
     let mut futs = Vec::new();
     for label_with_modifiers in provider_labels_with_modifiers {
         for configured_providers_label in target_resolution_config
             .get_configured_provider_label_with_modifiers(&mut ctx, &label_with_modifiers)
             .await?
         {
+            let aspects = aspects.dupe();
             futs.push(DiceComputations::declare_closure(|ctx| {
                 async move {
-                    let result = ctx.get_providers(&configured_providers_label).await;
+                    let result = if aspects.is_empty() {
+                        ctx.get_providers(&configured_providers_label).await
+                    } else if !matches!(configured_providers_label.name(), ProvidersName::Default) {
+                        Err(AuditProvidersError::AspectOnSubtarget(
+                            configured_providers_label.to_string(),
+                        )
+                        .into())
+                    } else {
+                        get_aspect_providers(ctx, &aspects, configured_providers_label.target())
+                            .await
+                            .map(MaybeCompatible::Compatible)
+                    };
                     (configured_providers_label, result)
                 }
                 .boxed()
@@ -0,0 +1,364 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::time::Duration;
+
+use buck2_client_ctx::client_ctx::BuckSubcommand;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::common::BuckArgMatches;
+use buck2_client_ctx::events_ctx::EventsCtx;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_common::invocation_paths::InvocationPaths;
+use buck2_data::ActionKey;
+use buck2_data::ActionName;
+use buck2_data::ActionOutput;
+use buck2_event_log::read::EventLogPathBuf;
+use buck2_event_log::stream_value::StreamValue;
+use buck2_event_observer::display::TargetDisplayOptions;
+use buck2_event_observer::display::display_action_identity;
+use buck2_fs::fs_util;
+use buck2_fs::paths::abs_norm_path::AbsNormPath;
+use buck2_fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_fs::paths::file_name::FileNameBuf;
+use buck2_fs::paths::forward_rel_path::ForwardRelativePath;
+use buck2_fs::paths::forward_rel_path::ForwardRelativePathBuf;
+use futures::TryStreamExt;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(tag = Input)]
+enum CheckDeterminismError {
+    #[error("Build {0} of the determinism check failed")]
+    BuildFailed(usize),
+    #[error("Found {0} non-deterministic action(s)")]
+    NonDeterministic(usize),
+}
+
+/// Builds targets twice and reports actions whose outputs differ.
+///
+/// Each build runs its own buck2 server in the client process (as with `--no-buckd`) under its own
+/// isolation dir (and so writes to its own buck-out), with remote caches disabled and every output
+/// materialized. The two builds see a different timezone, locale and umask, and run at different
+/// times. Actions whose output digests differ between the builds are reported along with the files
+/// in their outputs that differ. The buck-outs of both builds are deleted afterwards.
+#[derive(Debug, clap::Parser)]
+pub struct CheckDeterminismCommand {
+    /// Patterns to build.
+    #[clap(value_name = "TARGET_PATTERNS", required = true)]
+    patterns: Vec<String>,
+
+    /// Additional arguments passed to both `buck2 build` invocations.
+    #[clap(last = true, value_name = "BUILD_ARGS")]
+    build_args: Vec<String>,
+}
+
+/// How the environment of each build is perturbed.
+struct Perturbation {
+    tz: &'static str,
+    lang: &'static str,
+    /// Only applied on Unix.
+    #[cfg_attr(not(unix), allow(dead_code))]
+    umask: u32,
+}
+
+const PERTURBATIONS: [Perturbation; 2] = [
+    Perturbation {
+        tz: "UTC",
+        lang: "C",
+        umask: 0o022,
+    },
+    Perturbation {
+        tz: "Pacific/Kiritimati",
+        lang: "en_US.UTF-8",
+        umask: 0o002,
+    },
+];
+
+struct ActionExecutionData {
+    name: Option<ActionName>,
+    outputs: Vec<ActionOutput>,
+}
+
+impl ActionExecutionData {
+    fn output_digests(&self) -> impl Iterator<Item = &str> {
+        self.outputs.iter().map(|o| o.tiny_digest.as_str())
+    }
+}
+
+async fn kill_daemon(isolation: &str) -> buck2_error::Result<()> {
+    buck2_util::process::async_background_command(std::env::current_exe()?)
+        .arg("--isolation-dir")
+        .arg(isolation)
+        .arg("kill")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await?;
+    Ok(())
+}
+
+async fn run_build(
+    run: usize,
+    isolation: &str,
+    event_log: &AbsNormPath,
+    patterns: &[String],
+    build_args: &[String],
+) -> buck2_error::Result<()> {
+    let perturbation = &PERTURBATIONS[run];
+
+    // Make sure no daemon is left over from a previous check in this isolation dir.
+    kill_daemon(isolation).await?;
+
+    // The daemon always resets its umask when it daemonizes, so the server runs in the build's
+    // client process instead, which inherits the umask (and environment) it is spawned with.
+    let mut command = buck2_util::process::async_background_command(std::env::current_exe()?);
+    #[cfg(unix)]
+    {
+        let umask = perturbation.umask as libc::mode_t;
+        // SAFETY: `umask` is async-signal-safe.
+        unsafe {
+            command.pre_exec(move || {
+                libc::umask(umask);
+                Ok(())
+            });
+        }
+    }
+    let status = command
+        .arg("--isolation-dir")
+        .arg(isolation)
+        .arg("--no-buckd")
+        .arg("build")
+        .args(patterns)
+        .arg("--no-remote-cache")
+        .arg("--config")
+        .arg("buck2.materializations=all")
+        .arg("--event-log")
+        .arg(event_log.as_path())
+        .args(build_args)
+        .env("TZ", perturbation.tz)
+        .env("LANG", perturbation.lang)
+        .env("LC_ALL", perturbation.lang)
+        .stdin(Stdio::null())
+        .status()
+        .await?;
+
+    if !status.success() {
+        return Err(CheckDeterminismError::BuildFailed(run + 1).into());
+    }
+    Ok(())
+}
+
+async fn read_actions(
+    event_log: AbsNormPathBuf,
+) -> buck2_error::Result<Vec<(ActionKey, ActionExecutionData)>> {
+    let (_invocation, mut events) = EventLogPathBuf::infer(event_log.into_abs_path_buf())?
+        .unpack_stream()
+        .await?;
+
+    let mut out = Vec::new();
+    while let Some(event) = events.try_next().await? {
+        let StreamValue::Event(event) = event else {
+            continue;
+        };
+        let Some(buck2_data::buck_event::Data::SpanEnd(end)) = event.data else {
+            continue;
+        };
+        let Some(buck2_data::span_end_event::Data::ActionExecution(data)) = end.data else {
+            continue;
+        };
+        if let Some(key) = data.key {
+            out.push((
+                key,
+                ActionExecutionData {
+                    name: data.name,
+                    outputs: data.outputs,
+                },
+            ));
+        }
+    }
+    Ok(out)
+}
+
+/// Lists the files under the output at `a` in the first build and `b` in the second which differ,
+/// as `display` joined with their path below the output.
+fn diff_outputs(
+    a: &AbsNormPath,
+    b: &AbsNormPath,
+    display: &ForwardRelativePath,
+    out: &mut Vec<ForwardRelativePathBuf>,
+) -> buck2_error::Result<()> {
+    let (Some(meta_a), Some(meta_b)) = (
+        fs_util::symlink_metadata_if_exists(a)?,
+        fs_util::symlink_metadata_if_exists(b)?,
+    ) else {
+        out.push(display.to_buf());
+        return Ok(());
+    };
+
+    if meta_a.is_dir() && meta_b.is_dir() {
+        let mut names = BTreeSet::new();
+        for dir in [a, b] {
+            for entry in fs_util::read_dir(dir)? {
+                names.insert(entry?.file_name().to_string_lossy().into_owned());
+            }
+        }
+        for name in names {
+            let name = ForwardRelativePath::new(&name)?;
+            diff_outputs(&a.join(name), &b.join(name), &display.join(name), out)?;
+        }
+        return Ok(());
+    }
+
+    let same = if meta_a.is_symlink() && meta_b.is_symlink() {
+        fs_util::read_link(a)? == fs_util::read_link(b)?
+    } else if meta_a.is_file() && meta_b.is_file() {
+        meta_a.len() == meta_b.len() && fs_util::read(a)? == fs_util::read(b)?
+    } else {
+        false
+    };
+    if !same {
+        out.push(display.to_buf());
+    }
+    Ok(())
+}
+
+impl CheckDeterminismCommand {
+    /// Runs one build per perturbation and reports the actions whose outputs differ between them,
+    /// returning how many there were.
+    async fn check(
+        &self,
+        runs: &[InvocationPaths],
+        log_dir: &AbsNormPath,
+    ) -> buck2_error::Result<usize> {
+        let mut actions = Vec::new();
+        for (run, paths) in runs.iter().enumerate() {
+            if run > 0 {
+                // Make sure timestamps embedded in outputs differ between the builds.
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            let event_log = log_dir.join(ForwardRelativePath::new(&format!("run-{run}.pb.zst"))?);
+            buck2_client_ctx::eprintln!("Determinism check: build {} of 2", run + 1)?;
+            run_build(
+                run,
+                paths.isolation.as_str(),
+                &event_log,
+                &self.patterns,
+                &self.build_args,
+            )
+            .await?;
+            actions.push(read_actions(event_log).await?);
+        }
+
+        let buck_outs = runs
+            .iter()
+            .map(|paths| paths.buck_out_path())
+            .collect::<Vec<_>>();
+        let second = actions.pop().unwrap();
+        let first = actions.pop().unwrap();
+        let first = first.into_iter().collect::<HashMap<_, _>>();
+
+        let mut non_deterministic = 0;
+        for (key, data2) in second {
+            let Some(data1) = first.get(&key) else {
+                continue;
+            };
+            if data1.output_digests().eq(data2.output_digests()) {
+                continue;
+            }
+            non_deterministic += 1;
+
+            buck2_client_ctx::println!(
+                "{}",
+                display_action_identity(
+                    Some(&key),
+                    data2.name.as_ref(),
+                    TargetDisplayOptions::for_log(),
+                )?
+            )?;
+            for (output1, output2) in data1.outputs.iter().zip(&data2.outputs) {
+                if output1.tiny_digest == output2.tiny_digest {
+                    continue;
+                }
+                if output1.path.is_empty() || output2.path.is_empty() {
+                    buck2_client_ctx::println!(
+                        "  <unknown output>: {} vs {}",
+                        output1.tiny_digest,
+                        output2.tiny_digest
+                    )?;
+                    continue;
+                }
+                // Content-based paths differ between the builds, so report those of the second.
+                let path1 = ForwardRelativePath::new(&output1.path)?;
+                let path2 = ForwardRelativePath::new(&output2.path)?;
+                let mut files = Vec::new();
+                diff_outputs(
+                    &buck_outs[0].join(path1),
+                    &buck_outs[1].join(path2),
+                    path2,
+                    &mut files,
+                )?;
+                for file in files {
+                    buck2_client_ctx::println!("  {file}")?;
+                }
+            }
+        }
+        Ok(non_deterministic)
+    }
+}
+
+impl BuckSubcommand for CheckDeterminismCommand {
+    const COMMAND_NAME: &'static str = "debug-check-determinism";
+
+    async fn exec_impl(
+        self,
+        _matches: BuckArgMatches<'_>,
+        ctx: ClientCommandContext<'_>,
+        _events_ctx: &mut EventsCtx,
+    ) -> ExitResult {
+        let paths = ctx.paths()?;
+        let log_dir = paths
+            .tmp_dir()
+            .join(ForwardRelativePath::unchecked_new("check-determinism"));
+        fs_util::create_dir_all(&log_dir)?;
+
+        let runs = (0..PERTURBATIONS.len())
+            .map(|run| {
+                Ok(InvocationPaths {
+                    roots: paths.roots.clone(),
+                    isolation: FileNameBuf::try_from(format!(
+                        "{}-determinism-{}",
+                        paths.isolation, run
+                    ))?,
+                })
+            })
+            .collect::<buck2_error::Result<Vec<_>>>()?;
+
+        let result = self.check(&runs, &log_dir).await;
+
+        // Both builds ran their server in-process and have exited, so their state can be deleted.
+        for paths in &runs {
+            fs_util::remove_all(paths.buck_out_path())?;
+            fs_util::remove_all(paths.daemon_dir()?.path)?;
+        }
+
+        let non_deterministic = result?;
+        if non_deterministic > 0 {
+            return buck2_error::Error::from(CheckDeterminismError::NonDeterministic(
+                non_deterministic,
+            ))
+            .into();
+        }
+        buck2_client_ctx::println!("No non-deterministic actions found.")?;
+        ExitResult::success()
+    }
+}
@@ -17,6 +17,7 @@ use buck2_common::argv::SanitizedArgv;
 
 use crate::allocative::AllocativeCommand;
 use crate::allocator_stats::AllocatorStatsCommand;
+use crate::check_determinism::CheckDeterminismCommand;
 use crate::chrome_trace::ChromeTraceCommand;
 use crate::crash::CrashCommand;
 use crate::daemon_dir::DaemonDirCommand;
@@ -38,6 +39,7 @@ use crate::upload_re_logs::UploadReLogsCommand;
 
 mod allocative;
 mod allocator_stats;
+mod check_determinism;
 mod chrome_trace;
 mod crash;
 mod daemon_dir;
@@ -93,6 +95,7 @@ pub enum DebugCommand {
     PersistEventLogs(PersistEventLogsCommand),
     #[clap(subcommand)]
     Paranoid(ParanoidCommand),
+    CheckDeterminism(CheckDeterminismCommand),
     Eval(EvalCommand),
     ThreadDump(ThreadDumpCommand),
 }
@@ -124,6 +127,7 @@ impl DebugCommand {
             DebugCommand::TraceIo(cmd) => ctx.exec(cmd, matches, events_ctx),
             DebugCommand::PersistEventLogs(cmd) => cmd.exec(matches, ctx, events_ctx),
             DebugCommand::Paranoid(cmd) => cmd.exec(matches, ctx),
+            DebugCommand::CheckDeterminism(cmd) => ctx.exec(cmd, matches, events_ctx),
             DebugCommand::Eval(cmd) => ctx.exec(cmd, matches, events_ctx),
             DebugCommand::ThreadDump(cmd) => cmd.exec(matches, ctx),
         }
@@ -19,6 +19,9 @@ use buck2_client_ctx::event_log_options::EventLogOptions;
 use buck2_client_ctx::events_ctx::EventsCtx;
 use buck2_client_ctx::exit_result::ClientIoError;
 use buck2_client_ctx::exit_result::ExitResult;
+use buck2_data::error::ErrorTag;
+use buck2_error::buck2_error;
+use buck2_error::conversion::clap::buck_error_clap_parser;
 use buck2_error::conversion::from_any_with_tag;
 use buck2_event_log::stream_value::StreamValue;
 use buck2_event_observer::display::CriticalPathEntryDisplay;
@@ -29,6 +32,8 @@ use tokio_stream::StreamExt;
 use crate::LogCommandOutputFormat;
 use crate::LogCommandOutputFormatWithWriter;
 use crate::transform_format;
+use crate::what_if;
+use crate::what_if::WhatIf;
 
 /// Show the critical path for a selected build.
 ///
@@ -45,12 +50,26 @@ use crate::transform_format;
 /// `<kind>\t<name>\t<category>\t<identifier>\t<execution_kind>\t<total_duration>\t<user_duration>\t<potential_improvement_duration>\t<non_critical_path_time>\t<start_offset>`
 ///
 /// All durations are in microseconds. Start offset is in microseconds from the beginning of the build.
+///
+/// With `--what-if`, the critical path is recomputed under hypothetical scenarios instead. This
+/// requires the build to have been run with `-c buck2.log_critical_path_graph=true`.
 #[derive(Debug, clap::Parser)]
 pub struct CriticalPathCommand {
     #[clap(flatten)]
     event_log: EventLogOptions,
     #[clap(flatten)]
     format: LogCommandOutputFormat,
+    /// Recompute the critical path as if the build had been different. May be repeated.
+    ///
+    /// `category=<CATEGORY>:<FACTOR>` makes actions of that category FACTOR times faster,
+    /// `cache-hits` assumes every action was a cache hit, and `jobs=<N>` limits local
+    /// execution to N concurrent actions.
+    #[clap(
+        long,
+        value_name = "SCENARIO",
+        value_parser = buck_error_clap_parser(what_if::parse_what_if)
+    )]
+    what_if: Vec<WhatIf>,
 }
 
 impl BuckSubcommand for CriticalPathCommand {
@@ -62,8 +81,12 @@ impl BuckSubcommand for CriticalPathCommand {
         ctx: ClientCommandContext<'_>,
         _events_ctx: &mut EventsCtx,
     ) -> ExitResult {
-        let Self { event_log, format } = self;
-        log_critical_path_command_exec(ctx, event_log, format, PathKind::Critical).await
+        let Self {
+            event_log,
+            format,
+            what_if,
+        } = self;
+        log_critical_path_command_exec(ctx, event_log, format, PathKind::Critical, what_if).await
     }
 }
 
@@ -104,7 +127,7 @@ impl BuckSubcommand for SlowestPathCommand {
         _events_ctx: &mut EventsCtx,
     ) -> ExitResult {
         let Self { event_log, format } = self;
-        log_critical_path_command_exec(ctx, event_log, format, PathKind::Slowest).await
+        log_critical_path_command_exec(ctx, event_log, format, PathKind::Slowest, Vec::new()).await
     }
 }
 
@@ -121,6 +144,7 @@ async fn log_critical_path_command_exec(
     event_log: EventLogOptions,
     format: LogCommandOutputFormat,
     path_kind: PathKind,
+    what_if: Vec<WhatIf>,
 ) -> ExitResult {
     let log_path = event_log.get(&ctx).await?;
 
@@ -141,6 +165,29 @@ async fn log_critical_path_command_exec(
                 Some(buck2_data::buck_event::Data::Instant(instant)) => match instant.data {
                     Some(buck2_data::instant_event::Data::BuildGraphInfo(build_graph)) => {
                         match path_kind {
+                            PathKind::Critical if !what_if.is_empty() => {
+                                let graph = build_graph.graph.as_ref().ok_or_else(|| {
+                                    buck2_error!(
+                                        ErrorTag::Input,
+                                        "This build did not log its action graph; rerun it with `-c buck2.log_critical_path_graph=true` to use --what-if"
+                                    )
+                                })?;
+                                let outcome = what_if::what_if(graph, &what_if)?;
+                                let baseline = outcome.baseline.as_secs_f64();
+                                let estimate = outcome.estimate.as_secs_f64();
+                                let change = if baseline > 0.0 {
+                                    (estimate - baseline) / baseline * 100.0
+                                } else {
+                                    0.0
+                                };
+                                buck2_client_ctx::eprintln!(
+                                    "Critical path: {:.3}s, estimated: {:.3}s ({:+.1}%)",
+                                    baseline,
+                                    estimate,
+                                    change
+                                )?;
+                                log_critical_path(&outcome.critical_path, format.clone()).await?;
+                            }
                             PathKind::Critical => {
                                 log_critical_path(&build_graph.critical_path2, format.clone())
                                     .await?;
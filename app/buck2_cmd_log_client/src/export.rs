@@ -0,0 +1,523 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use std::collections::HashMap;
+
+use buck2_cli_proto::command_result;
+use buck2_client_ctx::client_ctx::BuckSubcommand;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::common::BuckArgMatches;
+use buck2_client_ctx::events_ctx::EventsCtx;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_client_ctx::path_arg::PathArg;
+use buck2_data::ActionExecutionKind;
+use buck2_data::error::ErrorTag;
+use buck2_error::BuckErrorContext;
+use buck2_event_log::file_names::retrieve_all_logs;
+use buck2_event_log::file_names::retrieve_nth_recent_log;
+use buck2_event_log::read::EventLogPathBuf;
+use buck2_event_log::stream_value::StreamValue;
+use buck2_event_log::utils::Invocation;
+use buck2_event_observer::action_stats::is_cache_hit;
+use buck2_event_observer::display::TargetDisplayOptions;
+use buck2_event_observer::display::display_action_key;
+use buck2_event_observer::display::display_configured_target_label;
+use futures::TryStreamExt;
+use gazebo::variants::VariantName;
+use rusqlite::Connection;
+use rusqlite::Transaction;
+
+/// Tables created in the exported database. Every table has a `trace_id` column referring to
+/// `invocations`. Times are in milliseconds, timestamps in milliseconds since the Unix epoch.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS invocations (
+        trace_id            TEXT PRIMARY KEY,
+        command             TEXT,
+        command_line        TEXT NOT NULL,
+        working_dir         TEXT NOT NULL,
+        start_time_ms       INTEGER,
+        duration_ms         INTEGER,
+        success             INTEGER,
+        log_path            TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS spans (
+        trace_id            TEXT NOT NULL,
+        span_id             INTEGER NOT NULL,
+        parent_id           INTEGER,
+        kind                TEXT NOT NULL,
+        start_time_ms       INTEGER,
+        end_time_ms         INTEGER,
+        duration_ms         INTEGER,
+        PRIMARY KEY         (trace_id, span_id)
+    );
+    CREATE TABLE IF NOT EXISTS actions (
+        trace_id            TEXT NOT NULL,
+        span_id             INTEGER NOT NULL,
+        target              TEXT,
+        kind                TEXT NOT NULL,
+        category            TEXT,
+        identifier          TEXT,
+        execution_kind      TEXT NOT NULL,
+        cache_hit           INTEGER NOT NULL,
+        failed              INTEGER NOT NULL,
+        wall_time_ms        INTEGER,
+        output_size         INTEGER NOT NULL,
+        PRIMARY KEY         (trace_id, span_id)
+    );
+    CREATE TABLE IF NOT EXISTS test_results (
+        trace_id            TEXT NOT NULL,
+        target              TEXT,
+        name                TEXT NOT NULL,
+        status              TEXT NOT NULL,
+        duration_ms         INTEGER,
+        max_memory_used_bytes INTEGER
+    );
+    CREATE TABLE IF NOT EXISTS errors (
+        trace_id            TEXT NOT NULL,
+        message             TEXT NOT NULL,
+        category_key        TEXT,
+        tags                TEXT NOT NULL
+    );
+";
+
+const TABLES: &[&str] = &["invocations", "spans", "actions", "test_results", "errors"];
+
+/// Export event logs to a SQLite database for ad-hoc analysis.
+///
+/// The database has the tables `invocations`, `spans`, `actions`, `test_results` and `errors`,
+/// all keyed by `trace_id`. Exporting to an existing database adds to it, and exporting a log
+/// again replaces what was previously exported from it.
+///
+/// Example: `buck2 log export --sqlite builds.db --all && sqlite3 builds.db 'SELECT category,
+/// SUM(wall_time_ms) FROM actions GROUP BY category'`
+#[derive(Debug, clap::Parser)]
+pub struct ExportCommand {
+    /// Path of the SQLite database to write to. It is created if it doesn't exist.
+    #[clap(long, value_name = "PATH")]
+    sqlite: PathArg,
+
+    /// Export every event log in the log directory.
+    #[clap(long, conflicts_with_all = ["recent", "paths"])]
+    all: bool,
+
+    /// Export the event log from a recent command.
+    #[clap(long, value_name = "NUMBER", conflicts_with = "paths")]
+    recent: Option<usize>,
+
+    /// Paths to event logs to export. Defaults to the most recent command.
+    #[clap(value_name = "PATH")]
+    paths: Vec<PathArg>,
+}
+
+impl BuckSubcommand for ExportCommand {
+    const COMMAND_NAME: &'static str = "log-export";
+
+    async fn exec_impl(
+        self,
+        _matches: BuckArgMatches<'_>,
+        ctx: ClientCommandContext<'_>,
+        _events_ctx: &mut EventsCtx,
+    ) -> ExitResult {
+        let logs = if self.all {
+            retrieve_all_logs(ctx.paths()?)?
+        } else if !self.paths.is_empty() {
+            self.paths
+                .iter()
+                .map(|path| EventLogPathBuf::infer(path.resolve(&ctx.working_dir)))
+                .collect::<buck2_error::Result<_>>()?
+        } else {
+            vec![retrieve_nth_recent_log(
+                ctx.paths()?,
+                self.recent.unwrap_or(0),
+            )?]
+        };
+
+        let db_path = self.sqlite.resolve(&ctx.working_dir);
+        let mut connection = Connection::open(&db_path).with_buck_error_context(|| {
+            format!("Error opening SQLite database `{}`", db_path.display())
+        })?;
+        connection
+            .execute_batch(SCHEMA)
+            .buck_error_context("Error creating tables")?;
+
+        for log in &logs {
+            let (invocation, mut events) = log.unpack_stream().await?;
+            let command = log.command_from_filename().ok();
+            let mut exporter = LogExporter::new(connection.transaction()?, &invocation)?;
+            while let Some(value) = events.try_next().await? {
+                exporter.value(value)?;
+            }
+            exporter.finish(&invocation, command, &log.path().to_string_lossy())?;
+            buck2_client_ctx::eprintln!("Exported {}", log.path().display())?;
+        }
+
+        ExitResult::success()
+    }
+}
+
+struct OpenSpan {
+    parent_id: Option<i64>,
+    kind: &'static str,
+    start_time_ms: Option<i64>,
+}
+
+/// Exports a single event log, inside a transaction.
+struct LogExporter<'a> {
+    tx: Transaction<'a>,
+    trace_id: String,
+    open_spans: HashMap<u64, OpenSpan>,
+    start_time_ms: Option<i64>,
+    duration_ms: Option<i64>,
+    success: Option<bool>,
+}
+
+impl<'a> LogExporter<'a> {
+    fn new(tx: Transaction<'a>, invocation: &Invocation) -> buck2_error::Result<Self> {
+        let trace_id = invocation.trace_id.to_string();
+        for table in TABLES {
+            tx.execute(
+                &format!("DELETE FROM {table} WHERE trace_id = ?1"),
+                [&trace_id],
+            )
+            .with_buck_error_context(|| format!("Error clearing previous export from {table}"))?;
+        }
+        Ok(Self {
+            tx,
+            trace_id,
+            open_spans: HashMap::new(),
+            start_time_ms: invocation.start_time.map(|t| millis(&t.into())),
+            duration_ms: None,
+            success: None,
+        })
+    }
+
+    fn value(&mut self, value: StreamValue) -> buck2_error::Result<()> {
+        match value {
+            StreamValue::Event(event) => self.event(&event),
+            StreamValue::Result(result) => {
+                let errors = match result.result {
+                    Some(command_result::Result::Error(error)) => vec![error],
+                    Some(command_result::Result::BuildResponse(v)) => v.errors,
+                    Some(command_result::Result::TestResponse(v)) => v.errors,
+                    Some(command_result::Result::BxlResponse(v)) => v.errors,
+                    _ => Vec::new(),
+                };
+                for error in errors {
+                    self.error(&error)?;
+                }
+                Ok(())
+            }
+            StreamValue::PartialResult(_) => Ok(()),
+        }
+    }
+
+    fn event(&mut self, event: &buck2_data::BuckEvent) -> buck2_error::Result<()> {
+        let time_ms = event.timestamp.as_ref().map(millis);
+        match &event.data {
+            Some(buck2_data::buck_event::Data::SpanStart(start)) => {
+                if let Some(data) = &start.data {
+                    if self.start_time_ms.is_none() {
+                        self.start_time_ms = time_ms;
+                    }
+                    self.open_spans.insert(
+                        event.span_id,
+                        OpenSpan {
+                            parent_id: (event.parent_id != 0).then_some(event.parent_id as i64),
+                            kind: data.variant_name(),
+                            start_time_ms: time_ms,
+                        },
+                    );
+                }
+            }
+            Some(buck2_data::buck_event::Data::SpanEnd(end)) => {
+                let duration_ms = end.duration.as_ref().map(duration_millis);
+                if let Some(span) = self.open_spans.remove(&event.span_id) {
+                    self.tx.execute(
+                        "INSERT INTO spans (trace_id, span_id, parent_id, kind, start_time_ms, end_time_ms, duration_ms) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        rusqlite::params![
+                            self.trace_id,
+                            event.span_id as i64,
+                            span.parent_id,
+                            span.kind,
+                            span.start_time_ms,
+                            time_ms,
+                            duration_ms,
+                        ],
+                    )?;
+                }
+                match &end.data {
+                    Some(buck2_data::span_end_event::Data::ActionExecution(action)) => {
+                        self.action(event.span_id, action)?;
+                    }
+                    Some(buck2_data::span_end_event::Data::Command(command)) => {
+                        self.duration_ms = duration_ms;
+                        self.success = Some(command.is_success);
+                    }
+                    _ => {}
+                }
+            }
+            Some(buck2_data::buck_event::Data::Instant(instant)) => {
+                if let Some(buck2_data::instant_event::Data::TestResult(test)) = &instant.data {
+                    self.test_result(test)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn action(
+        &mut self,
+        span_id: u64,
+        action: &buck2_data::ActionExecutionEnd,
+    ) -> buck2_error::Result<()> {
+        let target = action
+            .key
+            .as_ref()
+            .and_then(|key| display_action_key(key, TargetDisplayOptions::for_log()).ok());
+        let execution_kind = ActionExecutionKind::try_from(action.execution_kind)
+            .unwrap_or(ActionExecutionKind::NotSet);
+        self.tx.execute(
+            "INSERT INTO actions (trace_id, span_id, target, kind, category, identifier, execution_kind, cache_hit, failed, wall_time_ms, output_size) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            rusqlite::params![
+                self.trace_id,
+                span_id as i64,
+                target,
+                action.kind().as_str_name(),
+                action.name.as_ref().map(|n| &n.category),
+                action.name.as_ref().map(|n| &n.identifier),
+                execution_kind.as_str_name(),
+                is_cache_hit(execution_kind),
+                action.failed,
+                action.wall_time.as_ref().map(duration_millis),
+                action.output_size as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn test_result(&mut self, test: &buck2_data::TestResult) -> buck2_error::Result<()> {
+        let target = test
+            .target_label
+            .as_ref()
+            .and_then(|t| display_configured_target_label(t, TargetDisplayOptions::for_log()).ok());
+        self.tx.execute(
+            "INSERT INTO test_results (trace_id, target, name, status, duration_ms, max_memory_used_bytes) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                self.trace_id,
+                target,
+                test.name,
+                test.status().as_str_name(),
+                test.duration.as_ref().map(duration_millis),
+                test.max_memory_used_bytes.map(|b| b as i64),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn error(&mut self, error: &buck2_data::ErrorReport) -> buck2_error::Result<()> {
+        let tags = error
+            .tags
+            .iter()
+            .filter_map(|t| ErrorTag::try_from(*t).ok())
+            .map(|t| t.as_str_name())
+            .collect::<Vec<_>>()
+            .join(",");
+        self.tx.execute(
+            "INSERT INTO errors (trace_id, message, category_key, tags) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![self.trace_id, error.message, error.category_key, tags],
+        )?;
+        Ok(())
+    }
+
+    fn finish(
+        mut self,
+        invocation: &Invocation,
+        command: Option<&str>,
+        log_path: &str,
+    ) -> buck2_error::Result<()> {
+        // Spans that never ended, e.g. because the command was interrupted.
+        for (span_id, span) in std::mem::take(&mut self.open_spans) {
+            self.tx.execute(
+                "INSERT INTO spans (trace_id, span_id, parent_id, kind, start_time_ms) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![
+                    self.trace_id,
+                    span_id as i64,
+                    span.parent_id,
+                    span.kind,
+                    span.start_time_ms,
+                ],
+            )?;
+        }
+        self.tx.execute(
+            "INSERT INTO invocations (trace_id, command, command_line, working_dir, start_time_ms, duration_ms, success, log_path) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            rusqlite::params![
+                self.trace_id,
+                command,
+                invocation.display_command_line(),
+                invocation.working_dir,
+                self.start_time_ms,
+                self.duration_ms,
+                self.success,
+                log_path,
+            ],
+        )?;
+        self.tx.commit()?;
+        Ok(())
+    }
+}
+
+fn millis(timestamp: &prost_types::Timestamp) -> i64 {
+    timestamp.seconds * 1000 + i64::from(timestamp.nanos) / 1_000_000
+}
+
+fn duration_millis(duration: &prost_types::Duration) -> i64 {
+    duration.seconds * 1000 + i64::from(duration.nanos) / 1_000_000
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use buck2_data::ActionExecutionEnd;
+    use buck2_data::ActionExecutionStart;
+    use buck2_data::CommandEnd;
+    use buck2_data::CommandStart;
+    use buck2_data::SpanEndEvent;
+    use buck2_data::SpanStartEvent;
+    use buck2_wrapper_common::invocation_id::TraceId;
+
+    use super::*;
+
+    fn event(
+        span_id: u64,
+        parent_id: u64,
+        seconds: i64,
+        data: buck2_data::buck_event::Data,
+    ) -> StreamValue {
+        StreamValue::Event(Box::new(buck2_data::BuckEvent {
+            timestamp: Some(prost_types::Timestamp { seconds, nanos: 0 }),
+            span_id,
+            parent_id,
+            data: Some(data),
+            ..Default::default()
+        }))
+    }
+
+    fn end(seconds: i64, data: buck2_data::span_end_event::Data) -> buck2_data::buck_event::Data {
+        SpanEndEvent {
+            duration: Some(prost_types::Duration { seconds, nanos: 0 }),
+            data: Some(data),
+            ..Default::default()
+        }
+        .into()
+    }
+
+    fn query(connection: &Connection, sql: &str) -> String {
+        connection
+            .query_row(sql, [], |row| row.get::<_, String>(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_export() -> buck2_error::Result<()> {
+        let invocation = Invocation {
+            command_line_args: vec!["buck2".to_owned(), "build".to_owned()],
+            expanded_command_line_args: Vec::new(),
+            working_dir: "/repo".to_owned(),
+            trace_id: TraceId::from_str("7b797fa8-62f1-4123-85f9-875cd74b0a63")?,
+            start_time: None,
+        };
+        let values = || {
+            vec![
+                event(
+                    1,
+                    0,
+                    10,
+                    SpanStartEvent {
+                        data: Some(CommandStart::default().into()),
+                    }
+                    .into(),
+                ),
+                event(
+                    2,
+                    1,
+                    11,
+                    SpanStartEvent {
+                        data: Some(ActionExecutionStart::default().into()),
+                    }
+                    .into(),
+                ),
+                event(
+                    2,
+                    1,
+                    13,
+                    end(
+                        2,
+                        ActionExecutionEnd {
+                            execution_kind: ActionExecutionKind::ActionCache as i32,
+                            ..Default::default()
+                        }
+                        .into(),
+                    ),
+                ),
+                event(
+                    1,
+                    0,
+                    15,
+                    end(
+                        5,
+                        CommandEnd {
+                            is_success: true,
+                            ..Default::default()
+                        }
+                        .into(),
+                    ),
+                ),
+            ]
+        };
+
+        let mut connection = Connection::open_in_memory()?;
+        connection.execute_batch(SCHEMA)?;
+
+        // Exporting twice replaces the first export.
+        for _ in 0..2 {
+            let mut exporter = LogExporter::new(connection.transaction()?, &invocation)?;
+            for value in values() {
+                exporter.value(value)?;
+            }
+            exporter.finish(&invocation, Some("build"), "/logs/log.pb.zst")?;
+        }
+
+        assert_eq!(
+            query(
+                &connection,
+                "SELECT command || ' ' || start_time_ms || ' ' || duration_ms || ' ' || success FROM invocations"
+            ),
+            "build 10000 5000 1"
+        );
+        assert_eq!(
+            query(
+                &connection,
+                "SELECT GROUP_CONCAT(kind || ':' || duration_ms) FROM (SELECT * FROM spans ORDER BY span_id)"
+            ),
+            "Command:5000,ActionExecution:2000"
+        );
+        assert_eq!(
+            query(
+                &connection,
+                "SELECT COUNT(*) || ' ' || SUM(cache_hit) || ' ' || execution_kind FROM actions"
+            ),
+            "1 1 ACTION_EXECUTION_KIND_ACTION_CACHE"
+        );
+
+        Ok(())
+    }
+}
@@ -21,14 +21,18 @@ use dupe::Dupe;
 
 mod critical_path;
 mod diff;
+mod export;
 mod external_configs;
+mod output_sizes;
 pub(crate) mod path_log;
 mod replay;
 mod show_log;
 mod show_user_log;
 mod summary;
+mod trends;
 mod what_cmd;
 mod what_failed;
+mod what_if;
 mod what_materialized;
 pub(crate) mod what_ran;
 mod what_up;
@@ -124,6 +128,7 @@ pub enum LogCommand {
     WhatUp(what_up::WhatUpCommand),
     WhatMaterialized(what_materialized::WhatMaterializedCommand),
     WhatUploaded(what_uploaded::WhatUploadedCommand),
+    OutputSizes(output_sizes::OutputSizesCommand),
     CriticalPath(critical_path::CriticalPathCommand),
     SlowestPath(critical_path::SlowestPathCommand),
     Replay(replay::ReplayCommand),
@@ -132,6 +137,8 @@ pub enum LogCommand {
     #[clap(subcommand)]
     Diff(diff::DiffCommand),
     ExternalConfigs(external_configs::ExternalConfigsCommand),
+    Export(export::ExportCommand),
+    Trends(trends::TrendsCommand),
 }
 
 impl LogCommand {
@@ -150,6 +157,7 @@ impl LogCommand {
             Self::WhatUp(cmd) => ctx.exec(cmd, matches, events_ctx),
             Self::WhatMaterialized(cmd) => ctx.exec(cmd, matches, events_ctx),
             Self::WhatUploaded(cmd) => ctx.exec(cmd, matches, events_ctx),
+            Self::OutputSizes(cmd) => ctx.exec(cmd, matches, events_ctx),
             Self::CriticalPath(cmd) => ctx.exec(cmd, matches, events_ctx),
             Self::SlowestPath(cmd) => ctx.exec(cmd, matches, events_ctx),
             Self::Replay(cmd) => ctx.exec(cmd, matches, events_ctx),
@@ -157,6 +165,8 @@ impl LogCommand {
             Self::Summary(cmd) => ctx.exec(cmd, matches, events_ctx),
             Self::Diff(cmd) => cmd.exec(matches, ctx, events_ctx),
             Self::ExternalConfigs(cmd) => ctx.exec(cmd, matches, events_ctx),
+            Self::Export(cmd) => ctx.exec(cmd, matches, events_ctx),
+            Self::Trends(cmd) => ctx.exec(cmd, matches, events_ctx),
         }
     }
 
@@ -174,6 +184,7 @@ impl LogCommand {
             Self::WhatUp(cmd) => cmd.logging_name(),
             Self::WhatMaterialized(cmd) => cmd.logging_name(),
             Self::WhatUploaded(cmd) => cmd.logging_name(),
+            Self::OutputSizes(cmd) => cmd.logging_name(),
             Self::CriticalPath(cmd) => cmd.logging_name(),
             Self::SlowestPath(cmd) => cmd.logging_name(),
             Self::Replay(cmd) => cmd.logging_name(),
@@ -181,6 +192,8 @@ impl LogCommand {
             Self::Summary(cmd) => cmd.logging_name(),
             Self::Diff(_) => "log-diff",
             Self::ExternalConfigs(cmd) => cmd.logging_name(),
+            Self::Export(cmd) => cmd.logging_name(),
+            Self::Trends(cmd) => cmd.logging_name(),
         }
     }
 }
@@ -0,0 +1,184 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use buck2_client_ctx::client_ctx::BuckSubcommand;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::common::BuckArgMatches;
+use buck2_client_ctx::event_log_options::EventLogOptions;
+use buck2_client_ctx::events_ctx::EventsCtx;
+use buck2_client_ctx::exit_result::ClientIoError;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_event_log::stream_value::StreamValue;
+use buck2_event_observer::display;
+use buck2_event_observer::display::TargetDisplayOptions;
+use buck2_event_observer::humanized::HumanizedBytes;
+use tokio_stream::StreamExt;
+
+use crate::LogCommandOutputFormat;
+use crate::LogCommandOutputFormatWithWriter;
+use crate::transform_format;
+
+/// Shows the largest action outputs of each target built by the selected invocation.
+///
+/// Targets are sorted by the total size of their outputs, largest first.
+///
+/// The `tabulated` format produces tab-delimited output:
+/// `<target>\t<target_bytes>\t<action>\t<bytes>`
+#[derive(Debug, clap::Parser)]
+pub struct OutputSizesCommand {
+    #[clap(flatten)]
+    event_log: EventLogOptions,
+    #[clap(flatten)]
+    output: LogCommandOutputFormat,
+    /// Number of targets to show.
+    #[clap(long, default_value = "10")]
+    limit: usize,
+    /// Number of actions to show for each target.
+    #[clap(long, default_value = "5")]
+    actions_per_target: usize,
+}
+
+#[derive(serde::Serialize)]
+struct OutputRecord {
+    target: String,
+    target_bytes: u64,
+    action: String,
+    bytes: u64,
+}
+
+#[derive(Default)]
+struct TargetOutputs {
+    total_bytes: u64,
+    /// `(action, bytes)` for each action.
+    actions: Vec<(String, u64)>,
+}
+
+fn print_target(
+    output: &mut LogCommandOutputFormatWithWriter,
+    target: &str,
+    outputs: &TargetOutputs,
+    limit: usize,
+) -> Result<(), ClientIoError> {
+    let records = outputs
+        .actions
+        .iter()
+        .take(limit)
+        .map(|(action, bytes)| OutputRecord {
+            target: target.to_owned(),
+            target_bytes: outputs.total_bytes,
+            action: action.clone(),
+            bytes: *bytes,
+        });
+    match output {
+        LogCommandOutputFormatWithWriter::Readable(w) => {
+            writeln!(
+                w,
+                "{} ({})",
+                target,
+                HumanizedBytes::new(outputs.total_bytes)
+            )?;
+            for record in records {
+                writeln!(
+                    w,
+                    "  {:>10}  {}",
+                    HumanizedBytes::new(record.bytes).to_string(),
+                    record.action
+                )?;
+            }
+        }
+        LogCommandOutputFormatWithWriter::Tabulated(w) => {
+            for record in records {
+                writeln!(
+                    w,
+                    "{}\t{}\t{}\t{}",
+                    record.target, record.target_bytes, record.action, record.bytes
+                )?;
+            }
+        }
+        LogCommandOutputFormatWithWriter::Csv(writer) => {
+            for record in records {
+                writer.serialize(record)?;
+            }
+        }
+        LogCommandOutputFormatWithWriter::Json(w) => {
+            for record in records {
+                serde_json::to_writer(w.by_ref(), &record)?;
+                w.write_all("\n".as_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl BuckSubcommand for OutputSizesCommand {
+    const COMMAND_NAME: &'static str = "log-output-sizes";
+
+    async fn exec_impl(
+        self,
+        _matches: BuckArgMatches<'_>,
+        ctx: ClientCommandContext<'_>,
+        _events_ctx: &mut EventsCtx,
+    ) -> ExitResult {
+        let Self {
+            event_log,
+            output,
+            limit,
+            actions_per_target,
+        } = self;
+
+        buck2_client_ctx::stdio::print_with_writer::<buck2_error::Error, _>(async move |w| {
+            let mut output = transform_format(output, w);
+            let log_path = event_log.get(&ctx).await?;
+
+            let (invocation, mut events) = log_path.unpack_stream().await?;
+            buck2_client_ctx::eprintln!(
+                "Showing output sizes from: {}",
+                invocation.display_command_line()
+            )?;
+
+            let mut targets = HashMap::<String, TargetOutputs>::new();
+            while let Some(event) = events.try_next().await? {
+                let StreamValue::Event(event) = event else {
+                    continue;
+                };
+                let Some(buck2_data::buck_event::Data::SpanEnd(end)) = event.data else {
+                    continue;
+                };
+                let Some(buck2_data::span_end_event::Data::ActionExecution(action)) = end.data
+                else {
+                    continue;
+                };
+                let Some(key) = action.key.as_ref() else {
+                    continue;
+                };
+                let target = display::display_action_key(key, TargetDisplayOptions::for_log())?;
+                let name = display::display_action_name_opt(action.name.as_ref());
+
+                let entry = targets.entry(target).or_default();
+                entry.total_bytes += action.output_size;
+                entry.actions.push((name, action.output_size));
+            }
+
+            let mut targets = targets.into_iter().collect::<Vec<_>>();
+            targets.sort_by(|(_, a), (_, b)| b.total_bytes.cmp(&a.total_bytes));
+            for (target, mut outputs) in targets.into_iter().take(limit) {
+                outputs.actions.sort_by(|a, b| b.1.cmp(&a.1));
+                print_target(&mut output, &target, &outputs, actions_per_target)?;
+            }
+
+            Ok(())
+        })
+        .await?;
+        ExitResult::success()
+    }
+}
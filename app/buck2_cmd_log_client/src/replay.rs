@@ -32,6 +32,7 @@ use buck2_event_log::read::EventLogPathBuf;
 use buck2_event_log::stream_value::StreamValue;
 use buck2_event_log::utils::Invocation;
 use buck2_event_observer::span_tracker::EventTimestamp;
+use buck2_events::span::SpanId;
 use futures::Stream;
 use futures::StreamExt;
 use futures::TryStreamExt;
@@ -50,6 +51,8 @@ pub(crate) enum ReplayError {
     InvalidSpeed(f64),
     #[error("Invalid seek {0}")]
     InvalidSeek(f64),
+    #[error("No action with span ID {0} in the event log")]
+    SpanNotFound(SpanId),
 }
 
 /// Replay an event log.
@@ -73,6 +76,10 @@ pub struct ReplayCommand {
     #[clap(long, default_value = "0.0")]
     pub seek: f64,
 
+    /// Skip to the start of the action with the given span ID before starting the replay
+    #[clap(long, value_name = "SPAN_ID", conflicts_with = "seek")]
+    pub seek_span: Option<u64>,
+
     /// Preload the event log. This is typically only useful for benchmarking.
     #[clap(long)]
     preload: bool,
@@ -94,6 +101,7 @@ impl BuckSubcommand for ReplayCommand {
             event_log,
             speed,
             seek,
+            seek_span,
             preload,
             console_opts,
         } = self;
@@ -106,7 +114,10 @@ impl BuckSubcommand for ReplayCommand {
             return ExitResult::from(buck2_error::Error::from(ReplayError::InvalidSeek(seek)));
         }
 
-        let seek = Duration::from_secs(1).mul_f64(seek);
+        let seek = match seek_span {
+            Some(span_id) => ReplaySeek::Span(SpanId::from_u64(span_id)?),
+            None => ReplaySeek::Time(Duration::from_secs(1).mul_f64(seek)),
+        };
 
         let work = async {
             let (event_stream, invocation, timekeeper) =
@@ -155,6 +166,14 @@ impl BuckSubcommand for ReplayCommand {
     }
 }
 
+#[derive(Copy, Clone)]
+enum ReplaySeek {
+    /// Time since the start of the command.
+    Time(Duration),
+    /// Start of an action span.
+    Span(SpanId),
+}
+
 struct ReplayResult {
     errors: Vec<buck2_data::ErrorReport>,
 }
@@ -182,7 +201,7 @@ impl TryFrom<buck2_cli_proto::command_result::Result> for ReplayResult {
 async fn make_replayer(
     log_path: EventLogPathBuf,
     speed: f64,
-    seek: Duration,
+    seek: ReplaySeek,
     preload: bool,
 ) -> buck2_error::Result<(
     impl Stream<Item = buck2_error::Result<StreamValue>> + Unpin,
@@ -191,6 +210,29 @@ async fn make_replayer(
 )> {
     let (invocation, events) = log_path.unpack_stream().await?;
 
+    // If the log is indexed, skip straight to the chunk containing the seek target instead of
+    // decoding everything before it. Spans started in skipped chunks won't be shown.
+    let events = match log_path.read_index()? {
+        Some(index) => {
+            let chunk = match seek {
+                ReplaySeek::Time(seek) => invocation
+                    .start_time
+                    .map(|start_time| index.chunk_for_time(start_time + seek)),
+                ReplaySeek::Span(span_id) => index.chunk_for_action_start(span_id),
+            };
+            match chunk {
+                Some(chunk) if chunk > 0 => {
+                    // Keep the command start, which the console needs.
+                    let (_invocation, skipped) =
+                        log_path.unpack_stream_from_chunk(&index, chunk).await?;
+                    events.take(1).chain(skipped).boxed()
+                }
+                _ => events.boxed(),
+            }
+        }
+        None => events.boxed(),
+    };
+
     let mut events = if preload {
         let events = events.try_collect::<Vec<_>>().await?;
         futures::stream::iter(events).map(Ok).left_stream()
@@ -219,12 +261,20 @@ async fn make_replayer(
         }
     };
 
-    let seek_timestamp = timestamp_add_duration(start_time, seek);
-
     // Note: Seeking forward in time on a large log might take a while; intentionally do this before
     // computing the `command_start_instant` so that the time that superconsole starts up actually
     // aligns with that instant and not that instant + however long this seek took
-    let res = find_next_event_with_delay(&sink, &mut events, Some(seek_timestamp)).await;
+    let (res, seek_timestamp) = match seek {
+        ReplaySeek::Time(seek) => {
+            let seek_timestamp = timestamp_add_duration(start_time, seek);
+            let res = find_next_event_with_delay(&sink, &mut events, Some(seek_timestamp)).await;
+            (res, seek_timestamp)
+        }
+        ReplaySeek::Span(span_id) => match find_span_start(&sink, &mut events, span_id).await {
+            Some((e, ts)) => (Some((e, ts)), ts),
+            None => return Err(ReplayError::SpanNotFound(span_id).into()),
+        },
+    };
 
     // The point in real time at which we treat the command as having happened - delays of
     // subsequent events are calculated relative to this.
@@ -349,6 +399,32 @@ async fn find_next_event_with_delay(
     None
 }
 
+/// Replay events from the stream into the sink until we find the start of the given span
+async fn find_span_start(
+    sink: &UnboundedSender<buck2_error::Result<StreamValue>>,
+    events: &mut (impl Stream<Item = buck2_error::Result<StreamValue>> + Unpin),
+    span_id: SpanId,
+) -> Option<(buck2_error::Result<StreamValue>, prost_types::Timestamp)> {
+    while let Some(event) = events.next().await {
+        if let Ok(StreamValue::Event(buck_event)) = &event {
+            if buck_event.span_id == span_id.0.get()
+                && matches!(
+                    buck_event.data,
+                    Some(buck2_data::buck_event::Data::SpanStart(_))
+                )
+            {
+                let ts = buck_event.timestamp.unwrap();
+                return Some((event, ts));
+            }
+        }
+        if sink.send(event).is_err() {
+            // The sink is closed, so we can stop sending events.
+            return None;
+        }
+    }
+    None
+}
+
 /// State that describes how to convert an instant in the timeline of the replay command to a
 /// timestamp in the timeline of the command being replayed
 #[derive(Copy, Clone)]
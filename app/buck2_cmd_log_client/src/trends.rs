@@ -0,0 +1,367 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use buck2_client_ctx::client_ctx::BuckSubcommand;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::common::BuckArgMatches;
+use buck2_client_ctx::events_ctx::EventsCtx;
+use buck2_client_ctx::exit_result::ClientIoError;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_common::build_history::BuildHistoryEntry;
+use buck2_common::build_history::BuildHistoryManager;
+use buck2_error::conversion::from_any_with_tag;
+use chrono::Datelike;
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::LogCommandOutputFormat;
+use crate::LogCommandOutputFormatWithWriter;
+use crate::transform_format;
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum TrendsPeriod {
+    Day,
+    Week,
+}
+
+/// Show how command performance changed over time.
+///
+/// Every command that runs on the daemon appends a short summary to a local build history in
+/// buck-out, which outlives the (regularly deleted) event logs. This command groups that history
+/// by command and target patterns, then by day or week.
+///
+/// For every period it shows the number of runs, the median duration, critical path and actions
+/// run, the mean cache hit rate and the peak memory. `change` is the change in median duration
+/// relative to the previous period; periods slower than `--threshold` are flagged as regressions.
+///
+/// The `tabulated` format produces tab-delimited output:
+/// `<command>\t<targets>\t<period>\t<runs>\t<median_duration_ms>\t<duration_change_percent>\t<median_critical_path_ms>\t<median_actions_run>\t<cache_hit_rate>\t<peak_memory_bytes>\t<regression>`
+#[derive(Debug, clap::Parser)]
+pub struct TrendsCommand {
+    /// Only show this command, e.g. `build`.
+    #[clap(long)]
+    command: Option<String>,
+
+    /// Only show invocations that included this target pattern.
+    #[clap(long)]
+    pattern: Option<String>,
+
+    /// How many days of history to show.
+    #[clap(long, default_value = "30")]
+    days: u64,
+
+    /// Group runs by day or by week.
+    #[clap(long, value_enum, default_value = "day")]
+    period: TrendsPeriod,
+
+    /// Flag periods whose median duration is at least this many percent slower than the previous one.
+    #[clap(long, default_value = "20")]
+    threshold: f64,
+
+    /// Include failed invocations, which are excluded by default since they often stop early.
+    #[clap(long)]
+    include_failures: bool,
+
+    #[clap(flatten)]
+    format: LogCommandOutputFormat,
+}
+
+impl BuckSubcommand for TrendsCommand {
+    const COMMAND_NAME: &'static str = "log-trends";
+
+    async fn exec_impl(
+        self,
+        _matches: BuckArgMatches<'_>,
+        ctx: ClientCommandContext<'_>,
+        _events_ctx: &mut EventsCtx,
+    ) -> ExitResult {
+        let manager = BuildHistoryManager::new(ctx.paths()?.build_history_dir())?;
+        let since_ms = SystemTime::now()
+            .checked_sub(Duration::from_secs(self.days * 24 * 60 * 60))
+            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_millis() as u64);
+
+        let entries: Vec<BuildHistoryEntry> = manager
+            .read()
+            .await?
+            .into_iter()
+            .filter(|e| e.start_time_ms >= since_ms)
+            .filter(|e| self.include_failures || e.success)
+            .filter(|e| self.command.as_ref().is_none_or(|c| &e.command == c))
+            .filter(|e| {
+                self.pattern
+                    .as_ref()
+                    .is_none_or(|p| e.target_patterns.contains(p))
+            })
+            .collect();
+
+        if entries.is_empty() {
+            buck2_client_ctx::eprintln!("No matching build history found")?;
+            return ExitResult::success();
+        }
+
+        let rows = trend_rows(&entries, self.period, self.threshold);
+        log_trends(&rows, self.format).await?;
+        ExitResult::success()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct TrendRow<'a> {
+    command: &'a str,
+    targets: String,
+    /// First day of the period.
+    period: String,
+    runs: usize,
+    median_duration_ms: Option<u64>,
+    /// Change in median duration relative to the previous period.
+    duration_change_percent: Option<f64>,
+    median_critical_path_ms: Option<u64>,
+    median_actions_run: Option<u64>,
+    cache_hit_rate: f32,
+    peak_memory_bytes: Option<u64>,
+    regression: bool,
+}
+
+fn period_start(start_time_ms: u64, period: TrendsPeriod) -> Option<NaiveDate> {
+    let date = chrono::DateTime::from_timestamp_millis(start_time_ms as i64)?
+        .with_timezone(&chrono::Local)
+        .date_naive();
+    match period {
+        TrendsPeriod::Day => Some(date),
+        TrendsPeriod::Week => date.checked_sub_days(chrono::Days::new(
+            date.weekday().num_days_from_monday() as u64,
+        )),
+    }
+}
+
+fn median(mut values: Vec<u64>) -> Option<u64> {
+    values.sort_unstable();
+    values.get(values.len() / 2).copied()
+}
+
+fn trend_rows(
+    entries: &[BuildHistoryEntry],
+    period: TrendsPeriod,
+    threshold: f64,
+) -> Vec<TrendRow<'_>> {
+    let mut groups: BTreeMap<(&str, String), BTreeMap<NaiveDate, Vec<&BuildHistoryEntry>>> =
+        BTreeMap::new();
+    for entry in entries {
+        let Some(start) = period_start(entry.start_time_ms, period) else {
+            continue;
+        };
+        groups
+            .entry((entry.command.as_str(), entry.target_patterns.join(" ")))
+            .or_default()
+            .entry(start)
+            .or_default()
+            .push(entry);
+    }
+
+    let mut rows = Vec::new();
+    for ((command, targets), periods) in groups {
+        let mut previous_duration = None;
+        for (start, runs) in periods {
+            let median_duration_ms = median(runs.iter().filter_map(|e| e.duration_ms).collect());
+            let duration_change_percent = match (previous_duration, median_duration_ms) {
+                (Some(prev), Some(cur)) if prev > 0 => {
+                    Some((cur as f64 - prev as f64) * 100.0 / prev as f64)
+                }
+                _ => None,
+            };
+            if median_duration_ms.is_some() {
+                previous_duration = median_duration_ms;
+            }
+            rows.push(TrendRow {
+                command,
+                targets: targets.clone(),
+                period: start.to_string(),
+                runs: runs.len(),
+                median_duration_ms,
+                duration_change_percent,
+                median_critical_path_ms: median(
+                    runs.iter().filter_map(|e| e.critical_path_ms).collect(),
+                ),
+                median_actions_run: median(runs.iter().map(|e| e.actions_run).collect()),
+                cache_hit_rate: runs.iter().map(|e| e.cache_hit_rate).sum::<f32>()
+                    / runs.len() as f32,
+                peak_memory_bytes: runs.iter().filter_map(|e| e.peak_memory_bytes).max(),
+                regression: duration_change_percent.is_some_and(|c| c >= threshold),
+            });
+        }
+    }
+    rows
+}
+
+fn fmt_millis(ms: Option<u64>) -> String {
+    ms.map_or_else(
+        || "-".to_owned(),
+        |ms| format!("{:.1}s", ms as f64 / 1000.0),
+    )
+}
+
+async fn log_trends(
+    rows: &[TrendRow<'_>],
+    format: LogCommandOutputFormat,
+) -> buck2_error::Result<()> {
+    buck2_client_ctx::stdio::print_with_writer::<buck2_error::Error, _>(async move |w| {
+        let mut log_writer = transform_format(format, w);
+        let mut current_group = None;
+        for row in rows {
+            let res: Result<(), ClientIoError> = {
+                match &mut log_writer {
+                    LogCommandOutputFormatWithWriter::Readable(writer) => {
+                        let group = (row.command, row.targets.as_str());
+                        if current_group != Some(group) {
+                            if current_group.is_some() {
+                                writeln!(writer)?;
+                            }
+                            writeln!(writer, "{} {}", row.command, row.targets)?;
+                            #[allow(clippy::write_literal)] // easier to match the format below
+                            writeln!(
+                                writer,
+                                "{:<10} {:>5} {:>10} {:>8} {:>10} {:>8} {:>6} {:>10}",
+                                "period",
+                                "runs",
+                                "duration",
+                                "change",
+                                "crit_path",
+                                "actions",
+                                "cache",
+                                "memory",
+                            )?;
+                            current_group = Some(group);
+                        }
+                        writeln!(
+                            writer,
+                            "{:<10} {:>5} {:>10} {:>8} {:>10} {:>8} {:>6} {:>10}{}",
+                            row.period,
+                            row.runs,
+                            fmt_millis(row.median_duration_ms),
+                            row.duration_change_percent
+                                .map_or_else(|| "-".to_owned(), |c| format!("{c:+.0}%")),
+                            fmt_millis(row.median_critical_path_ms),
+                            row.median_actions_run
+                                .map_or_else(|| "-".to_owned(), |a| a.to_string()),
+                            format!("{:.0}%", row.cache_hit_rate * 100.0),
+                            row.peak_memory_bytes.map_or_else(
+                                || "-".to_owned(),
+                                |b| format!("{}MiB", b / (1024 * 1024))
+                            ),
+                            if row.regression {
+                                "  <- regression"
+                            } else {
+                                ""
+                            },
+                        )?;
+                    }
+                    LogCommandOutputFormatWithWriter::Tabulated(writer) => {
+                        // This should match the format specified in the docstring on TrendsCommand
+                        writeln!(
+                            writer,
+                            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                            row.command,
+                            row.targets,
+                            row.period,
+                            row.runs,
+                            row.median_duration_ms
+                                .map_or(String::new(), |v| v.to_string()),
+                            row.duration_change_percent
+                                .map_or(String::new(), |v| format!("{v:.1}")),
+                            row.median_critical_path_ms
+                                .map_or(String::new(), |v| v.to_string()),
+                            row.median_actions_run
+                                .map_or(String::new(), |v| v.to_string()),
+                            row.cache_hit_rate,
+                            row.peak_memory_bytes
+                                .map_or(String::new(), |v| v.to_string()),
+                            row.regression,
+                        )?;
+                    }
+                    LogCommandOutputFormatWithWriter::Json(writer) => {
+                        serde_json::to_writer(writer.by_ref(), row)?;
+                        writer.write_all("\n".as_bytes())?;
+                    }
+                    LogCommandOutputFormatWithWriter::Csv(writer) => {
+                        writer
+                            .serialize(row)
+                            .map_err(|e| from_any_with_tag(e, buck2_error::ErrorTag::LogCmd))?;
+                    }
+                }
+                Ok(())
+            };
+            res?
+        }
+        Ok(())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+    fn entry(command: &str, day: u64, duration_ms: u64) -> BuildHistoryEntry {
+        BuildHistoryEntry {
+            trace_id: format!("{command}-{day}-{duration_ms}"),
+            command: command.to_owned(),
+            target_patterns: vec!["//foo/...".to_owned()],
+            start_time_ms: 1_700_000_000_000 + day * DAY_MS,
+            success: true,
+            duration_ms: Some(duration_ms),
+            critical_path_ms: Some(duration_ms / 2),
+            cache_hit_rate: 0.5,
+            actions_run: 10,
+            peak_memory_bytes: Some(1024),
+        }
+    }
+
+    #[test]
+    fn test_trend_rows() {
+        let entries = vec![
+            entry("build", 0, 1000),
+            entry("build", 0, 3000),
+            entry("build", 0, 2000),
+            entry("build", 1, 2100),
+            entry("build", 2, 3000),
+            entry("test", 2, 500),
+        ];
+        let rows = trend_rows(&entries, TrendsPeriod::Day, 20.0);
+        let summary: Vec<_> = rows
+            .iter()
+            .map(|r| {
+                (
+                    r.command,
+                    r.runs,
+                    r.median_duration_ms,
+                    r.duration_change_percent.map(|c| c.round() as i64),
+                    r.regression,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("build", 3, Some(2000), None, false),
+                ("build", 1, Some(2100), Some(5), false),
+                ("build", 1, Some(3000), Some(43), true),
+                ("test", 1, Some(500), None, false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,323 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! Recomputes the critical path from a logged `CriticalPathGraph` under hypothetical scenarios.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use buck2_critical_path::GraphBuilder;
+use buck2_critical_path::compute_critical_path_potentials;
+use buck2_critical_path::simulate_bounded_parallelism;
+use buck2_data::ActionExecutionKind;
+use buck2_data::critical_path_entry2::ActionExecution;
+use buck2_data::critical_path_entry2::Entry;
+use buck2_data::error::ErrorTag;
+use buck2_error::buck2_error;
+use buck2_event_observer::action_stats::is_cache_hit;
+
+/// A hypothetical change to the build, passed as `--what-if`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum WhatIf {
+    /// `category=<CATEGORY>:<FACTOR>`: actions of this category run `factor` times faster.
+    FasterCategory { category: String, factor: f64 },
+    /// `cache-hits`: every action is served from a cache.
+    CacheHits,
+    /// `jobs=<N>`: at most N actions execute locally at the same time.
+    Jobs(usize),
+}
+
+pub(crate) fn parse_what_if(s: &str) -> buck2_error::Result<WhatIf> {
+    let invalid = || {
+        buck2_error!(
+            ErrorTag::Input,
+            "Invalid scenario `{}`, expected `category=<CATEGORY>:<FACTOR>`, `cache-hits` or `jobs=<N>`",
+            s
+        )
+    };
+
+    if s == "cache-hits" {
+        return Ok(WhatIf::CacheHits);
+    }
+    if let Some(jobs) = s.strip_prefix("jobs=") {
+        return match jobs.parse() {
+            Ok(jobs) if jobs > 0 => Ok(WhatIf::Jobs(jobs)),
+            _ => Err(invalid()),
+        };
+    }
+    if let Some(rest) = s.strip_prefix("category=") {
+        let (category, factor) = rest.rsplit_once(':').ok_or_else(invalid)?;
+        return match factor.trim_end_matches('x').parse::<f64>() {
+            Ok(factor) if factor > 0.0 && !category.is_empty() => Ok(WhatIf::FasterCategory {
+                category: category.to_owned(),
+                factor,
+            }),
+            _ => Err(invalid()),
+        };
+    }
+    Err(invalid())
+}
+
+pub(crate) struct WhatIfOutcome {
+    /// Critical path through the logged graph, as it actually ran.
+    pub(crate) baseline: Duration,
+    /// Estimated wall time under the scenarios. This is the critical path, unless `jobs=N`
+    /// was requested in which case it accounts for actions waiting for a local slot.
+    pub(crate) estimate: Duration,
+    /// The critical path under the scenarios (ignoring `jobs=N`).
+    pub(crate) critical_path: Vec<buck2_data::CriticalPathEntry2>,
+}
+
+fn action(node: &buck2_data::CriticalPathGraphNode) -> Option<&ActionExecution> {
+    match node.entry.as_ref()?.entry.as_ref()? {
+        Entry::ActionExecution(action) => Some(action),
+        _ => None,
+    }
+}
+
+fn category(action: &ActionExecution) -> &str {
+    action.name.as_ref().map_or("", |n| n.category.as_str())
+}
+
+fn is_local(kind: ActionExecutionKind) -> bool {
+    matches!(
+        kind,
+        ActionExecutionKind::Local
+            | ActionExecutionKind::LocalDepFile
+            | ActionExecutionKind::LocalWorker
+    )
+}
+
+fn median(mut values: Vec<u64>) -> Option<u64> {
+    values.sort_unstable();
+    values.get(values.len() / 2).copied()
+}
+
+/// Per-node durations (in microseconds) and whether the node needs a local job slot, after
+/// applying the scenarios.
+fn apply_scenarios(
+    graph: &buck2_data::CriticalPathGraph,
+    scenarios: &[WhatIf],
+) -> (Vec<u64>, Vec<bool>) {
+    let mut durations: Vec<u64> = graph.nodes.iter().map(|n| n.duration_us).collect();
+    let mut local: Vec<bool> = graph
+        .nodes
+        .iter()
+        .map(|n| action(n).is_some_and(|a| is_local(a.execution_kind())))
+        .collect();
+
+    for scenario in scenarios {
+        match scenario {
+            WhatIf::FasterCategory {
+                category: faster,
+                factor,
+            } => {
+                for (node, duration) in graph.nodes.iter().zip(durations.iter_mut()) {
+                    if action(node).is_some_and(|a| category(a) == faster) {
+                        *duration = (*duration as f64 / factor) as u64;
+                    }
+                }
+            }
+            WhatIf::CacheHits => {
+                // Estimate how long a cache hit takes from the actual cache hits in this build,
+                // per category if possible.
+                let mut hits_by_category: HashMap<&str, Vec<u64>> = HashMap::new();
+                let mut all_hits = Vec::new();
+                for node in &graph.nodes {
+                    if let Some(a) = action(node) {
+                        if is_cache_hit(a.execution_kind()) {
+                            hits_by_category
+                                .entry(category(a))
+                                .or_default()
+                                .push(node.duration_us);
+                            all_hits.push(node.duration_us);
+                        }
+                    }
+                }
+                let hit_by_category: HashMap<&str, u64> = hits_by_category
+                    .into_iter()
+                    .filter_map(|(c, v)| Some((c, median(v)?)))
+                    .collect();
+                let hit = median(all_hits).unwrap_or(0);
+
+                for (i, node) in graph.nodes.iter().enumerate() {
+                    if let Some(a) = action(node) {
+                        if !is_cache_hit(a.execution_kind()) {
+                            let estimate = hit_by_category.get(category(a)).copied().unwrap_or(hit);
+                            durations[i] = durations[i].min(estimate);
+                            local[i] = false;
+                        }
+                    }
+                }
+            }
+            WhatIf::Jobs(_) => {}
+        }
+    }
+
+    (durations, local)
+}
+
+pub(crate) fn what_if(
+    graph: &buck2_data::CriticalPathGraph,
+    scenarios: &[WhatIf],
+) -> buck2_error::Result<WhatIfOutcome> {
+    let mut builder = GraphBuilder::new();
+    for (i, node) in graph.nodes.iter().enumerate() {
+        // Nodes are in topological order, so all deps have already been pushed.
+        builder
+            .push(i as u32, node.deps.iter().copied(), i)
+            .map_err(|e| buck2_error!(ErrorTag::LogCmd, "Invalid critical path graph: {}", e))?;
+    }
+    let (deps, _keys, index) = builder.finish();
+
+    let logged = index.map_ref(|i| graph.nodes[*i].duration_us);
+    let (_, baseline, _, _) = compute_critical_path_potentials(&deps, &logged)?;
+
+    let (durations, local) = apply_scenarios(graph, scenarios);
+    let weights = index.map_ref(|i| durations[*i]);
+    let (critical_path, cost, replacement_costs, _) =
+        compute_critical_path_potentials(&deps, &weights)?;
+
+    let jobs = scenarios.iter().rev().find_map(|s| match s {
+        WhatIf::Jobs(jobs) => Some(*jobs),
+        _ => None,
+    });
+    let estimate = match jobs {
+        Some(jobs) => {
+            let needs_slot = index.map_ref(|i| local[*i]);
+            simulate_bounded_parallelism(&deps, &weights, &needs_slot, jobs)?
+        }
+        None => cost.runtime,
+    };
+
+    let mut start_offset = 0;
+    let critical_path = critical_path
+        .iter()
+        .map(|(cp_idx, vertex_idx)| {
+            let duration = weights[*vertex_idx];
+            let mut entry = graph.nodes[index[*vertex_idx]]
+                .entry
+                .clone()
+                .unwrap_or_default();
+            entry.total_duration = Duration::from_micros(duration).try_into().ok();
+            entry.user_duration = entry.total_duration.clone();
+            entry.potential_improvement_duration =
+                Duration::from_micros(cost.runtime - replacement_costs[cp_idx].runtime)
+                    .try_into()
+                    .ok();
+            entry.start_offset_ns = Some(start_offset * 1000);
+            start_offset += duration;
+            entry
+        })
+        .collect();
+
+    Ok(WhatIfOutcome {
+        baseline: Duration::from_micros(baseline.runtime),
+        estimate: Duration::from_micros(estimate),
+        critical_path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action_node(
+        deps: Vec<u32>,
+        duration_us: u64,
+        category: &str,
+        kind: ActionExecutionKind,
+    ) -> buck2_data::CriticalPathGraphNode {
+        buck2_data::CriticalPathGraphNode {
+            deps,
+            duration_us,
+            entry: Some(buck2_data::CriticalPathEntry2 {
+                entry: Some(
+                    ActionExecution {
+                        name: Some(buck2_data::ActionName {
+                            category: category.to_owned(),
+                            identifier: String::new(),
+                        }),
+                        execution_kind: kind.into(),
+                        ..Default::default()
+                    }
+                    .into(),
+                ),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Two local compiles in parallel, a cache hit, and a link depending on all of them.
+    fn graph() -> buck2_data::CriticalPathGraph {
+        buck2_data::CriticalPathGraph {
+            nodes: vec![
+                action_node(vec![], 100, "cxx_compile", ActionExecutionKind::Local),
+                action_node(vec![], 80, "cxx_compile", ActionExecutionKind::Local),
+                action_node(vec![], 2, "cxx_compile", ActionExecutionKind::ActionCache),
+                action_node(vec![0, 1, 2], 50, "cxx_link", ActionExecutionKind::Local),
+            ],
+        }
+    }
+
+    fn micros(d: Duration) -> u128 {
+        d.as_micros()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse_what_if("category=cxx_compile:4x").unwrap(),
+            WhatIf::FasterCategory {
+                category: "cxx_compile".to_owned(),
+                factor: 4.0
+            }
+        );
+        assert_eq!(parse_what_if("cache-hits").unwrap(), WhatIf::CacheHits);
+        assert_eq!(parse_what_if("jobs=8").unwrap(), WhatIf::Jobs(8));
+        assert!(parse_what_if("jobs=0").is_err());
+        assert!(parse_what_if("category=cxx_compile").is_err());
+        assert!(parse_what_if("faster").is_err());
+    }
+
+    #[test]
+    fn test_no_scenario() {
+        let outcome = what_if(&graph(), &[]).unwrap();
+        assert_eq!(micros(outcome.baseline), 150);
+        assert_eq!(micros(outcome.estimate), 150);
+        assert_eq!(outcome.critical_path.len(), 2);
+    }
+
+    #[test]
+    fn test_faster_category() {
+        let scenario = WhatIf::FasterCategory {
+            category: "cxx_compile".to_owned(),
+            factor: 2.0,
+        };
+        let outcome = what_if(&graph(), &[scenario]).unwrap();
+        assert_eq!(micros(outcome.baseline), 150);
+        assert_eq!(micros(outcome.estimate), 100);
+    }
+
+    #[test]
+    fn test_cache_hits() {
+        let outcome = what_if(&graph(), &[WhatIf::CacheHits]).unwrap();
+        // Compiles take as long as the cache hit we observed, and so does the link.
+        assert_eq!(micros(outcome.estimate), 4);
+    }
+
+    #[test]
+    fn test_jobs() {
+        let outcome = what_if(&graph(), &[WhatIf::Jobs(1)]).unwrap();
+        // The two compiles run one after the other.
+        assert_eq!(micros(outcome.estimate), 230);
+        assert_eq!(micros(outcome.baseline), 150);
+    }
+}
@@ -33,6 +33,7 @@ use buck2_event_observer::what_ran::WhatRanOutputWriter;
 use buck2_event_observer::what_ran::WhatRanRelevantAction;
 use buck2_event_observer::what_ran::WhatRanState;
 use buck2_events::span::SpanId;
+use futures::StreamExt;
 use futures::TryStreamExt;
 use futures::stream::Stream;
 use indexmap::IndexMap;
@@ -143,7 +144,18 @@ impl BuckSubcommand for WhatRanCommand {
             };
             let log_path = event_log.get(&ctx).await?;
 
-            let (invocation, events) = log_path.unpack_stream().await?;
+            // Failed actions are indexed, so only read the parts of the log that matter.
+            let index = if failed { log_path.read_index()? } else { None };
+            let (invocation, events) = match index {
+                Some(index) => {
+                    let chunks = index.failed_action_chunks();
+                    log_path.unpack_stream_chunks(&index, chunks).await?
+                }
+                None => {
+                    let (invocation, events) = log_path.unpack_stream().await?;
+                    (invocation, events.boxed())
+                }
+            };
 
             buck2_client_ctx::eprintln!(
                 "Showing commands from: {}{}",
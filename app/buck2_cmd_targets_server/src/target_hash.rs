@@ -8,11 +8,15 @@
  * above-listed licenses.
  */
 
+pub(crate) mod snapshot;
+
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::hash::Hash;
 use std::hash::Hasher;
+use std::mem;
 use std::sync::Arc;
+use std::sync::Mutex;
 
 use async_recursion::async_recursion;
 use async_trait::async_trait;
@@ -27,6 +31,8 @@ use buck2_core::global_cfg_options::GlobalCfgOptions;
 use buck2_core::package::PackageLabelWithModifiers;
 use buck2_core::target::configured_or_unconfigured::ConfiguredOrUnconfiguredTargetLabel;
 use buck2_core::target::label::label::TargetLabel;
+use buck2_node::attrs::inspect_options::AttrInspectOptions;
+use buck2_node::nodes::attributes::TYPE;
 use buck2_node::nodes::configured::ConfiguredTargetNode;
 use buck2_node::nodes::unconfigured::TargetNode;
 use buck2_query::query::environment::QueryTarget;
@@ -39,6 +45,7 @@ use dice::DiceTransaction;
 use dice_futures::spawn::DropcancelJoinHandle;
 use dice_futures::spawn::spawn_dropcancel;
 use dupe::Dupe;
+use dupe::OptionDupedExt;
 use futures::FutureExt;
 use futures::StreamExt;
 use futures::future::Shared;
@@ -49,6 +56,8 @@ use os_str_bytes::OsStrBytes;
 use siphasher::sip128::Hasher128;
 use siphasher::sip128::SipHasher24;
 
+use crate::target_hash::snapshot::TargetHashComponents;
+
 #[derive(Clone, Dupe, derive_more::Display)]
 #[display("{:032x}", _0)]
 pub struct BuckTargetHash(pub u128);
@@ -98,6 +107,16 @@ pub enum TargetHashesFileMode {
     None,
 }
 
+impl TargetHashesFileMode {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            TargetHashesFileMode::PathsOnly(_) => "paths_only",
+            TargetHashesFileMode::PathsAndContents => "paths_and_contents",
+            TargetHashesFileMode::None => "none",
+        }
+    }
+}
+
 #[async_trait]
 trait FileHasher: Send + Sync {
     /// Obtain information about a path in some manner.
@@ -183,6 +202,25 @@ impl FileHasher for PathsAndContentsHasher {
     }
 }
 
+/// Shared implementation of `TargetHashingTargetNode::attr_hashes`.
+fn attr_hashes<'a, A: Hash>(
+    use_fast_hash: bool,
+    rule_type: &impl Hash,
+    attrs: impl Iterator<Item = (&'a str, A)>,
+) -> Vec<(String, BuckTargetHash)> {
+    let mut res = vec![(
+        TYPE.to_owned(),
+        TargetHashes::hash_one(use_fast_hash, |mut hasher| rule_type.hash(&mut hasher)),
+    )];
+    res.extend(attrs.map(|(name, value)| {
+        (
+            name.to_owned(),
+            TargetHashes::hash_one(use_fast_hash, |mut hasher| value.hash(&mut hasher)),
+        )
+    }));
+    res
+}
+
 /// Types of node that can be target hashed (just configured and unconfigured).
 /// This trait is purposely defined here instead of in buck2_node crate
 /// so that we can only access the public fields of these nodes.
@@ -192,6 +230,10 @@ pub(crate) trait TargetHashingTargetNode: QueryTarget {
     /// Importantly, we look at the nodes after configuration (for the configured case).
     fn target_hash<H: Hasher>(&self, state: &mut H);
 
+    /// Hash the rule type and each attribute separately, so that a change of the target hash
+    /// can be attributed to the attributes that caused it.
+    fn attr_hashes(&self, use_fast_hash: bool) -> Vec<(String, BuckTargetHash)>;
+
     // Takes in Target Nodes and returns a new set of (un)Configured
     // Target Nodes based on type of hashing specified.
     async fn get_target_nodes(
@@ -210,6 +252,15 @@ impl TargetHashingTargetNode for ConfiguredTargetNode {
         self.target_hash(state)
     }
 
+    fn attr_hashes(&self, use_fast_hash: bool) -> Vec<(String, BuckTargetHash)> {
+        attr_hashes(
+            use_fast_hash,
+            self.rule_type(),
+            self.attrs(AttrInspectOptions::All)
+                .map(|x| (x.name, x.value)),
+        )
+    }
+
     async fn get_target_nodes(
         dice: &mut DiceComputations,
         loaded_targets: Vec<(
@@ -231,6 +282,15 @@ impl TargetHashingTargetNode for TargetNode {
         self.target_hash(state)
     }
 
+    fn attr_hashes(&self, use_fast_hash: bool) -> Vec<(String, BuckTargetHash)> {
+        attr_hashes(
+            use_fast_hash,
+            self.rule_type(),
+            self.attrs(AttrInspectOptions::All)
+                .map(|x| (x.name, x.value)),
+        )
+    }
+
     async fn get_target_nodes(
         _dice: &mut DiceComputations,
         loaded_targets: Vec<(
@@ -249,6 +309,8 @@ impl TargetHashingTargetNode for TargetNode {
 pub struct TargetHashes {
     // key is an unconfigured target label, but the hash is generated from the configured target label.
     target_mapping: HashMap<TargetLabel, buck2_error::Result<BuckTargetHash>>,
+    // What went into the hash of each target, only recorded if requested.
+    components: HashMap<TargetLabel, TargetHashComponents>,
 }
 
 #[derive(buck2_error::Error, Debug)]
@@ -265,16 +327,55 @@ impl TargetHashes {
         self.target_mapping.get(label)
     }
 
+    pub(crate) fn iter(
+        &self,
+    ) -> impl Iterator<Item = (&TargetLabel, &buck2_error::Result<BuckTargetHash>)> {
+        self.target_mapping.iter()
+    }
+
+    pub(crate) fn components(&self, label: &TargetLabel) -> Option<&TargetHashComponents> {
+        self.components.get(label)
+    }
+
+    fn record_components<T: TargetHashingTargetNode>(
+        target: &T,
+        input_hashes: &[(CellPath, buck2_error::Result<Vec<u8>>)],
+        dep_hashes: impl IntoIterator<Item = (String, Option<BuckTargetHash>)>,
+        use_fast_hash: bool,
+    ) -> TargetHashComponents {
+        TargetHashComponents {
+            attrs: target
+                .attr_hashes(use_fast_hash)
+                .into_iter()
+                .map(|(name, hash)| (name, hash.to_string()))
+                .collect(),
+            files: input_hashes
+                .iter()
+                .filter_map(|(path, digest)| {
+                    let digest = digest.as_ref().ok()?;
+                    let hash = TargetHashes::hash_one(use_fast_hash, |hasher| hasher.write(digest));
+                    Some((path.to_string(), hash.to_string()))
+                })
+                .collect(),
+            deps: dep_hashes
+                .into_iter()
+                .filter_map(|(dep, hash)| Some((dep, hash?.to_string())))
+                .collect(),
+        }
+    }
+
     async fn compute_recursive_target_hashes<T: TargetHashingTargetNode, L: AsyncNodeLookup<T>>(
         dice: DiceTransaction,
         lookup: L,
         targets: TargetSet<T>,
         file_hasher: Option<Arc<dyn FileHasher>>,
         use_fast_hash: bool,
+        record_components: bool,
     ) -> buck2_error::Result<Self>
     where
         T::Key: ConfiguredOrUnconfiguredTargetLabel,
     {
+        let components = Arc::new(Mutex::new(HashMap::new()));
         let mut hashes: HashMap<
             T::Key,
             Shared<DropcancelJoinHandle<buck2_error::Result<BuckTargetHash>>>,
@@ -293,7 +394,10 @@ impl TargetHashes {
                     })
                 })
                 .collect::<Result<Vec<_>, TargetHashError>>()?;
+            let dep_labels: Vec<String> = target.deps().map(|dep| dep.to_string()).collect();
 
+            let record = record_components && targets.contains(target.node_key());
+            let components = components.dupe();
             let file_hasher = file_hasher.dupe();
             let dice = dice.dupe();
 
@@ -322,6 +426,21 @@ impl TargetHashes {
                             let (dep_hashes, input_hashes) =
                                 join!(join_all(dep_futures), join_all(input_futs));
 
+                            if record {
+                                let target_components = TargetHashes::record_components(
+                                    &target,
+                                    &input_hashes,
+                                    dep_labels.into_iter().zip(
+                                        dep_hashes.iter().map(|hash| hash.as_ref().ok().duped()),
+                                    ),
+                                    use_fast_hash,
+                                );
+                                components.lock().unwrap().insert(
+                                    target.node_key().unconfigured_label().dupe(),
+                                    target_components,
+                                );
+                            }
+
                             TargetHashes::hash_deps(dep_hashes, &mut *hasher)?;
                             TargetHashes::hash_files(input_hashes, &mut *hasher)?;
 
@@ -369,13 +488,18 @@ impl TargetHashes {
                 );
             }
         }
-        Ok(Self { target_mapping })
+        let components = mem::take(&mut *components.lock().unwrap());
+        Ok(Self {
+            target_mapping,
+            components,
+        })
     }
 
     async fn compute_immediate_target_hashes<T: TargetHashingTargetNode>(
         targets: TargetSet<T>,
         file_hasher: Option<Arc<dyn FileHasher>>,
         use_fast_hash: bool,
+        record_components: bool,
     ) -> buck2_error::Result<Self>
     where
         T::Key: ConfiguredOrUnconfiguredTargetLabel,
@@ -385,10 +509,12 @@ impl TargetHashes {
             .map(|target| {
                 let file_hasher = file_hasher.dupe();
                 async move {
+                    let mut target_components = None;
                     let hash_result: buck2_error::Result<BuckTargetHash> = try {
                         let mut hasher = TargetHashes::new_hasher(use_fast_hash);
                         TargetHashes::hash_node(&target, &mut *hasher);
 
+                        let mut input_hashes = Vec::new();
                         if let Some(file_hasher) = file_hasher {
                             let mut input_futs = Vec::new();
                             target.inputs_for_each(|cell_path| {
@@ -400,15 +526,24 @@ impl TargetHashes {
                                 buck2_error::Ok(())
                             })?;
 
-                            let input_hashes = join_all(input_futs).await;
-                            TargetHashes::hash_files(input_hashes, &mut *hasher)?;
+                            input_hashes = join_all(input_futs).await;
+                        }
+                        if record_components {
+                            target_components = Some(TargetHashes::record_components(
+                                &target,
+                                &input_hashes,
+                                [],
+                                use_fast_hash,
+                            ));
                         }
+                        TargetHashes::hash_files(input_hashes, &mut *hasher)?;
 
                         hasher.finish_u128()
                     };
                     (
                         target.node_key().unconfigured_label().dupe(),
                         hash_result.map_err(buck2_error::Error::from),
+                        target_components,
                     )
                 }
                 .boxed()
@@ -416,9 +551,19 @@ impl TargetHashes {
             })
             .collect();
 
-        let target_mapping: HashMap<TargetLabel, buck2_error::Result<BuckTargetHash>> =
-            join_all(hashing_futures).await.into_iter().collect();
-        Ok(Self { target_mapping })
+        let mut target_mapping: HashMap<TargetLabel, buck2_error::Result<BuckTargetHash>> =
+            HashMap::new();
+        let mut components = HashMap::new();
+        for (label, hash, target_components) in join_all(hashing_futures).await {
+            if let Some(target_components) = target_components {
+                components.insert(label.dupe(), target_components);
+            }
+            target_mapping.insert(label, hash);
+        }
+        Ok(Self {
+            target_mapping,
+            components,
+        })
     }
 
     pub fn compute_immediate_one(node: &TargetNode, use_fast_hash: bool) -> BuckTargetHash {
@@ -438,6 +583,7 @@ impl TargetHashes {
         file_hash_mode: TargetHashesFileMode,
         use_fast_hash: bool,
         target_hash_recursive: bool,
+        record_components: bool,
     ) -> buck2_error::Result<Self>
     where
         T::Key: ConfiguredOrUnconfiguredTargetLabel,
@@ -445,10 +591,23 @@ impl TargetHashes {
         let targets = T::get_target_nodes(&mut dice, targets, global_cfg_options).await?;
         let file_hasher = Self::new_file_hasher(dice.dupe(), file_hash_mode);
         if target_hash_recursive {
-            Self::compute_recursive_target_hashes(dice, lookup, targets, file_hasher, use_fast_hash)
-                .await
+            Self::compute_recursive_target_hashes(
+                dice,
+                lookup,
+                targets,
+                file_hasher,
+                use_fast_hash,
+                record_components,
+            )
+            .await
         } else {
-            Self::compute_immediate_target_hashes(targets, file_hasher, use_fast_hash).await
+            Self::compute_immediate_target_hashes(
+                targets,
+                file_hasher,
+                use_fast_hash,
+                record_components,
+            )
+            .await
         }
     }
 
@@ -477,6 +636,12 @@ impl TargetHashes {
         }
     }
 
+    fn hash_one(use_fast_hash: bool, f: impl FnOnce(&mut dyn BuckTargetHasher)) -> BuckTargetHash {
+        let mut hasher = TargetHashes::new_hasher(use_fast_hash);
+        f(&mut *hasher);
+        hasher.finish_u128()
+    }
+
     fn hash_node<T: TargetHashingTargetNode>(node: &T, mut hasher: &mut dyn BuckTargetHasher) {
         node.target_hash(&mut hasher);
     }
@@ -0,0 +1,399 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! Target hashes persisted to a file, so that the hashes of a later state can be compared against
+//! them without evaluating the earlier state again.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt;
+
+use buck2_error::BuckErrorContext;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::target_hash::TargetHashes;
+
+/// Bumped whenever the format changes or hashes are computed differently.
+const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(tag = Input)]
+enum TargetHashSnapshotError {
+    #[error("Target hash snapshot has version {0}, but this buck2 writes version {1}")]
+    VersionMismatch(u32, u32),
+    #[error(
+        "Target hash snapshot was computed with {0}, but the current hashes are computed with {1}"
+    )]
+    OptionsMismatch(TargetHashSnapshotOptions, TargetHashSnapshotOptions),
+}
+
+/// What went into the hash of a target, beyond its label.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct TargetHashComponents {
+    /// Hash of the rule type and of each attribute value, by attribute name.
+    pub(crate) attrs: BTreeMap<String, String>,
+    /// Hash of each input file, by path.
+    pub(crate) files: BTreeMap<String, String>,
+    /// Hash of each dependency, by label. Only recorded for recursive hashes.
+    pub(crate) deps: BTreeMap<String, String>,
+}
+
+/// The options target hashes were computed with. Hashes computed with different options
+/// are not comparable.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct TargetHashSnapshotOptions {
+    pub(crate) graph_type: String,
+    pub(crate) file_mode: String,
+    pub(crate) fast_hash: bool,
+    pub(crate) recursive: bool,
+}
+
+impl fmt::Display for TargetHashSnapshotOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "graph type `{}`, file mode `{}`, {} hash function, {}",
+            self.graph_type,
+            self.file_mode,
+            if self.fast_hash { "fast" } else { "strong" },
+            if self.recursive {
+                "recursive"
+            } else {
+                "not recursive"
+            },
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct TargetHashSnapshotEntry {
+    hash: String,
+    #[serde(flatten)]
+    components: TargetHashComponents,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct TargetHashSnapshot {
+    version: u32,
+    options: TargetHashSnapshotOptions,
+    targets: BTreeMap<String, TargetHashSnapshotEntry>,
+}
+
+/// How the hash of a target differs from the one recorded in a snapshot.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum TargetHashChange {
+    /// The target is not in the snapshot.
+    Added,
+    /// The target is in the snapshot, but not in the current state.
+    Removed,
+    /// The hash of the target changed, because of these.
+    Changed(Vec<TargetHashChangeReason>),
+}
+
+impl TargetHashChange {
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            TargetHashChange::Added => "added",
+            TargetHashChange::Removed => "removed",
+            TargetHashChange::Changed(_) => "changed",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum TargetHashChangeReason {
+    /// The rule type or an attribute value changed, or the attribute was added or removed.
+    Attr(String),
+    /// An input file changed, or was added or removed.
+    File(String),
+    /// The hash of a dependency changed, or the dependency was added or removed.
+    Dep(String),
+    /// Something else went into the hash, e.g. package modifiers.
+    Other,
+}
+
+impl TargetHashChangeReason {
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            TargetHashChangeReason::Attr(_) => "attr",
+            TargetHashChangeReason::File(_) => "file",
+            TargetHashChangeReason::Dep(_) => "dep",
+            TargetHashChangeReason::Other => "other",
+        }
+    }
+
+    pub(crate) fn name(&self) -> Option<&str> {
+        match self {
+            TargetHashChangeReason::Attr(name)
+            | TargetHashChangeReason::File(name)
+            | TargetHashChangeReason::Dep(name) => Some(name),
+            TargetHashChangeReason::Other => None,
+        }
+    }
+}
+
+impl fmt::Display for TargetHashChangeReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{} {}", self.kind(), name),
+            None => write!(f, "{}", self.kind()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct TargetHashDiff {
+    pub(crate) target: String,
+    pub(crate) change: TargetHashChange,
+}
+
+impl TargetHashSnapshot {
+    /// Snapshot of the successfully computed hashes. `hashes` must have recorded components.
+    pub(crate) fn new(options: TargetHashSnapshotOptions, hashes: &TargetHashes) -> Self {
+        let targets = hashes
+            .iter()
+            .filter_map(|(label, hash)| {
+                let hash = hash.as_ref().ok()?;
+                Some((
+                    label.to_string(),
+                    TargetHashSnapshotEntry {
+                        hash: hash.to_string(),
+                        components: hashes.components(label).cloned().unwrap_or_default(),
+                    },
+                ))
+            })
+            .collect();
+        Self {
+            version: SNAPSHOT_VERSION,
+            options,
+            targets,
+        }
+    }
+
+    /// Parses a snapshot previously serialized with `to_json`. The client reads the file, since
+    /// the path is relative to the client's working directory.
+    pub(crate) fn from_json(contents: &str) -> buck2_error::Result<Self> {
+        let snapshot: Self = serde_json::from_str(contents)
+            .buck_error_context("Failed to parse target hash snapshot")?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(TargetHashSnapshotError::VersionMismatch(
+                snapshot.version,
+                SNAPSHOT_VERSION,
+            )
+            .into());
+        }
+        Ok(snapshot)
+    }
+
+    /// Serializes the snapshot, for the client to write it to a file.
+    pub(crate) fn to_json(&self) -> buck2_error::Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// The targets whose hash in `current` differs from the one in this snapshot, sorted by label.
+    pub(crate) fn diff(
+        &self,
+        current: &TargetHashSnapshot,
+    ) -> buck2_error::Result<Vec<TargetHashDiff>> {
+        if self.options != current.options {
+            return Err(TargetHashSnapshotError::OptionsMismatch(
+                self.options.clone(),
+                current.options.clone(),
+            )
+            .into());
+        }
+
+        let labels: BTreeSet<&String> = self.targets.keys().chain(current.targets.keys()).collect();
+        Ok(labels
+            .into_iter()
+            .filter_map(|label| {
+                let change = match (self.targets.get(label), current.targets.get(label)) {
+                    (None, None) => return None,
+                    (None, Some(_)) => TargetHashChange::Added,
+                    (Some(_), None) => TargetHashChange::Removed,
+                    (Some(old), Some(new)) => {
+                        if old.hash == new.hash {
+                            return None;
+                        }
+                        TargetHashChange::Changed(Self::change_reasons(
+                            &old.components,
+                            &new.components,
+                        ))
+                    }
+                };
+                Some(TargetHashDiff {
+                    target: label.clone(),
+                    change,
+                })
+            })
+            .collect())
+    }
+
+    fn change_reasons(
+        old: &TargetHashComponents,
+        new: &TargetHashComponents,
+    ) -> Vec<TargetHashChangeReason> {
+        fn changed_keys<'a>(
+            old: &'a BTreeMap<String, String>,
+            new: &'a BTreeMap<String, String>,
+        ) -> impl Iterator<Item = String> + 'a {
+            old.keys()
+                .chain(new.keys())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .filter(|key| old.get(*key) != new.get(*key))
+                .cloned()
+        }
+
+        let mut reasons: Vec<_> = changed_keys(&old.attrs, &new.attrs)
+            .map(TargetHashChangeReason::Attr)
+            .chain(changed_keys(&old.files, &new.files).map(TargetHashChangeReason::File))
+            .chain(changed_keys(&old.deps, &new.deps).map(TargetHashChangeReason::Dep))
+            .collect();
+        if reasons.is_empty() {
+            reasons.push(TargetHashChangeReason::Other);
+        }
+        reasons
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::target_hash::snapshot::SNAPSHOT_VERSION;
+    use crate::target_hash::snapshot::TargetHashChange;
+    use crate::target_hash::snapshot::TargetHashChangeReason;
+    use crate::target_hash::snapshot::TargetHashComponents;
+    use crate::target_hash::snapshot::TargetHashDiff;
+    use crate::target_hash::snapshot::TargetHashSnapshot;
+    use crate::target_hash::snapshot::TargetHashSnapshotEntry;
+    use crate::target_hash::snapshot::TargetHashSnapshotOptions;
+
+    fn options(fast_hash: bool) -> TargetHashSnapshotOptions {
+        TargetHashSnapshotOptions {
+            graph_type: "configured".to_owned(),
+            file_mode: "paths_and_contents".to_owned(),
+            fast_hash,
+            recursive: true,
+        }
+    }
+
+    fn map(items: &[(&str, &str)]) -> BTreeMap<String, String> {
+        items
+            .iter()
+            .map(|(k, v)| ((*k).to_owned(), (*v).to_owned()))
+            .collect()
+    }
+
+    fn snapshot(targets: &[(&str, &str, TargetHashComponents)]) -> TargetHashSnapshot {
+        TargetHashSnapshot {
+            version: SNAPSHOT_VERSION,
+            options: options(true),
+            targets: targets
+                .iter()
+                .map(|(label, hash, components)| {
+                    (
+                        (*label).to_owned(),
+                        TargetHashSnapshotEntry {
+                            hash: (*hash).to_owned(),
+                            components: components.clone(),
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = snapshot(&[
+            (
+                "root//:a",
+                "1",
+                TargetHashComponents {
+                    attrs: map(&[("srcs", "1"), ("name", "1")]),
+                    files: map(&[("root//a.c", "1")]),
+                    deps: map(&[("root//:b", "1")]),
+                },
+            ),
+            ("root//:b", "1", TargetHashComponents::default()),
+            ("root//:removed", "1", TargetHashComponents::default()),
+            ("root//:same", "1", TargetHashComponents::default()),
+        ]);
+        let new = snapshot(&[
+            (
+                "root//:a",
+                "2",
+                TargetHashComponents {
+                    attrs: map(&[("srcs", "2"), ("name", "1"), ("deps", "1")]),
+                    files: map(&[("root//a.c", "2")]),
+                    deps: map(&[("root//:b", "2")]),
+                },
+            ),
+            ("root//:b", "2", TargetHashComponents::default()),
+            ("root//:added", "1", TargetHashComponents::default()),
+            ("root//:same", "1", TargetHashComponents::default()),
+        ]);
+
+        assert_eq!(
+            vec![
+                TargetHashDiff {
+                    target: "root//:a".to_owned(),
+                    change: TargetHashChange::Changed(vec![
+                        TargetHashChangeReason::Attr("deps".to_owned()),
+                        TargetHashChangeReason::Attr("srcs".to_owned()),
+                        TargetHashChangeReason::File("root//a.c".to_owned()),
+                        TargetHashChangeReason::Dep("root//:b".to_owned()),
+                    ]),
+                },
+                TargetHashDiff {
+                    target: "root//:added".to_owned(),
+                    change: TargetHashChange::Added,
+                },
+                TargetHashDiff {
+                    target: "root//:b".to_owned(),
+                    change: TargetHashChange::Changed(vec![TargetHashChangeReason::Other]),
+                },
+                TargetHashDiff {
+                    target: "root//:removed".to_owned(),
+                    change: TargetHashChange::Removed,
+                },
+            ],
+            old.diff(&new).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_diff_options_mismatch() {
+        let old = snapshot(&[]);
+        let mut new = snapshot(&[]);
+        new.options = options(false);
+        assert!(old.diff(&new).is_err());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let old = snapshot(&[(
+            "root//:a",
+            "1",
+            TargetHashComponents {
+                attrs: map(&[("srcs", "1")]),
+                files: map(&[]),
+                deps: map(&[("root//:b", "1")]),
+            },
+        )]);
+        let json = old.to_json().unwrap();
+        let new = TargetHashSnapshot::from_json(&json).unwrap();
+        assert_eq!(old.targets, new.targets);
+        assert_eq!(old.options, new.options);
+    }
+}
@@ -10,6 +10,7 @@
 
 pub mod default;
 pub mod fmt;
+pub mod impact;
 pub mod resolve_alias;
 pub mod streaming;
 
@@ -40,6 +41,7 @@ use zstd::stream::write as zstd;
 use crate::targets::default::TargetHashOptions;
 use crate::targets::default::targets_batch;
 use crate::targets::fmt::create_formatter;
+use crate::targets::impact::targets_impact;
 use crate::targets::resolve_alias::targets_resolve_aliases;
 use crate::targets::streaming::targets_streaming;
 
@@ -232,6 +234,7 @@ async fn targets_with_output(
                 Ok(TargetsResponse {
                     error_count: res.errors,
                     serialized_targets_output: String::new(),
+                    target_hash_snapshot: None,
                 })
             } else {
                 let formatter = create_formatter(request, other)?;
@@ -257,6 +260,9 @@ async fn targets_with_output(
                 .await
             }
         }
+        Some(targets_request::Targets::Impact(impact)) => {
+            targets_impact(server_ctx, dice, request, impact, parsed_target_patterns).await
+        }
         None => Err(internal_error!("Missing field in proto request")),
     }
 }
@@ -37,6 +37,8 @@ use dupe::OptionDupedExt;
 
 use crate::target_hash::TargetHashes;
 use crate::target_hash::TargetHashesFileMode;
+use crate::target_hash::snapshot::TargetHashSnapshot;
+use crate::target_hash::snapshot::TargetHashSnapshotOptions;
 use crate::targets::fmt::Stats;
 use crate::targets::fmt::TargetFormatter;
 use crate::targets::fmt::TargetInfo;
@@ -46,6 +48,8 @@ pub(crate) struct TargetHashOptions {
     fast_hash: bool,
     graph_type: TargetHashGraphType,
     recursive: bool,
+    snapshot_write: bool,
+    snapshot_diff: Option<String>,
 }
 
 impl TargetHashOptions {
@@ -78,8 +82,19 @@ impl TargetHashOptions {
             graph_type: TargetHashGraphType::try_from(request.target_hash_graph_type)
                 .expect("buck cli should send valid target hash graph type"),
             recursive: request.target_hash_recursive,
+            snapshot_write: request.target_hash_snapshot_write,
+            snapshot_diff: request.target_hash_snapshot_diff.clone(),
         })
     }
+
+    fn snapshot_options(&self) -> TargetHashSnapshotOptions {
+        TargetHashSnapshotOptions {
+            graph_type: self.graph_type.as_str_name().to_lowercase(),
+            file_mode: self.file_mode.name().to_owned(),
+            fast_hash: self.fast_hash,
+            recursive: self.recursive,
+        }
+    }
 }
 
 pub(crate) async fn targets_batch(
@@ -93,6 +108,11 @@ pub(crate) async fn targets_batch(
 ) -> buck2_error::Result<TargetsResponse> {
     let results = &load_patterns(&mut dice, parsed_patterns, MissingTargetBehavior::Fail).await?;
 
+    let snapshot_options = hash_options.snapshot_options();
+    let snapshot_write = hash_options.snapshot_write;
+    let snapshot_diff = hash_options.snapshot_diff.clone();
+    let record_components = snapshot_write || snapshot_diff.is_some();
+
     let target_hashes = dice
         .dupe()
         .with_linear_recompute(|linear_ctx| async move {
@@ -106,6 +126,7 @@ pub(crate) async fn targets_batch(
                         hash_options.file_mode,
                         hash_options.fast_hash,
                         hash_options.recursive,
+                        record_components,
                     )
                     .await?,
                 )),
@@ -118,6 +139,7 @@ pub(crate) async fn targets_batch(
                         hash_options.file_mode,
                         hash_options.fast_hash,
                         hash_options.recursive,
+                        record_components,
                     )
                     .await?,
                 )),
@@ -126,6 +148,21 @@ pub(crate) async fn targets_batch(
         })
         .await?;
 
+    let snapshot = target_hashes
+        .as_ref()
+        .filter(|_| record_components)
+        .map(|hashes| TargetHashSnapshot::new(snapshot_options, hashes));
+    let target_hash_snapshot = match &snapshot {
+        Some(snapshot) if snapshot_write => Some(snapshot.to_json()?),
+        _ => None,
+    };
+    let diffs = match (&snapshot_diff, &snapshot) {
+        (Some(contents), Some(snapshot)) => {
+            Some(TargetHashSnapshot::from_json(contents)?.diff(snapshot)?)
+        }
+        _ => None,
+    };
+
     let mut buffer = String::new();
     formatter.begin(&mut buffer);
     let mut stats = Stats::default();
@@ -136,6 +173,9 @@ pub(crate) async fn targets_batch(
                 stats.success += 1;
                 for (_, node) in res.iter() {
                     stats.targets += 1;
+                    if diffs.is_some() {
+                        continue;
+                    }
                     let target_hash = target_hashes
                         .as_ref()
                         .and_then(|hashes| hashes.get(node.label()))
@@ -178,6 +218,13 @@ pub(crate) async fn targets_batch(
             }
         }
     }
+    for diff in diffs.iter().flatten() {
+        if needs_separator {
+            formatter.separator(&mut buffer);
+        }
+        needs_separator = true;
+        formatter.target_hash_diff(diff, &mut buffer);
+    }
     formatter.end(&stats, &mut buffer);
     if !keep_going && let Some(e) = stats.to_error() {
         Err(e)
@@ -185,6 +232,7 @@ pub(crate) async fn targets_batch(
         Ok(TargetsResponse {
             error_count: stats.errors,
             serialized_targets_output: buffer,
+            target_hash_snapshot,
         })
     }
 }
@@ -49,6 +49,8 @@ use regex::RegexSet;
 
 use crate::json::QuotedJson;
 use crate::target_hash::BuckTargetHash;
+use crate::target_hash::snapshot::TargetHashChange;
+use crate::target_hash::snapshot::TargetHashDiff;
 
 pub(crate) struct TargetInfo<'a> {
     pub(crate) node: TargetNodeRef<'a>,
@@ -71,6 +73,8 @@ pub(crate) trait TargetFormatter: Send + Sync {
     /// Called between each target/imports/package_error
     fn separator(&self, buffer: &mut String) {}
     fn target(&self, target_info: TargetInfo<'_>, buffer: &mut String) {}
+    /// Output instead of the targets when comparing against a target hash snapshot.
+    fn target_hash_diff(&self, diff: &TargetHashDiff, buffer: &mut String) {}
     fn imports(
         &self,
         source: &CellPath,
@@ -295,6 +299,37 @@ impl TargetFormatter for JsonFormat {
         self.writer.entry_end(buffer, first);
     }
 
+    fn target_hash_diff(&self, diff: &TargetHashDiff, buffer: &mut String) {
+        self.writer.entry_start(buffer);
+        let mut first = true;
+        self.writer.entry_item(
+            buffer,
+            &mut first,
+            "buck.target",
+            QuotedJson::quote_str(&diff.target),
+        );
+        self.writer.entry_item(
+            buffer,
+            &mut first,
+            "buck.target_hash_change",
+            QuotedJson::quote_str(diff.change.kind()),
+        );
+        if let TargetHashChange::Changed(reasons) = &diff.change {
+            self.writer.entry_item(
+                buffer,
+                &mut first,
+                "buck.target_hash_change_reasons",
+                QuotedJson::list(reasons.iter().map(|reason| {
+                    QuotedJson::from_serde_json_value(serde_json::json!({
+                        "kind": reason.kind(),
+                        "name": reason.name(),
+                    }))
+                })),
+            );
+        }
+        self.writer.entry_end(buffer, first);
+    }
+
     fn imports(
         &self,
         source: &CellPath,
@@ -547,6 +582,24 @@ impl TargetFormatter for TargetNameFormat {
             print_target_call_stack_after_target(buffer, target_info.node.call_stack().as_deref());
         }
     }
+
+    fn target_hash_diff(&self, diff: &TargetHashDiff, buffer: &mut String) {
+        match &diff.change {
+            TargetHashChange::Changed(reasons) => writeln!(
+                buffer,
+                "{} {}: {}",
+                diff.target,
+                diff.change.kind(),
+                reasons
+                    .iter()
+                    .map(|reason| reason.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+            .unwrap(),
+            change => writeln!(buffer, "{} {}", diff.target, change.kind()).unwrap(),
+        }
+    }
 }
 
 impl ConfiguredTargetFormatter for TargetNameFormat {
@@ -0,0 +1,606 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! Server-side implementation of `buck2 targets --changed-files` command.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+use std::fmt::Write;
+use std::path::Path;
+
+use buck2_cli_proto::TargetsRequest;
+use buck2_cli_proto::TargetsResponse;
+use buck2_cli_proto::targets_request;
+use buck2_cli_proto::targets_request::OutputFormat;
+use buck2_cli_proto::targets_request::TargetHashGraphType;
+use buck2_common::dice::cells::HasCellResolver;
+use buck2_core::build_file_path::BuildFilePath;
+use buck2_core::bzl::ImportPath;
+use buck2_core::cells::cell_path::CellPath;
+use buck2_core::cells::name::CellName;
+use buck2_core::global_cfg_options::GlobalCfgOptions;
+use buck2_core::package::PackageLabel;
+use buck2_core::package::PackageLabelWithModifiers;
+use buck2_core::pattern::pattern::ParsedPattern;
+use buck2_core::pattern::pattern_type::TargetPatternExtra;
+use buck2_core::target::configured_or_unconfigured::ConfiguredOrUnconfiguredTargetLabel;
+use buck2_core::target::label::label::TargetLabel;
+use buck2_error::BuckErrorContext;
+use buck2_error::internal_error;
+use buck2_fs::paths::abs_path::AbsPath;
+use buck2_interpreter::load_module::INTERPRETER_CALCULATION_IMPL;
+use buck2_interpreter::load_module::InterpreterCalculation;
+use buck2_node::load_patterns::MissingTargetBehavior;
+use buck2_node::load_patterns::load_patterns;
+use buck2_node::nodes::attributes::PACKAGE;
+use buck2_node::nodes::configured::ConfiguredTargetNode;
+use buck2_node::nodes::frontend::TargetGraphCalculation;
+use buck2_node::nodes::lookup::ConfiguredTargetNodeLookup;
+use buck2_node::nodes::lookup::TargetNodeLookup;
+use buck2_node::nodes::unconfigured::TargetNode;
+use buck2_query::query::environment::QueryTargetDepsSuccessors;
+use buck2_query::query::traversal::AsyncNodeLookup;
+use buck2_query::query::traversal::async_depth_first_postorder_traversal;
+use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::global_cfg_options::global_cfg_options_from_client_context;
+use dice::DiceComputations;
+use dice::DiceTransaction;
+use dupe::Dupe;
+use itertools::Itertools;
+
+use crate::json::QuotedJson;
+use crate::target_hash::TargetHashingTargetNode;
+use crate::targets::fmt::JsonWriter;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(tag = Input)]
+enum ImpactError {
+    #[error("`--stat` format is not supported by `--changed-files`")]
+    StatFormatNotSupported,
+}
+
+/// Why a target is affected by the changed files.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum ImpactReason {
+    /// A changed file is one of the inputs of the target (or is inside an input directory).
+    Input(CellPath),
+    /// The build file of the target, a `PACKAGE` file above it, or a file loaded by either changed.
+    BuildFile(CellPath),
+    /// A buckconfig of the cell of the target, or of the root cell, changed. Which keys changed
+    /// is not considered, so this may include targets which are not actually affected.
+    Config(CellPath),
+    /// The target depends on an affected target.
+    Dependency(TargetLabel),
+}
+
+impl ImpactReason {
+    fn kind(&self) -> &'static str {
+        match self {
+            ImpactReason::Input(_) => "input",
+            ImpactReason::BuildFile(_) => "build_file",
+            ImpactReason::Config(_) => "config",
+            ImpactReason::Dependency(_) => "dep",
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            ImpactReason::Input(path)
+            | ImpactReason::BuildFile(path)
+            | ImpactReason::Config(path) => {
+                serde_json::json!({ "kind": self.kind(), "path": path.to_string() })
+            }
+            ImpactReason::Dependency(label) => {
+                serde_json::json!({ "kind": self.kind(), "target": label.to_string() })
+            }
+        }
+    }
+}
+
+impl fmt::Display for ImpactReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImpactReason::Input(path)
+            | ImpactReason::BuildFile(path)
+            | ImpactReason::Config(path) => write!(f, "{} {}", self.kind(), path),
+            ImpactReason::Dependency(label) => write!(f, "{} {}", self.kind(), label),
+        }
+    }
+}
+
+/// The set of changed files, indexed for the lookups impact analysis needs.
+struct ChangedFiles {
+    files: HashSet<CellPath>,
+    /// Every directory containing a changed file, mapped to the smallest changed file in it.
+    dirs: HashMap<CellPath, CellPath>,
+    /// Changed buckconfigs.
+    configs: Vec<CellPath>,
+    root_cell: CellName,
+}
+
+impl ChangedFiles {
+    fn new(files: HashSet<CellPath>, root_cell: CellName) -> Self {
+        let mut dirs: HashMap<CellPath, CellPath> = HashMap::new();
+        for file in &files {
+            for dir in file.ancestors().skip(1) {
+                let entry = dirs.entry(dir.to_owned()).or_insert_with(|| file.clone());
+                if *file < *entry {
+                    *entry = file.clone();
+                }
+            }
+        }
+        let configs = files
+            .iter()
+            .filter(|file| is_buckconfig(file))
+            .cloned()
+            .sorted()
+            .collect();
+        Self {
+            files,
+            dirs,
+            configs,
+            root_cell,
+        }
+    }
+
+    fn contains(&self, path: &CellPath) -> bool {
+        self.files.contains(path)
+    }
+
+    /// The changed buckconfigs which may affect the targets in `cell`: `read_config` reads the
+    /// config of the cell of the file it is called from, and `read_root_config` that of the root.
+    fn configs_affecting(&self, cell: CellName) -> impl Iterator<Item = &CellPath> {
+        self.configs
+            .iter()
+            .filter(move |config| config.cell() == cell || config.cell() == self.root_cell)
+    }
+
+    /// The changed file which is the input `input`, or which is inside it if it is a directory.
+    fn changed_input(&self, input: &CellPath) -> Option<&CellPath> {
+        self.files.get(input).or_else(|| self.dirs.get(input))
+    }
+}
+
+/// Buckconfigs are read from the root of each cell.
+fn is_buckconfig(path: &CellPath) -> bool {
+    let path = path.path().as_str();
+    path == ".buckconfig" || path == ".buckconfig.local" || path.starts_with(".buckconfig.d/")
+}
+
+trait ImpactFormatter {
+    /// Before writing anything.
+    fn begin(&self, buffer: &mut String);
+
+    /// After writing everything.
+    fn end(&self, buffer: &mut String);
+
+    /// Between items
+    fn separator(&self, buffer: &mut String);
+
+    /// Emit an affected target
+    fn emit(&self, label: &TargetLabel, reasons: &[ImpactReason], buffer: &mut String);
+}
+
+impl ImpactFormatter for JsonWriter {
+    fn begin(&self, buffer: &mut String) {
+        self.begin(buffer);
+    }
+
+    fn end(&self, buffer: &mut String) {
+        self.end(buffer);
+    }
+
+    fn separator(&self, buffer: &mut String) {
+        self.separator(buffer);
+    }
+
+    fn emit(&self, label: &TargetLabel, reasons: &[ImpactReason], buffer: &mut String) {
+        let mut first = true;
+        self.entry_start(buffer);
+        // Using a format consistent with the output of `buck2 targets`
+        self.entry_item(
+            buffer,
+            &mut first,
+            PACKAGE,
+            QuotedJson::quote_display(label.pkg()),
+        );
+        self.entry_item(
+            buffer,
+            &mut first,
+            "name",
+            QuotedJson::quote_display(label.name()),
+        );
+        self.entry_item(
+            buffer,
+            &mut first,
+            "reasons",
+            QuotedJson::list(
+                reasons
+                    .iter()
+                    .map(|reason| QuotedJson::from_serde_json_value(reason.to_json())),
+            ),
+        );
+        self.entry_end(buffer, first);
+    }
+}
+
+struct LinesWriter;
+
+impl ImpactFormatter for LinesWriter {
+    fn begin(&self, _buffer: &mut String) {}
+
+    fn end(&self, _buffer: &mut String) {}
+
+    fn separator(&self, _buffer: &mut String) {}
+
+    fn emit(&self, label: &TargetLabel, reasons: &[ImpactReason], buffer: &mut String) {
+        writeln!(buffer, "{}\t{}", label, reasons.iter().join(", ")).unwrap();
+    }
+}
+
+pub(crate) async fn targets_impact(
+    server_ctx: &dyn ServerCommandContextTrait,
+    mut dice: DiceTransaction,
+    request: &TargetsRequest,
+    impact: &targets_request::Impact,
+    parsed_patterns: Vec<ParsedPattern<TargetPatternExtra>>,
+) -> buck2_error::Result<TargetsResponse> {
+    let output_format = OutputFormat::try_from(request.output_format)
+        .internal_error("Invalid value of `output_format`")?;
+
+    let json_writer;
+
+    let formatter = match output_format {
+        OutputFormat::Unknown => return Err(internal_error!("`output_format` not set")),
+        OutputFormat::Text => &LinesWriter as &dyn ImpactFormatter,
+        OutputFormat::Json => {
+            json_writer = JsonWriter { json_lines: false };
+            &json_writer as &dyn ImpactFormatter
+        }
+        OutputFormat::JsonLines => {
+            json_writer = JsonWriter { json_lines: true };
+            &json_writer as &dyn ImpactFormatter
+        }
+        OutputFormat::Stats => return Err(ImpactError::StatFormatNotSupported.into()),
+    };
+
+    let cell_resolver = dice.get_cell_resolver().await?;
+    let fs = server_ctx.project_root();
+    let changed = &ChangedFiles::new(
+        impact
+            .changed_files
+            .iter()
+            .map(|path| {
+                let path = AbsPath::new(Path::new(&path))?;
+                cell_resolver.get_cell_path_from_abs_path(path, fs)
+            })
+            .collect::<buck2_error::Result<_>>()?,
+        cell_resolver.root_cell(),
+    );
+
+    let global_cfg_options = &global_cfg_options_from_client_context(
+        request
+            .target_cfg
+            .as_ref()
+            .internal_error("target_cfg must be set")?,
+        server_ctx,
+        &mut dice,
+    )
+    .await?;
+
+    let results = &load_patterns(&mut dice, parsed_patterns, MissingTargetBehavior::Fail).await?;
+
+    let graph_type = TargetHashGraphType::try_from(impact.graph_type)
+        .internal_error("buck cli should send valid graph type")?;
+
+    let affected = dice
+        .dupe()
+        .with_linear_recompute(|linear_ctx| async move {
+            match graph_type {
+                TargetHashGraphType::Configured => {
+                    compute_impact::<ConfiguredTargetNode, _>(
+                        dice.dupe(),
+                        ConfiguredTargetNodeLookup(&linear_ctx),
+                        results.iter_loaded_targets_by_package().collect(),
+                        global_cfg_options,
+                        changed,
+                    )
+                    .await
+                }
+                TargetHashGraphType::Unconfigured => {
+                    compute_impact::<TargetNode, _>(
+                        dice.dupe(),
+                        TargetNodeLookup(&linear_ctx),
+                        results.iter_loaded_targets_by_package().collect(),
+                        global_cfg_options,
+                        changed,
+                    )
+                    .await
+                }
+                TargetHashGraphType::None => Err(internal_error!("graph type not set")),
+            }
+        })
+        .await?;
+
+    let mut buffer = String::new();
+    formatter.begin(&mut buffer);
+    for (i, (label, reasons)) in affected.iter().enumerate() {
+        if i != 0 {
+            formatter.separator(&mut buffer);
+        }
+        formatter.emit(label, reasons, &mut buffer);
+    }
+    formatter.end(&mut buffer);
+
+    Ok(TargetsResponse {
+        error_count: 0,
+        serialized_targets_output: buffer,
+        target_hash_snapshot: None,
+    })
+}
+
+/// Computes the requested targets affected by `changed`, and why they are affected.
+///
+/// The whole dependency closure of the requested targets is visited in postorder,
+/// so whether the dependencies of a target are affected is known when visiting it.
+async fn compute_impact<T: TargetHashingTargetNode, L: AsyncNodeLookup<T>>(
+    mut dice: DiceTransaction,
+    lookup: L,
+    targets: Vec<(
+        PackageLabelWithModifiers,
+        buck2_error::Result<Vec<TargetNode>>,
+    )>,
+    global_cfg_options: &GlobalCfgOptions,
+    changed: &ChangedFiles,
+) -> buck2_error::Result<Vec<(TargetLabel, Vec<ImpactReason>)>>
+where
+    T::Key: ConfiguredOrUnconfiguredTargetLabel,
+{
+    let targets = T::get_target_nodes(&mut dice, targets, global_cfg_options).await?;
+
+    let mut nodes = Vec::new();
+    async_depth_first_postorder_traversal(
+        &lookup,
+        targets.iter_names(),
+        QueryTargetDepsSuccessors,
+        |node| {
+            nodes.push(node);
+            Ok(())
+        },
+    )
+    .await?;
+
+    let build_files: HashMap<PackageLabel, BuildFilePath> = nodes
+        .iter()
+        .map(|node| {
+            let build_file = node.buildfile_path();
+            (build_file.package(), build_file.clone())
+        })
+        .collect();
+    let changed_build_files = changed_build_files(&mut dice, build_files, changed).await?;
+
+    let mut affected: HashMap<T::Key, Vec<ImpactReason>> = HashMap::new();
+    for node in &nodes {
+        let mut reasons: Vec<ImpactReason> = changed
+            .configs_affecting(node.buildfile_path().package().cell_name())
+            .cloned()
+            .map(ImpactReason::Config)
+            .collect();
+        if let Some(files) = changed_build_files.get(&node.buildfile_path().package()) {
+            reasons.extend(files.iter().cloned().map(ImpactReason::BuildFile));
+        }
+        node.inputs_for_each(|input| {
+            if let Some(file) = changed.changed_input(&input) {
+                reasons.push(ImpactReason::Input(file.clone()));
+            }
+            buck2_error::Ok(())
+        })?;
+        for dep in node.deps() {
+            if affected.contains_key(dep) {
+                reasons.push(ImpactReason::Dependency(dep.unconfigured_label().dupe()));
+            }
+        }
+        if !reasons.is_empty() {
+            reasons.sort();
+            reasons.dedup();
+            affected.insert(node.node_key().clone(), reasons);
+        }
+    }
+
+    let mut result: Vec<_> = targets
+        .iter()
+        .filter_map(|target| {
+            let reasons = affected.remove(target.node_key())?;
+            Some((target.node_key().unconfigured_label().dupe(), reasons))
+        })
+        .collect();
+    result.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(result)
+}
+
+/// For each package, the changed files among its build file, the `PACKAGE` files above it,
+/// and everything these load (transitively). Packages without changes are omitted.
+async fn changed_build_files(
+    dice: &mut DiceComputations<'_>,
+    build_files: HashMap<PackageLabel, BuildFilePath>,
+    changed: &ChangedFiles,
+) -> buck2_error::Result<HashMap<PackageLabel, Vec<CellPath>>> {
+    // Direct changes and direct imports of each package.
+    let mut packages: Vec<(PackageLabel, Vec<CellPath>, Vec<ImportPath>)> = Vec::new();
+    let mut package_files: HashMap<PackageLabel, Option<(CellPath, Vec<ImportPath>)>> =
+        HashMap::new();
+    for (package, build_file) in build_files {
+        let mut files = Vec::new();
+        let build_file = build_file.path();
+        if changed.contains(&build_file) {
+            files.push(build_file);
+        }
+        let mut imports = dice
+            .get_interpreter_results(package.dupe())
+            .await?
+            .imports()
+            .to_vec();
+
+        // Like `buck2 targets --imports`, this does not cross cell boundaries,
+        // which `PACKAGE` file evaluation does.
+        let mut current = Some(package.dupe());
+        while let Some(p) = current {
+            if !package_files.contains_key(&p) {
+                let deps = INTERPRETER_CALCULATION_IMPL
+                    .get()?
+                    .get_package_file_deps(dice, p.dupe())
+                    .await?
+                    .map(|(path, imports)| (path.path().clone(), imports));
+                package_files.insert(p.dupe(), deps);
+            }
+            if let Some((path, package_imports)) = &package_files[&p] {
+                if changed.contains(path) {
+                    files.push(path.clone());
+                }
+                imports.extend(package_imports.iter().cloned());
+            }
+            current = p.parent()?;
+        }
+
+        packages.push((package, files, imports));
+    }
+
+    // The graph of loads reachable from any package.
+    let mut loads: HashMap<ImportPath, Vec<ImportPath>> = HashMap::new();
+    let mut todo: Vec<ImportPath> = packages
+        .iter()
+        .flat_map(|(_, _, imports)| imports.iter().cloned())
+        .collect();
+    while let Some(import) = todo.pop() {
+        if loads.contains_key(&import) {
+            continue;
+        }
+        let module = dice.get_loaded_module_from_import_path(&import).await?;
+        let imports: Vec<_> = module.imports().cloned().collect();
+        todo.extend(imports.iter().cloned());
+        loads.insert(import, imports);
+    }
+
+    let mut memo = HashMap::new();
+    let mut result = HashMap::new();
+    for (package, mut files, imports) in packages {
+        for import in &imports {
+            files.extend(changed_loads(import, &loads, changed, &mut memo));
+        }
+        if !files.is_empty() {
+            files.sort();
+            files.dedup();
+            result.insert(package, files);
+        }
+    }
+    Ok(result)
+}
+
+/// The changed files among `import` and the files it loads (transitively).
+fn changed_loads(
+    import: &ImportPath,
+    loads: &HashMap<ImportPath, Vec<ImportPath>>,
+    changed: &ChangedFiles,
+    memo: &mut HashMap<ImportPath, Vec<CellPath>>,
+) -> Vec<CellPath> {
+    if let Some(files) = memo.get(import) {
+        return files.clone();
+    }
+    let mut files = Vec::new();
+    if changed.contains(import.path()) {
+        files.push(import.path().clone());
+    }
+    // Starlark forbids load cycles, so this recursion terminates.
+    for dep in loads.get(import).into_iter().flatten() {
+        files.extend(changed_loads(dep, loads, changed, memo));
+    }
+    files.sort();
+    files.dedup();
+    memo.insert(import.clone(), files.clone());
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use buck2_core::cells::cell_path::CellPath;
+    use buck2_core::cells::name::CellName;
+
+    use crate::targets::impact::ChangedFiles;
+    use crate::targets::impact::is_buckconfig;
+
+    #[test]
+    fn test_is_buckconfig() {
+        assert!(is_buckconfig(&CellPath::testing_new("root//.buckconfig")));
+        assert!(is_buckconfig(&CellPath::testing_new(
+            "root//.buckconfig.local"
+        )));
+        assert!(is_buckconfig(&CellPath::testing_new(
+            "root//.buckconfig.d/foo"
+        )));
+        assert!(!is_buckconfig(&CellPath::testing_new(
+            "root//foo/.buckconfig"
+        )));
+        assert!(!is_buckconfig(&CellPath::testing_new("root//BUCK")));
+    }
+
+    #[test]
+    fn test_changed_input() {
+        let changed = ChangedFiles::new(
+            HashSet::from([
+                CellPath::testing_new("root//foo/bar/b.txt"),
+                CellPath::testing_new("root//foo/bar/a.txt"),
+                CellPath::testing_new("root//.buckconfig"),
+            ]),
+            CellName::testing_new("root"),
+        );
+        assert_eq!(
+            Some(&CellPath::testing_new("root//foo/bar/a.txt")),
+            changed.changed_input(&CellPath::testing_new("root//foo/bar/a.txt"))
+        );
+        assert_eq!(
+            Some(&CellPath::testing_new("root//foo/bar/a.txt")),
+            changed.changed_input(&CellPath::testing_new("root//foo"))
+        );
+        assert_eq!(
+            None,
+            changed.changed_input(&CellPath::testing_new("root//foo/baz"))
+        );
+        assert_eq!(
+            vec![CellPath::testing_new("root//.buckconfig")],
+            changed.configs
+        );
+    }
+
+    #[test]
+    fn test_configs_affecting() {
+        let changed = ChangedFiles::new(
+            HashSet::from([
+                CellPath::testing_new("root//.buckconfig"),
+                CellPath::testing_new("other//.buckconfig.local"),
+            ]),
+            CellName::testing_new("root"),
+        );
+        let configs_affecting = |cell| {
+            changed
+                .configs_affecting(CellName::testing_new(cell))
+                .map(|config| config.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["root//.buckconfig"], configs_affecting("root"));
+        assert_eq!(vec!["root//.buckconfig"], configs_affecting("third"));
+        assert_eq!(
+            vec!["other//.buckconfig.local", "root//.buckconfig"],
+            configs_affecting("other")
+        );
+    }
+}
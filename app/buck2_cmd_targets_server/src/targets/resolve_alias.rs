@@ -202,5 +202,6 @@ pub(crate) async fn targets_resolve_aliases(
     Ok(TargetsResponse {
         error_count: 0,
         serialized_targets_output: buffer,
+        target_hash_snapshot: None,
     })
 }
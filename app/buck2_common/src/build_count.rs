@@ -164,16 +164,7 @@ impl BuildCountManager {
 
     async fn lock_with_timeout(&self) -> buck2_error::Result<FileLockGuard> {
         self.ensure_dir().await?;
-        let file = std::fs::File::create(&self.lock_file_path)?;
-        let fileref = &file;
-        client_utils::retrying(
-            Duration::from_millis(5),
-            Duration::from_millis(100),
-            Self::LOCK_TIMEOUT,
-            || async { buck2_error::Ok(fs4::fs_std::FileExt::try_lock_exclusive(fileref)?) },
-        )
-        .await?;
-        Ok(FileLockGuard { file })
+        FileLockGuard::lock_exclusive(&self.lock_file_path, Self::LOCK_TIMEOUT).await
     }
 
     /// Updates the build counts for set of targets (on success) and returns the min.
@@ -204,14 +195,45 @@ impl BuildCountManager {
 }
 
 #[must_use]
-struct FileLockGuard {
+pub(crate) struct FileLockGuard {
     file: std::fs::File,
 }
 
+impl FileLockGuard {
+    /// Takes an exclusive lock on `lock_file_path`, creating it if necessary.
+    pub(crate) async fn lock_exclusive(
+        lock_file_path: &AbsNormPathBuf,
+        timeout: Duration,
+    ) -> buck2_error::Result<FileLockGuard> {
+        let file = std::fs::File::create(lock_file_path)?;
+        let fileref = &file;
+        client_utils::retrying(
+            Duration::from_millis(5),
+            Duration::from_millis(100),
+            timeout,
+            || async { buck2_error::Ok(fs4::fs_std::FileExt::try_lock_exclusive(fileref)?) },
+        )
+        .await?;
+        Ok(FileLockGuard { file })
+    }
+
+    /// Takes an exclusive lock on `lock_file_path` if it is free, creating it if necessary.
+    pub(crate) fn try_lock_exclusive(
+        lock_file_path: &AbsNormPathBuf,
+    ) -> buck2_error::Result<Option<FileLockGuard>> {
+        let file = std::fs::File::create(lock_file_path)?;
+        match fs4::fs_std::FileExt::try_lock_exclusive(&file) {
+            Ok(()) => Ok(Some(FileLockGuard { file })),
+            Err(e) if e.raw_os_error() == fs4::lock_contended_error().raw_os_error() => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
 impl Drop for FileLockGuard {
     fn drop(&mut self) {
         fs4::fs_std::FileExt::unlock(&self.file)
-            .expect("Unexpected failure to release a lock file for build count");
+            .expect("Unexpected failure to release a lock file");
     }
 }
 
@@ -0,0 +1,228 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use buck2_error::BuckErrorContext;
+use buck2_fs::async_fs_util;
+use buck2_fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_fs::paths::file_name::FileName;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+
+use crate::build_count::FileLockGuard;
+
+// Version for the serialized history file on disk.
+// Update if changing BuildHistoryEntry in a way that older readers can't parse.
+pub const BUILD_HISTORY_VERSION: u64 = 1;
+
+/// A compact summary of a single command, appended to the build history after it finishes.
+///
+/// Unlike event logs, which are deleted once there are too many of them, history entries are
+/// small enough that we can keep months of them around to spot regressions.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BuildHistoryEntry {
+    pub trace_id: String,
+    /// Command name, e.g. `build` or `test`.
+    pub command: String,
+    /// Target patterns as passed on the command line, if the command resolved any.
+    pub target_patterns: Vec<String>,
+    /// Milliseconds since the Unix epoch.
+    pub start_time_ms: u64,
+    pub success: bool,
+    pub duration_ms: Option<u64>,
+    pub critical_path_ms: Option<u64>,
+    pub cache_hit_rate: f32,
+    /// Actions that were executed (locally or remotely) rather than served from a cache.
+    pub actions_run: u64,
+    pub peak_memory_bytes: Option<u64>,
+}
+
+/// BuildHistoryManager appends a summary of each command to an append-only JSON lines file.
+///
+/// The file is compacted (dropping the oldest entries) once it grows past `MAX_FILE_BYTES`.
+pub struct BuildHistoryManager {
+    base_dir: AbsNormPathBuf,
+    file_path: AbsNormPathBuf,
+    lock_file_path: AbsNormPathBuf,
+}
+
+impl BuildHistoryManager {
+    const LOCK_FILE_NAME: &'static str = "build_history.lock";
+    /// At roughly 300 bytes per entry this keeps tens of thousands of commands.
+    const MAX_FILE_BYTES: u64 = 8 * 1024 * 1024;
+
+    pub fn new(base_dir: AbsNormPathBuf) -> buck2_error::Result<Self> {
+        let file_path = base_dir.join(FileName::new(&format!("{BUILD_HISTORY_VERSION}.jsonl"))?);
+        let lock_file_path = base_dir.join(FileName::new(Self::LOCK_FILE_NAME)?);
+        Ok(Self {
+            base_dir,
+            file_path,
+            lock_file_path,
+        })
+    }
+
+    async fn try_lock(&self) -> buck2_error::Result<Option<FileLockGuard>> {
+        async_fs_util::create_dir_all(&self.base_dir).await?;
+        FileLockGuard::try_lock_exclusive(&self.lock_file_path)
+    }
+
+    /// Appends an entry, compacting the file if it got too large.
+    ///
+    /// This runs as the client exits, so rather than wait for a concurrent client holding the
+    /// lock, the entry is dropped and `false` returned.
+    pub async fn append(&self, entry: &BuildHistoryEntry) -> buck2_error::Result<bool> {
+        let Some(_guard) = self.try_lock().await? else {
+            return Ok(false);
+        };
+
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.file_path)
+            .await
+            .with_buck_error_context(|| format!("Opening {}", self.file_path.display()))?;
+        file.write_all(&line).await?;
+        file.flush().await?;
+        let len = file.metadata().await?.len();
+        drop(file);
+
+        if len > Self::MAX_FILE_BYTES {
+            self.compact().await?;
+        }
+        Ok(true)
+    }
+
+    /// Keeps the most recent entries that fit in half of `MAX_FILE_BYTES`.
+    ///
+    /// The compacted file is written next to the history and renamed over it, so that readers,
+    /// which don't take the lock, never see it half written.
+    async fn compact(&self) -> buck2_error::Result<()> {
+        let Some(contents) = async_fs_util::read_to_string_if_exists(&self.file_path).await? else {
+            return Ok(());
+        };
+        let budget = (Self::MAX_FILE_BYTES / 2) as usize;
+        let mut keep_from = contents.len();
+        for (offset, _) in contents.rmatch_indices('\n').skip(1) {
+            if contents.len() - offset > budget {
+                break;
+            }
+            keep_from = offset + 1;
+        }
+        let temp_path = self.base_dir.join(FileName::new(&format!(
+            "{BUILD_HISTORY_VERSION}.jsonl.tmp"
+        ))?);
+        async_fs_util::write(&temp_path, &contents[keep_from..]).await?;
+        tokio::fs::rename(&temp_path, &self.file_path)
+            .await
+            .with_buck_error_context(|| format!("Renaming {}", temp_path.display()))
+    }
+
+    /// Returns all entries, oldest first.
+    ///
+    /// Lines that fail to parse (e.g. a write torn by a killed client) are skipped.
+    pub async fn read(&self) -> buck2_error::Result<Vec<BuildHistoryEntry>> {
+        let Some(contents) = async_fs_util::read_to_string_if_exists(&self.file_path).await? else {
+            return Ok(Vec::new());
+        };
+        Ok(contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(trace_id: &str, duration_ms: u64) -> BuildHistoryEntry {
+        BuildHistoryEntry {
+            trace_id: trace_id.to_owned(),
+            command: "build".to_owned(),
+            target_patterns: vec!["//some:target".to_owned()],
+            start_time_ms: 1_700_000_000_000,
+            success: true,
+            duration_ms: Some(duration_ms),
+            critical_path_ms: Some(duration_ms / 2),
+            cache_hit_rate: 0.5,
+            actions_run: 10,
+            peak_memory_bytes: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_no_such_file() -> buck2_error::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let bhm = BuildHistoryManager::new(temp_dir.path().join("missing").try_into()?)?;
+        assert_eq!(bhm.read().await?, Vec::new());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_append_and_read() -> buck2_error::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let bhm = BuildHistoryManager::new(temp_dir.path().join("history").try_into()?)?;
+        bhm.append(&entry("a", 100)).await?;
+        bhm.append(&entry("b", 200)).await?;
+        assert_eq!(bhm.read().await?, vec![entry("a", 100), entry("b", 200)]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_append_skips_when_locked() -> buck2_error::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let bhm = BuildHistoryManager::new(temp_dir.path().to_path_buf().try_into()?)?;
+        let guard = bhm.try_lock().await?;
+        assert!(guard.is_some());
+        assert!(!bhm.append(&entry("a", 100)).await?);
+        drop(guard);
+        assert!(bhm.append(&entry("b", 200)).await?);
+        assert_eq!(bhm.read().await?, vec![entry("b", 200)]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_skips_torn_lines() -> buck2_error::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let bhm = BuildHistoryManager::new(temp_dir.path().to_path_buf().try_into()?)?;
+        bhm.append(&entry("a", 100)).await?;
+        let mut contents = tokio::fs::read_to_string(&bhm.file_path).await?;
+        contents.push_str("{\"trace_id\":\"b\",\"comm");
+        tokio::fs::write(&bhm.file_path, contents).await?;
+        assert_eq!(bhm.read().await?, vec![entry("a", 100)]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compact_keeps_newest() -> buck2_error::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let bhm = BuildHistoryManager::new(temp_dir.path().to_path_buf().try_into()?)?;
+        let line = format!("{}\n", serde_json::to_string(&entry("old", 1))?);
+        let count = (BuildHistoryManager::MAX_FILE_BYTES as usize / line.len()) + 1;
+        tokio::fs::write(&bhm.file_path, line.repeat(count)).await?;
+
+        bhm.append(&entry("new", 2)).await?;
+
+        let len = tokio::fs::metadata(&bhm.file_path).await?.len();
+        assert!(len <= BuildHistoryManager::MAX_FILE_BYTES / 2);
+        let entries = bhm.read().await?;
+        assert_eq!(entries.last(), Some(&entry("new", 2)));
+        assert!(entries.len() > 1);
+
+        Ok(())
+    }
+}
@@ -462,6 +462,8 @@ pub struct DaemonStartupConfig {
     pub log_download_method: LogDownloadMethod,
     pub health_check_config: HealthCheckConfig,
     pub retained_event_logs: usize,
+    /// Address (e.g. `127.0.0.1:9464`) on which the daemon serves OpenMetrics, if any.
+    pub metrics_address: Option<String>,
 }
 
 impl DaemonStartupConfig {
@@ -544,6 +546,12 @@ impl DaemonStartupConfig {
                 })
                 .and_then(|s| s.parse::<usize>().ok())
                 .unwrap_or(DEFAULT_RETAINED_EVENT_LOGS),
+            metrics_address: config
+                .get(BuckconfigKeyRef {
+                    section: "buck2",
+                    property: "metrics_address",
+                })
+                .map(ToOwned::to_owned),
         })
     }
 
@@ -573,6 +581,7 @@ impl DaemonStartupConfig {
             },
             health_check_config: HealthCheckConfig::default(),
             retained_event_logs: DEFAULT_RETAINED_EVENT_LOGS,
+            metrics_address: None,
         }
     }
 }
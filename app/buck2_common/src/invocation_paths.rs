@@ -107,6 +107,11 @@ impl InvocationPaths {
             .join(ForwardRelativePath::unchecked_new("build_count"))
     }
 
+    pub fn build_history_dir(&self) -> AbsNormPathBuf {
+        self.buck_out_path()
+            .join(ForwardRelativePath::unchecked_new("build_history"))
+    }
+
     pub fn dice_dump_dir(&self) -> AbsNormPathBuf {
         self.buck_out_path()
             .join(ForwardRelativePath::unchecked_new("dice_dump"))
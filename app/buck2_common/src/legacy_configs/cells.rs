@@ -19,6 +19,8 @@ use buck2_core::cells::CellResolver;
 use buck2_core::cells::alias::NonEmptyCellAlias;
 use buck2_core::cells::cell_root_path::CellRootPath;
 use buck2_core::cells::cell_root_path::CellRootPathBuf;
+use buck2_core::cells::external::ArchiveCellSetup;
+use buck2_core::cells::external::ArchiveFormat;
 use buck2_core::cells::external::ExternalCellOrigin;
 use buck2_core::cells::external::GitCellSetup;
 use buck2_core::cells::external::GitObjectFormat;
@@ -498,6 +500,12 @@ impl BuckConfigBasedCells {
             Unknown(String),
             #[error("Missing buckconfig `{0}.{1}` for external cell configuration")]
             MissingConfiguration(String, String),
+            #[error(
+                "Cannot infer the archive type of external cell `{0}` from its URL, set `{1}.type`"
+            )]
+            UnknownArchiveFormat(CellName, String),
+            #[error("`{0}.sha256` must be a SHA256 digest of 64 hex characters (got: `{1}`)")]
+            InvalidSha256(String, String),
         }
 
         let get_config = |section: &str, property: &str| {
@@ -534,6 +542,46 @@ impl BuckConfigBasedCells {
                 commit: Arc::from(commit),
                 object_format,
             }))
+        } else if value == "archive" {
+            let section = &format!("external_cell_{}", cell.as_str());
+            let urls: Vec<Arc<str>> = get_config(section, "urls")?
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(Arc::from)
+                .collect();
+            if urls.is_empty() {
+                return Err(ExternalCellOriginParseError::MissingConfiguration(
+                    section.to_owned(),
+                    "urls".to_owned(),
+                )
+                .into());
+            }
+            let sha256 = get_config(section, "sha256")?.to_ascii_lowercase();
+            if !is_hex_digest(&sha256, 64) {
+                return Err(ExternalCellOriginParseError::InvalidSha256(
+                    section.to_owned(),
+                    sha256,
+                )
+                .into());
+            }
+            let format = match get_config(section, "type") {
+                Ok(s) => ArchiveFormat::from_str(s)?,
+                Err(_) => ArchiveFormat::from_url(&urls[0]).ok_or_else(|| {
+                    ExternalCellOriginParseError::UnknownArchiveFormat(cell, section.to_owned())
+                })?,
+            };
+            let strip_prefix = get_config(section, "strip_prefix")
+                .ok()
+                .map(|prefix| prefix.trim_matches('/'))
+                .filter(|prefix| !prefix.is_empty())
+                .map(Arc::from);
+            Ok(ExternalCellOrigin::Archive(ArchiveCellSetup {
+                urls: urls.into(),
+                sha256: Arc::from(sha256),
+                strip_prefix,
+                format,
+            }))
         } else {
             Err(ExternalCellOriginParseError::Unknown(value.to_owned()).into())
         }
@@ -634,6 +682,11 @@ async fn get_project_buckconfig_paths(
     Ok(buckconfig_paths)
 }
 
+/// Whether `s` is a lowercase hex digest of `len` characters.
+fn is_hex_digest(s: &str, len: usize) -> bool {
+    s.len() == len && s.bytes().all(|c| matches!(c, b'0'..=b'9' | b'a'..=b'f'))
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -641,6 +694,8 @@ mod tests {
     use buck2_cli_proto::ConfigOverride;
     use buck2_core::cells::cell_root_path::CellRootPath;
     use buck2_core::cells::cell_root_path::CellRootPathBuf;
+    use buck2_core::cells::external::ArchiveCellSetup;
+    use buck2_core::cells::external::ArchiveFormat;
     use buck2_core::cells::external::ExternalCellOrigin;
     use buck2_core::cells::external::GitCellSetup;
     use buck2_core::cells::name::CellName;
@@ -1346,4 +1401,112 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_archive_external_cell() -> buck2_error::Result<()> {
+        initialize_external_cells_impl();
+
+        let mut file_ops = TestConfigParserFileOps::new(&[(
+            ".buckconfig",
+            indoc!(
+                r#"
+                    [cells]
+                        root = .
+                        libfoo = foo/
+                    [external_cells]
+                        libfoo = archive
+                    [external_cell_libfoo]
+                        urls = https://example.com/libfoo-1.0.tar.gz, https://mirror.example.com/libfoo-1.0.tar.gz
+                        sha256 = AAAAAAAABBBBBBBBCCCCCCCCDDDDDDDDEEEEEEEEFFFFFFFF0000000011111111
+                        strip_prefix = libfoo-1.0/
+                "#
+            ),
+        )])?;
+
+        let resolver = BuckConfigBasedCells::testing_parse_with_file_ops(&mut file_ops, &[])
+            .await?
+            .cell_resolver;
+
+        let instance = resolver.get(CellName::testing_new("libfoo")).unwrap();
+
+        assert_eq!(
+            instance.external(),
+            Some(&ExternalCellOrigin::Archive(ArchiveCellSetup {
+                urls: vec![
+                    Arc::from("https://example.com/libfoo-1.0.tar.gz"),
+                    Arc::from("https://mirror.example.com/libfoo-1.0.tar.gz"),
+                ]
+                .into(),
+                sha256: "aaaaaaaabbbbbbbbccccccccddddddddeeeeeeeeffffffff0000000011111111".into(),
+                strip_prefix: Some("libfoo-1.0".into()),
+                format: ArchiveFormat::TarGz,
+            })),
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_archive_external_cell_unknown_format() -> buck2_error::Result<()> {
+        initialize_external_cells_impl();
+
+        let mut file_ops = TestConfigParserFileOps::new(&[(
+            ".buckconfig",
+            indoc!(
+                r#"
+                    [cells]
+                        root = .
+                        libfoo = foo/
+                    [external_cells]
+                        libfoo = archive
+                    [external_cell_libfoo]
+                        urls = https://example.com/download?id=1
+                        sha256 = aaaaaaaabbbbbbbbccccccccddddddddeeeeeeeeffffffff0000000011111111
+                "#
+            ),
+        )])?;
+
+        let e = BuckConfigBasedCells::testing_parse_with_file_ops(&mut file_ops, &[])
+            .await
+            .err()
+            .unwrap();
+
+        let e = format!("{e:?}");
+        assert!(e.contains("Cannot infer the archive type"), "error: {e}");
+
+        Ok(())
+    }
+    #[tokio::test]
+    async fn test_archive_external_cell_invalid_sha256() -> buck2_error::Result<()> {
+        initialize_external_cells_impl();
+
+        let mut file_ops = TestConfigParserFileOps::new(&[(
+            ".buckconfig",
+            indoc!(
+                r#"
+                    [cells]
+                        root = .
+                        libfoo = foo/
+                    [external_cells]
+                        libfoo = archive
+                    [external_cell_libfoo]
+                        urls = https://example.com/libfoo.tar.gz
+                        sha256 = not-a-digest
+                "#
+            ),
+        )])?;
+
+        let e = BuckConfigBasedCells::testing_parse_with_file_ops(&mut file_ops, &[])
+            .await
+            .err()
+            .unwrap();
+
+        let e = format!("{e:?}");
+        assert!(
+            e.contains("`external_cell_libfoo.sha256` must be a SHA256 digest"),
+            "error: {e}"
+        );
+
+        Ok(())
+    }
 }
@@ -22,6 +22,7 @@
 pub mod argv;
 pub mod buckd_connection;
 pub mod build_count;
+pub mod build_history;
 pub mod buildfiles;
 pub mod cas_digest;
 pub mod chunk_reader;
@@ -47,6 +48,7 @@ pub mod kill_util;
 pub mod legacy_configs;
 pub mod liveliness_observer;
 pub mod local_resource_state;
+pub mod lockfile;
 pub mod manifold;
 pub mod memory;
 pub mod package_boundary;
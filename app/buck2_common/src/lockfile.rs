@@ -0,0 +1,348 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! `buck2.lock`: pins every external cell and the digests of every `download_file` URL. Written
+//! by `buck2 fetch`, and checked by builds when present.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use buck2_core::cells::external::ExternalCellOrigin;
+use buck2_core::cells::name::CellName;
+use buck2_error::BuckErrorContext;
+use derive_more::Display;
+use dice::DiceComputations;
+use dice::DiceTransactionUpdater;
+use dice::InjectedKey;
+use dupe::Dupe;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Name of the lockfile, in the project root.
+pub const LOCKFILE: &str = "buck2.lock";
+const LOCKFILE_VERSION: u32 = 1;
+
+#[derive(buck2_error::Error, Debug)]
+#[buck2(tag = Input)]
+enum LockfileError {
+    #[error("`buck2.lock` has version {0}, but this buck2 reads version {1}")]
+    UnsupportedVersion(u32, u32),
+    #[error("`{url}` is downloaded with different digests: `{first}` and `{second}`")]
+    ConflictingDigests {
+        url: String,
+        first: String,
+        second: String,
+    },
+    #[error("{0} is not pinned in `buck2.lock`, run `buck2 fetch` to update it")]
+    NotPinned(String),
+    #[error(
+        "{what} is pinned to `{locked}` in `buck2.lock`, but the build uses `{actual}`, run `buck2 fetch` to update it"
+    )]
+    Mismatch {
+        what: String,
+        locked: String,
+        actual: String,
+    },
+}
+
+/// Contents of `buck2.lock`. Everything is kept in sorted maps so that the file is stable and
+/// diffs well.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Allocative)]
+pub struct Lockfile {
+    version: u32,
+    #[serde(default)]
+    external_cells: BTreeMap<String, LockedExternalCell>,
+    #[serde(default)]
+    downloads: BTreeMap<String, LockedDownload>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Allocative)]
+#[serde(tag = "origin", rename_all = "snake_case")]
+enum LockedExternalCell {
+    Bundled,
+    Git {
+        repo: String,
+        commit: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        object_format: Option<String>,
+    },
+    Archive {
+        urls: Vec<String>,
+        sha256: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        strip_prefix: Option<String>,
+        #[serde(rename = "type")]
+        format: String,
+    },
+}
+
+impl LockedExternalCell {
+    fn new(origin: &ExternalCellOrigin) -> Self {
+        match origin {
+            ExternalCellOrigin::Bundled(_) => Self::Bundled,
+            ExternalCellOrigin::Git(git) => Self::Git {
+                repo: git.git_origin.to_string(),
+                commit: git.commit.to_string(),
+                object_format: git.object_format.as_ref().map(|f| f.to_string()),
+            },
+            ExternalCellOrigin::Archive(archive) => Self::Archive {
+                urls: archive.urls.iter().map(|u| u.to_string()).collect(),
+                sha256: archive.sha256.to_string(),
+                strip_prefix: archive.strip_prefix.as_ref().map(|p| p.to_string()),
+                format: archive.format.to_string(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for LockedExternalCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bundled => write!(f, "bundled"),
+            Self::Git { repo, commit, .. } => write!(f, "git {repo} {commit}"),
+            Self::Archive { urls, sha256, .. } => {
+                write!(f, "archive {} sha256:{sha256}", urls.join(","))
+            }
+        }
+    }
+}
+
+/// The digests a `download_file` URL is downloaded with.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Allocative)]
+pub struct LockedDownload {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sha1: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sha256: Option<String>,
+}
+
+impl LockedDownload {
+    pub fn new(sha1: Option<&str>, sha256: Option<&str>) -> Self {
+        Self {
+            sha1: sha1.map(str::to_owned),
+            sha256: sha256.map(str::to_owned),
+        }
+    }
+}
+
+impl fmt::Display for LockedDownload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.sha1, &self.sha256) {
+            (Some(sha1), Some(sha256)) => write!(f, "sha1:{sha1}, sha256:{sha256}"),
+            (Some(sha1), None) => write!(f, "sha1:{sha1}"),
+            (None, Some(sha256)) => write!(f, "sha256:{sha256}"),
+            (None, None) => write!(f, "<none>"),
+        }
+    }
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Self {
+            version: LOCKFILE_VERSION,
+            external_cells: BTreeMap::new(),
+            downloads: BTreeMap::new(),
+        }
+    }
+}
+
+impl Lockfile {
+    pub fn parse(contents: &str) -> buck2_error::Result<Self> {
+        let lockfile: Self = serde_json::from_str(contents)
+            .with_buck_error_context(|| format!("Error parsing `{LOCKFILE}`"))?;
+        if lockfile.version != LOCKFILE_VERSION {
+            return Err(
+                LockfileError::UnsupportedVersion(lockfile.version, LOCKFILE_VERSION).into(),
+            );
+        }
+        Ok(lockfile)
+    }
+
+    pub fn to_json(&self) -> buck2_error::Result<String> {
+        let mut json = serde_json::to_string_pretty(self)
+            .with_buck_error_context(|| format!("Error serializing `{LOCKFILE}`"))?;
+        json.push('\n');
+        Ok(json)
+    }
+
+    pub fn external_cells_len(&self) -> usize {
+        self.external_cells.len()
+    }
+
+    pub fn downloads_len(&self) -> usize {
+        self.downloads.len()
+    }
+
+    pub fn add_external_cell(&mut self, cell: CellName, origin: &ExternalCellOrigin) {
+        self.external_cells
+            .insert(cell.as_str().to_owned(), LockedExternalCell::new(origin));
+    }
+
+    /// Records a download, failing if the same URL was already recorded with another digest.
+    pub fn add_download(
+        &mut self,
+        url: String,
+        download: LockedDownload,
+    ) -> buck2_error::Result<()> {
+        match self.downloads.get(&url) {
+            Some(existing) if existing != &download => Err(LockfileError::ConflictingDigests {
+                first: existing.to_string(),
+                second: download.to_string(),
+                url,
+            }
+            .into()),
+            Some(_) => Ok(()),
+            None => {
+                self.downloads.insert(url, download);
+                Ok(())
+            }
+        }
+    }
+
+    /// Fails unless the external cell is pinned to `origin`.
+    pub fn check_external_cell(
+        &self,
+        cell: CellName,
+        origin: &ExternalCellOrigin,
+    ) -> buck2_error::Result<()> {
+        let what = format!("External cell `{cell}`");
+        let actual = LockedExternalCell::new(origin);
+        match self.external_cells.get(cell.as_str()) {
+            None => Err(LockfileError::NotPinned(what).into()),
+            Some(locked) if *locked != actual => Err(LockfileError::Mismatch {
+                what,
+                locked: locked.to_string(),
+                actual: actual.to_string(),
+            }
+            .into()),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Fails unless `url` is pinned to the digests of `download`.
+    pub fn check_download(&self, url: &str, download: &LockedDownload) -> buck2_error::Result<()> {
+        let what = format!("Download `{url}`");
+        match self.downloads.get(url) {
+            None => Err(LockfileError::NotPinned(what).into()),
+            Some(locked) if locked != download => Err(LockfileError::Mismatch {
+                what,
+                locked: locked.to_string(),
+                actual: download.to_string(),
+            }
+            .into()),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+/// The lockfile the builds in a transaction are checked against, injected into DICE so that
+/// computations checking against it are invalidated when it changes.
+#[derive(Clone, Dupe, Display, Debug, Eq, Hash, PartialEq, Allocative)]
+#[display("{:?}", self)]
+struct LockfileKey;
+
+impl InjectedKey for LockfileKey {
+    type Value = Option<Arc<Lockfile>>;
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+#[async_trait]
+pub trait HasLockfile {
+    /// The lockfile to check against, if any.
+    async fn get_lockfile(&mut self) -> buck2_error::Result<Option<Arc<Lockfile>>>;
+}
+
+pub trait SetLockfile {
+    fn set_lockfile(&mut self, lockfile: Option<Lockfile>) -> buck2_error::Result<()>;
+}
+
+#[async_trait]
+impl HasLockfile for DiceComputations<'_> {
+    async fn get_lockfile(&mut self) -> buck2_error::Result<Option<Arc<Lockfile>>> {
+        Ok(self.compute(&LockfileKey).await?)
+    }
+}
+
+impl SetLockfile for DiceTransactionUpdater {
+    fn set_lockfile(&mut self, lockfile: Option<Lockfile>) -> buck2_error::Result<()> {
+        Ok(self.changed_to(vec![(LockfileKey, lockfile.map(Arc::new))])?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lockfile_roundtrip() {
+        let mut lockfile = Lockfile::default();
+        lockfile.external_cells.insert(
+            "prelude".to_owned(),
+            LockedExternalCell::Archive {
+                urls: vec!["https://example.com/prelude.tar.gz".to_owned()],
+                sha256: "ab".repeat(32),
+                strip_prefix: None,
+                format: "tar.gz".to_owned(),
+            },
+        );
+        lockfile
+            .add_download(
+                "https://example.com/a.jar".to_owned(),
+                LockedDownload::new(Some(&"cd".repeat(20)), None),
+            )
+            .unwrap();
+
+        let json = lockfile.to_json().unwrap();
+        assert!(json.contains(r#""origin": "archive""#), "{json}");
+        assert!(!json.contains("strip_prefix"), "{json}");
+        assert_eq!(lockfile, Lockfile::parse(&json).unwrap());
+    }
+
+    #[test]
+    fn test_lockfile_conflicting_downloads() {
+        let mut lockfile = Lockfile::default();
+        let download = |sha1: &str| LockedDownload::new(Some(&sha1.repeat(20)), None);
+        let url = "https://example.com/a.jar";
+        lockfile
+            .add_download(url.to_owned(), download("aa"))
+            .unwrap();
+        lockfile
+            .add_download(url.to_owned(), download("aa"))
+            .unwrap();
+        let err = lockfile
+            .add_download(url.to_owned(), download("bb"))
+            .unwrap_err();
+        assert!(err.to_string().contains("different digests"), "{err}");
+    }
+
+    #[test]
+    fn test_lockfile_check_download() {
+        let mut lockfile = Lockfile::default();
+        let download = |sha1: &str| LockedDownload::new(Some(&sha1.repeat(20)), None);
+        let url = "https://example.com/a.jar";
+        lockfile
+            .add_download(url.to_owned(), download("aa"))
+            .unwrap();
+
+        lockfile.check_download(url, &download("aa")).unwrap();
+        let err = lockfile.check_download(url, &download("bb")).unwrap_err();
+        assert!(err.to_string().contains("is pinned to"), "{err}");
+        let err = lockfile
+            .check_download("https://example.com/b.jar", &download("aa"))
+            .unwrap_err();
+        assert!(err.to_string().contains("is not pinned"), "{err}");
+    }
+}
@@ -19,10 +19,11 @@ use dupe::Dupe;
 
 use crate::cells::name::CellName;
 
-#[derive(Debug, Clone, Dupe, Allocative, PartialEq, Eq)]
+#[derive(Debug, Clone, Dupe, Allocative, PartialEq, Eq, Hash)]
 pub enum ExternalCellOrigin {
     Bundled(CellName),
     Git(GitCellSetup),
+    Archive(ArchiveCellSetup),
 }
 
 #[derive(
@@ -43,15 +44,81 @@ pub struct GitCellSetup {
     pub object_format: Option<GitObjectFormat>,
 }
 
+/// An external cell fetched as an archive (e.g. a release tarball) over http.
+#[derive(Debug, Clone, Dupe, allocative::Allocative, PartialEq, Eq, Hash)]
+pub struct ArchiveCellSetup {
+    /// Mirrors of the same archive, tried in order. Never empty.
+    pub urls: Arc<[Arc<str>]>,
+    // Guaranteed to be a valid sha256 digest, lowercase.
+    pub sha256: Arc<str>,
+    /// Directory inside the archive to use as the root of the cell.
+    pub strip_prefix: Option<Arc<str>>,
+    pub format: ArchiveFormat,
+}
+
+impl fmt::Display for ArchiveCellSetup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "archive({}, {})", self.urls[0], self.sha256)
+    }
+}
+
 impl fmt::Display for ExternalCellOrigin {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Bundled(cell) => write!(f, "bundled({cell})"),
             Self::Git(git) => write!(f, "{git}"),
+            Self::Archive(archive) => write!(f, "{archive}"),
         }
     }
 }
 
+#[derive(Debug, Display, Eq, PartialEq, Clone, Copy, Dupe, Hash, Allocative)]
+pub enum ArchiveFormat {
+    #[display("tar")]
+    Tar,
+    #[display("tar.gz")]
+    TarGz,
+    #[display("tar.zst")]
+    TarZst,
+    #[display("zip")]
+    Zip,
+}
+
+impl FromStr for ArchiveFormat {
+    type Err = buck2_error::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tar" => Ok(ArchiveFormat::Tar),
+            "tar.gz" | "tgz" => Ok(ArchiveFormat::TarGz),
+            "tar.zst" | "tzst" => Ok(ArchiveFormat::TarZst),
+            "zip" => Ok(ArchiveFormat::Zip),
+            _ => Err(buck2_error!(
+                buck2_error::ErrorTag::Input,
+                "archive type must be one of `tar`, `tar.gz`, `tar.zst` or `zip` (got: {})",
+                &s,
+            )),
+        }
+    }
+}
+
+impl ArchiveFormat {
+    /// Guesses the format from the extension of a URL, ignoring any query string.
+    pub fn from_url(url: &str) -> Option<Self> {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        [
+            (".tar.gz", Self::TarGz),
+            (".tgz", Self::TarGz),
+            (".tar.zst", Self::TarZst),
+            (".tzst", Self::TarZst),
+            (".tar", Self::Tar),
+            (".zip", Self::Zip),
+        ]
+        .into_iter()
+        .find_map(|(extension, format)| path.ends_with(extension).then_some(format))
+    }
+}
+
 #[derive(Debug, Display, Eq, PartialEq, Clone, Dupe, Hash, Allocative)]
 pub enum GitObjectFormat {
     #[display("sha1")]
@@ -104,3 +171,28 @@ impl GitObjectFormat {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_format_from_url() {
+        assert_eq!(
+            Some(ArchiveFormat::TarGz),
+            ArchiveFormat::from_url("https://example.com/foo-1.0.tar.gz")
+        );
+        assert_eq!(
+            Some(ArchiveFormat::Zip),
+            ArchiveFormat::from_url("https://example.com/foo.zip?raw=true")
+        );
+        assert_eq!(
+            Some(ArchiveFormat::TarZst),
+            ArchiveFormat::from_url("https://example.com/foo.tzst")
+        );
+        assert_eq!(
+            None,
+            ArchiveFormat::from_url("https://example.com/download")
+        );
+    }
+}
@@ -40,6 +40,12 @@ impl Default for LocalExecutorOptions {
     }
 }
 
+#[derive(Debug, Eq, Hash, PartialEq, Clone, Allocative, Pagable)]
+pub struct ExternalExecutorOptions {
+    /// Path of the Unix socket the external executor listens on.
+    pub socket: String,
+}
+
 #[derive(Debug, Eq, Hash, PartialEq, Clone, Allocative, Pagable)]
 pub struct RemoteEnabledExecutorOptions {
     pub executor: RemoteEnabledExecutor,
@@ -247,6 +253,9 @@ pub enum Executor {
     /// This executor interacts with a RE backend. It may use that to read or write to caches, or
     /// to execute commands.
     RemoteEnabled(RemoteEnabledExecutorOptions),
+    /// This executor forwards commands to an external process, which runs them on the local
+    /// filesystem (or syncs it to wherever it runs them).
+    External(ExternalExecutorOptions),
     /// Can't run any actions
     None,
 }
@@ -276,6 +285,7 @@ impl Display for Executor {
                     options.executor, cache, options.cache_upload_behavior, dep_file_cache
                 )
             }
+            Self::External(options) => write!(f, "External + socket {}", options.socket),
             Self::None => write!(f, "None"),
         }
     }
@@ -393,6 +403,7 @@ impl CommandExecutorConfig {
         match &self.executor {
             Executor::Local(_) => false,
             Executor::RemoteEnabled(options) => options.remote_cache_enabled,
+            Executor::External(_) => false,
             Executor::None => false,
         }
     }
@@ -342,6 +342,7 @@ impl BuckOutPathResolver {
             match origin {
                 ExternalCellOrigin::Bundled(_) => ForwardRelativePath::new("bundled").unwrap(),
                 ExternalCellOrigin::Git(_) => ForwardRelativePath::new("git").unwrap(),
+                ExternalCellOrigin::Archive(_) => ForwardRelativePath::new("archive").unwrap(),
             },
             match &origin {
                 ExternalCellOrigin::Bundled(cell) => {
@@ -350,11 +351,26 @@ impl BuckOutPathResolver {
                 ExternalCellOrigin::Git(setup) => {
                     ForwardRelativePath::new(setup.commit.as_ref()).unwrap()
                 }
+                ExternalCellOrigin::Archive(setup) => {
+                    ForwardRelativePath::new(setup.sha256.as_ref()).unwrap()
+                }
             },
             path.as_ref(),
         ]))
     }
 
+    /// Where the downloaded archive of an `archive` external cell is kept, keyed by its sha256.
+    ///
+    /// The archive is kept around after extraction so that the cell can be re-extracted without
+    /// network access.
+    pub fn resolve_external_cell_archive(&self, sha256: &str) -> ProjectRelativePathBuf {
+        ProjectRelativePathBuf::from(ForwardRelativePathBuf::concat([
+            self.buck_out_v2.as_forward_relative_path(),
+            ForwardRelativePath::new("external_cells/archive_cache").unwrap(),
+            ForwardRelativePath::new(sha256).unwrap(),
+        ]))
+    }
+
     pub fn resolve_scratch(
         &self,
         path: &BuckOutScratchPath,
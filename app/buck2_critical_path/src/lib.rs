@@ -14,6 +14,7 @@ mod builder;
 mod critical_path_accessor;
 mod graph;
 mod potential;
+mod schedule;
 mod types;
 
 #[cfg(test)]
@@ -26,6 +27,7 @@ pub use graph::Graph;
 pub use graph::GraphVertex;
 pub use graph::TopoSortError;
 pub use potential::compute_critical_path_potentials;
+pub use schedule::simulate_bounded_parallelism;
 pub use types::CriticalPathIndex;
 pub use types::CriticalPathVertexData;
 pub use types::OptionalVertexId;
@@ -0,0 +1,152 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::graph::Graph;
+use crate::graph::TopoSortError;
+use crate::types::VertexData;
+use crate::types::VertexId;
+
+/// Simulates executing `deps` when the vertices for which `needs_slot` is set have to share
+/// `slots` execution slots, while all other vertices start as soon as their dependencies finish.
+///
+/// Ready vertices waiting for a slot are started in order of their longest path to a sink, which
+/// is what an ideal scheduler would do. Returns the time at which the last vertex finishes. With
+/// unbounded slots, this is the cost of the critical path.
+pub fn simulate_bounded_parallelism(
+    deps: &Graph,
+    weights: &VertexData<u64>,
+    needs_slot: &VertexData<bool>,
+    slots: usize,
+) -> Result<u64, TopoSortError> {
+    let topo_order = deps.topo_sort()?;
+    let rdeps = deps.reversed();
+    let (cost_to_sink, _) = rdeps.find_longest_paths(topo_order.iter().copied(), weights);
+
+    let mut pending_deps = deps.allocate_vertex_data(0usize);
+    for idx in deps.iter_vertices() {
+        pending_deps[idx] = deps.iter_edges(idx).count();
+    }
+
+    // Vertices that are running, by finish time.
+    let mut running = BinaryHeap::new();
+    // Vertices that are waiting for a slot, by cost to sink.
+    let mut waiting = BinaryHeap::new();
+
+    let make_ready = |idx: VertexId,
+                      now: u64,
+                      running: &mut BinaryHeap<Reverse<(u64, VertexId)>>,
+                      waiting: &mut BinaryHeap<(u64, VertexId)>| {
+        if needs_slot[idx] {
+            waiting.push((cost_to_sink[idx].runtime, idx));
+        } else {
+            running.push(Reverse((now + weights[idx], idx)));
+        }
+    };
+
+    for idx in deps.iter_vertices() {
+        if pending_deps[idx] == 0 {
+            make_ready(idx, 0, &mut running, &mut waiting);
+        }
+    }
+
+    let mut free_slots = slots.max(1);
+    let mut now = 0;
+
+    loop {
+        while free_slots > 0 {
+            let Some((_, idx)) = waiting.pop() else {
+                break;
+            };
+            free_slots -= 1;
+            running.push(Reverse((now + weights[idx], idx)));
+        }
+
+        let Some(Reverse((finish, idx))) = running.pop() else {
+            break;
+        };
+        now = finish;
+        if needs_slot[idx] {
+            free_slots += 1;
+        }
+
+        for dependent in rdeps.iter_edges(idx) {
+            pending_deps[dependent] -= 1;
+            if pending_deps[dependent] == 0 {
+                make_ready(dependent, now, &mut running, &mut waiting);
+            }
+        }
+    }
+
+    Ok(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::GraphBuilder;
+    use crate::potential::compute_critical_path_potentials;
+    use crate::test_utils::make_dag;
+    use crate::test_utils::seeded_rng;
+
+    /// Four independent vertices taking 10 each, followed by one that depends on all of them.
+    fn fan_in() -> (Graph, VertexData<u64>) {
+        let mut builder = GraphBuilder::new();
+        for key in ["a", "b", "c", "d"] {
+            builder.push(key, std::iter::empty(), 10).unwrap();
+        }
+        builder.push("e", vec!["a", "b", "c", "d"], 5).unwrap();
+        let (graph, _keys, weights) = builder.finish();
+        (graph, weights)
+    }
+
+    #[test]
+    fn test_bounded() {
+        let (graph, weights) = fan_in();
+        let needs_slot = graph.allocate_vertex_data(true);
+        assert_eq!(
+            simulate_bounded_parallelism(&graph, &weights, &needs_slot, 1).unwrap(),
+            45
+        );
+        assert_eq!(
+            simulate_bounded_parallelism(&graph, &weights, &needs_slot, 2).unwrap(),
+            25
+        );
+        assert_eq!(
+            simulate_bounded_parallelism(&graph, &weights, &needs_slot, 4).unwrap(),
+            15
+        );
+    }
+
+    #[test]
+    fn test_unbounded_vertices_ignore_slots() {
+        let (graph, weights) = fan_in();
+        let needs_slot = graph.allocate_vertex_data(false);
+        assert_eq!(
+            simulate_bounded_parallelism(&graph, &weights, &needs_slot, 1).unwrap(),
+            15
+        );
+    }
+
+    #[test]
+    fn test_unbounded_matches_critical_path() {
+        let dag = make_dag(1000, &mut seeded_rng());
+        let needs_slot = dag.graph.allocate_vertex_data(true);
+        let (_, critical_path_cost, _, _) =
+            compute_critical_path_potentials(&dag.graph, &dag.weights).unwrap();
+        assert_eq!(
+            simulate_bounded_parallelism(&dag.graph, &dag.weights, &needs_slot, usize::MAX)
+                .unwrap(),
+            critical_path_cost.runtime
+        );
+    }
+}
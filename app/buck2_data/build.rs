@@ -153,6 +153,14 @@ fn main() -> io::Result<()> {
             "event_time",
             "#[serde(default, with = \"crate::serialize_timestamp\")]",
         )
+        .field_attribute(
+            "buck.data.EventLogIndexChunk.first_timestamp",
+            "#[serde(default, with = \"crate::serialize_timestamp\")]",
+        )
+        .field_attribute(
+            "buck.data.EventLogIndexChunk.last_timestamp",
+            "#[serde(default, with = \"crate::serialize_timestamp\")]",
+        )
         .field_attribute(
             "suspend_duration",
             "#[serde(default, with = \"crate::serialize_duration_as_micros\")]",
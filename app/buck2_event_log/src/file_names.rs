@@ -21,6 +21,8 @@ use chrono::Utc;
 use futures::StreamExt;
 use gazebo::prelude::VecExt;
 
+use crate::index::INDEX_EXTENSION;
+use crate::index::index_path;
 use crate::read::EventLogPathBuf;
 use crate::utils::Encoding;
 use crate::utils::EventLogErrors;
@@ -46,10 +48,20 @@ pub(crate) fn get_logfile_name(
 
 pub(crate) async fn remove_old_logs(logdir: &AbsNormPath, retained_event_logs: usize) {
     if let Ok(logfiles) = get_files_in_log_dir(logdir) {
-        futures::stream::iter(logfiles.into_iter().rev().skip(retained_event_logs - 1))
+        // Indexes don't count towards retained logs, they are removed along with their log.
+        let logfiles = logfiles.into_iter().filter(|file| {
+            !file
+                .as_os_str()
+                .to_string_lossy()
+                .ends_with(INDEX_EXTENSION)
+        });
+        futures::stream::iter(logfiles.rev().skip(retained_event_logs - 1))
             .then(|file| async move {
                 // The oldest logs might be open from another concurrent build, so suppress error.
-                tokio::fs::remove_file(file).await.ok()
+                tokio::fs::remove_file(&file).await.ok();
+                if let Ok(index) = index_path(&file) {
+                    tokio::fs::remove_file(index).await.ok();
+                }
             })
             .collect::<Vec<_>>()
             .await;
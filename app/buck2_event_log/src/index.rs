@@ -0,0 +1,391 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! Random access into binary event logs.
+//!
+//! `PROTO_ZSTD` logs written by buck2 are split into independent zstd frames of roughly
+//! [`CHUNK_SIZE_BYTES`] of uncompressed data each. The first frame only contains the invocation
+//! header, and every following frame starts on a value boundary. A sidecar file with the
+//! [`INDEX_EXTENSION`] suffix records where each frame starts along with the timestamps and
+//! action spans it covers, so readers can decode only the frames they are interested in.
+//!
+//! The concatenated frames are still a valid zstd stream, so logs remain readable without the
+//! index.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::mem;
+use std::ops::Range;
+use std::time::SystemTime;
+
+use buck2_data::EventLogIndexChunk;
+use buck2_error::BuckErrorContext;
+use buck2_events::span::SpanId;
+use buck2_fs::paths::abs_path::AbsPath;
+use buck2_fs::paths::abs_path::AbsPathBuf;
+use prost::Message;
+
+pub(crate) const INDEX_EXTENSION: &str = ".index";
+
+/// Uncompressed size after which the writer starts a new chunk.
+const CHUNK_SIZE_BYTES: usize = 1024 * 1024;
+
+pub(crate) fn index_path(log_path: &AbsPath) -> buck2_error::Result<AbsPathBuf> {
+    let mut path = log_path.as_os_str().to_owned();
+    path.push(INDEX_EXTENSION);
+    AbsPathBuf::new(path)
+}
+
+/// What a serialized item contributes to the index.
+pub(crate) enum IndexEntry<'a> {
+    /// The invocation header, which is not a value and always precedes the first chunk.
+    Header,
+    /// A value that readers get back as a `StreamValue`.
+    Value(Option<&'a buck2_data::BuckEvent>),
+}
+
+/// Compresses a protobuf event log into independent zstd frames and indexes them.
+pub(crate) struct EventLogIndexWriter {
+    encoder: zstd::stream::write::Encoder<'static, Vec<u8>>,
+    /// Compressed bytes ready to be written to the log file.
+    compressed: Vec<u8>,
+    /// Compressed bytes produced so far, i.e. the offset of the next frame in the log file.
+    offset: u64,
+    /// Uncompressed bytes written to the current frame.
+    frame_bytes: usize,
+    chunk_size_bytes: usize,
+    values: u64,
+    index: buck2_data::EventLogIndex,
+}
+
+impl EventLogIndexWriter {
+    pub(crate) fn new() -> buck2_error::Result<Self> {
+        Self::with_chunk_size(CHUNK_SIZE_BYTES)
+    }
+
+    fn with_chunk_size(chunk_size_bytes: usize) -> buck2_error::Result<Self> {
+        Ok(Self {
+            encoder: new_encoder()?,
+            compressed: Vec::new(),
+            offset: 0,
+            frame_bytes: 0,
+            chunk_size_bytes,
+            values: 0,
+            index: buck2_data::EventLogIndex::default(),
+        })
+    }
+
+    /// Compress one serialized item, starting a new chunk first if needed.
+    pub(crate) fn write(&mut self, entry: IndexEntry<'_>, data: &[u8]) -> buck2_error::Result<()> {
+        if let IndexEntry::Value(event) = entry {
+            if self.index.chunks.is_empty() || self.frame_bytes >= self.chunk_size_bytes {
+                self.finish_frame()?;
+                self.index.chunks.push(EventLogIndexChunk {
+                    offset: self.offset,
+                    first_value: self.values,
+                    ..Default::default()
+                });
+            }
+            if let Some(event) = event {
+                self.record_event(event);
+            }
+            self.values += 1;
+        }
+
+        self.encoder
+            .write_all(data)
+            .buck_error_context("Failed to compress event")?;
+        self.frame_bytes += data.len();
+        self.drain_encoder();
+        Ok(())
+    }
+
+    fn record_event(&mut self, event: &buck2_data::BuckEvent) {
+        let chunk = self
+            .index
+            .chunks
+            .last_mut()
+            .expect("a chunk is started before any value");
+        if chunk.first_timestamp.is_none() {
+            chunk.first_timestamp = event.timestamp;
+        }
+        chunk.last_timestamp = event.timestamp;
+
+        match &event.data {
+            Some(buck2_data::buck_event::Data::SpanStart(span)) => {
+                if let Some(buck2_data::span_start_event::Data::ActionExecution(_)) = &span.data {
+                    chunk.action_starts.push(event.span_id);
+                }
+            }
+            Some(buck2_data::buck_event::Data::SpanEnd(span)) => {
+                if let Some(buck2_data::span_end_event::Data::ActionExecution(action)) = &span.data
+                {
+                    if action.failed {
+                        chunk.action_failures.push(event.span_id);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Flush the current frame so everything written so far can be decoded, without ending it.
+    pub(crate) fn flush(&mut self) -> buck2_error::Result<()> {
+        self.encoder
+            .flush()
+            .buck_error_context("Failed to flush compressed event log")?;
+        self.drain_encoder();
+        Ok(())
+    }
+
+    /// End the last frame. Nothing may be written afterwards.
+    pub(crate) fn finish(&mut self) -> buck2_error::Result<()> {
+        self.finish_frame()
+    }
+
+    /// Compressed bytes produced since the last call.
+    pub(crate) fn take_compressed(&mut self) -> Vec<u8> {
+        mem::take(&mut self.compressed)
+    }
+
+    pub(crate) async fn write_index(&self, log_path: &AbsPath) -> buck2_error::Result<()> {
+        let path = index_path(log_path)?;
+        tokio::fs::write(&path, self.index.encode_to_vec())
+            .await
+            .with_buck_error_context(|| {
+                format!("Failed to write event log index at `{}`", path.display())
+            })
+    }
+
+    fn finish_frame(&mut self) -> buck2_error::Result<()> {
+        if self.frame_bytes == 0 {
+            return Ok(());
+        }
+        let encoder = mem::replace(&mut self.encoder, new_encoder()?);
+        let remaining = encoder
+            .finish()
+            .buck_error_context("Failed to finish compressed event log frame")?;
+        self.offset += remaining.len() as u64;
+        self.compressed.extend_from_slice(&remaining);
+        self.frame_bytes = 0;
+        Ok(())
+    }
+
+    fn drain_encoder(&mut self) {
+        let output = self.encoder.get_mut();
+        self.offset += output.len() as u64;
+        self.compressed.append(output);
+    }
+}
+
+fn new_encoder() -> buck2_error::Result<zstd::stream::write::Encoder<'static, Vec<u8>>> {
+    zstd::stream::write::Encoder::new(Vec::new(), 0)
+        .buck_error_context("Failed to create zstd encoder")
+}
+
+/// The sidecar index of an event log, see the module docs.
+pub struct EventLogIndex {
+    index: buck2_data::EventLogIndex,
+}
+
+impl EventLogIndex {
+    pub(crate) fn decode(data: &[u8]) -> buck2_error::Result<Self> {
+        let index = buck2_data::EventLogIndex::decode(data)
+            .buck_error_context("Invalid event log index")?;
+        Ok(Self { index })
+    }
+
+    pub fn chunks(&self) -> &[EventLogIndexChunk] {
+        &self.index.chunks
+    }
+
+    /// Offset in the log file at which chunk `chunk` ends, or `None` for the last chunk.
+    pub(crate) fn chunk_end(&self, chunk: usize) -> Option<u64> {
+        self.index.chunks.get(chunk + 1).map(|c| c.offset)
+    }
+
+    /// The first chunk containing events that happened at or after `time`, or the last chunk if
+    /// there are none.
+    pub fn chunk_for_time(&self, time: SystemTime) -> usize {
+        // Timestamps only roughly increase through the log, so go by the last event of each
+        // chunk rather than the first.
+        self.index
+            .chunks
+            .iter()
+            .position(|chunk| {
+                chunk
+                    .last_timestamp
+                    .and_then(|ts| SystemTime::try_from(ts).ok())
+                    .is_some_and(|last| last >= time)
+            })
+            .unwrap_or(self.index.chunks.len().saturating_sub(1))
+    }
+
+    /// The chunk in which the action with this span started.
+    pub fn chunk_for_action_start(&self, span_id: SpanId) -> Option<usize> {
+        let span_id = span_id.0.get();
+        self.index
+            .chunks
+            .iter()
+            .position(|chunk| chunk.action_starts.contains(&span_id))
+    }
+
+    /// Ranges of chunks that contain everything logged about failed actions, from their start to
+    /// their end. Overlapping ranges are merged, and the result is sorted.
+    pub fn failed_action_chunks(&self) -> Vec<Range<usize>> {
+        let starts: HashMap<u64, usize> = self
+            .index
+            .chunks
+            .iter()
+            .enumerate()
+            .flat_map(|(i, chunk)| chunk.action_starts.iter().map(move |span| (*span, i)))
+            .collect();
+
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for (end, chunk) in self.index.chunks.iter().enumerate() {
+            for span in &chunk.action_failures {
+                // If the start is missing, the index is incomplete: read everything before.
+                let start = starts.get(span).copied().unwrap_or(0);
+                ranges.push(start..end + 1);
+            }
+        }
+
+        ranges.sort_by_key(|r| r.start);
+        let mut merged: Vec<Range<usize>> = Vec::new();
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buck2_data::ActionExecutionEnd;
+    use buck2_data::ActionExecutionStart;
+    use buck2_data::SpanEndEvent;
+    use buck2_data::SpanStartEvent;
+
+    use super::*;
+
+    fn at(seconds: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    fn start(span_id: u64, seconds: u64) -> buck2_data::BuckEvent {
+        buck2_data::BuckEvent {
+            timestamp: Some(at(seconds).into()),
+            span_id,
+            data: Some(
+                SpanStartEvent {
+                    data: Some(ActionExecutionStart::default().into()),
+                }
+                .into(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    fn end(span_id: u64, seconds: u64, failed: bool) -> buck2_data::BuckEvent {
+        buck2_data::BuckEvent {
+            timestamp: Some(at(seconds).into()),
+            span_id,
+            data: Some(
+                SpanEndEvent {
+                    data: Some(
+                        ActionExecutionEnd {
+                            failed,
+                            ..Default::default()
+                        }
+                        .into(),
+                    ),
+                    ..Default::default()
+                }
+                .into(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    /// Write a log with one chunk per event.
+    fn write_log(
+        events: &[buck2_data::BuckEvent],
+    ) -> buck2_error::Result<(EventLogIndexWriter, Vec<u8>)> {
+        let mut writer = EventLogIndexWriter::with_chunk_size(1)?;
+        writer.write(IndexEntry::Header, b"header")?;
+        for event in events {
+            writer.write(IndexEntry::Value(Some(event)), &event.encode_to_vec())?;
+        }
+        writer.finish()?;
+        let log = writer.take_compressed();
+        Ok((writer, log))
+    }
+
+    #[test]
+    fn test_chunks_are_independent_frames() -> buck2_error::Result<()> {
+        let events = vec![
+            start(1, 10),
+            start(2, 11),
+            end(1, 12, true),
+            end(2, 13, false),
+        ];
+        let (writer, log) = write_log(&events)?;
+        let index = EventLogIndex::decode(&writer.index.encode_to_vec())?;
+
+        assert_eq!(index.chunks().len(), 4);
+        assert_eq!(writer.offset, log.len() as u64);
+
+        // The whole log is still a single valid zstd stream.
+        let mut expected = b"header".to_vec();
+        for event in &events {
+            expected.extend(event.encode_to_vec());
+        }
+        assert_eq!(zstd::stream::decode_all(log.as_slice())?, expected);
+
+        for (i, chunk) in index.chunks().iter().enumerate() {
+            assert_eq!(chunk.first_value, i as u64);
+            let end = index.chunk_end(i).map_or(log.len(), |end| end as usize);
+            let data = zstd::stream::decode_all(&log[chunk.offset as usize..end])?;
+            assert_eq!(data, events[i].encode_to_vec());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lookups() -> buck2_error::Result<()> {
+        let events = vec![
+            start(1, 10),
+            start(2, 11),
+            start(3, 12),
+            end(2, 13, false),
+            end(3, 14, true),
+            end(1, 15, false),
+        ];
+        let (writer, _log) = write_log(&events)?;
+        let index = EventLogIndex::decode(&writer.index.encode_to_vec())?;
+
+        assert_eq!(index.chunk_for_action_start(SpanId::from_u64(2)?), Some(1));
+        assert_eq!(index.chunk_for_action_start(SpanId::from_u64(4)?), None);
+
+        assert_eq!(index.chunk_for_time(at(0)), 0);
+        assert_eq!(index.chunk_for_time(at(13)), 3);
+        assert_eq!(index.chunk_for_time(at(100)), 5);
+
+        assert_eq!(index.failed_action_chunks(), vec![2..5]);
+
+        Ok(())
+    }
+}
@@ -22,6 +22,7 @@ use tokio::process::Child;
 use tokio::task::JoinHandle;
 
 pub mod file_names;
+pub mod index;
 pub mod read;
 pub mod stream_value;
 pub mod ttl;
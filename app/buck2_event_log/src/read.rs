@@ -9,6 +9,8 @@
  */
 
 use std::io;
+use std::io::SeekFrom;
+use std::ops::Range;
 use std::pin::Pin;
 use std::str::FromStr;
 use std::sync::atomic::AtomicUsize;
@@ -24,6 +26,7 @@ use buck2_cli_proto::*;
 use buck2_error::BuckErrorContext;
 use buck2_events::BuckEvent;
 use buck2_fs::async_fs_util;
+use buck2_fs::fs_util;
 use buck2_fs::paths::abs_path::AbsPath;
 use buck2_fs::paths::abs_path::AbsPathBuf;
 use buck2_wrapper_common::invocation_id::TraceId;
@@ -36,11 +39,15 @@ use prost::Message;
 use regex::Regex;
 use tokio::io::AsyncBufReadExt;
 use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncSeekExt;
 use tokio::io::BufReader;
 use tokio::io::ReadBuf;
 use tokio_stream::wrappers::LinesStream;
 use tokio_util::codec::FramedRead;
 
+use crate::index::EventLogIndex;
+use crate::index::index_path;
 use crate::stream_value::StreamValue;
 use crate::utils::Compression;
 use crate::utils::Encoding;
@@ -235,23 +242,7 @@ impl EventLogPathBuf {
             .buck_error_context("Invalid Invocation")?;
         let invocation = Invocation::from_proto(invocation);
 
-        let events = stream.and_then(|data| async move {
-            let val = buck2_cli_proto::CommandProgress::decode_length_delimited(data)
-                .buck_error_context("Invalid CommandProgress")?;
-            match val.progress {
-                Some(command_progress::Progress::Event(event)) => Ok(StreamValue::Event(event)),
-                Some(command_progress::Progress::Result(result)) => Ok(StreamValue::Result(result)),
-                Some(command_progress::Progress::PartialResult(result)) => {
-                    Ok(StreamValue::PartialResult(result))
-                }
-                None => Err(buck2_error::buck2_error!(
-                    buck2_error::ErrorTag::InvalidEvent,
-                    "Event type not recognized"
-                )),
-            }
-        });
-
-        Ok((invocation, events.boxed()))
+        Ok((invocation, decode_protobuf_values(stream)))
     }
 
     async fn unpack_stream_inner<'a>(
@@ -287,6 +278,76 @@ impl EventLogPathBuf {
         self.unpack_stream_inner(None).await
     }
 
+    /// Read the index stored next to this log, if there is one and it matches the log.
+    pub fn read_index(&self) -> buck2_error::Result<Option<EventLogIndex>> {
+        if self.encoding.mode != LogMode::Protobuf
+            || !matches!(self.encoding.compression, Compression::Zstd)
+        {
+            return Ok(None);
+        }
+        let Some(data) = fs_util::read_if_exists(index_path(&self.path)?)? else {
+            return Ok(None);
+        };
+        let index = EventLogIndex::decode(&data)?;
+        // The index is written once the log is complete. Ignore it if the log was since
+        // truncated or replaced.
+        let len = fs_util::metadata(&self.path)?.len();
+        if index
+            .chunks()
+            .last()
+            .is_some_and(|chunk| chunk.offset >= len)
+        {
+            return Ok(None);
+        }
+        Ok(Some(index))
+    }
+
+    /// Read the invocation, then only the events in the given ranges of chunks of the index.
+    ///
+    /// Ranges must be sorted and not overlap.
+    pub async fn unpack_stream_chunks(
+        &self,
+        index: &EventLogIndex,
+        chunks: Vec<Range<usize>>,
+    ) -> buck2_error::Result<(
+        Invocation,
+        BoxStream<'static, buck2_error::Result<StreamValue>>,
+    )> {
+        assert_eq!(self.encoding.mode, LogMode::Protobuf);
+
+        // Only decodes the first frame, which holds the invocation.
+        let (invocation, _events) = self.unpack_stream_protobuf(None).await?;
+
+        let mut readers = Vec::with_capacity(chunks.len());
+        for range in chunks {
+            if range.is_empty() {
+                continue;
+            }
+            let start = index.chunks()[range.start].offset;
+            let end = index.chunk_end(range.end - 1);
+            readers.push(self.open_chunks(start, end).await?);
+        }
+
+        let events = futures::stream::iter(readers)
+            .map(|reader| decode_protobuf_values(FramedRead::new(reader, ProtobufSplitter)))
+            .flatten();
+
+        Ok((invocation, events.boxed()))
+    }
+
+    /// Read the invocation, then the events from the given chunk of the index onwards.
+    pub async fn unpack_stream_from_chunk(
+        &self,
+        index: &EventLogIndex,
+        chunk: usize,
+    ) -> buck2_error::Result<(
+        Invocation,
+        BoxStream<'static, buck2_error::Result<StreamValue>>,
+    )> {
+        self.unpack_stream_chunks(index, vec![chunk..index.chunks().len()])
+            .await
+    }
+
     async fn open<'a>(
         &self,
         stats: Option<&'a ReaderStats>,
@@ -315,15 +376,36 @@ impl EventLogPathBuf {
                 GzipDecoder::new(BufReader::new(file)),
                 decompressed_bytes,
             )) as EventLogReader,
-            Compression::Zstd => Box::new(CountingReader::new(
-                ZstdDecoder::new(BufReader::new(file)),
-                decompressed_bytes,
-            )) as EventLogReader,
+            Compression::Zstd => {
+                let mut decoder = ZstdDecoder::new(BufReader::new(file));
+                // Indexed logs are made of many zstd frames.
+                decoder.multiple_members(true);
+                Box::new(CountingReader::new(decoder, decompressed_bytes)) as EventLogReader
+            }
         };
 
         Ok(file)
     }
 
+    /// Open the zstd frames between the `start` and `end` offsets of the compressed log.
+    async fn open_chunks(
+        &self,
+        start: u64,
+        end: Option<u64>,
+    ) -> buck2_error::Result<EventLogReader<'static>> {
+        let mut file = async_fs_util::open(&self.path).await?;
+        file.seek(SeekFrom::Start(start))
+            .await
+            .with_buck_error_context(|| format!("Error seeking in {}", self.path.display()))?;
+        let file = match end {
+            Some(end) => Box::new(file.take(end - start)) as EventLogReader,
+            None => Box::new(file) as EventLogReader,
+        };
+        let mut decoder = ZstdDecoder::new(BufReader::new(file));
+        decoder.multiple_members(true);
+        Ok(Box::new(decoder))
+    }
+
     pub async fn get_summary(&self) -> buck2_error::Result<EventLogSummary> {
         let (invocation, events) = self.unpack_stream().await?;
         let buck_event: BuckEvent = events
@@ -350,6 +432,28 @@ impl EventLogPathBuf {
     }
 }
 
+fn decode_protobuf_values<'a>(
+    stream: FramedRead<EventLogReader<'a>, ProtobufSplitter>,
+) -> BoxStream<'a, buck2_error::Result<StreamValue>> {
+    stream
+        .and_then(|data| async move {
+            let val = buck2_cli_proto::CommandProgress::decode_length_delimited(data)
+                .buck_error_context("Invalid CommandProgress")?;
+            match val.progress {
+                Some(command_progress::Progress::Event(event)) => Ok(StreamValue::Event(event)),
+                Some(command_progress::Progress::Result(result)) => Ok(StreamValue::Result(result)),
+                Some(command_progress::Progress::PartialResult(result)) => {
+                    Ok(StreamValue::PartialResult(result))
+                }
+                None => Err(buck2_error::buck2_error!(
+                    buck2_error::ErrorTag::InvalidEvent,
+                    "Event type not recognized"
+                )),
+            }
+        })
+        .boxed()
+}
+
 #[cfg(test)]
 mod tests {
     use buck2_data::CommandStart;
@@ -30,6 +30,7 @@ use tokio::fs::OpenOptions;
 use crate::FutureChildOutput;
 use crate::file_names::get_logfile_name;
 use crate::file_names::remove_old_logs;
+use crate::index::IndexEntry;
 use crate::read::EventLogPathBuf;
 use crate::should_block_on_log_upload;
 use crate::should_upload_log;
@@ -37,7 +38,6 @@ use crate::user_event_types::try_get_user_event;
 use crate::utils::Encoding;
 use crate::utils::EventLogErrors;
 use crate::utils::Invocation;
-use crate::wait_for_child_and_log;
 use crate::writer::EventLogType;
 use crate::writer::NamedEventLogWriter;
 use crate::writer::SerializeForLog;
@@ -238,15 +238,7 @@ impl WriteEventLog {
                 writer.shutdown().await
             }
 
-            // NOTE: We call `into_iter()` here and that implicitly drops the `writer.file`, which
-            // is necessary for an actual `close` call to be send to the child FD (it is a bit of
-            // an odd behavior in Tokio that `shutdown` doesn't do that).
-            let futs = writers
-                .into_iter()
-                .filter_map(|w| w.child())
-                .map(|proc| wait_for_child_and_log(proc, "Event Log"));
-
-            futures::future::join_all(futs).await;
+            futures::future::join_all(writers.into_iter().map(|w| w.finish())).await;
         }
     }
 }
@@ -298,13 +290,7 @@ async fn start_persist_event_log_subprocess(
         None
     };
 
-    Ok(NamedEventLogWriter::new(
-        path,
-        pipe,
-        bytes_written,
-        EventLogType::System,
-        process_to_wait_for,
-    ))
+    NamedEventLogWriter::new_indexed(path, pipe, bytes_written, process_to_wait_for)
 }
 
 async fn open_event_log_for_writing(
@@ -405,6 +391,10 @@ impl SerializeForLog for Invocation {
             .buck_error_context("Failed to serialize event")?;
         Ok(true)
     }
+
+    fn index_entry(&self) -> IndexEntry<'_> {
+        IndexEntry::Header
+    }
 }
 
 #[derive(Serialize)]
@@ -446,6 +436,13 @@ impl SerializeForLog for StreamValueForWrite<'_> {
 
         Ok(false)
     }
+
+    fn index_entry(&self) -> IndexEntry<'_> {
+        match self {
+            Self::Event(event) => IndexEntry::Value(Some(*event)),
+            Self::Result(_) => IndexEntry::Value(None),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -629,6 +626,54 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_indexed_log() -> buck2_error::Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let log = EventLogPathBuf {
+            path: AbsPathBuf::try_from(tmp_dir.path().join("log.pb.zst")).unwrap(),
+            encoding: Encoding::PROTO_ZSTD,
+        };
+        let file = tokio::fs::File::create(&log.path).await?;
+
+        let mut write_event_log = WriteEventLog::new_test(log.clone()).await?;
+        write_event_log.state = LogWriterState::Opened {
+            writers: vec![NamedEventLogWriter::new_indexed(
+                log.clone(),
+                file,
+                None,
+                None,
+            )?],
+        };
+
+        let event = make_event();
+        write_event_log.log_invocation(event.trace_id()?).await?;
+        write_event_log
+            .write_ln(&[StreamValueForWrite::Event(event.event())])
+            .await?;
+        write_event_log.exit().await;
+
+        let index = log.read_index()?.expect("index was written");
+        assert_eq!(index.chunks().len(), 1);
+        assert_eq!(index.chunks()[0].first_value, 0);
+
+        // The invocation is in its own frame, the whole log is still readable.
+        let (invocation, events) = log.unpack_stream().await?;
+        assert_eq!(invocation.trace_id, event.trace_id()?);
+        assert_eq!(events.try_collect::<Vec<_>>().await?.len(), 1);
+
+        let (invocation, events) = log.unpack_stream_from_chunk(&index, 0).await?;
+        assert_eq!(invocation.trace_id, event.trace_id()?);
+        let events = events.try_collect::<Vec<_>>().await?;
+        match events.as_slice() {
+            [StreamValue::Event(e)] => {
+                assert_eq!(BuckEvent::try_from(e.clone())?.data(), event.data())
+            }
+            _ => panic!("expected one event"),
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn test_stream_value_serialize_to_protobuf_length_delimited() {
         let event = make_event();
@@ -24,9 +24,12 @@ use tokio::io::AsyncWrite;
 use tokio::io::AsyncWriteExt;
 
 use crate::FutureChildOutput;
+use crate::index::EventLogIndexWriter;
+use crate::index::IndexEntry;
 use crate::read::EventLogPathBuf;
 use crate::utils::Compression;
 use crate::utils::LogMode;
+use crate::wait_for_child_and_log;
 
 type EventLogWriter = Box<dyn AsyncWrite + Send + Sync + Unpin + 'static>;
 
@@ -88,6 +91,8 @@ pub(crate) enum EventLogType {
 pub(crate) struct NamedEventLogWriter {
     path: EventLogPathBuf,
     file: EventLogWriter,
+    /// If set, compression is done here rather than by `file`, so that the log can be indexed.
+    index: Option<EventLogIndexWriter>,
     event_log_type: EventLogType,
     /// If this writing is done by a subprocess, that process's output, assuming we intend to wait
     /// for it to exit.
@@ -118,12 +123,36 @@ impl NamedEventLogWriter {
         Self {
             path,
             file,
+            index: None,
             event_log_type,
             process_to_wait_for,
         }
     }
 
+    /// Write a `PROTO_ZSTD` system log along with its index.
+    pub(crate) fn new_indexed(
+        path: EventLogPathBuf,
+        file: impl AsyncWrite + std::marker::Send + std::marker::Unpin + std::marker::Sync + 'static,
+        bytes_written: Option<Arc<AtomicU64>>,
+        process_to_wait_for: Option<FutureChildOutput>,
+    ) -> buck2_error::Result<Self> {
+        assert_eq!(path.encoding.mode, LogMode::Protobuf);
+        assert!(matches!(path.encoding.compression, Compression::Zstd));
+        Ok(Self {
+            path,
+            file: Box::new(CountingReader::new(file, bytes_written)),
+            index: Some(EventLogIndexWriter::new()?),
+            event_log_type: EventLogType::System,
+            process_to_wait_for,
+        })
+    }
+
     pub(crate) async fn flush(&mut self) -> buck2_error::Result<()> {
+        if let Some(index) = &mut self.index {
+            index.flush()?;
+            let compressed = index.take_compressed();
+            self.write_all(&compressed).await?;
+        }
         match self.file.flush().await {
             Ok(_) => Ok(()),
             Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {
@@ -139,13 +168,42 @@ impl NamedEventLogWriter {
     }
 
     pub(crate) async fn shutdown(&mut self) {
+        if let Some(index) = &mut self.index {
+            if let Err(e) = index.finish() {
+                tracing::warn!("Failed to finish log file at `{}`: {:#}", self.path.path, e);
+            }
+            let compressed = index.take_compressed();
+            if let Err(e) = self.write_all(&compressed).await {
+                tracing::warn!("Failed to finish log file at `{}`: {:#}", self.path.path, e);
+            }
+        }
         if let Err(e) = self.file.shutdown().await {
             tracing::warn!("Failed to flush log file at `{}`: {:#}", self.path.path, e);
         }
     }
 
-    pub(crate) fn child(mut self) -> Option<FutureChildOutput> {
-        self.process_to_wait_for.take()
+    /// Wait for the subprocess persisting the log, if any, then write the index. The index must
+    /// only appear once the log it points into is complete, and until the subprocess exits it
+    /// may still be writing the log.
+    pub(crate) async fn finish(self) {
+        let Self {
+            path,
+            file,
+            index,
+            process_to_wait_for,
+            ..
+        } = self;
+        // Dropping the file is necessary for an actual `close` call to be sent to the child FD
+        // (it is a bit of an odd behavior in Tokio that `shutdown` doesn't do that).
+        drop(file);
+        if let Some(child) = process_to_wait_for {
+            wait_for_child_and_log(child, "Event Log").await;
+        }
+        if let Some(index) = index {
+            if let Err(e) = index.write_index(&path.path).await {
+                tracing::warn!("{:#}", e);
+            }
+        }
     }
 
     fn serialize_event<'b, T>(&self, mut buf: &mut Vec<u8>, event: &T) -> buck2_error::Result<()>
@@ -193,9 +251,19 @@ impl NamedEventLogWriter {
         I: IntoIterator<Item = &'b T> + Clone + 'b,
     {
         for event in events.clone() {
+            let start = buf.len();
             self.serialize_event(&mut buf, event)?;
+            if let Some(index) = &mut self.index {
+                index.write(event.index_entry(), &buf[start..])?;
+            }
+        }
+        match &mut self.index {
+            Some(index) => {
+                let compressed = index.take_compressed();
+                self.write_all(&compressed).await?;
+            }
+            None => self.write_all(&buf).await?,
         }
-        self.write_all(&buf).await?;
         Ok(())
     }
 }
@@ -204,4 +272,5 @@ pub(crate) trait SerializeForLog {
     fn serialize_to_json(&self, buf: &mut Vec<u8>) -> buck2_error::Result<()>;
     fn serialize_to_protobuf_length_delimited(&self, buf: &mut Vec<u8>) -> buck2_error::Result<()>;
     fn maybe_serialize_user_event(&self, buf: &mut Vec<u8>) -> buck2_error::Result<bool>;
+    fn index_entry(&self) -> IndexEntry<'_>;
 }
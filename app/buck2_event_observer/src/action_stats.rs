@@ -137,6 +137,27 @@ pub fn was_local_action(action: &buck2_data::ActionExecutionEnd) -> bool {
         || action.execution_kind() == buck2_data::ActionExecutionKind::LocalWorker
 }
 
+/// Whether an action's outputs were served from an action cache, local or remote, instead of
+/// running it. Local dep file hits are not cache hits: the action was skipped because its
+/// previous outputs are still up to date.
+pub fn is_cache_hit(kind: buck2_data::ActionExecutionKind) -> bool {
+    use buck2_data::ActionExecutionKind;
+
+    match kind {
+        ActionExecutionKind::ActionCache
+        | ActionExecutionKind::RemoteDepFileCache
+        | ActionExecutionKind::LocalActionCache => true,
+        ActionExecutionKind::NotSet
+        | ActionExecutionKind::Local
+        | ActionExecutionKind::Remote
+        | ActionExecutionKind::Simple
+        | ActionExecutionKind::Deferred
+        | ActionExecutionKind::LocalDepFile
+        | ActionExecutionKind::LocalWorker
+        | ActionExecutionKind::RemoteWorker => false,
+    }
+}
+
 pub fn scheduling_mode(action: &buck2_data::ActionExecutionEnd) -> Option<SchedulingMode> {
     action
         .scheduling_mode
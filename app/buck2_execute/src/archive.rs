@@ -0,0 +1,616 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! Reading and writing zip and tar archives in-process.
+//!
+//! Archives are written deterministically: entries are sorted, timestamps and ownership are
+//! zeroed, and permissions are normalized to `0o644`/`0o755`.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+
+use buck2_common::io::fs::is_executable;
+use buck2_core::cells::external::ArchiveFormat;
+use buck2_error::BuckErrorContext;
+use buck2_error::ErrorTag;
+use buck2_error::buck2_error;
+use buck2_fs::fs_util;
+use buck2_fs::paths::abs_norm_path::AbsNormPath;
+use buck2_fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_fs::paths::forward_rel_path::ForwardRelativePath;
+use buck2_fs::paths::forward_rel_path::ForwardRelativePathBuf;
+
+#[derive(buck2_error::Error, Debug)]
+#[buck2(tag = Input)]
+enum ArchiveError {
+    #[error("Archive entry `{0}` is not a valid relative path")]
+    InvalidEntryPath(String),
+    #[error("Archive entry `{0}` is a symlink without a target")]
+    SymlinkWithoutTarget(String),
+    #[error("Archive entry `{0}` is a symlink to `{1}`, which is outside of the archive")]
+    EscapingSymlink(ForwardRelativePathBuf, String),
+    #[error("Archive entry `{0}` is written through the symlink `{1}`")]
+    ThroughSymlink(ForwardRelativePathBuf, ForwardRelativePathBuf),
+    #[error("Archive entry `{0}` is a hard link to `{1}`, which is not a regular file")]
+    LinkToNonFile(ForwardRelativePathBuf, ForwardRelativePathBuf),
+    #[error("Both `{0}` and `{1}` would be written to `{2}` in the archive")]
+    ConflictingEntries(AbsNormPathBuf, AbsNormPathBuf, ForwardRelativePathBuf),
+    #[error("`{0}` is a symlink, which cannot be added to an archive")]
+    Symlink(AbsNormPathBuf),
+}
+
+fn zip_error(e: zip::result::ZipError) -> buck2_error::Error {
+    buck2_error!(ErrorTag::Input, "{}", e)
+}
+
+/// Extracts `archive` into `dest`, which must exist.
+///
+/// With a `strip_prefix`, only entries under that directory are extracted, relative to it.
+/// Returns the number of entries extracted.
+pub fn extract(
+    archive: &AbsNormPath,
+    format: ArchiveFormat,
+    dest: &AbsNormPath,
+    strip_prefix: Option<&str>,
+) -> buck2_error::Result<usize> {
+    let mut extractor = Extractor {
+        dest,
+        strip_prefix,
+        extracted: 0,
+    };
+    match format {
+        ArchiveFormat::Tar => extractor.extract_tar(fs_util::open_file(archive)?)?,
+        ArchiveFormat::TarGz => {
+            extractor.extract_tar(flate2::read::GzDecoder::new(fs_util::open_file(archive)?))?
+        }
+        ArchiveFormat::TarZst => extractor.extract_tar(zstd::stream::read::Decoder::new(
+            fs_util::open_file(archive)?,
+        )?)?,
+        ArchiveFormat::Zip => {
+            // Zip archives need seeking, which `FileReadGuard` does not support.
+            let file = File::open(archive.as_path())
+                .with_buck_error_context(|| format!("Error opening `{}`", archive.display()))?;
+            extractor.extract_zip(file)?
+        }
+    }
+    Ok(extractor.extracted)
+}
+
+struct Extractor<'a> {
+    dest: &'a AbsNormPath,
+    strip_prefix: Option<&'a str>,
+    /// Number of entries written, to detect a `strip_prefix` that matches nothing.
+    extracted: usize,
+}
+
+impl Extractor<'_> {
+    /// Maps a path in the archive to a path under `dest`, or `None` if the entry is skipped.
+    fn destination(&self, entry: &Path) -> buck2_error::Result<Option<ForwardRelativePathBuf>> {
+        let name = entry
+            .to_str()
+            .ok_or_else(|| ArchiveError::InvalidEntryPath(entry.display().to_string()))?;
+        let name = name.trim_start_matches("./").trim_end_matches('/');
+        let name = match self.strip_prefix {
+            None => name,
+            Some(prefix) => match name.strip_prefix(prefix) {
+                Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+                    rest.trim_start_matches('/')
+                }
+                _ => return Ok(None),
+            },
+        };
+        if name.is_empty() {
+            return Ok(None);
+        }
+        let path = ForwardRelativePath::new(name)
+            .map_err(|_| ArchiveError::InvalidEntryPath(name.to_owned()))?;
+        Ok(Some(path.to_buf()))
+    }
+
+    /// Fails if any directory containing `path` is a symlink, since writing through it could
+    /// escape `dest`.
+    fn check_parents(&self, path: &ForwardRelativePath) -> buck2_error::Result<()> {
+        let mut parent = path.parent();
+        while let Some(dir) = parent.filter(|d| !d.is_empty()) {
+            if fs_util::symlink_metadata_if_exists(self.dest.join(dir))?
+                .is_some_and(|m| m.file_type().is_symlink())
+            {
+                return Err(ArchiveError::ThroughSymlink(path.to_buf(), dir.to_buf()).into());
+            }
+            parent = dir.parent();
+        }
+        Ok(())
+    }
+
+    /// Creates the directories containing `path`, and removes a symlink previously extracted to
+    /// `path` so that the entry replaces it rather than writing through it.
+    fn prepare(&self, path: &ForwardRelativePath) -> buck2_error::Result<AbsNormPathBuf> {
+        self.check_parents(path)?;
+        let out = self.dest.join(path);
+        if let Some(parent) = out.parent() {
+            fs_util::create_dir_all(parent)?;
+        }
+        if fs_util::symlink_metadata_if_exists(&out)?.is_some_and(|m| m.file_type().is_symlink()) {
+            fs_util::remove_file(&out)?;
+        }
+        Ok(out)
+    }
+
+    fn write_file(
+        &mut self,
+        path: &ForwardRelativePath,
+        contents: &mut impl Read,
+        executable: bool,
+    ) -> buck2_error::Result<()> {
+        let out = self.prepare(path)?;
+        let mut file = fs_util::create_file(&out)?;
+        io::copy(contents, &mut file)
+            .with_buck_error_context(|| format!("Error extracting `{path}`"))?;
+        drop(file);
+        if executable {
+            fs_util::set_executable(&out, true)?;
+        }
+        self.extracted += 1;
+        Ok(())
+    }
+
+    fn extract_tar(&mut self, reader: impl Read) -> buck2_error::Result<()> {
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let Some(path) = self.destination(&entry.path()?)? else {
+                continue;
+            };
+            match entry.header().entry_type() {
+                tar::EntryType::Directory => {
+                    fs_util::create_dir_all(self.prepare(&path)?)?;
+                    self.extracted += 1;
+                }
+                tar::EntryType::Regular | tar::EntryType::Continuous => {
+                    let executable = entry.header().mode()? & 0o111 != 0;
+                    self.write_file(&path, &mut entry, executable)?;
+                }
+                tar::EntryType::Symlink => {
+                    let target = entry
+                        .link_name()?
+                        .ok_or_else(|| ArchiveError::SymlinkWithoutTarget(path.to_string()))?;
+                    // Symlinks may only point to other entries, so that nothing extracted later
+                    // can be written through them to outside of `dest`.
+                    let escaping = || {
+                        ArchiveError::EscapingSymlink(path.to_buf(), target.display().to_string())
+                    };
+                    if target.is_absolute() {
+                        return Err(escaping().into());
+                    }
+                    path.parent()
+                        .unwrap_or(ForwardRelativePath::empty())
+                        .join_system_normalized(&target)
+                        .map_err(|_| escaping())?;
+                    fs_util::symlink(&*target, self.prepare(&path)?)?;
+                    self.extracted += 1;
+                }
+                tar::EntryType::Link => {
+                    // Hard links refer to an earlier entry by its path in the archive.
+                    let target = entry
+                        .link_name()?
+                        .ok_or_else(|| ArchiveError::SymlinkWithoutTarget(path.to_string()))?;
+                    if let Some(target) = self.destination(&target)? {
+                        // The target must have been extracted as a regular file, rather than a
+                        // symlink that could point anywhere.
+                        self.check_parents(&target)?;
+                        let src = self.dest.join(&target);
+                        if !fs_util::symlink_metadata(&src)?.is_file() {
+                            return Err(ArchiveError::LinkToNonFile(path, target).into());
+                        }
+                        fs_util::copy(&src, self.prepare(&path)?)?;
+                        self.extracted += 1;
+                    }
+                }
+                // Metadata entries (e.g. pax headers) are handled by the `tar` crate, and
+                // devices or fifos have no business being in a source archive.
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn extract_zip(&mut self, file: File) -> buck2_error::Result<()> {
+        let mut archive = zip::ZipArchive::new(file).map_err(zip_error)?;
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).map_err(zip_error)?;
+            let Some(path) = self.destination(Path::new(entry.name()))? else {
+                continue;
+            };
+            if entry.is_dir() {
+                fs_util::create_dir_all(self.prepare(&path)?)?;
+                self.extracted += 1;
+            } else {
+                let executable = entry.unix_mode().is_some_and(|mode| mode & 0o111 != 0);
+                self.write_file(&path, &mut entry, executable)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+enum ArchiveEntry {
+    Dir,
+    File {
+        src: AbsNormPathBuf,
+        executable: bool,
+    },
+}
+
+/// The entries of an archive to create, keyed (and so sorted) by their path in the archive.
+#[derive(Default)]
+pub struct ArchiveContents {
+    entries: BTreeMap<ForwardRelativePathBuf, ArchiveEntry>,
+}
+
+impl ArchiveContents {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the file or directory at `src` as `dest` in the archive, along with the directories
+    /// containing it. Directories are added recursively. Symlinks are rejected, so the archive
+    /// only ever contains regular files and directories.
+    pub fn add(
+        &mut self,
+        src: &AbsNormPath,
+        dest: &ForwardRelativePath,
+    ) -> buck2_error::Result<()> {
+        let mut parent = dest.parent();
+        while let Some(dir) = parent.filter(|d| !d.is_empty()) {
+            self.entries
+                .entry(dir.to_buf())
+                .or_insert(ArchiveEntry::Dir);
+            parent = dir.parent();
+        }
+        self.add_recursive(src, dest)
+    }
+
+    fn add_recursive(
+        &mut self,
+        src: &AbsNormPath,
+        dest: &ForwardRelativePath,
+    ) -> buck2_error::Result<()> {
+        let metadata = fs_util::symlink_metadata(src)?;
+        if metadata.file_type().is_symlink() {
+            return Err(ArchiveError::Symlink(src.to_buf()).into());
+        }
+        if metadata.is_dir() {
+            if !dest.is_empty() {
+                self.entries.insert(dest.to_buf(), ArchiveEntry::Dir);
+            }
+            for child in fs_util::read_dir(src)? {
+                let child = child?;
+                let name = child.file_name();
+                let name = name
+                    .to_str()
+                    .ok_or_else(|| ArchiveError::InvalidEntryPath(child.path().to_string()))?;
+                self.add_recursive(&child.path(), &dest.join(ForwardRelativePath::new(name)?))?;
+            }
+            return Ok(());
+        }
+        let entry = ArchiveEntry::File {
+            src: src.to_buf(),
+            executable: is_executable(&metadata),
+        };
+        match self.entries.insert(dest.to_buf(), entry) {
+            Some(ArchiveEntry::File { src: previous, .. }) => {
+                Err(ArchiveError::ConflictingEntries(previous, src.to_buf(), dest.to_buf()).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Writes the archive to `out`, which must not exist.
+    pub fn write(&self, out: &AbsNormPath, format: ArchiveFormat) -> buck2_error::Result<()> {
+        match format {
+            ArchiveFormat::Tar => {
+                self.write_tar(fs_util::create_file(out)?)?;
+            }
+            ArchiveFormat::TarGz => {
+                // `GzEncoder` writes a zero mtime and no file name in the header.
+                let encoder = flate2::write::GzEncoder::new(
+                    fs_util::create_file(out)?,
+                    flate2::Compression::default(),
+                );
+                self.write_tar(encoder)?.finish()?;
+            }
+            ArchiveFormat::TarZst => {
+                let encoder = zstd::stream::write::Encoder::new(fs_util::create_file(out)?, 0)?;
+                self.write_tar(encoder)?.finish()?;
+            }
+            ArchiveFormat::Zip => {
+                // Zip archives need seeking, which `FileWriteGuard` does not support.
+                let file = File::create(out.as_path())
+                    .with_buck_error_context(|| format!("Error creating `{}`", out.display()))?;
+                self.write_zip(file)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_tar<W: Write>(&self, writer: W) -> buck2_error::Result<W> {
+        let mut builder = tar::Builder::new(writer);
+        for (path, entry) in &self.entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_mtime(0);
+            header.set_uid(0);
+            header.set_gid(0);
+            match entry {
+                ArchiveEntry::Dir => {
+                    header.set_entry_type(tar::EntryType::Directory);
+                    header.set_mode(0o755);
+                    header.set_size(0);
+                    builder.append_data(&mut header, path.as_str(), io::empty())?;
+                }
+                ArchiveEntry::File { src, executable } => {
+                    let file = fs_util::open_file(src)?;
+                    header.set_entry_type(tar::EntryType::Regular);
+                    header.set_mode(if *executable { 0o755 } else { 0o644 });
+                    header.set_size(fs_util::metadata(src)?.len());
+                    builder.append_data(&mut header, path.as_str(), file)?;
+                }
+            }
+        }
+        Ok(builder.into_inner()?)
+    }
+
+    fn write_zip(&self, file: File) -> buck2_error::Result<()> {
+        let mut writer = zip::ZipWriter::new(file);
+        let options = zip::write::FileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            // The default is the earliest time zip can represent, 1980-01-01.
+            .last_modified_time(zip::DateTime::default());
+        for (path, entry) in &self.entries {
+            match entry {
+                ArchiveEntry::Dir => {
+                    writer
+                        .add_directory(path.as_str(), options.unix_permissions(0o755))
+                        .map_err(zip_error)?;
+                }
+                ArchiveEntry::File { src, executable } => {
+                    let mode = if *executable { 0o755 } else { 0o644 };
+                    writer
+                        .start_file(path.as_str(), options.unix_permissions(mode))
+                        .map_err(zip_error)?;
+                    io::copy(&mut fs_util::open_file(src)?, &mut writer)
+                        .with_buck_error_context(|| format!("Error archiving `{path}`"))?;
+                }
+            }
+        }
+        writer.finish().map_err(zip_error)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn destination(strip_prefix: Option<&str>, entry: &str) -> buck2_error::Result<Option<String>> {
+        let dest =
+            AbsNormPathBuf::new(if cfg!(windows) { "C:\\dest" } else { "/dest" }.into()).unwrap();
+        let extractor = Extractor {
+            dest: &dest,
+            strip_prefix,
+            extracted: 0,
+        };
+        Ok(extractor
+            .destination(Path::new(entry))?
+            .map(|p| p.to_string()))
+    }
+
+    #[test]
+    fn test_destination() -> buck2_error::Result<()> {
+        assert_eq!(Some("src/a.c".to_owned()), destination(None, "./src/a.c")?);
+        assert_eq!(
+            Some("src/a.c".to_owned()),
+            destination(Some("foo-1.0"), "foo-1.0/src/a.c")?
+        );
+        assert_eq!(None, destination(Some("foo-1.0"), "foo-1.0/")?);
+        assert_eq!(None, destination(Some("foo-1.0"), "foo-1.01/a.c")?);
+        assert_eq!(None, destination(Some("foo-1.0"), "README")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_destination_rejects_escaping_paths() {
+        assert!(destination(None, "../evil").is_err());
+        assert!(destination(None, "a/../../evil").is_err());
+        assert!(destination(Some("foo"), "foo/../evil").is_err());
+    }
+
+    /// Writes a tar archive with the given `(path, type, link target)` entries to `out`.
+    fn write_tar(
+        out: &AbsNormPath,
+        entries: &[(&str, tar::EntryType, &str)],
+    ) -> buck2_error::Result<()> {
+        let mut builder = tar::Builder::new(fs_util::create_file(out)?);
+        for (path, entry_type, target) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(*entry_type);
+            header.set_mode(0o644);
+            header.set_size(0);
+            if !target.is_empty() {
+                header.set_link_name(target)?;
+            }
+            builder.append_data(&mut header, path, io::empty())?;
+        }
+        builder.into_inner()?;
+        Ok(())
+    }
+
+    /// Extracts a tar archive with the given entries into a fresh directory.
+    fn extract_tar(entries: &[(&str, tar::EntryType, &str)]) -> buck2_error::Result<usize> {
+        let tempdir = tempfile::tempdir()?;
+        let root = AbsNormPathBuf::new(tempdir.path().to_owned())?;
+        let archive = root.join(ForwardRelativePath::new("a.tar")?);
+        write_tar(&archive, entries)?;
+        let dest = root.join(ForwardRelativePath::new("dest")?);
+        fs_util::create_dir_all(&dest)?;
+        extract(&archive, ArchiveFormat::Tar, &dest, None)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_extract_rejects_escaping_symlinks() {
+        use tar::EntryType::*;
+
+        assert!(extract_tar(&[("dir/link", Symlink, "../file")]).is_ok());
+        for target in ["../evil", "a/../../evil", "/etc"] {
+            let err = extract_tar(&[("link", Symlink, target)]).unwrap_err();
+            assert!(err.to_string().contains("outside of the archive"), "{err}");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_extract_rejects_writing_through_symlinks() {
+        use tar::EntryType::*;
+
+        let err = extract_tar(&[
+            ("dir", Directory, ""),
+            ("link", Symlink, "dir"),
+            ("link/file", Regular, ""),
+        ])
+        .unwrap_err();
+        assert!(err.to_string().contains("through the symlink"), "{err}");
+
+        let err = extract_tar(&[
+            ("file", Regular, ""),
+            ("link", Symlink, "file"),
+            ("hard", Link, "link"),
+        ])
+        .unwrap_err();
+        assert!(err.to_string().contains("not a regular file"), "{err}");
+
+        // A later entry replaces a symlink rather than writing through it.
+        assert_eq!(
+            2,
+            extract_tar(&[("link", Symlink, "file"), ("link", Regular, "")]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_extract_strip_prefix() -> buck2_error::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let root = AbsNormPathBuf::new(tempdir.path().to_owned())?;
+        let src = root.join(ForwardRelativePath::new("src")?);
+        fs_util::create_dir_all(&src)?;
+        fs_util::write(src.join(ForwardRelativePath::new("a.txt")?), "a")?;
+        let archive = root.join(ForwardRelativePath::new("out.tar")?);
+        let mut contents = ArchiveContents::new();
+        contents.add(&src, ForwardRelativePath::new("foo-1.0")?)?;
+        contents.write(&archive, ArchiveFormat::Tar)?;
+
+        let dest = root.join(ForwardRelativePath::new("stripped")?);
+        fs_util::create_dir_all(&dest)?;
+        assert_eq!(
+            1,
+            extract(&archive, ArchiveFormat::Tar, &dest, Some("foo-1.0"))?
+        );
+        assert_eq!(
+            "a",
+            fs_util::read_to_string(dest.join(ForwardRelativePath::new("a.txt")?))?
+        );
+
+        let dest = root.join(ForwardRelativePath::new("unmatched")?);
+        fs_util::create_dir_all(&dest)?;
+        assert_eq!(
+            0,
+            extract(&archive, ArchiveFormat::Tar, &dest, Some("foo"))?
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_archive_is_independent_of_metadata() -> buck2_error::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tempdir = tempfile::tempdir()?;
+        let root = AbsNormPathBuf::new(tempdir.path().to_owned())?;
+        let mut archives = Vec::new();
+        for (name, mode, files) in [("first", 0o600, ["a", "b"]), ("second", 0o664, ["b", "a"])] {
+            let src = root.join(ForwardRelativePath::new(name)?);
+            fs_util::create_dir_all(&src)?;
+            for file in files {
+                let path = src.join(ForwardRelativePath::new(file)?);
+                fs_util::write(&path, file)?;
+                fs_util::set_permissions(&path, std::fs::Permissions::from_mode(mode))?;
+            }
+            let out = root.join(ForwardRelativePath::new(&format!("{name}.tar"))?);
+            let mut contents = ArchiveContents::new();
+            contents.add(&src, ForwardRelativePath::new("pkg")?)?;
+            contents.write(&out, ArchiveFormat::Tar)?;
+            archives.push(fs_util::read(&out)?);
+        }
+        assert_eq!(archives[0], archives[1]);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_archive_rejects_symlinks() -> buck2_error::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let root = AbsNormPathBuf::new(tempdir.path().to_owned())?;
+        let src = root.join(ForwardRelativePath::new("src")?);
+        fs_util::create_dir_all(&src)?;
+        fs_util::symlink("/etc/passwd", src.join(ForwardRelativePath::new("link")?))?;
+        let err = ArchiveContents::new()
+            .add(&src, ForwardRelativePath::new("pkg")?)
+            .unwrap_err();
+        assert!(err.to_string().contains("is a symlink"), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_archive_roundtrip_is_deterministic() -> buck2_error::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let root = AbsNormPathBuf::new(tempdir.path().to_owned())?;
+        let src = root.join(ForwardRelativePath::new("src")?);
+        fs_util::create_dir_all(src.join(ForwardRelativePath::new("sub")?))?;
+        fs_util::write(src.join(ForwardRelativePath::new("b.txt")?), "b")?;
+        fs_util::write(src.join(ForwardRelativePath::new("sub/a.txt")?), "a")?;
+
+        for (format, name) in [
+            (ArchiveFormat::Tar, "out.tar"),
+            (ArchiveFormat::TarGz, "out.tar.gz"),
+            (ArchiveFormat::TarZst, "out.tar.zst"),
+            (ArchiveFormat::Zip, "out.zip"),
+        ] {
+            let mut contents = ArchiveContents::new();
+            contents.add(&src, ForwardRelativePath::new("pkg/data")?)?;
+            let first = root.join(ForwardRelativePath::new(&format!("1-{name}"))?);
+            let second = root.join(ForwardRelativePath::new(&format!("2-{name}"))?);
+            contents.write(&first, format)?;
+            contents.write(&second, format)?;
+            assert_eq!(fs_util::read(&first)?, fs_util::read(&second)?, "{name}");
+
+            let dest = root.join(ForwardRelativePath::new(&format!("extracted-{name}"))?);
+            fs_util::create_dir_all(&dest)?;
+            extract(&first, format, &dest, Some("pkg"))?;
+            assert_eq!(
+                "a",
+                fs_util::read_to_string(dest.join(ForwardRelativePath::new("data/sub/a.txt")?))?,
+                "{name}"
+            );
+        }
+        Ok(())
+    }
+}
@@ -66,6 +66,28 @@ const ENV_ALLOW_LIST: &[&str] = &[
     "WINDIR",
 ];
 
+/// The variables inherited by commands run with BXL's `ctx.run_local()`. Their output is cached
+/// as long as the command line and inputs don't change, so they only get what is needed to find
+/// and run system tools.
+#[cfg(unix)]
+const RUN_LOCAL_ENV_ALLOW_LIST: &[&str] = &["PATH", "HOME", "USER", "LOGNAME", "TMPDIR"];
+
+#[cfg(windows)]
+const RUN_LOCAL_ENV_ALLOW_LIST: &[&str] = &[
+    "COMSPEC",
+    "HOMEDRIVE",
+    "HOMEPATH",
+    "PATH",
+    "PATHEXT",
+    "SYSTEMDRIVE",
+    "SYSTEMROOT",
+    "TEMP",
+    "TMP",
+    "USERNAME",
+    "USERPROFILE",
+    "WINDIR",
+];
+
 #[derive(Copy, Clone, Dupe, Debug)]
 pub struct EnvironmentInheritance {
     clear: bool,
@@ -83,7 +105,21 @@ impl EnvironmentInheritance {
         // size).
         static TEST_CELL: OnceLock<Vec<(&'static str, OsString)>> = OnceLock::new();
 
-        let values = TEST_CELL.get_or_init(|| {
+        Self::allowlist(&TEST_CELL, allowlists)
+    }
+
+    /// The environment of commands run with BXL's `ctx.run_local()`.
+    pub fn run_local_allowlist() -> Self {
+        static RUN_LOCAL_CELL: OnceLock<Vec<(&'static str, OsString)>> = OnceLock::new();
+
+        Self::allowlist(&RUN_LOCAL_CELL, &[RUN_LOCAL_ENV_ALLOW_LIST])
+    }
+
+    fn allowlist(
+        cell: &'static OnceLock<Vec<(&'static str, OsString)>>,
+        allowlists: &[&[&'static str]],
+    ) -> Self {
+        let values = cell.get_or_init(|| {
             let mut ret = Vec::new();
             for list in allowlists.iter() {
                 for key in list.iter() {
@@ -15,6 +15,7 @@
 #![feature(try_trait_v2)]
 #![feature(used_with_arg)]
 
+pub mod archive;
 pub mod artifact;
 pub mod artifact_utils;
 pub mod artifact_value;
@@ -8,10 +8,14 @@
  * above-listed licenses.
  */
 
+use std::sync::Arc;
+
 use buck2_directory::directory::directory::Directory;
 use buck2_directory::directory::directory_iterator::DirectoryIterator;
 use buck2_directory::directory::entry::DirectoryEntry;
 use buck2_directory::directory::walk::unordered_entry_walk;
+use dupe::Dupe;
+use sorted_vector_map::SortedVectorMap;
 
 use crate::artifact_value::ArtifactValue;
 use crate::directory::ActionDirectory;
@@ -51,3 +55,88 @@ where
         OutputCountAndBytes { count, bytes }
     }
 }
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(tag = Input)]
+enum OutputSizeError {
+    #[error(
+        "Invalid output size `{0}`, expected a number of bytes with an optional `K`, `M`, `G` or `T` suffix"
+    )]
+    InvalidSize(String),
+}
+
+/// Parses a size such as `1048576`, `512K`, `10MB` or `2GiB`. Suffixes are powers of 1024.
+pub fn parse_output_size(size: &str) -> buck2_error::Result<u64> {
+    let trimmed = size.trim();
+    let digits = trimmed.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let multiplier: u64 = match trimmed[digits.len()..].to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        "T" | "TB" | "TIB" => 1 << 40,
+        _ => return Err(OutputSizeError::InvalidSize(size.to_owned()).into()),
+    };
+    digits
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| OutputSizeError::InvalidSize(size.to_owned()).into())
+}
+
+/// Limits on the total size of the outputs of a single action, configured in the
+/// `[output_size_budgets]` buckconfig section.
+#[derive(Clone, Dupe, Default, Debug)]
+pub struct OutputSizeBudgets {
+    /// Budgets for actions of a given category.
+    pub by_category: Arc<SortedVectorMap<String, u64>>,
+    /// Budget for actions whose category has none.
+    pub default: Option<u64>,
+    /// Whether exceeding a budget fails the action, rather than only reporting a soft error.
+    pub enforce: bool,
+}
+
+impl OutputSizeBudgets {
+    /// The budget for an action, preferring one declared on the action itself.
+    pub fn budget_for(&self, category: &str, action_budget: Option<u64>) -> Option<u64> {
+        action_budget
+            .or_else(|| self.by_category.get(category).copied())
+            .or(self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output_size() -> buck2_error::Result<()> {
+        assert_eq!(123, parse_output_size("123")?);
+        assert_eq!(512 << 10, parse_output_size("512K")?);
+        assert_eq!(10 << 20, parse_output_size("10MB")?);
+        assert_eq!(2 << 30, parse_output_size("2 GiB")?);
+        assert_eq!(1 << 40, parse_output_size("1t")?);
+        assert!(parse_output_size("").is_err());
+        assert!(parse_output_size("10X").is_err());
+        assert!(parse_output_size("-1").is_err());
+        assert!(parse_output_size("99999999999T").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_budget_for() {
+        let budgets = OutputSizeBudgets {
+            by_category: Arc::new(SortedVectorMap::from_iter([("cxx_link".to_owned(), 10)])),
+            default: Some(100),
+            enforce: false,
+        };
+        assert_eq!(Some(1), budgets.budget_for("cxx_link", Some(1)));
+        assert_eq!(Some(10), budgets.budget_for("cxx_link", None));
+        assert_eq!(Some(100), budgets.budget_for("cxx_compile", None));
+        assert_eq!(
+            None,
+            OutputSizeBudgets::default().budget_for("cxx_link", None)
+        );
+    }
+}
@@ -12,6 +12,7 @@ pub mod action_cache;
 pub mod action_cache_upload_permission_checker;
 pub mod caching;
 pub(crate) mod empty_action_result;
+pub mod external;
 pub mod hybrid;
 pub mod local;
 pub mod re;
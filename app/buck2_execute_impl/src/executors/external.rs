@@ -0,0 +1,166 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use std::ffi::OsString;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use buck2_common::client_utils::get_channel_uds;
+use buck2_common::liveliness_observer::LivelinessObserver;
+use buck2_core::async_once_cell::AsyncOnceCell;
+use buck2_core::content_hash::ContentBasedPathHash;
+use buck2_core::fs::artifact_path_resolver::ArtifactFs;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_error::BuckErrorContext;
+use buck2_execute::execute::action_digest::ActionDigest;
+use buck2_execute::execute::request::CommandExecutionRequest;
+use buck2_execute_local::CommandResult;
+use buck2_execute_local::GatherOutputStatus;
+use buck2_external_executor_proto::ExecuteRequest;
+use buck2_external_executor_proto::execute_request::EnvironmentEntry;
+use buck2_external_executor_proto::external_executor_client::ExternalExecutorClient;
+use dashmap::DashMap;
+use dupe::Dupe;
+use tonic::transport::Channel;
+
+/// Commands can spell out long lists of inputs and outputs, and responses carry the full
+/// stdout/stderr of the command, so don't limit message sizes.
+const MAX_MESSAGE_SIZE_BYTES: usize = usize::MAX;
+
+/// Connections to the external executors used by an invocation, keyed by socket path.
+#[derive(Default)]
+pub struct ExternalExecutorPool {
+    handles: DashMap<String, Arc<ExternalExecutorHandle>>,
+}
+
+impl ExternalExecutorPool {
+    pub fn get(&self, socket: &str) -> Arc<ExternalExecutorHandle> {
+        self.handles
+            .entry(socket.to_owned())
+            .or_insert_with(|| {
+                Arc::new(ExternalExecutorHandle {
+                    socket: socket.to_owned(),
+                    client: AsyncOnceCell::new(),
+                })
+            })
+            .dupe()
+    }
+}
+
+/// A lazily connected client for an external executor, i.e. a process speaking the
+/// `external_executor` protocol over a Unix domain socket.
+///
+/// The `LocalExecutor` still materializes inputs, creates output directories and hashes outputs,
+/// and only delegates running the command itself.
+pub struct ExternalExecutorHandle {
+    socket: String,
+    client: AsyncOnceCell<ExternalExecutorClient<Channel>>,
+}
+
+impl ExternalExecutorHandle {
+    async fn client(&self) -> buck2_error::Result<ExternalExecutorClient<Channel>> {
+        let client = self
+            .client
+            .get_or_try_init(async {
+                let channel = get_channel_uds(Path::new(&self.socket), false)
+                    .await
+                    .with_buck_error_context(|| {
+                        format!("Error connecting to external executor at `{}`", self.socket)
+                    })?;
+                buck2_error::Ok(
+                    ExternalExecutorClient::new(channel)
+                        .max_encoding_message_size(MAX_MESSAGE_SIZE_BYTES)
+                        .max_decoding_message_size(MAX_MESSAGE_SIZE_BYTES),
+                )
+            })
+            .await?;
+        Ok(client.clone())
+    }
+
+    pub async fn exec_cmd(
+        &self,
+        artifact_fs: &ArtifactFs,
+        action_digest: &ActionDigest,
+        request: &CommandExecutionRequest,
+        args: &[String],
+        env: Vec<(OsString, OsString)>,
+        input_paths: &[ProjectRelativePathBuf],
+        liveliness_observer: &dyn LivelinessObserver,
+    ) -> buck2_error::Result<CommandResult> {
+        let output_paths = request
+            .outputs()
+            .map(|output| {
+                Ok(output
+                    .resolve(
+                        artifact_fs,
+                        Some(&ContentBasedPathHash::for_output_artifact()),
+                    )?
+                    .path
+                    .to_string())
+            })
+            .collect::<buck2_error::Result<Vec<_>>>()?;
+
+        let execute_request = ExecuteRequest {
+            action_digest: action_digest.to_string(),
+            argv: args.iter().map(|s| s.as_bytes().to_vec()).collect(),
+            env: env
+                .iter()
+                .map(|(k, v)| EnvironmentEntry {
+                    key: k.as_encoded_bytes().to_vec(),
+                    value: v.as_encoded_bytes().to_vec(),
+                })
+                .collect(),
+            project_root: artifact_fs.fs().root().to_string(),
+            working_directory: request.working_directory().to_string(),
+            input_paths: input_paths.iter().map(|p| p.to_string()).collect(),
+            output_paths,
+            timeout_s: request.timeout().map(|t| t.as_secs()),
+        };
+
+        let mut client = self.client().await?;
+
+        let (status, stdout, stderr) = tokio::select! {
+            response = client.execute(execute_request) => {
+                match response {
+                    Ok(response) => {
+                        let response = response.into_inner();
+                        let status = match response.timed_out_after_s {
+                            Some(timeout) => GatherOutputStatus::TimedOut(Duration::from_secs(timeout)),
+                            None => GatherOutputStatus::Finished {
+                                exit_code: response.exit_code,
+                                execution_stats: None,
+                            },
+                        };
+                        (status, response.stdout, response.stderr)
+                    }
+                    Err(status) => (
+                        GatherOutputStatus::SpawnFailed(format!(
+                            "Error sending command to external executor at `{}`: {}",
+                            self.socket, status,
+                        )),
+                        vec![],
+                        vec![],
+                    ),
+                }
+            }
+            // Dropping the request cancels the RPC, which the executor is expected to treat as a
+            // request to kill the command.
+            _ = liveliness_observer.while_alive() => (GatherOutputStatus::Cancelled, vec![], vec![]),
+        };
+
+        Ok(CommandResult {
+            status,
+            stdout,
+            stderr,
+            cgroup_result: None,
+        })
+    }
+}
@@ -73,7 +73,7 @@ use buck2_execute::materialize::materializer::Materializer;
 use buck2_execute_local::CommandResult;
 use buck2_execute_local::DefaultKillProcess;
 use buck2_execute_local::GatherOutputStatus;
-use buck2_execute_local::decode_command_event_stream;
+use buck2_execute_local::decode_command_event_stream_observing_stderr;
 use buck2_execute_local::maybe_absolutize_exe;
 use buck2_execute_local::spawn_command_and_stream_events;
 use buck2_execute_local::status_decoder::DefaultStatusDecoder;
@@ -107,6 +107,7 @@ use indexmap::IndexMap;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::info;
 
+use crate::executors::external::ExternalExecutorHandle;
 use crate::executors::worker::WorkerHandle;
 use crate::executors::worker::WorkerPool;
 use crate::incremental_actions_helper::get_incremental_path_map;
@@ -144,6 +145,8 @@ pub struct LocalExecutor {
     worker_pool: Option<Arc<WorkerPool>>,
     memory_tracker: Option<MemoryTrackerHandle>,
     daemon_id: DaemonId,
+    /// If set, commands are run by this external executor instead of being spawned locally.
+    external: Option<Arc<ExternalExecutorHandle>>,
 }
 
 impl LocalExecutor {
@@ -172,6 +175,14 @@ impl LocalExecutor {
             worker_pool,
             memory_tracker,
             daemon_id,
+            external: None,
+        }
+    }
+
+    pub fn with_external_executor(self, external: Arc<ExternalExecutorHandle>) -> Self {
+        Self {
+            external: Some(external),
+            ..self
         }
     }
 
@@ -192,6 +203,8 @@ impl LocalExecutor {
     ) -> impl futures::future::Future<Output = buck2_error::Result<CommandResult>> + Send + 'a {
         async move {
             let working_directory = self.root.join_cow(working_directory);
+            let mut stderr_tail = StderrTailReporter::new();
+            let on_stderr = |bytes: &[u8]| stderr_tail.on_stderr(bytes);
 
             match &self.forkserver {
                 #[cfg(unix)]
@@ -208,6 +221,7 @@ impl LocalExecutor {
                         self.knobs.enable_miniperf && !disable_miniperf,
                         cgroup,
                         freeze_rx,
+                        on_stderr,
                     )
                     .await
                 }
@@ -240,7 +254,7 @@ impl LocalExecutor {
                         freeze_rx,
                     )
                     .await?;
-                    decode_command_event_stream(stream).await
+                    decode_command_event_stream_observing_stderr(stream, on_stderr).await
                 }
                 .with_buck_error_context(|| format!("Failed to gather output from command: {exe}")),
             }
@@ -255,6 +269,7 @@ impl LocalExecutor {
         cancellations: &CancellationContext,
         liveliness_observer: impl LivelinessObserver + 'static,
         scratch_path: &ScratchPath,
+        input_paths: &[ProjectRelativePathBuf],
         args: &[String],
         worker: Option<&WorkerHandle>,
         env: &[(&str, StrOrOsStr<'_>)],
@@ -340,6 +355,22 @@ impl LocalExecutor {
                     Ok(worker
                         .exec_cmd(request.args(), env, request.timeout())
                         .await)
+                } else if let Some(external) = &self.external {
+                    let env: Vec<(OsString, OsString)> = env
+                        .into_iter()
+                        .map(|(k, v)| (OsString::from(k), v.to_owned()))
+                        .collect();
+                    external
+                        .exec_cmd(
+                            &self.artifact_fs,
+                            action_digest,
+                            request,
+                            args,
+                            env,
+                            input_paths,
+                            &liveliness_observer,
+                        )
+                        .await
                 } else {
                     self.exec(
                         &args[0],
@@ -378,6 +409,7 @@ impl LocalExecutor {
         cancellations: &CancellationContext,
         liveliness_observer: impl LivelinessObserver + 'static,
         scratch_path: &ScratchPath,
+        input_paths: &[ProjectRelativePathBuf],
         args: &[String],
         worker: Option<&WorkerHandle>,
         env: &[(&str, StrOrOsStr<'_>)],
@@ -390,7 +422,7 @@ impl LocalExecutor {
         ),
         CommandExecutionResult,
     > {
-        let (cgroup_session, mut start_future) = if worker.is_some() {
+        let (cgroup_session, mut start_future) = if worker.is_some() || self.external.is_some() {
             (None, None)
         } else {
             let command_type = if request.is_test() {
@@ -463,6 +495,7 @@ impl LocalExecutor {
                     cancellations,
                     liveliness_observer,
                     scratch_path,
+                    input_paths,
                     args,
                     worker,
                     env,
@@ -558,21 +591,23 @@ impl LocalExecutor {
                 )
                 .await;
 
-                let scratch_path = r1?.scratch;
+                let MaterializedInputPaths {
+                    scratch: scratch_path,
+                    paths: input_paths,
+                } = r1?;
                 r2?;
 
-                buck2_error::Ok((scratch_path, Instant::now() - start))
+                buck2_error::Ok((scratch_path, input_paths, Instant::now() - start))
             },
         )
         .boxed()
         .await;
 
-        let (scratch_path, input_materialization_duration) = match executor_stage_result {
-            Ok((scratch_path, input_materialization_duration)) => {
-                (scratch_path, input_materialization_duration)
-            }
-            Err(e) => return manager.error("materialize_inputs_failed", e),
-        };
+        let (scratch_path, input_paths, input_materialization_duration) =
+            match executor_stage_result {
+                Ok(x) => x,
+                Err(e) => return manager.error("materialize_inputs_failed", e),
+            };
 
         manager.start_waiting_category(WaitingCategory::Unknown);
 
@@ -645,10 +680,14 @@ impl LocalExecutor {
 
         let liveliness_observer = manager.inner.liveliness_observer.dupe().and(cancellation);
 
-        let (worker, manager) = self
-            .initialize_worker(request, manager, dispatcher.dupe())
-            .boxed()
-            .await?;
+        // External executors are sent the full command line instead of using persistent workers.
+        let (worker, manager) = if self.external.is_some() {
+            (None, manager)
+        } else {
+            self.initialize_worker(request, manager, dispatcher.dupe())
+                .boxed()
+                .await?
+        };
 
         let execution_kind = match worker {
             None => CommandExecutionKind::Local {
@@ -672,6 +711,7 @@ impl LocalExecutor {
                 cancellations,
                 liveliness_observer,
                 &scratch_path,
+                &input_paths,
                 args,
                 worker.as_deref(),
                 &env,
@@ -1290,6 +1330,55 @@ impl<'a> StrOrOsStr<'a> {
     }
 }
 
+/// Reports the end of a running command's stderr to the client, so that it can be shown while the
+/// command is still running. Most commands finish quickly, so nothing is reported until the command
+/// has been running for a while. Reports are throttled, and only keep the last few KiB.
+struct StderrTailReporter {
+    dispatcher: Option<EventDispatcher>,
+    tail: Vec<u8>,
+    started: Instant,
+    last_report: Option<Instant>,
+}
+
+impl StderrTailReporter {
+    const MAX_TAIL_BYTES: usize = 4096;
+    const REPORT_AFTER: Duration = Duration::from_secs(5);
+    const REPORT_INTERVAL: Duration = Duration::from_millis(500);
+
+    fn new() -> Self {
+        Self {
+            dispatcher: get_dispatcher_opt(),
+            tail: Vec::new(),
+            started: Instant::now(),
+            last_report: None,
+        }
+    }
+
+    fn on_stderr(&mut self, bytes: &[u8]) {
+        let Some(dispatcher) = &self.dispatcher else {
+            return;
+        };
+
+        self.tail.extend_from_slice(bytes);
+        if self.tail.len() > Self::MAX_TAIL_BYTES {
+            self.tail.drain(..self.tail.len() - Self::MAX_TAIL_BYTES);
+        }
+
+        let now = Instant::now();
+        if now.duration_since(self.started) < Self::REPORT_AFTER
+            || self
+                .last_report
+                .is_some_and(|last| now.duration_since(last) < Self::REPORT_INTERVAL)
+        {
+            return;
+        }
+        self.last_report = Some(now);
+        dispatcher.instant_event(buck2_data::CommandStderrTail {
+            stderr: String::from_utf8_lossy(&self.tail).into_owned(),
+        });
+    }
+}
+
 pub struct MaterializedInputPaths {
     pub scratch: ScratchPath,
     pub paths: Vec<ProjectRelativePathBuf>,
@@ -1642,6 +1731,7 @@ mod unix {
         enable_miniperf: bool,
         cgroup_path: Option<CgroupPathBuf>,
         freeze_rx: impl ActionFreezeEventReceiver,
+        on_stderr: impl FnMut(&[u8]),
     ) -> buck2_error::Result<CommandResult> {
         let exe = exe.as_ref();
 
@@ -1667,6 +1757,7 @@ mod unix {
                 req,
                 async move { liveliness_observer.while_alive().await },
                 freeze_rx,
+                on_stderr,
             )
             .await
     }
@@ -188,6 +188,7 @@ fn spawn_via_forkserver(
                 req,
                 async move { liveliness_observer.while_alive().await },
                 futures::stream::pending(),
+                |_| {},
             )
             .await
             .map(|CommandResult { status, .. }| status);
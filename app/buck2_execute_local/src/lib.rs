@@ -336,6 +336,18 @@ pub struct CommandResult {
 }
 
 pub async fn decode_command_event_stream<S>(stream: S) -> buck2_error::Result<CommandResult>
+where
+    S: Stream<Item = buck2_error::Result<CommandEvent>>,
+{
+    decode_command_event_stream_observing_stderr(stream, |_| {}).await
+}
+
+/// Like `decode_command_event_stream`, but also passes each chunk of stderr to `on_stderr` as the
+/// command produces it.
+pub async fn decode_command_event_stream_observing_stderr<S>(
+    stream: S,
+    mut on_stderr: impl FnMut(&[u8]),
+) -> buck2_error::Result<CommandResult>
 where
     S: Stream<Item = buck2_error::Result<CommandEvent>>,
 {
@@ -347,7 +359,10 @@ where
     while let Some(event) = stream.try_next().await? {
         match event {
             CommandEvent::Stdout(bytes) => stdout.extend(&bytes),
-            CommandEvent::Stderr(bytes) => stderr.extend(&bytes),
+            CommandEvent::Stderr(bytes) => {
+                on_stderr(&bytes);
+                stderr.extend(&bytes)
+            }
             CommandEvent::Exit(exit) => {
                 return Ok(CommandResult {
                     status: exit,
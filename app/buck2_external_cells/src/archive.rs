@@ -0,0 +1,192 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use std::io;
+
+use buck2_build_api::actions::artifact::get_artifact_fs::GetArtifactFs;
+use buck2_build_api::actions::impls::run_action_knobs::HasRunActionKnobs;
+use buck2_common::http::HasHttpClient;
+use buck2_core::cells::external::ArchiveCellSetup;
+use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_error::BuckErrorContext;
+use buck2_execute::archive::extract;
+use buck2_execute::digest_config::HasDigestConfig;
+use buck2_execute::execute::blocking::HasBlockingExecutor;
+use buck2_execute::execute::blocking::IoRequest;
+use buck2_execute::materialize::http::Checksum;
+use buck2_execute::materialize::http::http_download;
+use buck2_fs::fs_util;
+use buck2_fs::paths::abs_norm_path::AbsNormPath;
+use dice::CancellationContext;
+use dice::DiceComputations;
+use dupe::Dupe;
+use sha2::Digest;
+use sha2::Sha256;
+
+#[derive(buck2_error::Error, Debug)]
+#[buck2(tag = Input)]
+enum ArchiveError {
+    #[error(
+        "Archive `{0}` is not in the cache and network access is disabled. Place the archive at \
+        `{1}` to use it offline"
+    )]
+    NotInOfflineCache(String, ProjectRelativePathBuf),
+    #[error(
+        "Archive at `{path}` has sha256 `{obtained}`, but the external cell expects `{expected}`"
+    )]
+    DigestMismatch {
+        path: ProjectRelativePathBuf,
+        expected: String,
+        obtained: String,
+    },
+    #[error("No entry in archive `{0}` is under `strip_prefix` `{1}`")]
+    StripPrefixNotFound(String, String),
+}
+
+struct ArchiveExtractIoRequest {
+    setup: ArchiveCellSetup,
+    archive: ProjectRelativePathBuf,
+    path: ProjectRelativePathBuf,
+    /// Whether the archive's digest still needs to be checked. Downloads are checked while they
+    /// are downloaded, but an archive that was already in the cache may have been put there by
+    /// hand for offline use.
+    verify: bool,
+}
+
+impl IoRequest for ArchiveExtractIoRequest {
+    fn execute(self: Box<Self>, project_fs: &ProjectRoot) -> buck2_error::Result<()> {
+        let archive = project_fs.resolve(&self.archive);
+
+        if self.verify {
+            let obtained = sha256_of(&archive)?;
+            if obtained != *self.setup.sha256 {
+                // Don't let a bad archive stick around, so that the next attempt fetches it again.
+                fs_util::remove_file(&archive)?;
+                return Err(ArchiveError::DigestMismatch {
+                    path: self.archive,
+                    expected: self.setup.sha256.to_string(),
+                    obtained,
+                }
+                .into());
+            }
+        }
+
+        let dest = project_fs.resolve(&self.path);
+        fs_util::create_dir_all(&dest)?;
+        let extracted = extract(
+            &archive,
+            self.setup.format,
+            &dest,
+            self.setup.strip_prefix.as_deref(),
+        )?;
+
+        if let Some(strip_prefix) = &self.setup.strip_prefix {
+            if extracted == 0 {
+                return Err(ArchiveError::StripPrefixNotFound(
+                    self.setup.urls[0].to_string(),
+                    strip_prefix.to_string(),
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn sha256_of(path: &AbsNormPath) -> buck2_error::Result<String> {
+    let mut file = fs_util::open_file(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)
+        .with_buck_error_context(|| format!("Error reading `{}`", path.display()))?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Downloads the archive for `setup` (unless it is cached already) and extracts it into `path`,
+/// which must not exist.
+pub(crate) async fn fetch(
+    ctx: &mut DiceComputations<'_>,
+    setup: &ArchiveCellSetup,
+    path: &ProjectRelativePath,
+    cancellations: &CancellationContext,
+) -> buck2_error::Result<()> {
+    let artifact_fs = ctx.get_artifact_fs().await?;
+    let archive = artifact_fs
+        .buck_out_path_resolver()
+        .resolve_external_cell_archive(&setup.sha256);
+    let fs = artifact_fs.fs();
+
+    let cached = fs_util::try_exists(fs.resolve(&archive))?;
+    if !cached {
+        // The same knob that makes `download_file` read from the offline cache.
+        let offline = ctx
+            .per_transaction_data()
+            .get_run_action_knobs()
+            .use_network_action_output_cache;
+        if offline {
+            return Err(ArchiveError::NotInOfflineCache(setup.urls[0].to_string(), archive).into());
+        }
+        // Download next to the final location and move it into place once complete, so that an
+        // interrupted download never looks like a cached archive. The name is unique, since
+        // several cells (or daemons sharing a buck-out) can download the same archive at once.
+        let partial = artifact_fs
+            .buck_out_path_resolver()
+            .resolve_external_cell_archive(&format!(
+                "{}.{}.partial",
+                setup.sha256,
+                uuid::Uuid::new_v4()
+            ));
+        if let Err(e) = download(ctx, setup, fs, &partial).await {
+            // Best effort, the unique name means a leftover is never picked up.
+            drop(fs_util::remove_all(fs.resolve(&partial)));
+            return Err(e);
+        }
+        fs_util::rename(fs.resolve(&partial), fs.resolve(&archive))?;
+    }
+
+    ctx.get_blocking_executor()
+        .execute_io(
+            Box::new(ArchiveExtractIoRequest {
+                setup: setup.dupe(),
+                archive,
+                path: path.to_owned(),
+                verify: cached,
+            }),
+            cancellations,
+        )
+        .await
+}
+
+/// Downloads the archive to `path`, trying each URL in turn.
+async fn download(
+    ctx: &mut DiceComputations<'_>,
+    setup: &ArchiveCellSetup,
+    fs: &ProjectRoot,
+    path: &ProjectRelativePath,
+) -> buck2_error::Result<()> {
+    let client = ctx.per_transaction_data().get_http_client();
+    let digest_config = ctx.global_data().get_digest_config();
+    let checksum = Checksum::new(None, Some(&setup.sha256))?;
+
+    let mut last_error = None;
+    for url in setup.urls.iter() {
+        match http_download(&client, fs, digest_config, path, url, &checksum, false).await {
+            Ok(_) => return Ok(()),
+            Err(e) => {
+                tracing::warn!("Failed to download external cell archive from `{url}`: {e:#}");
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(last_error
+        .unwrap()
+        .context(format!("Failed to download `{}`", setup.urls[0])))
+}
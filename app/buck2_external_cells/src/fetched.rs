@@ -0,0 +1,356 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! Support for external cells whose contents are fetched into buck-out (git and archive cells).
+//!
+//! The fetching itself is origin specific and lives in the `git` and `archive` modules; this
+//! module takes care of deduplicating fetches, declaring the result to the materializer and
+//! serving file ops out of it.
+
+use std::collections::HashMap;
+use std::collections::hash_map;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::OnceLock;
+
+use buck2_build_api::actions::artifact::get_artifact_fs::GetArtifactFs;
+use buck2_common::dice::data::HasIoProvider;
+use buck2_common::file_ops::delegate::FileOpsDelegate;
+use buck2_common::file_ops::dice::ReadFileProxy;
+use buck2_common::file_ops::metadata::FileDigestConfig;
+use buck2_common::file_ops::metadata::RawDirEntry;
+use buck2_common::file_ops::metadata::RawPathMetadata;
+use buck2_common::io::IoProvider;
+use buck2_common::io::fs::FsIoProvider;
+use buck2_common::lockfile::HasLockfile;
+use buck2_core::cells::cell_path::CellPath;
+use buck2_core::cells::external::ExternalCellOrigin;
+use buck2_core::cells::name::CellName;
+use buck2_core::cells::paths::CellRelativePath;
+use buck2_core::fs::buck_out_path::BuckOutPathResolver;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_directory::directory::directory::Directory;
+use buck2_error::BuckErrorContext;
+use buck2_error::internal_error;
+use buck2_execute::artifact_value::ArtifactValue;
+use buck2_execute::digest_config::HasDigestConfig;
+use buck2_execute::directory::INTERNER;
+use buck2_execute::entry::build_entry_from_disk;
+use buck2_execute::execute::blocking::HasBlockingExecutor;
+use buck2_execute::execute::clean_output_paths::CleanOutputPaths;
+use buck2_execute::materialize::materializer::DeclareArtifactPayload;
+use buck2_execute::materialize::materializer::HasMaterializer;
+use buck2_execute::materialize::materializer::Materializer;
+use cmp_any::PartialEqAny;
+use dice::CancellationContext;
+use dice::DiceComputations;
+use dice::Key;
+use dupe::Dupe;
+use tokio::sync::Semaphore;
+
+use crate::archive;
+use crate::git;
+
+#[derive(buck2_error::Error, Debug)]
+#[buck2(tag = Tier0)]
+enum FetchedCellError {
+    #[error("Expected fetching external cell `{0}` to create a directory at `{1}`")]
+    NoDirectory(CellName, ProjectRelativePathBuf),
+}
+
+async fn download_impl(
+    ctx: &mut DiceComputations<'_>,
+    cell: CellName,
+    origin: &ExternalCellOrigin,
+    path: &ProjectRelativePath,
+    materializer: &dyn Materializer,
+    cancellations: &CancellationContext,
+) -> buck2_error::Result<()> {
+    let io = ctx.get_blocking_executor();
+    io.execute_io(
+        Box::new(CleanOutputPaths {
+            paths: vec![path.to_owned()],
+        }),
+        cancellations,
+    )
+    .await?;
+
+    match origin {
+        ExternalCellOrigin::Git(setup) => git::fetch(ctx, setup, path, cancellations).await?,
+        ExternalCellOrigin::Archive(setup) => {
+            archive::fetch(ctx, setup, path, cancellations).await?
+        }
+        ExternalCellOrigin::Bundled(_) => {
+            return Err(internal_error!("Bundled cells are not fetched"));
+        }
+    }
+
+    // Read and hash the contents. We have to do this because the materializer requires an artifact
+    // value. This work is kind of duplicated with the reading in the fileops, but only the first
+    // time the contents are downloaded. On subsequent invocations of the daemon, we won't rerun
+    // this however, so that case will still avoid doing unnecessary work.
+    let io_prov = ctx.global_data().get_io_provider();
+    let proj_root = io_prov.project_root().root();
+    let abs_path = proj_root.join(path);
+    let digest_config = ctx.global_data().get_digest_config();
+    let file_digest_config = FileDigestConfig::build(digest_config.cas_digest_config());
+    let entry = build_entry_from_disk(abs_path, file_digest_config, &*io, proj_root)
+        .await?
+        .0
+        .ok_or_else(|| FetchedCellError::NoDirectory(cell, path.to_owned()))?;
+    let entry = entry.map_dir(|d| {
+        d.to_builder()
+            .fingerprint(digest_config.as_directory_serializer())
+            .shared(&*INTERNER)
+    });
+
+    materializer
+        .declare_existing(vec![DeclareArtifactPayload {
+            path: path.to_owned(),
+            artifact: ArtifactValue::new(entry, None),
+            persist_full_directory_structure: false,
+        }])
+        .await?;
+
+    Ok(())
+}
+
+async fn download_and_materialize(
+    ctx: &mut DiceComputations<'_>,
+    cell: CellName,
+    path: &ProjectRelativePath,
+    origin: &ExternalCellOrigin,
+    cancellations: &CancellationContext,
+) -> buck2_error::Result<()> {
+    // Checked even if the cell is already on disk, so that a stale lockfile is noticed.
+    if let Some(lockfile) = ctx.get_lockfile().await? {
+        lockfile.check_external_cell(cell, origin)?;
+    }
+
+    let materializer = ctx.per_transaction_data().get_materializer();
+
+    if materializer.has_artifact_at(path.to_owned()).await? {
+        return Ok(());
+    }
+
+    // A map of paths to semaphores that are actually condvars which protect access to that
+    // directory. The path is derived from the commit hash or archive digest, so it identifies the
+    // contents.
+    static DIRECTORY_LICENSES: OnceLock<Mutex<HashMap<ProjectRelativePathBuf, Arc<Semaphore>>>> =
+        OnceLock::new();
+
+    // We have to write this in a slightly funny way to convince the compiler that there's no
+    // `map_guard` being held across an await point
+    let semaphore;
+    let semaphore_guard;
+    'populate: {
+        'wait: {
+            let mut map_guard = DIRECTORY_LICENSES
+                .get_or_init(Default::default)
+                .lock()
+                .unwrap();
+            let entry = map_guard.entry(path.to_owned());
+
+            match entry {
+                hash_map::Entry::Occupied(entry) => {
+                    // There's another key simultaneously populating this directory. Just wait for
+                    // it to finish and then return. We don't need to check the contents of the
+                    // directory, since we assume that the path uniquely identifies those.
+                    semaphore = entry.get().dupe();
+                    break 'wait;
+                }
+                hash_map::Entry::Vacant(entry) => {
+                    // It's on us to populate this directory. Make a condvar so that we block other accesses
+                    semaphore = Arc::new(Semaphore::new(1));
+                    semaphore_guard = semaphore.try_acquire().unwrap(); // we know there's a permit available
+                    entry.insert(semaphore.dupe());
+                    break 'populate;
+                }
+            }
+        }
+
+        drop(semaphore.acquire().await.unwrap());
+        return Ok(());
+    }
+
+    // Don't allow the actual download step to be cancelled. In principle it might be possible to
+    // properly clean up after a cancellation within the execution of this key, but we'd also have
+    // to deal with another key that might be waiting on this download to finish, which would be
+    // pretty complicated to deal with.
+    let res = cancellations
+        .critical_section(|| download_impl(ctx, cell, origin, path, &*materializer, cancellations))
+        .await;
+
+    // Give up our lock
+    drop(semaphore_guard);
+    DIRECTORY_LICENSES
+        .get()
+        .unwrap()
+        .lock()
+        .unwrap()
+        .remove(path)
+        .unwrap();
+
+    res
+}
+
+#[derive(allocative::Allocative)]
+pub(crate) struct FetchedFileOpsDelegate {
+    buck_out_resolver: BuckOutPathResolver,
+    cell: CellName,
+    origin: ExternalCellOrigin,
+    // The fs accesses in this code are sort of a mix between source file accesses and buck-out
+    // accesses. Unconditionally using an `FsIoProvider` turns out to give all the right behavior
+    io: FsIoProvider,
+}
+
+impl FetchedFileOpsDelegate {
+    fn resolve(&self, path: &CellRelativePath) -> ProjectRelativePathBuf {
+        self.buck_out_resolver
+            .resolve_external_cell_source(path, self.origin.dupe())
+    }
+
+    fn get_base_path(&self) -> ProjectRelativePathBuf {
+        self.resolve(CellRelativePath::empty())
+    }
+}
+
+#[async_trait::async_trait]
+impl FileOpsDelegate for FetchedFileOpsDelegate {
+    async fn read_file_if_exists(
+        &self,
+        _ctx: &mut DiceComputations<'_>,
+        path: &'async_trait CellRelativePath,
+    ) -> buck2_error::Result<ReadFileProxy> {
+        Ok(ReadFileProxy::new_with_captures(
+            (self.resolve(path), self.io.dupe()),
+            |(project_path, io)| async move {
+                (&io as &dyn IoProvider)
+                    .read_file_if_exists(project_path)
+                    .await
+            },
+        ))
+    }
+
+    async fn read_dir(
+        &self,
+        _ctx: &mut DiceComputations<'_>,
+        path: &'async_trait CellRelativePath,
+    ) -> buck2_error::Result<Arc<[RawDirEntry]>> {
+        let project_path = self.resolve(path);
+        let mut entries = (&self.io as &dyn IoProvider)
+            .read_dir(project_path)
+            .await
+            .with_buck_error_context(|| format!("Error listing dir `{path}`"))?;
+
+        // Make sure entries are deterministic, since read_dir isn't.
+        entries.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+
+        Ok(entries.into())
+    }
+
+    async fn read_path_metadata_if_exists(
+        &self,
+        _ctx: &mut DiceComputations<'_>,
+        path: &'async_trait CellRelativePath,
+    ) -> buck2_error::Result<Option<RawPathMetadata>> {
+        let project_path = self.resolve(path);
+
+        let Some(metadata) = (&self.io as &dyn IoProvider)
+            .read_path_metadata_if_exists(project_path)
+            .await
+            .with_buck_error_context(|| format!("Error accessing metadata for path `{path}`"))?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(metadata.try_map(
+            |path| match path.strip_prefix_opt(self.get_base_path()) {
+                Some(path) => Ok(Arc::new(CellPath::new(self.cell, path.to_owned().into()))),
+                None => Err(internal_error!(
+                    "Non-cell internal symlink at `{}` in cell `{}`",
+                    path,
+                    self.cell
+                )),
+            },
+        )?))
+    }
+
+    fn eq_token(&self) -> PartialEqAny<'_> {
+        PartialEqAny::always_false()
+    }
+}
+
+pub(crate) async fn get_file_ops_delegate(
+    ctx: &mut DiceComputations<'_>,
+    cell: CellName,
+    origin: ExternalCellOrigin,
+) -> buck2_error::Result<Arc<FetchedFileOpsDelegate>> {
+    #[derive(
+        dupe::Dupe,
+        Clone,
+        Debug,
+        derive_more::Display,
+        PartialEq,
+        Eq,
+        Hash,
+        allocative::Allocative
+    )]
+    #[display("({}, {})", _0, _1)]
+    struct FetchedFileOpsDelegateKey(CellName, ExternalCellOrigin);
+
+    #[async_trait::async_trait]
+    impl Key for FetchedFileOpsDelegateKey {
+        type Value = buck2_error::Result<Arc<FetchedFileOpsDelegate>>;
+
+        async fn compute(
+            &self,
+            ctx: &mut DiceComputations,
+            cancellations: &CancellationContext,
+        ) -> Self::Value {
+            let artifact_fs = ctx.get_artifact_fs().await?;
+            let ops = FetchedFileOpsDelegate {
+                buck_out_resolver: artifact_fs.buck_out_path_resolver().clone(),
+                cell: self.0,
+                origin: self.1.dupe(),
+                io: FsIoProvider::new(
+                    artifact_fs.fs().dupe(),
+                    ctx.global_data().get_digest_config().cas_digest_config(),
+                ),
+            };
+            download_and_materialize(ctx, self.0, &ops.get_base_path(), &self.1, cancellations)
+                .await?;
+            Ok(Arc::new(ops))
+        }
+
+        fn equality(_x: &Self::Value, _y: &Self::Value) -> bool {
+            false
+        }
+
+        fn validity(x: &Self::Value) -> bool {
+            // Fetching fails for reasons DICE doesn't track (the network, or an archive missing
+            // from the offline cache), so try again in the next command.
+            x.is_ok()
+        }
+    }
+
+    ctx.compute(&FetchedFileOpsDelegateKey(cell, origin))
+        .await?
+}
+
+pub(crate) async fn materialize_all(
+    ctx: &mut DiceComputations<'_>,
+    cell: CellName,
+    origin: ExternalCellOrigin,
+) -> buck2_error::Result<ProjectRelativePathBuf> {
+    // Get the `FetchedFileOpsDelegate` instance to make sure all the data is materialized.
+    let ops = get_file_ops_delegate(ctx, cell, origin).await?;
+    Ok(ops.get_base_path())
+}
@@ -24,7 +24,9 @@ use buck2_core::cells::name::CellName;
 use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
 use dice::DiceComputations;
 
+mod archive;
 mod bundled;
+mod fetched;
 mod git;
 
 struct ConcreteExternalCellsImpl;
@@ -48,8 +50,8 @@ impl buck2_common::external_cells::ExternalCellsImpl for ConcreteExternalCellsIm
             ExternalCellOrigin::Bundled(cell_name) => {
                 Ok(bundled::get_file_ops_delegate(ctx, cell_name).await? as _)
             }
-            ExternalCellOrigin::Git(setup) => {
-                Ok(git::get_file_ops_delegate(ctx, cell_name, setup).await? as _)
+            origin @ (ExternalCellOrigin::Git(_) | ExternalCellOrigin::Archive(_)) => {
+                Ok(fetched::get_file_ops_delegate(ctx, cell_name, origin).await? as _)
             }
         }
     }
@@ -90,7 +92,9 @@ impl buck2_common::external_cells::ExternalCellsImpl for ConcreteExternalCellsIm
         // now.
         let materialized_path = match origin {
             ExternalCellOrigin::Bundled(cell) => bundled::materialize_all(ctx, cell).await?,
-            ExternalCellOrigin::Git(setup) => git::materialize_all(ctx, cell, setup).await?,
+            origin @ (ExternalCellOrigin::Git(_) | ExternalCellOrigin::Archive(_)) => {
+                fetched::materialize_all(ctx, cell, origin).await?
+            }
         };
 
         Ok(io.project_root().copy(&materialized_path, &dest_path)?)
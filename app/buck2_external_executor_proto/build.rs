@@ -0,0 +1,25 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use std::env;
+use std::io;
+
+fn main() -> io::Result<()> {
+    let proto_files = &["external_executor.proto"];
+
+    let includes = if let Ok(path) = env::var("BUCK_PROTO_SRCS") {
+        vec![path]
+    } else {
+        vec![".".to_owned()]
+    };
+
+    let builder = buck2_protoc_dev::configure();
+    unsafe { builder.setup_protoc() }.compile(proto_files, &includes)
+}
@@ -8,6 +8,7 @@
  * above-listed licenses.
  */
 
+pub mod aspect;
 pub mod cell_path;
 pub mod cell_root;
 pub mod configuration;
@@ -0,0 +1,22 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use buck2_util::late_binding::LateBinding;
+use starlark::values::FrozenValue;
+
+/// `aspect()` value `impl` field.
+pub static FROZEN_ASPECT_GET_IMPL: LateBinding<
+    fn(FrozenValue) -> buck2_error::Result<FrozenValue>,
+> = LateBinding::new("FROZEN_ASPECT_GET_IMPL");
+
+/// `aspect()` value `attr_aspects` field: the attributes the aspect propagates along.
+pub static FROZEN_ASPECT_GET_ATTR_ASPECTS: LateBinding<
+    fn(FrozenValue) -> buck2_error::Result<Vec<String>>,
+> = LateBinding::new("FROZEN_ASPECT_GET_ATTR_ASPECTS");
@@ -0,0 +1,204 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use allocative::Allocative;
+use buck2_core::bzl::ImportPath;
+use buck2_error::BuckErrorContext;
+use buck2_interpreter::build_context::starlark_path_from_build_context;
+use buck2_interpreter::late_binding_ty::ProviderReprLate;
+use buck2_interpreter::types::aspect::FROZEN_ASPECT_GET_ATTR_ASPECTS;
+use buck2_interpreter::types::aspect::FROZEN_ASPECT_GET_IMPL;
+use buck2_node::aspect::AspectId;
+use derive_more::Display;
+use dupe::Dupe;
+use either::Either;
+use starlark::any::ProvidesStaticType;
+use starlark::environment::GlobalsBuilder;
+use starlark::eval::Evaluator;
+use starlark::starlark_complex_values;
+use starlark::starlark_module;
+use starlark::values::Freeze;
+use starlark::values::FreezeError;
+use starlark::values::FreezeResult;
+use starlark::values::Freezer;
+use starlark::values::FrozenRef;
+use starlark::values::FrozenValue;
+use starlark::values::NoSerialize;
+use starlark::values::StarlarkValue;
+use starlark::values::Trace;
+use starlark::values::Value;
+use starlark::values::list::ListType;
+use starlark::values::list_or_tuple::UnpackListOrTuple;
+use starlark::values::starlark_value;
+use starlark::values::typing::StarlarkCallable;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(tag = Input)]
+enum AspectError {
+    #[error("Aspect must be assigned to a variable, e.g. `license_aspect = aspect(...)`")]
+    AspectNotAssigned,
+    #[error("`aspect` can only be declared in .bzl files")]
+    OnlyBzl,
+    #[error("Non-unique list of attrs in `attr_aspects`")]
+    NonUniqueAttrs,
+    #[error("Expected an aspect, got `{0}`")]
+    NotAnAspect(String),
+}
+
+/// An aspect, created via `aspect()`.
+///
+/// Aspects are attached to dependency attributes with `attrs.dep(aspects = [...])`, and run over
+/// the providers of each dependency to produce additional providers.
+#[derive(Debug, Display, Trace, ProvidesStaticType, NoSerialize, Allocative)]
+#[display("aspect")]
+pub(crate) struct StarlarkAspect<'v> {
+    /// The name of this aspect, filled in by `export_as()`. This must be set before this
+    /// object can be used.
+    id: RefCell<Option<Arc<AspectId>>>,
+    /// The path where this `Aspect` is created and assigned.
+    path: ImportPath,
+    implementation: Value<'v>,
+    /// Attributes of the target the aspect is also applied along.
+    attr_aspects: Vec<String>,
+}
+
+#[derive(Debug, Display, ProvidesStaticType, NoSerialize, Allocative)]
+#[display("aspect")]
+pub(crate) struct FrozenStarlarkAspect {
+    id: Arc<AspectId>,
+    implementation: FrozenValue,
+    attr_aspects: Vec<String>,
+}
+
+starlark_complex_values!(StarlarkAspect);
+
+#[starlark_value(type = "Aspect")]
+impl<'v> StarlarkValue<'v> for StarlarkAspect<'v> {
+    fn export_as(
+        &self,
+        variable_name: &str,
+        _eval: &mut Evaluator<'v, '_, '_>,
+    ) -> starlark::Result<()> {
+        let mut id = self.id.borrow_mut();
+        // First export wins
+        if id.is_none() {
+            *id = Some(Arc::new(AspectId {
+                path: self.path.clone(),
+                name: variable_name.to_owned(),
+            }));
+        }
+        Ok(())
+    }
+}
+
+#[starlark_value(type = "Aspect")]
+impl<'v> StarlarkValue<'v> for FrozenStarlarkAspect {
+    type Canonical = StarlarkAspect<'v>;
+}
+
+impl<'v> Freeze for StarlarkAspect<'v> {
+    type Frozen = FrozenStarlarkAspect;
+
+    fn freeze(self, freezer: &Freezer) -> FreezeResult<FrozenStarlarkAspect> {
+        let id = self
+            .id
+            .into_inner()
+            .ok_or_else(|| FreezeError::new(AspectError::AspectNotAssigned.to_string()))?;
+        Ok(FrozenStarlarkAspect {
+            id,
+            implementation: freezer.freeze(self.implementation)?,
+            attr_aspects: self.attr_aspects,
+        })
+    }
+}
+
+/// Returns the id of an aspect value, which must have been assigned to a global.
+pub(crate) fn aspect_id_from_value(value: Value) -> buck2_error::Result<Arc<AspectId>> {
+    match StarlarkAspect::from_value(value) {
+        Some(Either::Left(aspect)) => aspect
+            .id
+            .borrow()
+            .as_ref()
+            .map(Dupe::dupe)
+            .ok_or_else(|| AspectError::AspectNotAssigned.into()),
+        Some(Either::Right(aspect)) => Ok(aspect.id.dupe()),
+        None => Err(AspectError::NotAnAspect(value.to_repr()).into()),
+    }
+}
+
+fn unpack_frozen_aspect(
+    aspect: FrozenValue,
+) -> buck2_error::Result<FrozenRef<'static, FrozenStarlarkAspect>> {
+    aspect
+        .downcast_frozen_ref::<FrozenStarlarkAspect>()
+        .buck_error_context("Expecting FrozenStarlarkAspect")
+}
+
+pub(crate) fn init_frozen_aspect_get_impl() {
+    FROZEN_ASPECT_GET_IMPL.init(|aspect| Ok(unpack_frozen_aspect(aspect)?.implementation));
+    FROZEN_ASPECT_GET_ATTR_ASPECTS
+        .init(|aspect| Ok(unpack_frozen_aspect(aspect)?.attr_aspects.clone()));
+}
+
+#[starlark_module]
+pub(crate) fn register_aspect_function(builder: &mut GlobalsBuilder) {
+    /// Define an aspect: a function run over the providers of a target to produce additional
+    /// providers, without modifying the rule that defines the target.
+    ///
+    /// `impl` is called as `impl(target, deps)`, where `target` is the `Dependency` the aspect is
+    /// applied to and `deps` is a list of the `Dependency` values reached through the attributes
+    /// named in `attr_aspects`, with this aspect already applied to them. It returns a list of
+    /// providers, which must not include providers the target already has.
+    ///
+    /// Aspects are applied by rules with `attrs.dep(aspects = [...])`, and from the command line
+    /// with `buck2 audit providers --aspect`. The result for each target is computed once and
+    /// cached.
+    ///
+    /// ```python
+    /// LicensesInfo = provider(fields = ["licenses"])
+    ///
+    /// def _licenses_impl(target, deps):
+    ///     licenses = [l for d in deps for l in d[LicensesInfo].licenses]
+    ///     return [LicensesInfo(licenses = licenses + [target.label])]
+    ///
+    /// licenses_aspect = aspect(impl = _licenses_impl, attr_aspects = ["deps"])
+    /// ```
+    fn aspect<'v>(
+        #[starlark(require = named)] r#impl: StarlarkCallable<
+            'v,
+            (FrozenValue, ListType<FrozenValue>),
+            ListType<ProviderReprLate>,
+        >,
+        #[starlark(require = named, default = UnpackListOrTuple::default())]
+        attr_aspects: UnpackListOrTuple<String>,
+        eval: &mut Evaluator<'v, '_, '_>,
+    ) -> starlark::Result<StarlarkAspect<'v>> {
+        let path: ImportPath = (*starlark_path_from_build_context(eval)?
+            .unpack_load_file()
+            .ok_or(buck2_error::Error::from(AspectError::OnlyBzl))?)
+        .clone();
+
+        let attrs_set: HashSet<&String> = attr_aspects.items.iter().collect();
+        if attrs_set.len() != attr_aspects.items.len() {
+            return Err(buck2_error::Error::from(AspectError::NonUniqueAttrs).into());
+        }
+
+        Ok(StarlarkAspect {
+            id: RefCell::new(None),
+            path,
+            implementation: r#impl.0,
+            attr_aspects: attr_aspects.items,
+        })
+    }
+}
@@ -17,6 +17,7 @@ use buck2_error::BuckErrorContext;
 use buck2_interpreter::coerce::COERCE_PROVIDERS_LABEL_FOR_BZL;
 use buck2_interpreter::types::provider::callable::ValueAsProviderCallableLike;
 use buck2_interpreter::types::transition::transition_id_from_value;
+use buck2_node::aspect::AspectIds;
 use buck2_node::attrs::attr::Attribute;
 use buck2_node::attrs::attr_type::AttrType;
 use buck2_node::attrs::attr_type::any::AnyAttrType;
@@ -40,6 +41,7 @@ use starlark::values::list_or_tuple::UnpackListOrTuple;
 use starlark::values::tuple::UnpackTuple;
 use tracing::error;
 
+use crate::aspect::aspect_id_from_value;
 use crate::attrs::coerce::attr_type::AttrTypeExt;
 use crate::attrs::coerce::ctx::BuildAttrCoercionContext;
 use crate::attrs::starlark_attribute::StarlarkAttribute;
@@ -313,6 +315,10 @@ fn attr_module(registry: &mut GlobalsBuilder) {
     ///
     /// The `pulls_plugins` and `pulls_and_pushes_plugins` parameters control plugin propagation.
     /// See the [`plugins`](../plugins) namespace documentation for a full explanation.
+    ///
+    /// The `aspects` argument is a list of values created with `aspect()`. They are applied in
+    /// order to the dependency, and the providers they return are available on it alongside its
+    /// own.
     fn dep<'v>(
         #[starlark(require = named, default = UnpackListOrTuple::default())]
         providers: UnpackListOrTuple<Value<'v>>,
@@ -320,11 +326,20 @@ fn attr_module(registry: &mut GlobalsBuilder) {
         pulls_plugins: UnpackListOrTuple<PluginKindArg>,
         #[starlark(require = named, default = Either::Left(UnpackListOrTuple::default()))]
         pulls_and_pushes_plugins: Either<UnpackListOrTuple<PluginKindArg>, &'v AllPlugins>,
+        #[starlark(require = named, default = UnpackListOrTuple::default())]
+        aspects: UnpackListOrTuple<Value<'v>>,
         #[starlark(require = named)] default: Option<Value<'v>>,
         #[starlark(require = named, default = "")] doc: &str,
         eval: &mut Evaluator<'v, '_, '_>,
     ) -> starlark::Result<StarlarkAttribute> {
         let required_providers = dep_like_attr_handle_providers_arg(providers.items)?;
+        let aspects = AspectIds::from(
+            aspects
+                .items
+                .into_iter()
+                .map(aspect_id_from_value)
+                .collect::<buck2_error::Result<Vec<_>>>()?,
+        );
         let plugin_kinds = match pulls_and_pushes_plugins {
             Either::Right(_) => PluginKindSet::ALL,
             Either::Left(pulls_and_pushes_plugins) => {
@@ -342,7 +357,7 @@ fn attr_module(registry: &mut GlobalsBuilder) {
             }
         };
 
-        let coercer = AttrType::dep(required_providers, plugin_kinds);
+        let coercer = AttrType::dep_with_aspects(required_providers, plugin_kinds, aspects);
         Ok(Attribute::attr(eval, default, doc, coercer)?)
     }
 
@@ -69,6 +69,7 @@ pub struct BuildInterpreterConfiguror {
     host_info: HostInfo,
     record_target_call_stack: bool,
     skip_targets_with_duplicate_names: bool,
+    strict_globs: bool,
     global_target_interner: Arc<ConcurrentTargetLabelInterner>,
     /// For test.
     additional_globals: Option<AdditionalGlobalsFn>,
@@ -82,6 +83,7 @@ impl BuildInterpreterConfiguror {
         host_xcode_version: Option<XcodeVersionInfo>,
         record_target_call_stack: bool,
         skip_targets_with_duplicate_names: bool,
+        strict_globs: bool,
         additional_globals: Option<AdditionalGlobalsFn>,
         global_target_interner: Arc<ConcurrentTargetLabelInterner>,
     ) -> buck2_error::Result<Arc<Self>> {
@@ -90,6 +92,7 @@ impl BuildInterpreterConfiguror {
             host_info: HostInfo::new(host_platform, host_architecture, host_xcode_version),
             record_target_call_stack,
             skip_targets_with_duplicate_names,
+            strict_globs,
             additional_globals,
             global_target_interner,
         }))
@@ -150,6 +153,7 @@ impl BuildInterpreterConfiguror {
             package_implicits,
             record_target_call_stack,
             skip_targets_with_duplicate_names,
+            self.strict_globs,
             package_listing,
             super_package,
         ))
@@ -8,6 +8,7 @@
  * above-listed licenses.
  */
 
+use buck2_node::nodes::eval_result::GlobResult;
 use starlark::environment::GlobalsBuilder;
 use starlark::eval::Evaluator;
 use starlark::starlark_module;
@@ -58,8 +59,16 @@ pub(crate) fn register_path(builder: &mut GlobalsBuilder) {
     ) -> starlark::Result<ValueOfUnchecked<'v, UnpackList<String>>> {
         let extra = ModuleInternals::from_context(eval, "glob")?;
         let spec = GlobSpec::new(&include.items, &exclude.items)?;
-        let res = extra.resolve_glob(&spec).map(|path| path.as_str());
-        Ok(eval.heap().alloc_typed_unchecked(AllocList(res)).cast())
+        let matches = extra
+            .resolve_glob(&spec)
+            .map(|path| path.as_str())
+            .collect::<Vec<_>>();
+        extra.record_glob(GlobResult {
+            include: include.items,
+            exclude: exclude.items,
+            match_count: matches.len(),
+        })?;
+        Ok(eval.heap().alloc_typed_unchecked(AllocList(matches)).cast())
     }
 
     /// `package_name()` can only be called in buildfiles (e.g. BUCK files) or PACKAGE files, and returns the name of the package.
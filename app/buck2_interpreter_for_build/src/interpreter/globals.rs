@@ -27,6 +27,7 @@ use buck2_util::late_binding::LateBinding;
 use starlark::environment::GlobalsBuilder;
 use starlark::environment::LibraryExtension;
 
+use crate::aspect::register_aspect_function;
 use crate::attrs::attrs_global::register_attrs;
 use crate::interpreter::functions::dedupe::register_dedupe;
 use crate::interpreter::functions::host_info::register_host_info;
@@ -72,6 +73,7 @@ pub fn register_load_natives(builder: &mut GlobalsBuilder) {
     register_buck_regex(builder);
     register_load_symbols(builder);
     register_rule_function(builder);
+    register_aspect_function(builder);
     register_attrs(builder);
     register_plugins(builder);
     register_providers_label(builder);
@@ -23,6 +23,7 @@ use buck2_core::target::name::TargetNameRef;
 use buck2_events::dispatch::console_message;
 use buck2_interpreter::package_imports::ImplicitImport;
 use buck2_node::nodes::eval_result::EvaluationResult;
+use buck2_node::nodes::eval_result::GlobResult;
 use buck2_node::nodes::targets_map::TargetsMap;
 use buck2_node::nodes::targets_map::TargetsMapRecordError;
 use buck2_node::nodes::unconfigured::TargetNode;
@@ -44,6 +45,7 @@ impl From<ModuleInternals> for EvaluationResult {
             imports,
             buildfile_path,
             super_package,
+            globs,
             ..
         } = internals;
         let recorder = match state.into_inner() {
@@ -51,6 +53,7 @@ impl From<ModuleInternals> for EvaluationResult {
             State::RecordingTargets(RecordingTargets { recorder, .. }) => recorder,
         };
         EvaluationResult::new(buildfile_path, imports, super_package, recorder.take())
+            .with_globs(globs.into_inner())
     }
 }
 
@@ -89,8 +92,12 @@ pub struct ModuleInternals {
     package_implicits: Option<PackageImplicits>,
     record_target_call_stacks: bool,
     skip_targets_with_duplicate_names: bool,
+    /// Whether a `glob()` which matches no files is an error rather than a warning.
+    strict_globs: bool,
     /// The files owned by this directory. Is `None` for .bzl files.
     package_listing: PackageListing,
+    /// The `glob()` calls made so far.
+    globs: RefCell<Vec<GlobResult>>,
     pub(crate) super_package: SuperPackage,
 }
 
@@ -124,6 +131,13 @@ enum OncallErrors {
     AfterReadOncall,
 }
 
+#[derive(Debug, buck2_error::Error)]
+#[buck2(input)]
+enum GlobErrors {
+    #[error("`glob({0:?}, exclude = {1:?})` in `{2}` matched no files")]
+    NoMatches(Vec<String>, Vec<String>, Arc<BuildFilePath>),
+}
+
 impl ModuleInternals {
     pub(crate) fn new(
         attr_coercion_context: BuildAttrCoercionContext,
@@ -132,6 +146,7 @@ impl ModuleInternals {
         package_implicits: Option<PackageImplicits>,
         record_target_call_stacks: bool,
         skip_targets_with_duplicate_names: bool,
+        strict_globs: bool,
         package_listing: PackageListing,
         super_package: SuperPackage,
     ) -> Self {
@@ -143,7 +158,9 @@ impl ModuleInternals {
             package_implicits,
             record_target_call_stacks,
             skip_targets_with_duplicate_names,
+            strict_globs,
             package_listing,
+            globs: RefCell::new(Vec::new()),
             super_package,
         }
     }
@@ -246,6 +263,25 @@ impl ModuleInternals {
         spec.resolve_glob(self.package_listing.files())
     }
 
+    /// Records a `glob()` call. A glob with patterns which matches no files is usually a
+    /// mistake (e.g. after moving a directory), so it is reported with a warning, or an error
+    /// with `--strict-globs`.
+    pub(crate) fn record_glob(&self, glob: GlobResult) -> buck2_error::Result<()> {
+        if glob.match_count == 0 && !glob.include.is_empty() {
+            let error = GlobErrors::NoMatches(
+                glob.include.clone(),
+                glob.exclude.clone(),
+                self.buildfile_path.dupe(),
+            );
+            if self.strict_globs {
+                return Err(error.into());
+            }
+            console_message(error.to_string());
+        }
+        self.globs.borrow_mut().push(glob);
+        Ok(())
+    }
+
     pub(crate) fn sub_packages(&self) -> impl Iterator<Item = &PackageRelativePath> {
         self.package_listing
             .subpackages_within(PackageRelativePath::empty())
@@ -62,6 +62,7 @@ pub struct Tester {
     loaded_modules: LoadedModules,
     additional_globals: Vec<AdditionalGlobalsFn>,
     prelude_path: Option<PreludePath>,
+    strict_globs: bool,
     current_dir_with_allowed_relative_dirs: Arc<CellPathWithAllowedRelativeDir>,
 }
 
@@ -158,6 +159,7 @@ impl Tester {
             loaded_modules: LoadedModules::default(),
             additional_globals: Vec::new(),
             prelude_path: None,
+            strict_globs: false,
             current_dir_with_allowed_relative_dirs: current_dir_with_allowed_relative_dirs.into(),
         })
     }
@@ -174,6 +176,10 @@ impl Tester {
         self.prelude_path = Some(PreludePath::testing_new(prelude_import));
     }
 
+    pub fn set_strict_globs(&mut self, strict_globs: bool) {
+        self.strict_globs = strict_globs;
+    }
+
     fn interpreter(&self) -> buck2_error::Result<Arc<InterpreterForDir>> {
         let build_file_cell = BuildFileCell::new(self.cell_alias_resolver.resolve_self());
         let import_paths = ImplicitImportPaths::parse(
@@ -198,6 +204,7 @@ impl Tester {
                     None,
                     false,
                     false,
+                    self.strict_globs,
                     Some(AdditionalGlobalsFn(Arc::new(move |globals_builder| {
                         for additional_globals in &additional_globals {
                             (additional_globals.0)(globals_builder)
@@ -15,6 +15,7 @@
 
 use std::sync::Once;
 
+pub(crate) mod aspect;
 pub mod attrs;
 pub mod call_stack;
 pub mod interpreter;
@@ -27,6 +28,7 @@ pub mod super_package;
 pub fn init_late_bindings() {
     static ONCE: Once = Once::new();
     ONCE.call_once(|| {
+        aspect::init_frozen_aspect_get_impl();
         attrs::attrs_global::init_coerce_providers_label_for_bzl();
         interpreter::calculation::init_interpreter_calculation_impl();
         interpreter::calculation::init_target_graph_calculation_impl();
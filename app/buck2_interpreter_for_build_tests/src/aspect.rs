@@ -0,0 +1,98 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use buck2_interpreter_for_build::interpreter::testing::Tester;
+use indoc::indoc;
+
+#[test]
+fn aspect_is_attached_to_dep() -> buck2_error::Result<()> {
+    let mut tester = Tester::new()?;
+    tester.run_starlark_bzl_test(indoc!(
+        r#"
+        def _impl(target, deps):
+            return []
+
+        licenses_aspect = aspect(impl = _impl, attr_aspects = ["deps"])
+        dep_attr = attrs.dep(aspects = [licenses_aspect])
+        list_attr = attrs.list(attrs.dep(aspects = (licenses_aspect,)))
+
+        def test():
+            assert_eq("aspect", repr(licenses_aspect))
+            assert_eq("Aspect", type(licenses_aspect))
+        "#
+    ))?;
+    Ok(())
+}
+
+#[test]
+fn aspect_unbound() {
+    let mut tester = Tester::new().unwrap();
+    tester.run_starlark_bzl_test_expecting_error(
+        indoc!(
+            r#"
+        def _impl(target, deps):
+            return []
+        aspects = []
+        aspects.append(aspect(impl = _impl))
+        def test():
+            pass
+        "#
+        ),
+        "must be assigned to a variable",
+    );
+}
+
+#[test]
+fn aspect_rejects_duplicate_attr_aspects() {
+    let mut tester = Tester::new().unwrap();
+    tester.run_starlark_bzl_test_expecting_error(
+        indoc!(
+            r#"
+        def _impl(target, deps):
+            return []
+        licenses_aspect = aspect(impl = _impl, attr_aspects = ["deps", "deps"])
+        def test():
+            pass
+        "#
+        ),
+        "Non-unique list of attrs in `attr_aspects`",
+    );
+}
+
+#[test]
+fn dep_rejects_non_aspects() {
+    let mut tester = Tester::new().unwrap();
+    tester.run_starlark_bzl_test_expecting_error(
+        indoc!(
+            r#"
+        def test():
+            attrs.dep(aspects = ["//:defs.bzl%licenses_aspect"])
+        "#
+        ),
+        "Expected an aspect",
+    );
+}
+
+#[test]
+fn aspects_only_apply_to_identity_deps() {
+    let mut tester = Tester::new().unwrap();
+    tester.run_starlark_bzl_test_expecting_error(
+        indoc!(
+            r#"
+        def _impl(target, deps):
+            return []
+        licenses_aspect = aspect(impl = _impl)
+        def test():
+            attrs.exec_dep(aspects = [licenses_aspect])
+        "#
+        ),
+        "aspects",
+    );
+}
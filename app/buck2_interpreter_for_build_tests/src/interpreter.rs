@@ -24,6 +24,7 @@ use buck2_interpreter::paths::module::OwnedStarlarkModulePath;
 use buck2_interpreter::paths::path::StarlarkPath;
 use buck2_interpreter_for_build::interpreter::testing::CellsData;
 use buck2_interpreter_for_build::interpreter::testing::Tester;
+use buck2_interpreter_for_build::interpreter::testing::expect_error;
 use buck2_interpreter_for_build::interpreter::testing::run_simple_starlark_test;
 use buck2_node::attrs::inspect_options::AttrInspectOptions;
 use buck2_node::nodes::unconfigured::testing::targets_to_json;
@@ -557,3 +558,51 @@ fn test_oncall() -> buck2_error::Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn test_globs_are_recorded() -> buck2_error::Result<()> {
+    let tester = Tester::new()?;
+    let eval_result = tester.eval_build_file(
+        &BuildFilePath::testing_new("root//some/package:BUCK"),
+        indoc!(
+            r#"
+            glob(["*.java"], exclude = ["file2.java"])
+            glob(["*.txt"])
+            glob([])
+            "#
+        ),
+        PackageListing::testing_files(&["file1.java", "file2.java"]),
+    )?;
+
+    assert_eq!(
+        vec![
+            (vec!["*.java"], vec!["file2.java"], 1),
+            (vec!["*.txt"], vec![], 0),
+            (vec![], vec![], 0),
+        ],
+        eval_result.globs().map(|glob| (
+            glob.include.map(|p| p.as_str()),
+            glob.exclude.map(|p| p.as_str()),
+            glob.match_count
+        ))
+    );
+    Ok(())
+}
+
+#[test]
+fn test_strict_globs() -> buck2_error::Result<()> {
+    let mut tester = Tester::new()?;
+    tester.set_strict_globs(true);
+    let path = BuildFilePath::testing_new("root//some/package:BUCK");
+    let listing = || PackageListing::testing_files(&["file1.java"]);
+
+    tester.eval_build_file(&path, "glob([\"*.java\"])\nglob([])", listing())?;
+
+    let content = "glob([\"*.txt\"])";
+    expect_error(
+        tester.eval_build_file(&path, content, listing()),
+        content,
+        "matched no files",
+    );
+    Ok(())
+}
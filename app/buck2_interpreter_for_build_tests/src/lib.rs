@@ -11,6 +11,7 @@
 #![feature(error_generic_member_access)]
 #![cfg(test)]
 
+mod aspect;
 #[cfg(test)]
 mod attr;
 mod attrs;
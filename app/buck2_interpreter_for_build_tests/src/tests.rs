@@ -67,6 +67,7 @@ pub(crate) async fn calculation(fs: &ProjectRootTemp) -> DiceTransaction {
             None,
             false,
             false,
+            false,
             None,
             Arc::new(ConcurrentTargetLabelInterner::default()),
         )
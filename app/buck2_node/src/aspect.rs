@@ -0,0 +1,85 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+use std::fmt;
+use std::fmt::Display;
+use std::sync::Arc;
+
+use allocative::Allocative;
+use buck2_core::bzl::ImportPath;
+use dupe::Dupe;
+use pagable::Pagable;
+use strong_hash::StrongHash;
+
+/// The identifier used to find an aspect. Should point at the output of `aspect()`.
+#[derive(
+    Debug,
+    Clone,
+    derive_more::Display,
+    Eq,
+    PartialEq,
+    Hash,
+    StrongHash,
+    Pagable,
+    Allocative
+)]
+#[display("{}%{}", path, name)]
+pub struct AspectId {
+    /// The cell, package, and file that contains the output of `aspect()`
+    pub path: ImportPath,
+    /// The name of the symbol that is bound to the output of `aspect()`
+    pub name: String,
+}
+
+/// The aspects applied to a dependency, in the order they are applied.
+#[derive(
+    Debug, Eq, PartialEq, Hash, StrongHash, Clone, Dupe, Allocative, Pagable
+)]
+pub struct AspectIds(Option<Arc<Vec<Arc<AspectId>>>>);
+
+impl AspectIds {
+    pub const EMPTY: AspectIds = AspectIds(None);
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.aspects().is_empty()
+    }
+
+    #[inline]
+    pub fn aspects(&self) -> &[Arc<AspectId>] {
+        match &self.0 {
+            None => &[],
+            Some(aspects) => aspects,
+        }
+    }
+}
+
+impl From<Vec<Arc<AspectId>>> for AspectIds {
+    #[inline]
+    fn from(v: Vec<Arc<AspectId>>) -> Self {
+        if v.is_empty() {
+            AspectIds::EMPTY
+        } else {
+            AspectIds(Some(Arc::new(v)))
+        }
+    }
+}
+
+impl Display for AspectIds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, aspect) in self.aspects().iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{aspect}")?;
+        }
+        Ok(())
+    }
+}
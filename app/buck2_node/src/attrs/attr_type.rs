@@ -20,6 +20,7 @@ use dupe::Dupe;
 use once_cell::sync::Lazy;
 use pagable::Pagable;
 
+use crate::aspect::AspectIds;
 use crate::attrs::attr_type::any::AnyAttrType;
 use crate::attrs::attr_type::arg::ArgAttrType;
 use crate::attrs::attr_type::bool::BoolAttrType;
@@ -226,11 +227,24 @@ impl AttrType {
     /// If `required_providers` is non-empty, the dependency must return those providers
     /// from its implementation function. Otherwise an error will result at resolution time.
     pub fn dep(required_providers: ProviderIdSet, plugin_kinds: PluginKindSet) -> Self {
+        Self::dep_with_aspects(required_providers, plugin_kinds, AspectIds::EMPTY)
+    }
+
+    /// A dependency attribute like `dep()`, where `aspects` are applied in order to the
+    /// dependency and the providers they return are available alongside its own.
+    pub fn dep_with_aspects(
+        required_providers: ProviderIdSet,
+        plugin_kinds: PluginKindSet,
+        aspects: AspectIds,
+    ) -> Self {
         Self(Arc::new(AttrTypeInner2 {
-            inner: AttrTypeInner::Dep(DepAttrType::new(
-                required_providers,
-                DepAttrTransition::Identity(plugin_kinds),
-            )),
+            inner: AttrTypeInner::Dep(
+                DepAttrType::new(
+                    required_providers,
+                    DepAttrTransition::Identity(plugin_kinds),
+                )
+                .with_aspects(aspects),
+            ),
             may_have_queries: false,
         }))
     }
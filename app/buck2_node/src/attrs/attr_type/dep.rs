@@ -20,6 +20,7 @@ use pagable::Pagable;
 use static_assertions::assert_eq_size;
 use strong_hash::StrongHash;
 
+use crate::aspect::AspectIds;
 use crate::attrs::attr_type::attr_like::AttrLike;
 use crate::attrs::configuration_context::AttrConfigurationContext;
 use crate::attrs::configured_attr::ConfiguredAttr;
@@ -51,9 +52,11 @@ pub struct DepAttrType {
     /// are present on each attribute value.
     pub required_providers: ProviderIdSet,
     pub transition: DepAttrTransition,
+    /// Aspects applied to the dependency before its providers are made available, in order.
+    pub aspects: AspectIds,
 }
 
-assert_eq_size!(DepAttrType, [usize; 3]);
+assert_eq_size!(DepAttrType, [usize; 4]);
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash, Allocative, Pagable, StrongHash)]
 pub struct DepAttr<T: ProvidersLabelMaybeConfigured + AttrLike> {
@@ -74,6 +77,9 @@ impl DepAttr<ConfiguredProvidersLabel> {
         &self,
         traversal: &mut dyn ConfiguredAttrTraversal,
     ) -> buck2_error::Result<()> {
+        if !self.attr_type.aspects.is_empty() {
+            traversal.aspects(&self.label, &self.attr_type.aspects)?;
+        }
         match &self.attr_type.transition {
             DepAttrTransition::Identity(plugins) if plugins.is_empty() => {
                 traversal.dep(&self.label)
@@ -106,9 +112,14 @@ impl DepAttrType {
         Self {
             required_providers,
             transition,
+            aspects: AspectIds::EMPTY,
         }
     }
 
+    pub fn with_aspects(self, aspects: AspectIds) -> Self {
+        Self { aspects, ..self }
+    }
+
     pub(crate) fn configure(
         &self,
         label: &ProvidersLabel,
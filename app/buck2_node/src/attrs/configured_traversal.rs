@@ -15,6 +15,7 @@ use buck2_core::provider::label::ConfiguredProvidersLabel;
 use buck2_core::provider::label::ProvidersLabel;
 use buck2_core::target::label::label::TargetLabel;
 
+use crate::aspect::AspectIds;
 use crate::attrs::attr_type::query::ResolvedQueryLiterals;
 
 pub trait ConfiguredAttrTraversal {
@@ -39,6 +40,15 @@ pub trait ConfiguredAttrTraversal {
         self.dep(dep)
     }
 
+    /// Called for deps which have aspects applied to them, in addition to `dep`.
+    fn aspects(
+        &mut self,
+        _dep: &ConfiguredProvidersLabel,
+        _aspects: &AspectIds,
+    ) -> buck2_error::Result<()> {
+        Ok(())
+    }
+
     fn configuration_dep(&mut self, _dep: &ProvidersLabel) -> buck2_error::Result<()> {
         Ok(())
     }
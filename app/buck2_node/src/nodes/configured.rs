@@ -41,6 +41,7 @@ use once_cell::sync::Lazy;
 use starlark_map::Hashed;
 use starlark_map::ordered_map::OrderedMap;
 
+use crate::aspect::AspectIds;
 use crate::attrs::attr::Attribute;
 use crate::attrs::attr_type::AttrType;
 use crate::attrs::attr_type::dep::DepAttr;
@@ -812,6 +813,36 @@ impl<'a> ConfiguredTargetNodeRef<'a> {
         traversal.queries.into_iter()
     }
 
+    /// Deps which have aspects applied to them, with the aspects applied.
+    pub fn aspect_deps(self) -> impl Iterator<Item = (ConfiguredProvidersLabel, AspectIds)> + 'a {
+        struct Traversal {
+            aspect_deps: Vec<(ConfiguredProvidersLabel, AspectIds)>,
+        }
+        let mut traversal = Traversal {
+            aspect_deps: Vec::new(),
+        };
+        impl ConfiguredAttrTraversal for Traversal {
+            fn dep(&mut self, _dep: &ConfiguredProvidersLabel) -> buck2_error::Result<()> {
+                // ignored.
+                Ok(())
+            }
+
+            fn aspects(
+                &mut self,
+                dep: &ConfiguredProvidersLabel,
+                aspects: &AspectIds,
+            ) -> buck2_error::Result<()> {
+                self.aspect_deps.push((dep.dupe(), aspects.dupe()));
+                Ok(())
+            }
+        }
+
+        for a in self.attrs(AttrInspectOptions::All) {
+            a.traverse(self.label().pkg(), &mut traversal).unwrap();
+        }
+        traversal.aspect_deps.into_iter()
+    }
+
     pub fn rule_type(self) -> &'a RuleType {
         self.0.get().target_node.rule_type()
     }
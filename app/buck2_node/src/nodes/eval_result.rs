@@ -246,6 +246,14 @@ impl MissingTargets {
     }
 }
 
+/// A `glob()` call made while evaluating a build file, with the number of files it matched.
+#[derive(Debug, Allocative)]
+pub struct GlobResult {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub match_count: usize,
+}
+
 /// An EvaluationResult contains the list of targets resulting from evaluating a build file.
 #[derive(Debug, Allocative)]
 pub struct EvaluationResult {
@@ -256,6 +264,8 @@ pub struct EvaluationResult {
     imports: Vec<ImportPath>,
     super_package: SuperPackage,
     targets: TargetsMap,
+    /// The `glob()` calls made by the build file, in evaluation order.
+    globs: Vec<GlobResult>,
     pub starlark_profile: Option<Arc<dyn StarlarkProfileDataAndStatsDyn>>,
 }
 
@@ -271,11 +281,16 @@ impl EvaluationResult {
             imports,
             super_package,
             targets,
+            globs: Vec::new(),
             // This is populated later when `Evaluator` is finalized.
             starlark_profile: None,
         }
     }
 
+    pub fn with_globs(self, globs: Vec<GlobResult>) -> Self {
+        Self { globs, ..self }
+    }
+
     pub fn buildfile_path(&self) -> &Arc<BuildFilePath> {
         &self.buildfile_path
     }
@@ -292,6 +307,10 @@ impl EvaluationResult {
         &self.imports
     }
 
+    pub fn globs(&self) -> &[GlobResult] {
+        &self.globs
+    }
+
     pub fn super_package(&self) -> &SuperPackage {
         &self.super_package
     }
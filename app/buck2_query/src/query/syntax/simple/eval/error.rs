@@ -55,6 +55,8 @@ pub enum QueryError {
         "Set operation requires either two set types, or one set and one string, got `{0}` and `{1}`"
     )]
     SetIncompatibleTypes(&'static str, &'static str),
+    #[error("unbound variable `${0}`, variables must be bound with `let` before use")]
+    UnboundVariable(String),
     /// Used to propagate up an inner error. The inner span will mark where the inner error was (which itself may be the
     /// propagation of another error). This error will end up in a Spanned that indicates where this error (the propagation) occurs.
     /// Since QueryError has an impl for `From<Spanned<QueryError>>`, just propagating inner eval errors via `?` will hit this case (and
@@ -10,12 +10,17 @@
 
 //! Implementation of the cli and query_* attr query language.
 
+use std::sync::Arc;
+
 use buck2_query_parser::Expr;
+use buck2_query_parser::SpannedExpr;
 use buck2_query_parser::parse_expr;
+use buck2_query_parser::span::Span;
 use buck2_query_parser::spanned::Spanned;
 use futures::FutureExt;
 use gazebo::prelude::*;
 use gazebo::variants::VariantName;
+use tokio::sync::OnceCell;
 
 use crate::__derive_refs::indexmap::IndexSet;
 use crate::query::environment::QueryEnvironment;
@@ -27,14 +32,93 @@ use crate::query::syntax::simple::eval::values::QueryEvaluationValue;
 use crate::query::syntax::simple::eval::values::QueryResult;
 use crate::query::syntax::simple::eval::values::QueryValue;
 use crate::query::syntax::simple::functions::QueryFunctions;
+
+enum QueryBindingKind<'a, Env: QueryEnvironment> {
+    /// `let name = value`. The value is evaluated the first time it is referenced and then shared
+    /// by all the other references, including those in captured expressions like the filter of
+    /// `deps()`.
+    Value {
+        expr: &'a SpannedExpr<'a>,
+        /// The functions available where the binding is defined. A reference from a captured
+        /// expression may see more of them, but the value must not depend on where it is used.
+        functions: &'a dyn QueryFunctions<Env = Env>,
+        value: OnceCell<QueryValue<Env::Target>>,
+    },
+    /// A parameter of a user-defined function, bound to the (already evaluated) argument.
+    Argument(QueryValue<Env::Target>),
+    /// `let name(params) = body`. The body is evaluated with the functions available where it is
+    /// called, so a function called from a captured expression can use e.g. `first_order_deps()`.
+    Function {
+        params: &'a [Span<'a>],
+        body: &'a SpannedExpr<'a>,
+    },
+}
+
+struct QueryBinding<'a, Env: QueryEnvironment> {
+    name: &'a str,
+    kind: QueryBindingKind<'a, Env>,
+    parent: QueryScope<'a, Env>,
+}
+
+/// The names bound by the `let` expressions enclosing the expression being evaluated. Each binding
+/// is evaluated in the scope of its parent, so bindings are lexically scoped.
+pub struct QueryScope<'a, Env: QueryEnvironment>(Option<Arc<QueryBinding<'a, Env>>>);
+
+impl<'a, Env: QueryEnvironment> Clone for QueryScope<'a, Env> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<'a, Env: QueryEnvironment> Default for QueryScope<'a, Env> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<'a, Env: QueryEnvironment> QueryScope<'a, Env> {
+    /// Variables and functions live in different namespaces, as they are referenced differently.
+    fn lookup(&self, name: &str, is_function: bool) -> Option<&QueryBinding<'a, Env>> {
+        let mut current = self.0.as_deref();
+        while let Some(binding) = current {
+            if binding.name == name
+                && matches!(binding.kind, QueryBindingKind::Function { .. }) == is_function
+            {
+                return Some(binding);
+            }
+            current = binding.parent.0.as_deref();
+        }
+        None
+    }
+}
+
 pub struct QueryEvaluator<'e, Env: QueryEnvironment> {
     env: &'e Env,
     functions: &'e dyn QueryFunctions<Env = Env>,
+    scope: QueryScope<'e, Env>,
 }
 
 impl<'e, Env: QueryEnvironment> QueryEvaluator<'e, Env> {
     pub fn new(env: &'e Env, functions: &'e dyn QueryFunctions<Env = Env>) -> Self {
-        Self { env, functions }
+        Self::new_in_scope(env, functions, QueryScope::default())
+    }
+
+    /// Creates an evaluator for an expression that was captured in `scope`.
+    pub(crate) fn new_in_scope(
+        env: &'e Env,
+        functions: &'e dyn QueryFunctions<Env = Env>,
+        scope: QueryScope<'e, Env>,
+    ) -> Self {
+        Self {
+            env,
+            functions,
+            scope,
+        }
+    }
+
+    /// The bindings in scope, for evaluating expressions captured by a function.
+    pub fn scope(&self) -> QueryScope<'e, Env> {
+        self.scope.clone()
     }
 
     pub fn env(&self) -> &Env {
@@ -49,7 +133,10 @@ impl<'e, Env: QueryEnvironment> QueryEvaluator<'e, Env> {
         self.env.eval_literals(&[literal]).await
     }
 
-    async fn eval_internal(&self, expr: &Expr<'_>) -> Result<QueryValue<Env::Target>, QueryError> {
+    async fn eval_internal<'a>(
+        &'a self,
+        expr: &'a Expr<'a>,
+    ) -> Result<QueryValue<Env::Target>, QueryError> {
         // TODO(cjhopman): We should extract these functions to a map of name->functionobj and attach
         // more information to them like documentation and signature. Potentially we could generalize
         // the function impls there to work across bxl and here, but not sure if that's worth the
@@ -60,12 +147,17 @@ impl<'e, Env: QueryEnvironment> QueryEvaluator<'e, Env> {
             Expr::Function {
                 function_name,
                 args,
-            } => match self.functions.get(function_name) {
-                Some(func) => func.invoke(self, args).await,
-                None => Err(QueryError::UnknownFunction(
-                    (*function_name.fragment()).to_owned(),
-                )),
-            },
+            } => {
+                if let Some(function) = self.scope.lookup(function_name, true) {
+                    return self.invoke_user_function(function, args).await;
+                }
+                match self.functions.get(function_name) {
+                    Some(func) => func.invoke(self, args).await,
+                    None => Err(QueryError::UnknownFunction(
+                        (*function_name.fragment()).to_owned(),
+                    )),
+                }
+            }
             Expr::BinaryOpSequence(left, exprs) => {
                 let (left, rights) = futures::future::try_join(
                     self.eval(left),
@@ -106,7 +198,101 @@ impl<'e, Env: QueryEnvironment> QueryEvaluator<'e, Env> {
 
                 Ok(files.into())
             }
+            Expr::Let {
+                name,
+                params,
+                value,
+                body,
+            } => {
+                let kind = match params {
+                    Some(params) => QueryBindingKind::Function {
+                        params,
+                        body: value,
+                    },
+                    None => QueryBindingKind::Value {
+                        expr: value,
+                        functions: self.functions,
+                        value: OnceCell::new(),
+                    },
+                };
+                let evaluator = QueryEvaluator::new_in_scope(
+                    self.env,
+                    self.functions,
+                    QueryScope(Some(Arc::new(QueryBinding {
+                        name: name.fragment(),
+                        kind,
+                        parent: self.scope.clone(),
+                    }))),
+                );
+                Ok(evaluator.eval(body).await?.value)
+            }
+            Expr::Variable(name) => {
+                let Some(binding) = self.scope.lookup(name, false) else {
+                    return Err(QueryError::UnboundVariable((*name.fragment()).to_owned()));
+                };
+                match &binding.kind {
+                    QueryBindingKind::Value {
+                        expr,
+                        functions,
+                        value,
+                    } => {
+                        let value = value
+                            .get_or_try_init(|| async {
+                                let evaluator = QueryEvaluator::new_in_scope(
+                                    self.env,
+                                    *functions,
+                                    binding.parent.clone(),
+                                );
+                                Ok::<_, QueryError>(evaluator.eval(expr).await?.value)
+                            })
+                            .await?;
+                        Ok(value.clone())
+                    }
+                    QueryBindingKind::Argument(value) => Ok(value.clone()),
+                    QueryBindingKind::Function { .. } => {
+                        unreachable!("functions are not returned by variable lookup")
+                    }
+                }
+            }
+        }
+    }
+
+    async fn invoke_user_function<'a>(
+        &'a self,
+        function: &'a QueryBinding<'e, Env>,
+        args: &'a [SpannedExpr<'a>],
+    ) -> Result<QueryValue<Env::Target>, QueryError> {
+        let QueryBindingKind::Function { params, body } = &function.kind else {
+            unreachable!("only functions are returned by function lookup")
+        };
+        if args.len() > params.len() {
+            return Err(QueryError::TooManyArgs {
+                function: function.name.to_owned(),
+                max: params.len(),
+                actual: args.len(),
+            });
+        }
+        if args.len() < params.len() {
+            return Err(QueryError::TooFewArgs {
+                function: function.name.to_owned(),
+                min: params.len(),
+                actual: args.len(),
+            });
+        }
+
+        let values =
+            buck2_util::future::try_join_all(args.iter().map(|arg| self.eval(arg))).await?;
+
+        let mut scope = function.parent.clone();
+        for (param, value) in params.iter().zip(values) {
+            scope = QueryScope(Some(Arc::new(QueryBinding {
+                name: param.fragment(),
+                kind: QueryBindingKind::Argument(value.value),
+                parent: scope,
+            })));
         }
+        let evaluator = QueryEvaluator::new_in_scope(self.env, self.functions, scope);
+        Ok(evaluator.eval(body).await?.value)
     }
 
     pub fn eval<'a>(
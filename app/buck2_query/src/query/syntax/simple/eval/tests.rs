@@ -13,6 +13,8 @@
 #![cfg(test)]
 
 use std::borrow::Cow;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 
 use async_trait::async_trait;
 use buck2_core::build_file_path::BuildFilePath;
@@ -24,13 +26,16 @@ use dupe::Dupe;
 
 use crate::query::environment::QueryEnvironment;
 use crate::query::environment::QueryTarget;
+use crate::query::environment::TraversalFilter;
 use crate::query::graph::node::LabeledNode;
 use crate::query::graph::node::NodeKey;
 use crate::query::graph::successors::AsyncChildVisitor;
 use crate::query::syntax::simple::eval::error::QueryError;
 use crate::query::syntax::simple::eval::evaluator::QueryEvaluator;
 use crate::query::syntax::simple::eval::file_set::FileSet;
+use crate::query::syntax::simple::eval::literals::extract_target_literals;
 use crate::query::syntax::simple::eval::set::TargetSet;
+use crate::query::syntax::simple::eval::values::QueryValue;
 use crate::query::syntax::simple::functions::DefaultQueryFunctionsModule;
 
 #[derive(Clone, Hash, PartialEq, Eq, Debug, Display)]
@@ -143,7 +148,11 @@ impl QueryTarget for Target {
     }
 }
 
-struct Env;
+#[derive(Default)]
+struct Env {
+    eval_literals_calls: AtomicUsize,
+}
+
 #[async_trait]
 impl QueryEnvironment for Env {
     type Target = Target;
@@ -163,7 +172,8 @@ impl QueryEnvironment for Env {
         &self,
         _literal: &[&str],
     ) -> buck2_error::Result<TargetSet<Self::Target>> {
-        unimplemented!()
+        self.eval_literals_calls.fetch_add(1, Ordering::SeqCst);
+        Ok(TargetSet::new())
     }
 
     async fn eval_file_literal(&self, _literal: &str) -> buck2_error::Result<FileSet> {
@@ -199,13 +209,25 @@ impl QueryEnvironment for Env {
     ) -> buck2_error::Result<TargetSet<Self::Target>> {
         unimplemented!()
     }
+
+    async fn deps(
+        &self,
+        _targets: &TargetSet<Self::Target>,
+        _depth: Option<i32>,
+        filter: Option<&dyn TraversalFilter<Self::Target>>,
+    ) -> buck2_error::Result<TargetSet<Self::Target>> {
+        // Evaluates the filter as if the roots had two deps.
+        let filter = filter.expect("expected a filter");
+        filter.get_children(&Target {}).await?;
+        filter.get_children(&Target {}).await
+    }
 }
 
 #[tokio::test]
 pub async fn test_missing_arg() -> buck2_error::Result<()> {
     let input = "kind(a, kind(a, kind()))";
     let parsed = parse_expr(input)?;
-    match QueryEvaluator::new(&Env, &DefaultQueryFunctionsModule::new())
+    match QueryEvaluator::new(&Env::default(), &DefaultQueryFunctionsModule::new())
         .eval(&parsed)
         .await
     {
@@ -221,3 +243,124 @@ pub async fn test_missing_arg() -> buck2_error::Result<()> {
     }
     Ok(())
 }
+
+#[tokio::test]
+pub async fn test_let() -> buck2_error::Result<()> {
+    let env = Env::default();
+    let functions = DefaultQueryFunctionsModule::new();
+    let evaluator = QueryEvaluator::new(&env, &functions);
+
+    let parsed = parse_expr("let x = foo in $x")?;
+    let value = evaluator
+        .eval(&parsed)
+        .await
+        .map_err(QueryError::drop_spans)?;
+    assert_eq!(QueryValue::String("foo".to_owned()), value.value);
+
+    // The innermost binding wins, and a value sees the bindings where it is defined.
+    let parsed = parse_expr("let x = 1 in let y = $x in let x = 2 in $y")?;
+    let value = evaluator
+        .eval(&parsed)
+        .await
+        .map_err(QueryError::drop_spans)?;
+    assert_eq!(QueryValue::Integer(1), value.value);
+
+    // A function body also sees the bindings where it is defined, plus its parameters.
+    let parsed = parse_expr("let x = 1 in let f(y) = $x in let x = 2 in f($x)")?;
+    let value = evaluator
+        .eval(&parsed)
+        .await
+        .map_err(QueryError::drop_spans)?;
+    assert_eq!(QueryValue::Integer(1), value.value);
+
+    let parsed = parse_expr("let f(a, b) = $b in f(1, 2)")?;
+    let value = evaluator
+        .eval(&parsed)
+        .await
+        .map_err(QueryError::drop_spans)?;
+    assert_eq!(QueryValue::Integer(2), value.value);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_let_value_is_evaluated_once() -> buck2_error::Result<()> {
+    let env = Env::default();
+    let functions = DefaultQueryFunctionsModule::new();
+    let parsed = parse_expr("let x = set(a b) in $x + $x ^ $x")?;
+    QueryEvaluator::new(&env, &functions)
+        .eval(&parsed)
+        .await
+        .map_err(QueryError::drop_spans)?;
+    assert_eq!(1, env.eval_literals_calls.load(Ordering::SeqCst));
+
+    // Unused bindings are never evaluated.
+    let parsed = parse_expr("let x = set(a b) in 1")?;
+    QueryEvaluator::new(&env, &functions)
+        .eval(&parsed)
+        .await
+        .map_err(QueryError::drop_spans)?;
+    assert_eq!(1, env.eval_literals_calls.load(Ordering::SeqCst));
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_let_errors() -> buck2_error::Result<()> {
+    let env = Env::default();
+    let functions = DefaultQueryFunctionsModule::new();
+    for (input, expected) in [
+        ("let x = a in f($x)", "unknown function `f`"),
+        ("let f(a) = $a in f(1, 2)", "too many args. function `f`"),
+        ("let f(a, b) = $a in f(1)", "too few args. function `f`"),
+        // A value is evaluated with the functions where it is bound, not where it is used.
+        (
+            "let x = first_order_deps() in deps(a, 1, $x)",
+            "unknown function `first_order_deps`",
+        ),
+    ] {
+        let parsed = parse_expr(input)?;
+        match QueryEvaluator::new(&env, &functions).eval(&parsed).await {
+            Ok(v) => panic!("expected error for `{input}`, got `{:?}`", v.value),
+            Err(e) => {
+                let msg = format!("{:#}", QueryError::convert_error(e, input));
+                assert!(
+                    msg.contains(expected),
+                    "`{msg}` should contain `{expected}`"
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_let_literals() -> buck2_error::Result<()> {
+    let functions = DefaultQueryFunctionsModule::<Env>::new();
+    let literals = extract_target_literals(
+        &functions,
+        "let x = //foo:bar in let r = 'baz' in let unused = //unused:target in kind($r, deps($x))",
+    )?;
+    assert_eq!(vec!["//foo:bar".to_owned()], literals);
+
+    let literals = extract_target_literals(&functions, "let x = a in deps(b, 1, $x)")?;
+    assert_eq!(vec!["b".to_owned()], literals);
+
+    let literals = extract_target_literals(&functions, "let f(a, b) = kind($a, $b) in f(x, y)")?;
+    assert_eq!(vec!["y".to_owned()], literals);
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_let_in_captured_expr() -> buck2_error::Result<()> {
+    let env = Env::default();
+    let functions = DefaultQueryFunctionsModule::new();
+    // The filter is evaluated for each dep, in the scope of the `deps()` call.
+    let parsed = parse_expr("let x = set(a b) in deps(c, 1, $x)")?;
+    QueryEvaluator::new(&env, &functions)
+        .eval(&parsed)
+        .await
+        .map_err(QueryError::drop_spans)?;
+    // Once for `c` and once for `$x`, which is shared by every evaluation of the filter.
+    assert_eq!(2, env.eval_literals_calls.load(Ordering::SeqCst));
+    Ok(())
+}
@@ -18,12 +18,14 @@ use buck2_core::configuration::compatibility::MaybeCompatible;
 use buck2_query_derive::query_module;
 use buck2_query_parser::BinaryOp;
 use buck2_query_parser::Expr;
+use buck2_query_parser::span::Span;
 use buck2_query_parser::spanned::Spanned;
 use gazebo::variants::VariantName;
 
 use crate::query::environment::QueryEnvironment;
 use crate::query::syntax::simple::eval::error::QueryError;
 use crate::query::syntax::simple::eval::evaluator::QueryEvaluator;
+use crate::query::syntax::simple::eval::evaluator::QueryScope;
 use crate::query::syntax::simple::eval::file_set::FileSet;
 use crate::query::syntax::simple::eval::set::TargetSet;
 use crate::query::syntax::simple::eval::values::QueryResult;
@@ -66,47 +68,121 @@ pub trait QueryFunctionsVisitLiterals: Debug + Send + Sync {
     ) -> QueryResult<()>;
 }
 
+/// The `let` bindings in scope while visiting literals. A variable is visited by visiting the
+/// expression bound to it at the place where the variable is referenced, so that a literal bound to
+/// a variable is only treated as a target pattern if the variable is used as one.
+enum LiteralScope<'s, 'q> {
+    Empty,
+    Binding {
+        name: &'q str,
+        binding: LiteralBinding<'s, 'q>,
+        parent: &'s LiteralScope<'s, 'q>,
+    },
+}
+
+enum LiteralBinding<'s, 'q> {
+    /// A `let` value or a function argument, along with the scope it is evaluated in.
+    Expr {
+        expr: &'s Spanned<Expr<'q>>,
+        scope: &'s LiteralScope<'s, 'q>,
+    },
+    Function {
+        params: &'s [Span<'q>],
+        body: &'s Spanned<Expr<'q>>,
+    },
+}
+
+impl<'s, 'q> LiteralScope<'s, 'q> {
+    /// Returns the binding for `name` along with the scope it was defined in.
+    fn lookup(
+        &'s self,
+        name: &str,
+        is_function: bool,
+    ) -> Option<(&'s LiteralBinding<'s, 'q>, &'s LiteralScope<'s, 'q>)> {
+        let mut current = self;
+        while let LiteralScope::Binding {
+            name: binding_name,
+            binding,
+            parent,
+        } = current
+        {
+            if *binding_name == name
+                && matches!(binding, LiteralBinding::Function { .. }) == is_function
+            {
+                return Some((binding, *parent));
+            }
+            current = *parent;
+        }
+        None
+    }
+}
+
 impl<F: QueryFunctions> QueryFunctionsVisitLiterals for F {
     fn visit_literals<'q>(
         &self,
         visitor: &mut dyn QueryLiteralVisitor<'q>,
         expr: &Spanned<Expr<'q>>,
     ) -> QueryResult<()> {
-        fn visit_literals_recurse<'q, F: QueryFunctions>(
+        fn visit_literals_recurse<'s, 'q, F: QueryFunctions>(
             this: &F,
             visitor: &mut dyn QueryLiteralVisitor<'q>,
-            expr: &Expr<'q>,
+            expr: &'s Expr<'q>,
+            scope: &'s LiteralScope<'s, 'q>,
         ) -> Result<(), QueryError> {
             match expr {
                 Expr::Function {
                     function_name,
                     args,
-                } => match this.get(function_name) {
-                    Some(func) => {
-                        for (i, arg) in args.iter().enumerate() {
-                            visit_literals_item(
-                                this,
-                                visitor,
-                                arg,
-                                matches!(
-                                    func.arg_type(i)?,
-                                    QueryArgType::TargetSet
-                                        | QueryArgType::Set
-                                        | QueryArgType::Value
-                                ),
-                            )?;
+                } => {
+                    if let Some((LiteralBinding::Function { params, body }, parent)) =
+                        scope.lookup(function_name, true)
+                    {
+                        if args.len() > params.len() {
+                            return Err(QueryError::TooManyArgs {
+                                function: (*function_name.fragment()).to_owned(),
+                                max: params.len(),
+                                actual: args.len(),
+                            });
+                        }
+                        if args.len() < params.len() {
+                            return Err(QueryError::TooFewArgs {
+                                function: (*function_name.fragment()).to_owned(),
+                                min: params.len(),
+                                actual: args.len(),
+                            });
                         }
-                        Ok(())
+                        return visit_function_body(
+                            this, visitor, params, args, body, parent, scope,
+                        );
                     }
-                    None => Err(QueryError::UnknownFunction(
-                        (*function_name.fragment()).to_owned(),
-                    )),
-                },
+                    match this.get(function_name) {
+                        Some(func) => {
+                            for (i, arg) in args.iter().enumerate() {
+                                visit_literals_item(
+                                    this,
+                                    visitor,
+                                    arg,
+                                    matches!(
+                                        func.arg_type(i)?,
+                                        QueryArgType::TargetSet
+                                            | QueryArgType::Set
+                                            | QueryArgType::Value
+                                    ),
+                                    scope,
+                                )?;
+                            }
+                            Ok(())
+                        }
+                        None => Err(QueryError::UnknownFunction(
+                            (*function_name.fragment()).to_owned(),
+                        )),
+                    }
+                }
                 Expr::BinaryOpSequence(left, exprs) => {
-                    visit_literals_item(this, visitor, left, true)?;
+                    visit_literals_item(this, visitor, left, true, scope)?;
                     // All binary ops are on targetsets currently.
                     for (_, right) in exprs {
-                        visit_literals_item(this, visitor, right, true)?;
+                        visit_literals_item(this, visitor, right, true, scope)?;
                     }
                     Ok(())
                 }
@@ -117,7 +193,28 @@ impl<F: QueryFunctions> QueryFunctionsVisitLiterals for F {
                     Ok(())
                 }
                 Expr::FileSet(_args) => Ok(()),
-                Expr::String(..) | Expr::Integer(..) => {
+                Expr::Let {
+                    name,
+                    params,
+                    value,
+                    body,
+                } => {
+                    let binding = match params {
+                        Some(params) => LiteralBinding::Function {
+                            params,
+                            body: value,
+                        },
+                        None => LiteralBinding::Expr { expr: value, scope },
+                    };
+                    let scope = LiteralScope::Binding {
+                        name: name.fragment(),
+                        binding,
+                        parent: scope,
+                    };
+                    // Like the value itself, the literals in an unused binding are never visited.
+                    visit_literals_item(this, visitor, body, true, &scope)
+                }
+                Expr::String(..) | Expr::Integer(..) | Expr::Variable(..) => {
                     panic!(
                         "This shouldn't be called with literals, they should be handled in the caller"
                     )
@@ -125,11 +222,40 @@ impl<F: QueryFunctions> QueryFunctionsVisitLiterals for F {
             }
         }
 
-        fn visit_literals_item<'q, F: QueryFunctions>(
+        /// Visits the body of a user-defined function with each parameter bound to the argument it
+        /// is called with, so that the arguments are visited wherever the parameters are used.
+        fn visit_function_body<'s, 'q, F: QueryFunctions>(
+            this: &F,
+            visitor: &mut dyn QueryLiteralVisitor<'q>,
+            params: &'s [Span<'q>],
+            args: &'s [Spanned<Expr<'q>>],
+            body: &'s Spanned<Expr<'q>>,
+            definition_scope: &'s LiteralScope<'s, 'q>,
+            call_scope: &'s LiteralScope<'s, 'q>,
+        ) -> Result<(), QueryError> {
+            match (params.split_first(), args.split_first()) {
+                (Some((param, params)), Some((arg, args))) => {
+                    let scope = LiteralScope::Binding {
+                        name: param.fragment(),
+                        binding: LiteralBinding::Expr {
+                            expr: arg,
+                            scope: call_scope,
+                        },
+                        parent: definition_scope,
+                    };
+                    visit_function_body(this, visitor, params, args, body, &scope, call_scope)
+                }
+                _ => visit_literals_item(this, visitor, body, true, definition_scope)
+                    .map_err(QueryError::from),
+            }
+        }
+
+        fn visit_literals_item<'s, 'q, F: QueryFunctions>(
             this: &F,
             visitor: &mut dyn QueryLiteralVisitor<'q>,
-            expr: &Spanned<Expr<'q>>,
+            expr: &'s Spanned<Expr<'q>>,
             is_target_expr: bool,
+            scope: &'s LiteralScope<'s, 'q>,
         ) -> QueryResult<()> {
             expr.map_res(|value| -> Result<(), QueryError> {
                 match value {
@@ -141,13 +267,21 @@ impl<F: QueryFunctions> QueryFunctionsVisitLiterals for F {
                     Expr::Integer(..) => {
                         // ignored
                     }
-                    _ => visit_literals_recurse(this, visitor, value)?,
+                    Expr::Variable(name) => match scope.lookup(name, false) {
+                        Some((LiteralBinding::Expr { expr, scope }, _)) => {
+                            visit_literals_item(this, visitor, expr, is_target_expr, scope)?
+                        }
+                        _ => {
+                            return Err(QueryError::UnboundVariable((*name.fragment()).to_owned()));
+                        }
+                    },
+                    _ => visit_literals_recurse(this, visitor, value, scope)?,
                 }
                 Ok(())
             })
         }
 
-        visit_literals_item(self, visitor, expr, true)
+        visit_literals_item(self, visitor, expr, true, &LiteralScope::Empty)
     }
 }
 
@@ -214,6 +348,7 @@ impl<Env: QueryEnvironment> DefaultQueryFunctionsModule<Env> {
             .allpaths(
                 evaluator.env(),
                 evaluator.functions(),
+                evaluator.scope(),
                 &from,
                 &to,
                 captured_expr.as_ref(),
@@ -253,6 +388,7 @@ impl<Env: QueryEnvironment> DefaultQueryFunctionsModule<Env> {
             .somepath(
                 evaluator.env(),
                 evaluator.functions(),
+                evaluator.scope(),
                 &from,
                 &to,
                 captured_expr.as_ref(),
@@ -429,6 +565,7 @@ impl<Env: QueryEnvironment> DefaultQueryFunctionsModule<Env> {
             .deps(
                 evaluator.env(),
                 evaluator.functions(),
+                evaluator.scope(),
                 &targets,
                 depth.map(|v| v as i32),
                 captured_expr.as_ref(),
@@ -572,6 +709,7 @@ impl<Env: QueryEnvironment> DefaultQueryFunctionsModule<Env> {
             .rdeps(
                 evaluator.env(),
                 evaluator.functions(),
+                evaluator.scope(),
                 &universe,
                 &targets,
                 depth.map(|v| v as i32),
@@ -739,6 +877,7 @@ impl<Env: QueryEnvironment> DefaultQueryFunctions<Env> {
         &self,
         env: &Env,
         functions: &dyn QueryFunctions<Env = Env>,
+        scope: QueryScope<'_, Env>,
         from: &TargetSet<Env::Target>,
         to: &TargetSet<Env::Target>,
         captured_expr: Option<&CapturedExpr<'_>>,
@@ -746,7 +885,7 @@ impl<Env: QueryEnvironment> DefaultQueryFunctions<Env> {
         Ok(DepsFunction::<Env> {
             _marker: PhantomData,
         }
-        .invoke_allpaths(env, functions, from, to, captured_expr)
+        .invoke_allpaths(env, functions, scope, from, to, captured_expr)
         .await?)
     }
 
@@ -754,6 +893,7 @@ impl<Env: QueryEnvironment> DefaultQueryFunctions<Env> {
         &self,
         env: &Env,
         functions: &dyn QueryFunctions<Env = Env>,
+        scope: QueryScope<'_, Env>,
         from: &TargetSet<Env::Target>,
         to: &TargetSet<Env::Target>,
         captured_expr: Option<&CapturedExpr<'_>>,
@@ -761,7 +901,7 @@ impl<Env: QueryEnvironment> DefaultQueryFunctions<Env> {
         Ok(DepsFunction::<Env> {
             _marker: PhantomData,
         }
-        .invoke_somepath(env, functions, from, to, captured_expr)
+        .invoke_somepath(env, functions, scope, from, to, captured_expr)
         .await?)
     }
 
@@ -817,6 +957,7 @@ impl<Env: QueryEnvironment> DefaultQueryFunctions<Env> {
         &self,
         env: &Env,
         functions: &dyn QueryFunctions<Env = Env>,
+        scope: QueryScope<'_, Env>,
         targets: &TargetSet<Env::Target>,
         depth: Option<i32>,
         captured_expr: Option<&CapturedExpr<'_>>,
@@ -824,7 +965,7 @@ impl<Env: QueryEnvironment> DefaultQueryFunctions<Env> {
         DepsFunction::<Env> {
             _marker: PhantomData,
         }
-        .invoke_deps(env, functions, targets, depth, captured_expr)
+        .invoke_deps(env, functions, scope, targets, depth, captured_expr)
         .await
     }
 
@@ -873,6 +1014,7 @@ impl<Env: QueryEnvironment> DefaultQueryFunctions<Env> {
         &self,
         env: &Env,
         functions: &dyn QueryFunctions<Env = Env>,
+        scope: QueryScope<'_, Env>,
         universe: &TargetSet<Env::Target>,
         targets: &TargetSet<Env::Target>,
         depth: Option<i32>,
@@ -881,7 +1023,15 @@ impl<Env: QueryEnvironment> DefaultQueryFunctions<Env> {
         DepsFunction::<Env> {
             _marker: PhantomData,
         }
-        .invoke_rdeps(env, functions, universe, targets, depth, captured_expr)
+        .invoke_rdeps(
+            env,
+            functions,
+            scope,
+            universe,
+            targets,
+            depth,
+            captured_expr,
+        )
         .await
     }
 
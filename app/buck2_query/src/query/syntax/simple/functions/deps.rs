@@ -21,6 +21,7 @@ use crate::query::environment::QueryTarget;
 use crate::query::environment::TraversalFilter;
 use crate::query::syntax::simple::eval::error::QueryError;
 use crate::query::syntax::simple::eval::evaluator::QueryEvaluator;
+use crate::query::syntax::simple::eval::evaluator::QueryScope;
 use crate::query::syntax::simple::eval::set::TargetSet;
 use crate::query::syntax::simple::eval::values::QueryEvaluationValue;
 use crate::query::syntax::simple::eval::values::QueryValue;
@@ -89,6 +90,8 @@ pub(crate) struct DepsFunction<Env: QueryEnvironment> {
 struct Filter<'a, Env: QueryEnvironment> {
     inner_env: &'a Env,
     functions: &'a dyn QueryFunctions<Env = Env>,
+    /// The `let` bindings in scope where the filter expression appears.
+    scope: QueryScope<'a, Env>,
     expr: &'a CapturedExpr<'a>,
 }
 
@@ -97,6 +100,7 @@ impl<'a, Env: QueryEnvironment> DepsFunction<Env> {
         &'a self,
         env: &'a Env,
         functions: &'a dyn QueryFunctions<Env = Env>,
+        scope: QueryScope<'a, Env>,
         captured_expr: Option<&'a CapturedExpr>,
     ) -> Option<Filter<'a, Env>> {
         match captured_expr {
@@ -109,7 +113,11 @@ impl<'a, Env: QueryEnvironment> DepsFunction<Env> {
                             self.functions,
                             Box::new(DepsContextFunctions { target }),
                         );
-                        let evaluator = QueryEvaluator::new(self.inner_env, &augmented_functions);
+                        let evaluator = QueryEvaluator::new_in_scope(
+                            self.inner_env,
+                            &augmented_functions,
+                            self.scope.clone(),
+                        );
                         match evaluator.eval_parsed_query(self.expr.expr).await {
                             Ok(v) => match v.value {
                                 QueryEvaluationValue::TargetSet(v) => Ok(v),
@@ -127,6 +135,7 @@ impl<'a, Env: QueryEnvironment> DepsFunction<Env> {
                 Some(Filter::<'a, Env> {
                     inner_env: &env,
                     functions,
+                    scope,
                     expr,
                 })
             }
@@ -138,11 +147,12 @@ impl<'a, Env: QueryEnvironment> DepsFunction<Env> {
         &self,
         env: &Env,
         functions: &dyn QueryFunctions<Env = Env>,
+        scope: QueryScope<'_, Env>,
         targets: &TargetSet<Env::Target>,
         depth: Option<i32>,
         captured_expr: Option<&CapturedExpr<'_>>,
     ) -> buck2_error::Result<TargetSet<Env::Target>> {
-        let filter = self.make_filter(&env, functions, captured_expr);
+        let filter = self.make_filter(&env, functions, scope, captured_expr);
         let filter_ref = filter
             .as_ref()
             .map(|v| v as &dyn TraversalFilter<Env::Target>);
@@ -154,12 +164,13 @@ impl<'a, Env: QueryEnvironment> DepsFunction<Env> {
         &self,
         env: &Env,
         functions: &dyn QueryFunctions<Env = Env>,
+        scope: QueryScope<'_, Env>,
         universe: &TargetSet<Env::Target>,
         from: &TargetSet<Env::Target>,
         depth: Option<i32>,
         captured_expr: Option<&CapturedExpr<'_>>,
     ) -> buck2_error::Result<TargetSet<Env::Target>> {
-        let filter = self.make_filter(&env, functions, captured_expr);
+        let filter = self.make_filter(&env, functions, scope, captured_expr);
         let filter_ref = filter
             .as_ref()
             .map(|v| v as &dyn TraversalFilter<Env::Target>);
@@ -171,11 +182,12 @@ impl<'a, Env: QueryEnvironment> DepsFunction<Env> {
         &self,
         env: &Env,
         functions: &dyn QueryFunctions<Env = Env>,
+        scope: QueryScope<'_, Env>,
         from: &TargetSet<Env::Target>,
         to: &TargetSet<Env::Target>,
         captured_expr: Option<&CapturedExpr<'_>>,
     ) -> buck2_error::Result<TargetSet<Env::Target>> {
-        let filter = self.make_filter(&env, functions, captured_expr);
+        let filter = self.make_filter(&env, functions, scope, captured_expr);
         let filter_ref = filter
             .as_ref()
             .map(|v| v as &dyn TraversalFilter<Env::Target>);
@@ -187,11 +199,12 @@ impl<'a, Env: QueryEnvironment> DepsFunction<Env> {
         &self,
         env: &Env,
         functions: &dyn QueryFunctions<Env = Env>,
+        scope: QueryScope<'_, Env>,
         from: &TargetSet<Env::Target>,
         to: &TargetSet<Env::Target>,
         captured_expr: Option<&CapturedExpr<'_>>,
     ) -> buck2_error::Result<TargetSet<Env::Target>> {
-        let filter = self.make_filter(&env, functions, captured_expr);
+        let filter = self.make_filter(&env, functions, scope, captured_expr);
         let filter_ref = filter
             .as_ref()
             .map(|v| v as &dyn TraversalFilter<Env::Target>);
@@ -24,6 +24,7 @@ use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
 use buck2_core::global_cfg_options::GlobalCfgOptions;
 use buck2_core::provider::label::ConfiguredProvidersLabel;
 use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
+use buck2_query::query::syntax::simple::eval::evaluator::QueryScope;
 use buck2_query::query::syntax::simple::eval::file_set::FileSet;
 use buck2_query::query::syntax::simple::eval::set::TargetSet;
 use buck2_query::query::syntax::simple::eval::values::QueryValue;
@@ -107,6 +108,7 @@ impl BxlAqueryFunctions for BxlAqueryFunctionsImpl {
                 .allpaths(
                     &self.aquery_env(&self.aquery_delegate(&dice).await?).await?,
                     &DefaultQueryFunctionsModule::new(),
+                    QueryScope::default(),
                     from,
                     to,
                     captured_expr,
@@ -127,6 +129,7 @@ impl BxlAqueryFunctions for BxlAqueryFunctionsImpl {
                 .somepath(
                     &self.aquery_env(&self.aquery_delegate(&dice).await?).await?,
                     &DefaultQueryFunctionsModule::new(),
+                    QueryScope::default(),
                     from,
                     to,
                     captured_expr,
@@ -148,6 +151,7 @@ impl BxlAqueryFunctions for BxlAqueryFunctionsImpl {
                     .deps(
                         &self.aquery_env(&self.aquery_delegate(&dice).await?).await?,
                         &DefaultQueryFunctionsModule::new(),
+                        QueryScope::default(),
                         targets,
                         deps,
                         captured_expr,
@@ -170,6 +174,7 @@ impl BxlAqueryFunctions for BxlAqueryFunctionsImpl {
                     .rdeps(
                         &self.aquery_env(&self.aquery_delegate(&dice).await?).await?,
                         &DefaultQueryFunctionsModule::new(),
+                        QueryScope::default(),
                         universe,
                         targets,
                         depth,
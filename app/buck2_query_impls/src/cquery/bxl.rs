@@ -21,6 +21,7 @@ use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
 use buck2_core::global_cfg_options::GlobalCfgOptions;
 use buck2_node::configured_universe::CqueryUniverse;
 use buck2_node::nodes::configured::ConfiguredTargetNode;
+use buck2_query::query::syntax::simple::eval::evaluator::QueryScope;
 use buck2_query::query::syntax::simple::eval::file_set::FileSet;
 use buck2_query::query::syntax::simple::eval::set::TargetSet;
 use buck2_query::query::syntax::simple::functions::DefaultQueryFunctions;
@@ -105,6 +106,7 @@ impl BxlCqueryFunctions for BxlCqueryFunctionsImpl {
                                 .cquery_env(&self.setup_dice_query_delegate(&dice).await?, None)
                                 .await?,
                             &DefaultQueryFunctionsModule::new(),
+                            QueryScope::default(),
                             from,
                             to,
                             captured_expr,
@@ -131,6 +133,7 @@ impl BxlCqueryFunctions for BxlCqueryFunctionsImpl {
                                 .cquery_env(&self.setup_dice_query_delegate(&dice).await?, None)
                                 .await?,
                             &DefaultQueryFunctionsModule::new(),
+                            QueryScope::default(),
                             from,
                             to,
                             captured_expr,
@@ -171,6 +174,7 @@ impl BxlCqueryFunctions for BxlCqueryFunctionsImpl {
                             .cquery_env(&self.setup_dice_query_delegate(&dice).await?, None)
                             .await?,
                         &DefaultQueryFunctionsModule::new(),
+                        QueryScope::default(),
                         targets,
                         deps,
                         captured_expr,
@@ -196,6 +200,7 @@ impl BxlCqueryFunctions for BxlCqueryFunctionsImpl {
                             .cquery_env(&self.setup_dice_query_delegate(&dice).await?, None)
                             .await?,
                         &DefaultQueryFunctionsModule::new(),
+                        QueryScope::default(),
                         universe,
                         targets,
                         depth,
@@ -19,6 +19,7 @@ use buck2_core::fs::project::ProjectRoot;
 use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
 use buck2_core::global_cfg_options::GlobalCfgOptions;
 use buck2_node::nodes::unconfigured::TargetNode;
+use buck2_query::query::syntax::simple::eval::evaluator::QueryScope;
 use buck2_query::query::syntax::simple::eval::file_set::FileSet;
 use buck2_query::query::syntax::simple::eval::set::TargetSet;
 use buck2_query::query::syntax::simple::functions::DefaultQueryFunctions;
@@ -87,6 +88,7 @@ impl BxlUqueryFunctions for BxlUqueryFunctionsImpl {
                 .allpaths(
                     &self.uquery_env(&self.uquery_delegate(&dice).await?).await?,
                     &DefaultQueryFunctionsModule::new(),
+                    QueryScope::default(),
                     from,
                     to,
                     captured_expr,
@@ -107,6 +109,7 @@ impl BxlUqueryFunctions for BxlUqueryFunctionsImpl {
                 .somepath(
                     &self.uquery_env(&self.uquery_delegate(&dice).await?).await?,
                     &DefaultQueryFunctionsModule::new(),
+                    QueryScope::default(),
                     from,
                     to,
                     captured_expr,
@@ -128,6 +131,7 @@ impl BxlUqueryFunctions for BxlUqueryFunctionsImpl {
                     .deps(
                         &self.uquery_env(&self.uquery_delegate(&dice).await?).await?,
                         &DefaultQueryFunctionsModule::new(),
+                        QueryScope::default(),
                         targets,
                         deps,
                         captured_expr,
@@ -150,6 +154,7 @@ impl BxlUqueryFunctions for BxlUqueryFunctionsImpl {
                     .rdeps(
                         &self.uquery_env(&self.uquery_delegate(&dice).await?).await?,
                         &DefaultQueryFunctionsModule::new(),
+                        QueryScope::default(),
                         universe,
                         targets,
                         depth,
@@ -32,6 +32,8 @@
 //!        | EXPR ' + ' EXPR
 //!        | EXPR ' except ' EXPR
 //!        | EXPR ' - ' EXPR
+//!        | 'let' NAME ( '(' NAME ( ',' NAME ) * ')' ) ? '=' EXPR 'in' EXPR
+//!        | '$' NAME
 //!
 //! # word is much broader than a normal identifier-like thing would allow since we don't want to require
 //! # quoting targets "@fbcode//some:target" or common regexes ".*" or filenames "Foo.java".
@@ -43,7 +45,17 @@
 //! INTEGER ::= "0" | ("1-9" "0-9"*)
 //!
 //! FUNCTION_NAME ::= "a-zA-Z_" "a-zA-Z0-9_" *
+//!
+//! NAME ::= "a-zA-Z_" "a-zA-Z0-9_" *
 //! ```
+//!
+//! The body of a `let` extends as far to the right as possible, so `let x = a in $x + b` binds `x`
+//! in `$x + b`. A `let` with parameters defines a function that can be called like the builtin
+//! ones within its body, and its parameters are variables within its value.
+//!
+//! `let` is only a keyword when followed by `NAME =` or `NAME(...) =`, and `$NAME` is only a
+//! variable where `NAME` is bound. Elsewhere both are parsed as words, as they were before `let`
+//! was added.
 
 pub mod multi_query;
 pub mod placeholder;
@@ -70,6 +82,8 @@ use nom::character::complete::multispace0;
 use nom::character::complete::multispace1;
 use nom::combinator::all_consuming;
 use nom::combinator::cut;
+use nom::combinator::not;
+use nom::combinator::opt;
 use nom::combinator::recognize;
 use nom::error::ErrorKind;
 use nom::error::context;
@@ -86,8 +100,6 @@ use nom_language::error::convert_error;
 use crate::span::Span;
 use crate::spanned::Spanned;
 
-// TODO(cjhopman): Add `LET WORD = expr IN expr`
-
 // TODO(cjhopman): We should switch to our own error type here. VerboseError doesn't even allow us to construct
 // our own error messages (so, for example, we can't have a good error message for too large integers) and doesn't
 // support propagating anyhow or std errors (and since we can't do a custom message, we can't even capture them as a string).
@@ -126,6 +138,16 @@ pub enum Expr<'a> {
     BinaryOpSequence(Box<SpannedExpr<'a>>, Vec<(BinaryOp, SpannedExpr<'a>)>),
     Set(Vec<Span<'a>>),
     FileSet(Vec<Span<'a>>),
+    /// `let name = value in body`, or `let name(params) = value in body` to define a function.
+    Let {
+        name: Span<'a>,
+        params: Option<Vec<Span<'a>>>,
+        value: Box<SpannedExpr<'a>>,
+        body: Box<SpannedExpr<'a>>,
+    },
+    /// A reference to a variable bound by an enclosing `let` (or a function parameter), written as
+    /// `$name`.
+    Variable(Span<'a>),
 }
 
 impl Display for Expr<'_> {
@@ -182,6 +204,26 @@ impl Display for Expr<'_> {
                 }
                 f.write_str(")")?;
             }
+            Expr::Let {
+                name,
+                params,
+                value,
+                body,
+            } => {
+                write!(f, "let {}", name.fragment())?;
+                if let Some(params) = params {
+                    f.write_str("(")?;
+                    for (i, v) in params.iter().enumerate() {
+                        if i != 0 {
+                            f.write_str(", ")?;
+                        }
+                        f.write_str(v.fragment())?;
+                    }
+                    f.write_str(")")?;
+                }
+                write!(f, " = {value} in {body}")?;
+            }
+            Expr::Variable(name) => write!(f, "${}", name.fragment())?,
         }
         Ok(())
     }
@@ -242,7 +284,10 @@ pub fn parse_expr(input: &str) -> buck2_error::Result<SpannedExpr<'_>> {
     // Parse with fast error (`()`) first,
     // and on error reparse again with `VerboseError` to get detailed errors.
     match all_consuming(expr).parse(span) {
-        Ok((_, value)) => Ok(value),
+        Ok((_, mut value)) => {
+            resolve_variables(input, &mut value, &mut Vec::new());
+            Ok(value)
+        }
         Err(nom::Err::Failure(())) | Err(nom::Err::Error(())) => {
             match all_consuming(expr).parse(span) {
                 Ok(..) => unreachable!(
@@ -264,6 +309,55 @@ pub fn parse_expr(input: &str) -> buck2_error::Result<SpannedExpr<'_>> {
     }
 }
 
+/// Turns each `$name` that is not in the body of a `let` binding `name` (or in the value of a
+/// function with a parameter `name`) back into the word it was parsed as before `let` existed.
+fn resolve_variables<'a>(input: &'a str, expr: &mut SpannedExpr<'a>, bound: &mut Vec<&'a str>) {
+    match &mut expr.value {
+        Expr::Variable(name) => {
+            if !bound.contains(&name.fragment()) {
+                expr.value = Expr::String(&input[expr.position.clone()]);
+            }
+        }
+        Expr::Let {
+            name,
+            params: None,
+            value,
+            body,
+        } => {
+            resolve_variables(input, value, bound);
+            bound.push(name.fragment());
+            resolve_variables(input, body, bound);
+            bound.pop();
+        }
+        Expr::Let {
+            params: Some(params),
+            value,
+            body,
+            ..
+        } => {
+            // Functions are called rather than referenced with `$`, so only the parameters are
+            // bound, and only within the value.
+            let len = bound.len();
+            bound.extend(params.iter().map(|param| param.fragment()));
+            resolve_variables(input, value, bound);
+            bound.truncate(len);
+            resolve_variables(input, body, bound);
+        }
+        Expr::Function { args, .. } => {
+            for arg in args {
+                resolve_variables(input, arg, bound);
+            }
+        }
+        Expr::BinaryOpSequence(left, exprs) => {
+            resolve_variables(input, left, bound);
+            for (_, right) in exprs {
+                resolve_variables(input, right, bound);
+            }
+        }
+        Expr::String(..) | Expr::Integer(..) | Expr::Set(..) | Expr::FileSet(..) => {}
+    }
+}
+
 // Parses a non-infix op expression. This is split out so that we can parse a sequence of infix operators without recursion.
 fn single_expr<'a, E: NomParseError<'a>>(input: Span<'a>) -> NomResult<'a, SpannedExpr<'a>, E> {
     // The ordering here is a little important, the first three of these all have a pattern of identifying
@@ -278,8 +372,10 @@ fn single_expr<'a, E: NomParseError<'a>>(input: Span<'a>) -> NomResult<'a, Spann
         preceded(char('('), cut(terminated(expr, char(')')))),
         expr_set,
         expr_fileset,
+        expr_let,
         expr_function,
         expr_int,
+        expr_variable,
         expr_word,
     ))
     .parse(input)?;
@@ -439,6 +535,67 @@ fn expr_fileset<'a, E: NomParseError<'a>>(input: Span<'a>) -> NomResult<'a, Span
     .parse(input)
 }
 
+fn name<'a, E: NomParseError<'a>>(input: Span<'a>) -> NomResult<'a, Span<'a>, E> {
+    recognize(pair(
+        alt((alpha1, tag("_"))),
+        many0(alt((alphanumeric1, tag("_")))),
+    ))
+    .parse(input)
+}
+
+/// Tries to parse an Expr::Let. Will fail if it detects an unfinished "let name = " or
+/// "let name(params) = "
+fn expr_let<'a, E: NomParseError<'a>>(input: Span<'a>) -> NomResult<'a, SpannedExpr<'a>, E> {
+    fn let_params<'a, E: NomParseError<'a>>(input: Span<'a>) -> NomResult<'a, Vec<Span<'a>>, E> {
+        delimited(
+            terminated(char('('), multispace0),
+            separated_list0(delimited(multispace0, char(','), multispace0), name),
+            preceded(multispace0, char(')')),
+        )
+        .parse(input)
+    }
+
+    spanned(|input| {
+        let (input, (name, params)) = delimited(
+            terminated(tag("let"), multispace1),
+            pair(name, opt(let_params)),
+            delimited(multispace0, char('='), multispace0),
+        )
+        .parse(input)?;
+        cut(move |input| {
+            let (input, value) = expr(input)?;
+            let (input, _) =
+                context("let `in`", terminated(tag("in"), multispace1)).parse(input)?;
+            let (input, body) = expr(input)?;
+            Ok((
+                input,
+                Expr::Let {
+                    name,
+                    params,
+                    value: Box::new(value),
+                    body: Box::new(body),
+                },
+            ))
+        })
+        .parse(input)
+    })
+    .parse(input)
+}
+
+/// Tries to parse an Expr::Variable. Anything that isn't exactly "$name" is left for `expr_word`, so
+/// words like "$" or "$foo.*" keep their meaning. See `resolve_variables` for unbound names.
+fn expr_variable<'a, E: NomParseError<'a>>(input: Span<'a>) -> NomResult<'a, SpannedExpr<'a>, E> {
+    spanned(|input| {
+        let (input, name) = preceded(
+            char('$'),
+            terminated(name, not(alt((alphanumeric1, is_a("*/@.-_:$#%"))))),
+        )
+        .parse(input)?;
+        Ok((input, Expr::Variable(name)))
+    })
+    .parse(input)
+}
+
 /// Tries to parse an Expr::Function. Will fail if it detects an unfinished "func("
 // We don't need to worry about "set(" as the outermost expr() ensures that never gets to here.
 fn expr_function<'a, E: NomParseError<'a>>(input: Span<'a>) -> NomResult<'a, SpannedExpr<'a>, E> {
@@ -449,11 +606,7 @@ fn expr_function<'a, E: NomParseError<'a>>(input: Span<'a>) -> NomResult<'a, Spa
     }
 
     spanned(|input| {
-        let (input, function_name) = recognize(pair(
-            alt((alpha1, tag("_"))),
-            many0(alt((alphanumeric1, tag("_")))),
-        ))
-        .parse(input)?;
+        let (input, function_name) = name(input)?;
         let (input, _) = char('(').parse(input)?;
         cut(move |input| {
             let (input, args) = terminated(function_args, char(')')).parse(input)?;
@@ -643,6 +796,154 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_let() -> buck2_error::Result<()> {
+        run_tests(
+            expr_let,
+            &[
+                "let x = a in $x",
+                "let x=deps(a) in $x + rdeps(//..., $x)",
+                "let x = let y = a in $y in $x",
+                "let f(a) = deps($a, 1) in f(//foo:bar)",
+                "let f( a , b ) = $a ^ $b in f(x, y)",
+            ],
+            // As long as we don't match "let name =" or "let name(params) =", it should be
+            // recoverable
+            &[
+                "let",
+                "letter",
+                "let(a)",
+                "let + a",
+                "let x",
+                "let = a in b",
+                "let x(a = b in c",
+                "",
+                " let x = a in $x",
+            ],
+            // An error after "let name =" is non-recoverable
+            &["let x = a", "let x = a in", "let x = in b", "let f(a) = b"],
+        );
+
+        match parse_expr("let x = a in $x + b") {
+            Ok(Spanned {
+                value: Expr::Let {
+                    name, params, body, ..
+                },
+                ..
+            }) => {
+                assert_eq!("x", name.fragment());
+                assert!(params.is_none());
+                assert!(matches!(body.value, Expr::BinaryOpSequence(..)));
+            }
+            v => panic!("expected let expr, got `{v:?}`"),
+        }
+
+        // Parameters are variables in the function's value, but not in the body of the `let`.
+        match parse_expr("let f(a, b) = $a in f(x, $a)") {
+            Ok(Spanned {
+                value:
+                    Expr::Let {
+                        params: Some(params),
+                        value,
+                        body,
+                        ..
+                    },
+                ..
+            }) => {
+                assert_eq!(vec!["a", "b"], params.map(|p| p.fragment()));
+                assert!(matches!(value.value, Expr::Variable(..)));
+                match body.value {
+                    Expr::Function { args, .. } => {
+                        assert!(matches!(args[1].value, Expr::String("$a")));
+                    }
+                    v => panic!("expected function expr, got `{v:?}`"),
+                }
+            }
+            v => panic!("expected let expr, got `{v:?}`"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_variable() -> buck2_error::Result<()> {
+        run_tests(
+            expr_variable,
+            &["$x", "$_foo1"],
+            &["x", "$", "$1", "$x.*", "$x:y"],
+            &[],
+        );
+
+        // Words that merely start with `$` are still words.
+        match parse_expr("kind($x.*, a)") {
+            Ok(Spanned {
+                value: Expr::Function { args, .. },
+                ..
+            }) => {
+                assert!(matches!(args[0].value, Expr::String("$x.*")));
+            }
+            v => panic!("expected function expr, got `{v:?}`"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_let_words_still_accepted() -> buck2_error::Result<()> {
+        // Queries that used `let` or `$name` as words before `let` existed parse as they did.
+        for word in ["let", "letter", "$x", "$", "regex$", "'$x'"] {
+            match parse_expr(word) {
+                Ok(Spanned {
+                    value: Expr::String(parsed),
+                    ..
+                }) => assert_eq!(word.trim_matches('\''), parsed),
+                v => panic!("expected word for `{word}`, got `{v:?}`"),
+            }
+        }
+
+        match parse_expr("let + $x") {
+            Ok(Spanned {
+                value: Expr::BinaryOpSequence(left, right),
+                ..
+            }) => {
+                assert!(matches!(left.value, Expr::String("let")));
+                assert!(matches!(right[0].1.value, Expr::String("$x")));
+            }
+            v => panic!("expected union expr, got `{v:?}`"),
+        }
+
+        match parse_expr("attrfilter(name, $x, let)") {
+            Ok(Spanned {
+                value: Expr::Function { args, .. },
+                ..
+            }) => {
+                assert!(matches!(args[1].value, Expr::String("$x")));
+                assert!(matches!(args[2].value, Expr::String("let")));
+            }
+            v => panic!("expected function expr, got `{v:?}`"),
+        }
+
+        // `$name` is only a variable where `name` is bound, which excludes the bound value itself.
+        match parse_expr("let x = $x in $x + $y") {
+            Ok(Spanned {
+                value: Expr::Let { value, body, .. },
+                ..
+            }) => {
+                assert!(matches!(value.value, Expr::String("$x")));
+                match body.value {
+                    Expr::BinaryOpSequence(left, right) => {
+                        assert!(matches!(left.value, Expr::Variable(..)));
+                        assert!(matches!(right[0].1.value, Expr::String("$y")));
+                    }
+                    v => panic!("expected union expr, got `{v:?}`"),
+                }
+            }
+            v => panic!("expected let expr, got `{v:?}`"),
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn test_trailing_infix() -> buck2_error::Result<()> {
         run_tests(
@@ -13,6 +13,9 @@ use std::collections::HashSet;
 use std::sync::Arc;
 
 use buck2_event_observer::dice_state::DiceState;
+use buck2_event_observer::display::TargetDisplayOptions;
+use buck2_event_observer::display::display_action_key;
+use buck2_event_observer::display::display_configured_target_label;
 use buck2_event_observer::pending_estimate::pending_estimate;
 use buck2_event_observer::span_tracker;
 use buck2_event_observer::span_tracker::RootData;
@@ -25,11 +28,23 @@ use dupe::Dupe;
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 use parking_lot::MutexGuard;
+use tokio::sync::broadcast;
 use tokio::sync::oneshot;
 
 static ACTIVE_COMMANDS: Lazy<Mutex<HashMap<TraceId, ActiveCommandHandle>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+/// Events of all active commands that subscriptions may be interested in. Nothing is sent
+/// unless a subscription is listening.
+static COMMAND_EVENTS: Lazy<broadcast::Sender<Arc<buck2_subscription_proto::CommandEvent>>> =
+    Lazy::new(|| broadcast::channel(4096).0);
+
+/// Receive action, test and completion events of all active commands.
+pub fn subscribe_to_command_events()
+-> broadcast::Receiver<Arc<buck2_subscription_proto::CommandEvent>> {
+    COMMAND_EVENTS.subscribe()
+}
+
 /// Return the active commands, if you can access them.
 pub fn try_active_commands() -> Option<HashMap<TraceId, ActiveCommandHandle>> {
     // Note that this function is accessed during panic, so have to be super careful
@@ -103,6 +118,9 @@ pub struct ActiveCommandState {
     #[allow(unused)]
     pub argv: Vec<String>,
 
+    /// The command name reported by the client, e.g. `build`.
+    pub command_name: String,
+
     spans: Mutex<SpansSnapshot>,
 }
 
@@ -111,9 +129,10 @@ impl ActiveCommandState {
         *self.spans.lock()
     }
 
-    fn new(argv: Vec<String>) -> Self {
+    fn new(argv: Vec<String>, command_name: String) -> Self {
         Self {
             argv,
+            command_name,
             spans: Mutex::new(SpansSnapshot::default()),
         }
     }
@@ -150,6 +169,13 @@ impl ActiveCommandStateWriter {
     pub fn peek_event(&mut self, buck_event: &BuckEvent) {
         use buck2_data::buck_event::Data::*;
 
+        if COMMAND_EVENTS.receiver_count() > 0 {
+            if let Some(event) = command_event(buck_event) {
+                // Nothing to do if all subscriptions went away in the meantime.
+                let _ignored = COMMAND_EVENTS.send(Arc::new(event));
+            }
+        }
+
         let mut changed = false;
 
         match buck_event.data() {
@@ -215,6 +241,88 @@ impl ActiveCommandStateWriter {
     }
 }
 
+/// Converts the events that `SubscribeToCommandEvents` exposes.
+pub(crate) fn command_event(
+    buck_event: &BuckEvent,
+) -> Option<buck2_subscription_proto::CommandEvent> {
+    use buck2_data::buck_event::Data;
+    use buck2_subscription_proto::command_event::Event;
+
+    fn action(
+        key: Option<&buck2_data::ActionKey>,
+        name: Option<&buck2_data::ActionName>,
+    ) -> buck2_subscription_proto::Action {
+        buck2_subscription_proto::Action {
+            owner: key
+                .and_then(|key| display_action_key(key, TargetDisplayOptions::for_log()).ok())
+                .unwrap_or_default(),
+            category: name.map(|n| n.category.clone()).unwrap_or_default(),
+            identifier: name.map(|n| n.identifier.clone()).unwrap_or_default(),
+        }
+    }
+
+    fn micros(duration: Option<&prost_types::Duration>) -> u64 {
+        duration
+            .and_then(|d| std::time::Duration::try_from(d.clone()).ok())
+            .map_or(0, |d| d.as_micros() as u64)
+    }
+
+    let event: Event = match buck_event.data() {
+        Data::SpanStart(start) => match start.data.as_ref()? {
+            buck2_data::span_start_event::Data::ActionExecution(start) => {
+                buck2_subscription_proto::ActionStarted {
+                    action: Some(action(start.key.as_ref(), start.name.as_ref())),
+                }
+                .into()
+            }
+            _ => return None,
+        },
+        Data::SpanEnd(end) => match end.data.as_ref()? {
+            buck2_data::span_end_event::Data::ActionExecution(end) => {
+                buck2_subscription_proto::ActionFinished {
+                    action: Some(action(end.key.as_ref(), end.name.as_ref())),
+                    failed: end.failed,
+                    execution_kind: end.execution_kind().as_str_name().to_owned(),
+                    wall_time_us: micros(end.wall_time.as_ref()),
+                }
+                .into()
+            }
+            buck2_data::span_end_event::Data::Command(end) => {
+                buck2_subscription_proto::CommandFinished {
+                    success: end.is_success,
+                }
+                .into()
+            }
+            _ => return None,
+        },
+        Data::Instant(instant) => match instant.data.as_ref()? {
+            buck2_data::instant_event::Data::TestResult(result) => {
+                buck2_subscription_proto::TestResult {
+                    target: result
+                        .target_label
+                        .as_ref()
+                        .and_then(|label| {
+                            display_configured_target_label(label, TargetDisplayOptions::for_log())
+                                .ok()
+                        })
+                        .unwrap_or_default(),
+                    name: result.name.clone(),
+                    status: result.status().as_str_name().to_owned(),
+                    duration_us: micros(result.duration.as_ref()),
+                }
+                .into()
+            }
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    Some(buck2_subscription_proto::CommandEvent {
+        trace_id: buck_event.trace_id().ok()?.to_string(),
+        event: Some(event),
+    })
+}
+
 pub struct ActiveCommand {
     pub guard: ActiveCommandDropGuard,
     pub state: ActiveCommandStateWriter,
@@ -222,10 +330,14 @@ pub struct ActiveCommand {
 }
 
 impl ActiveCommand {
-    pub fn new(event_dispatcher: &EventDispatcher, sanitized_argv: Vec<String>) -> Self {
+    pub fn new(
+        event_dispatcher: &EventDispatcher,
+        sanitized_argv: Vec<String>,
+        command_name: String,
+    ) -> Self {
         let (sender, receiver) = oneshot::channel();
 
-        let state = Arc::new(ActiveCommandState::new(sanitized_argv));
+        let state = Arc::new(ActiveCommandState::new(sanitized_argv, command_name));
 
         let trace_id = event_dispatcher.trace_id().dupe();
         let result = {
@@ -284,6 +396,66 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_command_event() {
+        let trace = TraceId::new();
+        let mut receiver = subscribe_to_command_events();
+        let mut writer =
+            ActiveCommandStateWriter::new(Arc::new(ActiveCommandState::new(Vec::new())));
+
+        writer.peek_event(&BuckEvent::new(
+            SystemTime::now(),
+            trace.clone(),
+            Some(SpanId::next()),
+            None,
+            buck2_data::SpanEndEvent {
+                data: Some(
+                    buck2_data::ActionExecutionEnd {
+                        name: Some(buck2_data::ActionName {
+                            category: "cxx_compile".to_owned(),
+                            identifier: "foo.cpp".to_owned(),
+                        }),
+                        failed: true,
+                        ..Default::default()
+                    }
+                    .into(),
+                ),
+                ..Default::default()
+            }
+            .into(),
+        ));
+        // Not something subscriptions care about.
+        writer.peek_event(&BuckEvent::new(
+            SystemTime::now(),
+            trace.clone(),
+            Some(SpanId::next()),
+            None,
+            buck2_data::SpanStartEvent {
+                data: Some(buck2_data::AnalysisStart::default().into()),
+            }
+            .into(),
+        ));
+
+        // Other tests may run commands concurrently, so only look at ours.
+        let event = loop {
+            let event = receiver.try_recv().unwrap();
+            if event.trace_id == trace.to_string() {
+                break event;
+            }
+        };
+        assert_matches!(
+            &event.event,
+            Some(buck2_subscription_proto::command_event::Event::ActionFinished(finished)) => {
+                assert!(finished.failed);
+                assert_eq!(finished.action.as_ref().unwrap().category, "cxx_compile");
+                assert_eq!(finished.action.as_ref().unwrap().identifier, "foo.cpp");
+            }
+        );
+        while let Ok(event) = receiver.try_recv() {
+            assert_ne!(event.trace_id, trace.to_string());
+        }
+    }
+
     #[test]
     fn test_active_command_state() {
         let mut writer =
@@ -448,16 +620,16 @@ mod tests {
     #[test]
     fn test_multiple_active_commands() {
         let (dispatcher1, mut source1, id1) = create_dispatcher();
-        let _active1 = ActiveCommand::new(&dispatcher1, Vec::new());
+        let _active1 = ActiveCommand::new(&dispatcher1, Vec::new(), String::new());
 
         let (dispatcher2, mut source2, id2) = create_dispatcher();
-        let _active2 = ActiveCommand::new(&dispatcher2, Vec::new());
+        let _active2 = ActiveCommand::new(&dispatcher2, Vec::new(), String::new());
 
         check_concurrent_command_trace_ids_eq(source1.try_receive(), &[id2.to_string()]);
         check_concurrent_command_trace_ids_eq(source2.try_receive(), &[id1.to_string()]);
 
         let (dispatcher3, mut source3, id3) = create_dispatcher();
-        let _active3 = ActiveCommand::new(&dispatcher3, Vec::new());
+        let _active3 = ActiveCommand::new(&dispatcher3, Vec::new(), String::new());
 
         check_concurrent_command_trace_ids_eq(source1.try_receive(), &[id3.to_string()]);
         check_concurrent_command_trace_ids_eq(source2.try_receive(), &[id3.to_string()]);
@@ -58,6 +58,9 @@ use buck2_common::legacy_configs::configs::LegacyBuckConfig;
 use buck2_common::legacy_configs::dice::HasInjectedLegacyConfigs;
 use buck2_common::legacy_configs::file_ops::ConfigPath;
 use buck2_common::legacy_configs::key::BuckconfigKeyRef;
+use buck2_common::lockfile::LOCKFILE;
+use buck2_common::lockfile::Lockfile;
+use buck2_common::lockfile::SetLockfile;
 use buck2_configured::cycle::ConfiguredGraphCycleDescriptor;
 use buck2_core::execution_types::executor_config::CommandExecutorConfig;
 use buck2_core::execution_types::executor_config::RemoteExecutorUseCase;
@@ -71,6 +74,7 @@ use buck2_core::pattern::pattern_type::ConfiguredProvidersPatternExtra;
 use buck2_core::rollout_percentage::RolloutPercentage;
 use buck2_core::target::label::interner::ConcurrentTargetLabelInterner;
 use buck2_directory::directory::dashmap_directory_interner::DashMapDirectoryInterner;
+use buck2_error::BuckErrorContext;
 use buck2_events::dispatch::EventDispatcher;
 use buck2_events::metadata;
 use buck2_events::schedule_type::SandcastleScheduleType;
@@ -78,6 +82,8 @@ use buck2_execute::execute::blocking::SetBlockingExecutor;
 use buck2_execute::knobs::ExecutorGlobalKnobs;
 use buck2_execute::materialize::materializer::Materializer;
 use buck2_execute::materialize::materializer::SetMaterializer;
+use buck2_execute::output_size::OutputSizeBudgets;
+use buck2_execute::output_size::parse_output_size;
 use buck2_execute::re::client::RemoteExecutionClient;
 use buck2_execute::re::manager::ReConnectionHandle;
 use buck2_execute::re::manager::ReConnectionObserver;
@@ -125,6 +131,7 @@ use dupe::Dupe;
 use gazebo::prelude::SliceExt;
 use host_sharing::HostSharingBroker;
 use host_sharing::HostSharingStrategy;
+use sorted_vector_map::SortedVectorMap;
 use tracing::warn;
 
 use crate::active_commands::ActiveCommandDropGuard;
@@ -208,11 +215,14 @@ pub struct ServerCommandContext<'a> {
 
     record_target_call_stacks: bool,
     skip_targets_with_duplicate_names: bool,
+    strict_globs: bool,
     disable_starlark_types: bool,
     unstable_typecheck: bool,
 
     pub buck_out_dir: ProjectRelativePathBuf,
     isolation_prefix: FileNameBuf,
+    /// Paths of the daemon this command runs in (logs, build history, ...).
+    pub(crate) paths: InvocationPaths,
 
     /// Common build options associated with this command.
     build_options: Option<CommonBuildOptions>,
@@ -345,9 +355,11 @@ impl<'a> ServerCommandContext<'a> {
             starlark_profiling_manager,
             buck_out_dir: paths.buck_out_dir(),
             isolation_prefix: paths.isolation.clone(),
+            paths: paths.clone(),
             build_options: build_options.cloned(),
             record_target_call_stacks: client_context.target_call_stacks,
             skip_targets_with_duplicate_names: client_context.skip_targets_with_duplicate_names,
+            strict_globs: client_context.strict_globs,
             disable_starlark_types: client_context.disable_starlark_types,
             unstable_typecheck: client_context.unstable_typecheck,
             heartbeat_guard_handle: Some(heartbeat_guard_handle),
@@ -365,6 +377,7 @@ impl<'a> ServerCommandContext<'a> {
     async fn dice_updater<'s>(
         &'s self,
         build_signals: BuildSignalsInstaller,
+        checks_lockfile: bool,
     ) -> buck2_error::Result<DiceCommandUpdater<'s, 'a>> {
         let execution_strategy = self
             .build_options
@@ -402,6 +415,7 @@ impl<'a> ServerCommandContext<'a> {
             action_paths_interner: None,
             deduplicate_get_digests_ttl_calls: false,
             re_outputs_required: false,
+            output_size_budgets: OutputSizeBudgets::default(),
         };
 
         let concurrency = self
@@ -427,6 +441,7 @@ impl<'a> ServerCommandContext<'a> {
 
         Ok(DiceCommandUpdater {
             cmd_ctx: self,
+            checks_lockfile,
             execution_strategy,
             run_action_knobs,
             concurrency,
@@ -548,6 +563,7 @@ impl ServerCommandContext<'_> {
 
 struct DiceCommandUpdater<'s, 'a: 's> {
     cmd_ctx: &'s ServerCommandContext<'a>,
+    checks_lockfile: bool,
     execution_strategy: ExecutionStrategy,
     concurrency: Option<usize>,
     executor_config: Arc<CommandExecutorConfig>,
@@ -591,6 +607,7 @@ impl DiceUpdater for DiceCommandUpdater<'_, '_> {
             self.interpreter_xcode_version.clone(),
             self.cmd_ctx.record_target_call_stacks,
             self.cmd_ctx.skip_targets_with_duplicate_names,
+            self.cmd_ctx.strict_globs,
             None,
             // New interner for each transaction.
             Arc::new(ConcurrentTargetLabelInterner::default()),
@@ -606,6 +623,13 @@ impl DiceUpdater for DiceCommandUpdater<'_, '_> {
 
         ctx.set_enabled_optional_validations(optional_validations)?;
 
+        let lockfile = if self.checks_lockfile {
+            read_lockfile(self.cmd_ctx.base_context.daemon.io.project_root())?
+        } else {
+            None
+        };
+        ctx.set_lockfile(lockfile)?;
+
         let profiler_instrumentation_override =
             &self.cmd_ctx.starlark_profiling_manager.configuration;
 
@@ -636,6 +660,44 @@ impl DiceUpdater for DiceCommandUpdater<'_, '_> {
     }
 }
 
+/// Reads the `[output_size_budgets]` section, which maps action categories (or `default`) to the
+/// maximum total size of an action's outputs.
+fn output_size_budgets(root_config: &LegacyBuckConfig) -> buck2_error::Result<OutputSizeBudgets> {
+    let mut budgets = OutputSizeBudgets {
+        enforce: root_config
+            .parse::<bool>(BuckconfigKeyRef {
+                section: "buck2",
+                property: "enforce_output_size_budgets",
+            })?
+            .unwrap_or(false),
+        ..OutputSizeBudgets::default()
+    };
+    let Some(section) = root_config.get_section("output_size_budgets") else {
+        return Ok(budgets);
+    };
+    let mut by_category = SortedVectorMap::new();
+    for (category, size) in section.iter() {
+        let size = parse_output_size(size.as_str())
+            .with_buck_error_context(|| format!("Invalid `output_size_budgets.{category}`"))?;
+        if category == "default" {
+            budgets.default = Some(size);
+        } else {
+            by_category.insert(category.to_owned(), size);
+        }
+    }
+    budgets.by_category = Arc::new(by_category);
+    Ok(budgets)
+}
+
+/// Reads `buck2.lock` from the project root, if present.
+fn read_lockfile(project_root: &ProjectRoot) -> buck2_error::Result<Option<Lockfile>> {
+    let path = project_root.resolve(ProjectRelativePath::unchecked_new(LOCKFILE));
+    match fs_util::read_to_string_if_exists(&path)? {
+        Some(contents) => Ok(Some(Lockfile::parse(&contents)?)),
+        None => Ok(None),
+    }
+}
+
 impl DiceCommandUpdater<'_, '_> {
     fn make_user_computation_data(
         &self,
@@ -779,6 +841,8 @@ impl DiceCommandUpdater<'_, '_> {
             })?
             .unwrap_or(false);
 
+        run_action_knobs.output_size_budgets = output_size_budgets(root_config)?;
+
         let output_trees_download_semaphore_size = root_config.parse::<u32>(BuckconfigKeyRef {
             section: "buck2",
             property: "output_trees_download_semaphore_size",
@@ -824,6 +888,13 @@ impl DiceCommandUpdater<'_, '_> {
             })?
             .unwrap_or(CriticalPathBackendName::LongestPathGraph);
 
+        let log_critical_path_graph = root_config
+            .parse(BuckconfigKeyRef {
+                section: "buck2",
+                property: "log_critical_path_graph",
+            })?
+            .unwrap_or(false);
+
         let override_use_case = root_config.parse::<RemoteExecutorUseCase>(BuckconfigKeyRef {
             section: "buck2_re_client",
             property: "override_use_case",
@@ -882,6 +953,7 @@ impl DiceCommandUpdater<'_, '_> {
         );
         data.set_keep_going(self.keep_going);
         data.set_critical_path_backend(critical_path_backend);
+        data.set_log_critical_path_graph(log_critical_path_graph);
         data.init_local_resource_registry();
         data.init_bxl_streaming_tracker();
         initialize_read_dir_cache(&mut data);
@@ -1036,6 +1108,7 @@ impl ServerCommandContextTrait for ServerCommandContext<'_> {
     async fn dice_accessor<'s>(
         &'s self,
         _private: PrivateStruct,
+        checks_lockfile: bool,
     ) -> buck2_error::Result<DiceAccessor<'s>> {
         let (build_signals_installer, deferred_build_signals) = create_build_signals();
 
@@ -1047,7 +1120,10 @@ impl ServerCommandContextTrait for ServerCommandContext<'_> {
 
         Ok(DiceAccessor {
             dice_handler: self.base_context.daemon.dice_manager.dupe(),
-            setup: Box::new(self.dice_updater(build_signals_installer).await?),
+            setup: Box::new(
+                self.dice_updater(build_signals_installer, checks_lockfile)
+                    .await?,
+            ),
             is_nested_invocation,
             sanitized_argv: self.sanitized_argv.clone(),
             preemptible: self.preemptible,
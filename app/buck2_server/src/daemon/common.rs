@@ -51,6 +51,7 @@ use buck2_execute_impl::executors::action_cache::ActionCacheChecker;
 use buck2_execute_impl::executors::action_cache::RemoteDepFileCacheChecker;
 use buck2_execute_impl::executors::action_cache_upload_permission_checker::ActionCacheUploadPermissionChecker;
 use buck2_execute_impl::executors::caching::CacheUploader;
+use buck2_execute_impl::executors::external::ExternalExecutorPool;
 use buck2_execute_impl::executors::hybrid::FallbackTracker;
 use buck2_execute_impl::executors::hybrid::HybridExecutor;
 use buck2_execute_impl::executors::local::ForkserverAccess;
@@ -91,6 +92,8 @@ pub struct CommandExecutorFactory {
     materialize_failed_outputs: bool,
     /// Cache permission checks per command.
     cache_upload_permission_checker: Arc<ActionCacheUploadPermissionChecker>,
+    /// Connections to external executors, shared by all actions using the same socket.
+    external_executors: Arc<ExternalExecutorPool>,
     fallback_tracker: Arc<FallbackTracker>,
     re_use_case_override: Option<RemoteExecutorUseCase>,
     memory_tracker: Option<MemoryTrackerHandle>,
@@ -145,6 +148,7 @@ impl CommandExecutorFactory {
             materialize_failed_inputs,
             materialize_failed_outputs,
             cache_upload_permission_checker,
+            external_executors: Arc::new(ExternalExecutorPool::default()),
             fallback_tracker: Arc::new(FallbackTracker::new()),
             re_use_case_override,
             memory_tracker,
@@ -263,6 +267,30 @@ impl HasCommandExecutor for CommandExecutorFactory {
                     })
                 }
             }
+            Executor::External(external) => {
+                if self.strategy.ban_local() {
+                    // The command runs wherever the executor chooses, but Buck2 still materializes
+                    // its inputs and reads its outputs locally, so this counts as local execution.
+                    return Err(buck2_error::buck2_error!(
+                        buck2_error::ErrorTag::Input,
+                        "The desired execution strategy (`{:?}`) is incompatible with the external executor at `{}`, which counts as local execution",
+                        self.strategy,
+                        external.socket,
+                    ));
+                }
+                let executor = local_executor_new(&LocalExecutorOptions {
+                    use_persistent_workers: false,
+                })
+                .with_external_executor(self.external_executors.get(&external.socket));
+                Some(CommandExecutorResponse {
+                    executor: Arc::new(executor),
+                    platform: Default::default(),
+                    action_cache_checker: Arc::new(NoOpCommandOptionalExecutor {}),
+                    remote_dep_file_cache_checker: Arc::new(NoOpCommandOptionalExecutor {}),
+                    cache_uploader: Arc::new(NoOpCacheUploader {}),
+                    output_trees_download_config: self.output_trees_download_config.dupe(),
+                })
+            }
             Executor::RemoteEnabled(remote_options) => {
                 // NOTE: While we now have a legit flag for this, we keep the env var. This has been used
                 // in remediating prod incidents in the past, and this is the kind of thing that can easily
@@ -116,6 +116,7 @@ use crate::daemon::server_allocative::spawn_allocative;
 use crate::daemon::state::DaemonState;
 use crate::file_status::file_status_command;
 use crate::lsp::run_lsp_server_command;
+use crate::metrics_endpoint::spawn_metrics_endpoint;
 use crate::new_generic::new_generic_command;
 use crate::profile::profile_command;
 use crate::profiling_manager::StarlarkProfilingManager;
@@ -286,6 +287,8 @@ impl BuckdServer {
         let cert_state = CertState::new().await;
         certs_validation_background_job(cert_state.dupe()).await;
 
+        let metrics_address = init_ctx.daemon_startup_config.metrics_address.clone();
+
         let daemon_state = Arc::new(
             DaemonState::new(
                 fb,
@@ -300,6 +303,10 @@ impl BuckdServer {
             .await?,
         );
 
+        if let Some(metrics_address) = metrics_address {
+            spawn_metrics_endpoint(&metrics_address, daemon_state.dupe()).await;
+        }
+
         #[cfg(fbcode_build)]
         {
             let root_path =
@@ -448,7 +455,11 @@ impl BuckdServer {
             guard,
             daemon_shutdown_channel,
             state,
-        } = ActiveCommand::new(&dispatch, client_ctx.sanitized_argv.clone());
+        } = ActiveCommand::new(
+            &dispatch,
+            client_ctx.sanitized_argv.clone(),
+            client_ctx.command_name.clone(),
+        );
         let data = daemon_state.data();
 
         // Fire off a system-wide event to record the memory usage of this process.
@@ -1342,7 +1353,11 @@ impl DaemonApi for BuckdServer {
             let client_ctx = req.get_ref().client_context()?;
             let trace_id = client_ctx.trace_id.parse()?;
             let (event_source, dispatcher) = self.0.daemon_state.prepare_events(trace_id).await?;
-            let active_command = ActiveCommand::new(&dispatcher, client_ctx.sanitized_argv.clone());
+            let active_command = ActiveCommand::new(
+                &dispatcher,
+                client_ctx.sanitized_argv.clone(),
+                client_ctx.command_name.clone(),
+            );
             (event_source, dispatcher, active_command)
         };
 
@@ -23,8 +23,10 @@ mod file_status;
 mod heartbeat_guard;
 mod host_info;
 mod jemalloc_stats;
+mod local_http;
 pub mod lsp;
 mod materialize;
+mod metrics_endpoint;
 mod net_io;
 pub(crate) mod new_generic;
 pub mod profile;
@@ -33,4 +35,5 @@ pub mod profiling_manager;
 mod snapshot;
 mod subscription;
 mod trace_io;
+mod ui;
 mod version_control_revision;
@@ -0,0 +1,169 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! HTTP server for the daemon's local endpoints, on top of hyper. Every connection serves a single
+//! request and is then closed.
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use buck2_error::BuckErrorContext;
+use bytes::Bytes;
+use http::Request;
+use http::Response;
+use http::StatusCode;
+use http::header;
+use http_body_util::Full;
+use hyper::body::Incoming;
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpListener;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub(crate) struct HttpRequest {
+    pub(crate) method: String,
+    pub(crate) path: String,
+    query: String,
+}
+
+impl HttpRequest {
+    fn from_hyper(request: &Request<Incoming>) -> Self {
+        Self {
+            method: request.method().as_str().to_owned(),
+            path: request.uri().path().to_owned(),
+            query: request.uri().query().unwrap_or_default().to_owned(),
+        }
+    }
+
+    /// Returns the value of a query parameter. Values are not percent-decoded.
+    pub(crate) fn query_param(&self, name: &str) -> Option<&str> {
+        self.query.split('&').find_map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (key == name).then_some(value)
+        })
+    }
+}
+
+pub(crate) struct HttpResponse {
+    status: StatusCode,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub(crate) fn ok(content_type: &'static str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status: StatusCode::OK,
+            content_type,
+            body: body.into(),
+        }
+    }
+
+    pub(crate) fn json(value: &impl serde::Serialize) -> Self {
+        match serde_json::to_vec(value) {
+            Ok(body) => Self::ok("application/json", body),
+            Err(e) => Self::error(StatusCode::INTERNAL_SERVER_ERROR, format!("{e}")),
+        }
+    }
+
+    pub(crate) fn error(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: message.into().into_bytes(),
+        }
+    }
+
+    pub(crate) fn not_found() -> Self {
+        Self::error(StatusCode::NOT_FOUND, "")
+    }
+
+    pub(crate) fn method_not_allowed() -> Self {
+        Self::error(StatusCode::METHOD_NOT_ALLOWED, "")
+    }
+
+    fn into_hyper(self) -> Response<Full<Bytes>> {
+        let mut response = Response::new(Full::new(Bytes::from(self.body)));
+        *response.status_mut() = self.status;
+        let headers = response.headers_mut();
+        headers.insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static(self.content_type),
+        );
+        headers.insert(
+            header::CACHE_CONTROL,
+            header::HeaderValue::from_static("no-store"),
+        );
+        response
+    }
+}
+
+pub(crate) async fn bind(address: SocketAddr) -> buck2_error::Result<TcpListener> {
+    TcpListener::bind(address)
+        .await
+        .with_buck_error_context(|| format!("Error binding to `{address}`"))
+}
+
+/// Serves requests from `listener` with `handler` on a background task, until the daemon exits.
+pub(crate) fn spawn_server<H, F>(listener: TcpListener, handler: H)
+where
+    H: Fn(HttpRequest) -> F + Clone + Send + Sync + 'static,
+    F: Future<Output = HttpResponse> + Send + 'static,
+{
+    tokio::task::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    tracing::debug!("Error accepting local HTTP connection: {:#}", e);
+                    continue;
+                }
+            };
+            let handler = handler.clone();
+            tokio::task::spawn(async move {
+                let service = hyper::service::service_fn(move |request: Request<Incoming>| {
+                    let handler = handler.clone();
+                    async move {
+                        let response = handler(HttpRequest::from_hyper(&request)).await;
+                        Ok::<_, Infallible>(response.into_hyper())
+                    }
+                });
+                let connection = hyper::server::conn::http1::Builder::new()
+                    .keep_alive(false)
+                    .serve_connection(TokioIo::new(stream), service);
+                match tokio::time::timeout(REQUEST_TIMEOUT, connection).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => tracing::debug!("Error serving local HTTP request: {:#}", e),
+                    Err(_) => tracing::debug!("Timed out serving local HTTP request"),
+                }
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_param() {
+        let request = HttpRequest {
+            method: "GET".to_owned(),
+            path: "/api/command".to_owned(),
+            query: "trace_id=abc&token=t&flag".to_owned(),
+        };
+        assert_eq!(request.query_param("trace_id"), Some("abc"));
+        assert_eq!(request.query_param("token"), Some("t"));
+        assert_eq!(request.query_param("flag"), Some(""));
+        assert_eq!(request.query_param("missing"), None);
+    }
+}
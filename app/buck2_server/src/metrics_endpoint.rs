@@ -0,0 +1,426 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! Opt-in HTTP endpoint serving daemon metrics in the OpenMetrics text format, so that
+//! daemons on shared machines can be scraped by Prometheus.
+//!
+//! Enabled by setting `buck2.metrics_address` (e.g. `127.0.0.1:9464`).
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use buck2_core::soft_error;
+use buck2_error::buck2_error;
+use dupe::Dupe;
+
+use crate::active_commands::active_commands;
+use crate::daemon::state::DaemonState;
+use crate::local_http;
+use crate::local_http::HttpResponse;
+use crate::snapshot::SnapshotCollector;
+
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Binds `address` and serves metrics from a background task for the lifetime of the daemon.
+///
+/// Failing to bind (e.g. because another daemon on the machine uses the same port) is reported
+/// but does not prevent the daemon from starting.
+pub(crate) async fn spawn_metrics_endpoint(address: &str, daemon_state: Arc<DaemonState>) {
+    let listener = match bind(address).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            soft_error!("metrics_endpoint_bind_failed", e, quiet: false).ok();
+            return;
+        }
+    };
+
+    // Reuse a single collector so that host CPU usage accumulates across scrapes.
+    let collector = SnapshotCollector::new(daemon_state.data(), daemon_state.paths.buck_out_path());
+
+    local_http::spawn_server(listener, move |request| {
+        let collector = collector.dupe();
+        async move {
+            match (request.method.as_str(), request.path.as_str()) {
+                ("GET", "/metrics") => {
+                    let snapshot = collector.create_snapshot().await;
+                    HttpResponse::ok(CONTENT_TYPE, render(&snapshot, &active_command_counts()))
+                }
+                ("GET", _) => HttpResponse::not_found(),
+                _ => HttpResponse::method_not_allowed(),
+            }
+        }
+    });
+}
+
+async fn bind(address: &str) -> buck2_error::Result<tokio::net::TcpListener> {
+    let address: SocketAddr = address.parse().map_err(|_| {
+        buck2_error!(
+            buck2_error::ErrorTag::Input,
+            "Invalid `buck2.metrics_address`: `{}`, expected `<ip>:<port>`",
+            address
+        )
+    })?;
+    local_http::bind(address).await
+}
+
+/// Number of running commands, by command name (e.g. `build`).
+fn active_command_counts() -> BTreeMap<String, u64> {
+    let mut counts = BTreeMap::new();
+    for command in active_commands().values() {
+        let name = match command.state().command_name.as_str() {
+            "" => "unknown",
+            name => name,
+        };
+        *counts.entry(name.to_owned()).or_default() += 1;
+    }
+    counts
+}
+
+/// Writes metric families in the OpenMetrics text format.
+struct OpenMetricsWriter {
+    out: String,
+}
+
+impl OpenMetricsWriter {
+    fn new() -> Self {
+        Self { out: String::new() }
+    }
+
+    fn family<'a, V: Display>(
+        &mut self,
+        name: &str,
+        help: &str,
+        kind: &str,
+        samples: impl IntoIterator<Item = (Vec<(&'a str, &'a str)>, V)>,
+    ) {
+        // Counter samples carry a `_total` suffix, but the family name does not.
+        let suffix = if kind == "counter" { "_total" } else { "" };
+        writeln!(self.out, "# TYPE buck2_{name} {kind}").unwrap();
+        writeln!(self.out, "# HELP buck2_{name} {help}").unwrap();
+        for (labels, value) in samples {
+            write!(self.out, "buck2_{name}{suffix}").unwrap();
+            if !labels.is_empty() {
+                self.out.push('{');
+                for (i, (label, label_value)) in labels.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push(',');
+                    }
+                    write!(self.out, "{label}=\"{}\"", escape_label_value(label_value)).unwrap();
+                }
+                self.out.push('}');
+            }
+            writeln!(self.out, " {value}").unwrap();
+        }
+    }
+
+    fn gauge(&mut self, name: &str, help: &str, value: impl Display) {
+        self.family(name, help, "gauge", [(Vec::new(), value)]);
+    }
+
+    fn counter(&mut self, name: &str, help: &str, value: impl Display) {
+        self.family(name, help, "counter", [(Vec::new(), value)]);
+    }
+
+    fn finish(mut self) -> String {
+        self.out.push_str("# EOF\n");
+        self.out
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn micros_to_seconds(us: u64) -> f64 {
+    us as f64 / 1_000_000.0
+}
+
+fn render(snapshot: &buck2_data::Snapshot, active_commands: &BTreeMap<String, u64>) -> String {
+    let mut w = OpenMetricsWriter::new();
+
+    w.gauge(
+        "daemon_uptime_seconds",
+        "Time since the daemon started.",
+        snapshot.daemon_uptime_s,
+    );
+    w.family(
+        "active_commands",
+        "Commands currently running on the daemon.",
+        "gauge",
+        active_commands
+            .iter()
+            .map(|(command, count)| (vec![("command", command.as_str())], *count))
+            .collect::<Vec<_>>(),
+    );
+
+    // Memory and CPU.
+    if let Some(rss) = snapshot.buck2_rss {
+        w.gauge("rss_bytes", "Resident set size of the daemon.", rss);
+    }
+    w.gauge(
+        "max_rss_bytes",
+        "Peak resident set size of the daemon.",
+        snapshot.buck2_max_rss,
+    );
+    if let Some(active) = snapshot.malloc_bytes_active {
+        w.gauge(
+            "malloc_active_bytes",
+            "Bytes in active pages allocated by the allocator.",
+            active,
+        );
+    }
+    if let Some(allocated) = snapshot.malloc_bytes_allocated {
+        w.gauge(
+            "malloc_allocated_bytes",
+            "Bytes allocated by the application.",
+            allocated,
+        );
+    }
+    w.counter(
+        "cpu_user_seconds",
+        "User CPU time consumed by the daemon.",
+        micros_to_seconds(snapshot.buck2_user_cpu_us),
+    );
+    w.counter(
+        "cpu_system_seconds",
+        "System CPU time consumed by the daemon.",
+        micros_to_seconds(snapshot.buck2_system_cpu_us),
+    );
+    if let Some(used) = snapshot.used_disk_space_bytes {
+        w.gauge(
+            "used_disk_space_bytes",
+            "Used space on the disk holding buck-out.",
+            used,
+        );
+    }
+
+    // DICE.
+    w.gauge(
+        "dice_keys",
+        "Keys stored in the DICE graph.",
+        snapshot.dice_key_count,
+    );
+    w.gauge(
+        "dice_active_keys",
+        "DICE keys currently being computed.",
+        snapshot.dice_currently_active_key_count,
+    );
+    w.gauge(
+        "dice_active_transactions",
+        "Open DICE transactions.",
+        snapshot.dice_active_transaction_count,
+    );
+
+    // Queues.
+    w.gauge(
+        "blocking_executor_io_queue_size",
+        "IO operations waiting for the blocking executor.",
+        snapshot.blocking_executor_io_queue_size,
+    );
+    w.gauge(
+        "tokio_blocking_queue_depth",
+        "Tasks waiting for a tokio blocking thread.",
+        snapshot.tokio_blocking_queue_depth,
+    );
+    w.gauge(
+        "materializer_queue_size",
+        "Commands waiting for the deferred materializer.",
+        snapshot.deferred_materializer_queue_size,
+    );
+    w.counter(
+        "materializer_declares",
+        "Artifacts declared to the deferred materializer.",
+        snapshot.deferred_materializer_declares,
+    );
+    w.family(
+        "io_in_flight",
+        "Filesystem operations currently in progress.",
+        "gauge",
+        [
+            ("stat", snapshot.io_in_flight_stat),
+            ("copy", snapshot.io_in_flight_copy),
+            ("symlink", snapshot.io_in_flight_symlink),
+            ("hardlink", snapshot.io_in_flight_hardlink),
+            ("mk_dir", snapshot.io_in_flight_mk_dir),
+            ("read_dir", snapshot.io_in_flight_read_dir),
+            ("rm_dir", snapshot.io_in_flight_rm_dir),
+            ("rm_dir_all", snapshot.io_in_flight_rm_dir_all),
+            ("read", snapshot.io_in_flight_read),
+            ("write", snapshot.io_in_flight_write),
+            ("remove", snapshot.io_in_flight_remove),
+            ("rename", snapshot.io_in_flight_rename),
+        ]
+        .iter()
+        .map(|(op, value)| (vec![("op", *op)], *value))
+        .collect::<Vec<_>>(),
+    );
+
+    // Remote execution.
+    w.counter(
+        "re_download_bytes",
+        "Bytes downloaded from remote execution.",
+        snapshot.re_download_bytes,
+    );
+    w.counter(
+        "re_upload_bytes",
+        "Bytes uploaded to remote execution.",
+        snapshot.re_upload_bytes,
+    );
+    let re_requests = [
+        (
+            "upload",
+            snapshot.re_uploads_started,
+            snapshot.re_uploads_finished_successfully,
+            snapshot.re_uploads_finished_with_error,
+        ),
+        (
+            "download",
+            snapshot.re_downloads_started,
+            snapshot.re_downloads_finished_successfully,
+            snapshot.re_downloads_finished_with_error,
+        ),
+        (
+            "action_cache",
+            snapshot.re_action_cache_started,
+            snapshot.re_action_cache_finished_successfully,
+            snapshot.re_action_cache_finished_with_error,
+        ),
+        (
+            "execute",
+            snapshot.re_executes_started,
+            snapshot.re_executes_finished_successfully,
+            snapshot.re_executes_finished_with_error,
+        ),
+        (
+            "materialize",
+            snapshot.re_materializes_started,
+            snapshot.re_materializes_finished_successfully,
+            snapshot.re_materializes_finished_with_error,
+        ),
+        (
+            "write_action_result",
+            snapshot.re_write_action_results_started,
+            snapshot.re_write_action_results_finished_successfully,
+            snapshot.re_write_action_results_finished_with_error,
+        ),
+    ];
+    w.family(
+        "re_requests_started",
+        "Remote execution requests started.",
+        "counter",
+        re_requests
+            .iter()
+            .map(|(op, started, _, _)| (vec![("op", *op)], *started))
+            .collect::<Vec<_>>(),
+    );
+    w.family(
+        "re_requests_finished",
+        "Remote execution requests finished.",
+        "counter",
+        re_requests
+            .iter()
+            .flat_map(|(op, _, success, error)| {
+                [
+                    (vec![("op", *op), ("result", "success")], *success),
+                    (vec![("op", *op), ("result", "error")], *error),
+                ]
+            })
+            .collect::<Vec<_>>(),
+    );
+    w.counter(
+        "http_download_bytes",
+        "Bytes downloaded over HTTP.",
+        snapshot.http_download_bytes,
+    );
+
+    // Network.
+    let interfaces: BTreeMap<_, _> = snapshot.network_interface_stats.iter().collect();
+    w.family(
+        "network_receive_bytes",
+        "Bytes received by each network interface on the host.",
+        "counter",
+        interfaces
+            .iter()
+            .map(|(nic, stats)| (vec![("interface", nic.as_str())], stats.rx_bytes))
+            .collect::<Vec<_>>(),
+    );
+    w.family(
+        "network_transmit_bytes",
+        "Bytes sent by each network interface on the host.",
+        "counter",
+        interfaces
+            .iter()
+            .map(|(nic, stats)| (vec![("interface", nic.as_str())], stats.tx_bytes))
+            .collect::<Vec<_>>(),
+    );
+
+    // Event sink.
+    if let Some(depth) = snapshot.sink_buffer_depth {
+        w.gauge(
+            "event_sink_buffer_depth",
+            "Events buffered by the event sink.",
+            depth,
+        );
+    }
+    if let Some(dropped) = snapshot.sink_dropped {
+        w.counter(
+            "event_sink_dropped",
+            "Events dropped by the event sink.",
+            dropped,
+        );
+    }
+
+    w.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let snapshot = buck2_data::Snapshot {
+            daemon_uptime_s: 42,
+            buck2_rss: Some(1024),
+            buck2_user_cpu_us: 1_500_000,
+            re_executes_started: 3,
+            re_executes_finished_successfully: 2,
+            ..Default::default()
+        };
+        let active_commands = BTreeMap::from([("build".to_owned(), 2)]);
+        let out = render(&snapshot, &active_commands);
+
+        assert!(out.contains("# TYPE buck2_daemon_uptime_seconds gauge\n"));
+        assert!(out.contains("\nbuck2_daemon_uptime_seconds 42\n"));
+        assert!(out.contains("\nbuck2_active_commands{command=\"build\"} 2\n"));
+        assert!(out.contains("\nbuck2_rss_bytes 1024\n"));
+        assert!(out.contains("# TYPE buck2_cpu_user_seconds counter\n"));
+        assert!(out.contains("\nbuck2_cpu_user_seconds_total 1.5\n"));
+        assert!(out.contains("\nbuck2_re_requests_started_total{op=\"execute\"} 3\n"));
+        assert!(
+            out.contains(
+                "\nbuck2_re_requests_finished_total{op=\"execute\",result=\"success\"} 2\n"
+            )
+        );
+        assert!(!out.contains("malloc_active_bytes"));
+        assert!(out.ends_with("# EOF\n"));
+    }
+
+    #[test]
+    fn test_escape_label_value() {
+        assert_eq!(escape_label_value("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
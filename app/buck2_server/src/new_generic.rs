@@ -18,6 +18,7 @@ use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
 
 use crate::ctx::ServerCommandContext;
 use crate::materialize::materialize_command;
+use crate::ui::ui_command;
 
 pub(crate) async fn new_generic_command(
     context: &ServerCommandContext<'_>,
@@ -58,6 +59,13 @@ pub(crate) async fn new_generic_command(
                 .docs(context, partial_result_dispatcher, d)
                 .await?,
         ),
+        NewGenericRequest::Fetch(f) => NewGenericResponse::Fetch(
+            OTHER_SERVER_COMMANDS
+                .get()?
+                .fetch(context, partial_result_dispatcher, f)
+                .await?,
+        ),
+        NewGenericRequest::Ui(u) => NewGenericResponse::Ui(ui_command(context, u).await?),
     };
     let resp = serde_json::to_string(&resp)
         .buck_error_context("Could not serialize `NewGenericResponse`")?;
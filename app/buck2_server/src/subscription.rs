@@ -8,6 +8,8 @@
  * above-listed licenses.
  */
 
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 
 use buck2_error::BuckErrorContext;
@@ -18,6 +20,7 @@ use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
 use buck2_server_ctx::streaming_request_handler::StreamingRequestHandler;
 use futures::future::FutureExt;
 use gazebo::prelude::*;
+use tokio::sync::broadcast;
 use tokio::time::MissedTickBehavior;
 
 use crate::active_commands;
@@ -49,6 +52,7 @@ pub(crate) async fn run_subscription_server_command(
                 .buck_error_context("Error creating a materializer subscription")?;
 
             let mut wants_active_commands = false;
+            let mut command_events: Option<CommandEventsSubscription> = None;
 
             let mut ticker = tokio::time::interval(Duration::from_millis(100));
             ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
@@ -73,8 +77,35 @@ pub(crate) async fn run_subscription_server_command(
                             Request::SubscribeToActiveCommands(buck2_subscription_proto::SubscribeToActiveCommands {}) => {
                                 wants_active_commands = true;
                             }
+                            Request::SubscribeToCommandEvents(filter) => {
+                                command_events = Some(CommandEventsSubscription::new(filter));
+                            }
+                            Request::UnsubscribeFromCommandEvents(buck2_subscription_proto::UnsubscribeFromCommandEvents {}) => {
+                                command_events = None;
+                            }
                         }
                     }
+                    event = next_command_event(&mut command_events).fuse() => {
+                        let response: buck2_subscription_proto::subscription_response::Response = match event {
+                            Ok(event) => {
+                                if !command_events.as_ref().is_some_and(|s| s.matches(&event)) {
+                                    continue;
+                                }
+                                Arc::unwrap_or_clone(event).into()
+                            }
+                            Err(broadcast::error::RecvError::Lagged(count)) => {
+                                buck2_subscription_proto::CommandEventsDropped { count }.into()
+                            }
+                            Err(broadcast::error::RecvError::Closed) => {
+                                Err(buck2_error::buck2_error!(buck2_error::ErrorTag::Tier0, "Command events channel closed"))?
+                            }
+                        };
+                        partial_result_dispatcher.emit(buck2_cli_proto::SubscriptionResponseWrapper {
+                            response: Some(buck2_subscription_proto::SubscriptionResponse {
+                                response: Some(response)
+                            })
+                        });
+                    }
                     path = materializer_subscription.next_materialization().fuse() => {
                         let path = path.buck_error_context("Materializer hung up")?;
                         partial_result_dispatcher.emit(buck2_cli_proto::SubscriptionResponseWrapper {
@@ -92,6 +123,15 @@ pub(crate) async fn run_subscription_server_command(
                                 })
                             });
                         }
+                        if let Some(command_events) = &mut command_events {
+                            for event in command_events.progress() {
+                                partial_result_dispatcher.emit(buck2_cli_proto::SubscriptionResponseWrapper {
+                                    response: Some(buck2_subscription_proto::SubscriptionResponse {
+                                        response: Some(event.into())
+                                    })
+                                });
+                            }
+                        }
                     }
                 }
             };
@@ -136,3 +176,97 @@ fn active_commands_snapshot() -> buck2_subscription_proto::ActiveCommandsSnapsho
 
     buck2_subscription_proto::ActiveCommandsSnapshot { active_commands }
 }
+
+/// State of a `SubscribeToCommandEvents` request.
+struct CommandEventsSubscription {
+    filter: buck2_subscription_proto::SubscribeToCommandEvents,
+    receiver: broadcast::Receiver<Arc<buck2_subscription_proto::CommandEvent>>,
+    /// Progress last sent for each command, so we only send changes.
+    last_progress: HashMap<String, buck2_subscription_proto::ActiveCommandStats>,
+}
+
+impl CommandEventsSubscription {
+    fn new(filter: buck2_subscription_proto::SubscribeToCommandEvents) -> Self {
+        Self {
+            filter,
+            receiver: active_commands::subscribe_to_command_events(),
+            last_progress: HashMap::new(),
+        }
+    }
+
+    fn matches_trace_id(&self, trace_id: &str) -> bool {
+        self.filter.trace_id.is_empty() || self.filter.trace_id == trace_id
+    }
+
+    fn matches_action(&self, action: Option<&buck2_subscription_proto::Action>) -> bool {
+        self.filter.actions
+            && (self.filter.action_categories.is_empty()
+                || action.is_some_and(|a| self.filter.action_categories.contains(&a.category)))
+    }
+
+    fn matches(&self, event: &buck2_subscription_proto::CommandEvent) -> bool {
+        use buck2_subscription_proto::command_event::Event;
+
+        if !self.matches_trace_id(&event.trace_id) {
+            return false;
+        }
+        match &event.event {
+            Some(Event::ActionStarted(started)) => {
+                !self.filter.failed_actions_only && self.matches_action(started.action.as_ref())
+            }
+            Some(Event::ActionFinished(finished)) => {
+                (finished.failed || !self.filter.failed_actions_only)
+                    && self.matches_action(finished.action.as_ref())
+            }
+            Some(Event::TestResult(_)) => self.filter.tests,
+            Some(Event::CommandFinished(_)) => true,
+            Some(Event::BuildProgress(_)) | None => false,
+        }
+    }
+
+    /// Progress of matching commands that changed since the last call.
+    fn progress(&mut self) -> Vec<buck2_subscription_proto::CommandEvent> {
+        if !self.filter.progress {
+            return Vec::new();
+        }
+
+        let mut events = Vec::new();
+        let mut progress = HashMap::new();
+        for (trace_id, handle) in active_commands::active_commands().iter() {
+            let trace_id = trace_id.to_string();
+            if !self.matches_trace_id(&trace_id) {
+                continue;
+            }
+            let spans = handle.state().spans();
+            let stats = buck2_subscription_proto::ActiveCommandStats {
+                open_spans: spans.open,
+                closed_spans: spans.closed,
+                pending_spans: spans.pending,
+            };
+            if self.last_progress.get(&trace_id) != Some(&stats) {
+                events.push(buck2_subscription_proto::CommandEvent {
+                    trace_id: trace_id.clone(),
+                    event: Some(
+                        buck2_subscription_proto::BuildProgress {
+                            stats: Some(stats.clone()),
+                        }
+                        .into(),
+                    ),
+                });
+            }
+            progress.insert(trace_id, stats);
+        }
+        // Forget about commands that finished.
+        self.last_progress = progress;
+        events
+    }
+}
+
+async fn next_command_event(
+    subscription: &mut Option<CommandEventsSubscription>,
+) -> Result<Arc<buck2_subscription_proto::CommandEvent>, broadcast::error::RecvError> {
+    match subscription {
+        Some(subscription) => subscription.receiver.recv().await,
+        None => futures::future::pending().await,
+    }
+}
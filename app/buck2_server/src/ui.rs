@@ -0,0 +1,588 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! `buck2 ui`: a local web dashboard showing live and recent commands of this daemon.
+//!
+//! The server is started by the first `buck2 ui` and then lives as long as the daemon. It only
+//! listens on localhost, and every request must carry the random token included in the URL
+//! that `buck2 ui` prints.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::net::Ipv4Addr;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use buck2_cli_proto::new_generic::UiRequest;
+use buck2_cli_proto::new_generic::UiResponse;
+use buck2_common::build_history::BuildHistoryEntry;
+use buck2_common::build_history::BuildHistoryManager;
+use buck2_common::invocation_paths::InvocationPaths;
+use buck2_event_log::file_names::find_log_by_trace_id;
+use buck2_event_log::stream_value::StreamValue;
+use buck2_event_observer::display::CriticalPathEntryDisplay;
+use buck2_event_observer::display::TargetDisplayOptions;
+use buck2_events::BuckEvent;
+use buck2_events::dispatch::span_async;
+use buck2_server_ctx::commands::command_end;
+use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_subscription_proto::CommandEvent;
+use buck2_subscription_proto::command_event::Event;
+use buck2_wrapper_common::invocation_id::TraceId;
+use dupe::Dupe;
+use futures::TryStreamExt;
+use http::StatusCode;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use rand::distr::Alphanumeric;
+use rand::distr::SampleString;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::active_commands::active_commands;
+use crate::active_commands::command_event;
+use crate::active_commands::subscribe_to_command_events;
+use crate::ctx::ServerCommandContext;
+use crate::local_http;
+use crate::local_http::HttpRequest;
+use crate::local_http::HttpResponse;
+
+const UI_HTML: &str = include_str!("ui.html");
+
+/// How many finished commands `/api/commands` returns.
+const MAX_RECENT_COMMANDS: usize = 100;
+/// Events kept per live command. Large builds go past this, in which case the UI says the
+/// timeline is incomplete and the full one is available from the event log once finished.
+const MAX_LIVE_EVENTS: usize = 100_000;
+/// Live events are only recorded while the UI is open: recording stops, and the recorded events
+/// are dropped, once no request came in for this long.
+const LIVE_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(tag = Input)]
+enum UiError {
+    #[error("The UI is already running on port {running}, not on {requested}: {url}")]
+    AlreadyRunningOnOtherPort {
+        running: u16,
+        requested: u16,
+        url: String,
+    },
+}
+
+struct UiServer {
+    address: SocketAddr,
+    url: String,
+}
+
+/// The running UI server, if any.
+static UI_SERVER: Lazy<tokio::sync::Mutex<Option<UiServer>>> =
+    Lazy::new(|| tokio::sync::Mutex::new(None));
+
+pub(crate) async fn ui_command(
+    context: &ServerCommandContext<'_>,
+    req: UiRequest,
+) -> buck2_error::Result<UiResponse> {
+    let start_event = context
+        .command_start_event(buck2_data::UiCommandStart {}.into())
+        .await?;
+    span_async(start_event, async move {
+        let result = ensure_ui_server(&context.paths, req.port)
+            .await
+            .map(|url| UiResponse { url });
+        let end_event = command_end(&result, buck2_data::UiCommandEnd {});
+        (result.map_err(Into::into), end_event)
+    })
+    .await
+}
+
+/// Starts the UI server unless it is already running, and returns its URL.
+async fn ensure_ui_server(paths: &InvocationPaths, port: u16) -> buck2_error::Result<String> {
+    let mut server = UI_SERVER.lock().await;
+    if let Some(server) = &*server {
+        if port != 0 && port != server.address.port() {
+            return Err(UiError::AlreadyRunningOnOtherPort {
+                running: server.address.port(),
+                requested: port,
+                url: server.url.clone(),
+            }
+            .into());
+        }
+        return Ok(server.url.clone());
+    }
+
+    let listener = local_http::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, port))).await?;
+    let address = listener.local_addr()?;
+    let token = Alphanumeric.sample_string(&mut rand::rng(), 32);
+
+    let state = Arc::new(UiState {
+        paths: paths.clone(),
+        token: token.clone(),
+        live: Arc::new(LiveCommands::default()),
+    });
+    local_http::spawn_server(listener, move |request| {
+        let state = state.dupe();
+        async move { state.handle(request).await }
+    });
+
+    let url = format!("http://{address}/?token={token}");
+    *server = Some(UiServer {
+        address,
+        url: url.clone(),
+    });
+    Ok(url)
+}
+
+struct UiState {
+    paths: InvocationPaths,
+    token: String,
+    live: Arc<LiveCommands>,
+}
+
+impl UiState {
+    async fn handle(&self, request: HttpRequest) -> HttpResponse {
+        if request.method != "GET" {
+            return HttpResponse::method_not_allowed();
+        }
+        if request.query_param("token") != Some(self.token.as_str()) {
+            return HttpResponse::error(StatusCode::FORBIDDEN, "Missing or invalid token");
+        }
+        if request.path.starts_with("/api/") {
+            self.live.keep_recording();
+        }
+        let response = match request.path.as_str() {
+            "/" => Ok(HttpResponse::ok("text/html; charset=utf-8", UI_HTML)),
+            "/api/commands" => self.commands().await.map(|c| HttpResponse::json(&c)),
+            "/api/command" => match request.query_param("trace_id") {
+                Some(trace_id) => self.command(trace_id).await,
+                None => Ok(HttpResponse::error(
+                    StatusCode::BAD_REQUEST,
+                    "Missing `trace_id`",
+                )),
+            },
+            _ => Ok(HttpResponse::not_found()),
+        };
+        response.unwrap_or_else(|e| {
+            HttpResponse::error(StatusCode::INTERNAL_SERVER_ERROR, format!("{e:#}"))
+        })
+    }
+
+    async fn commands(&self) -> buck2_error::Result<CommandList> {
+        let active: Vec<_> = active_commands()
+            .iter()
+            .map(|(trace_id, command)| {
+                let spans = command.state().spans();
+                ActiveCommandRow {
+                    trace_id: trace_id.to_string(),
+                    command_line: command.state().argv.join(" "),
+                    open_spans: spans.open,
+                    finished_spans: spans.closed,
+                    pending_spans: spans.pending,
+                }
+            })
+            .collect();
+        self.live
+            .retain(|trace_id| active.iter().any(|c| c.trace_id == trace_id));
+
+        let mut recent = BuildHistoryManager::new(self.paths.build_history_dir())?
+            .read()
+            .await?;
+        recent.reverse();
+        recent.truncate(MAX_RECENT_COMMANDS);
+
+        Ok(CommandList { active, recent })
+    }
+
+    async fn command(&self, trace_id: &str) -> buck2_error::Result<HttpResponse> {
+        if let Some(details) = self.live.details(trace_id) {
+            return Ok(HttpResponse::json(&details));
+        }
+        let Ok(trace_id) = TraceId::from_str(trace_id) else {
+            return Ok(HttpResponse::error(
+                StatusCode::BAD_REQUEST,
+                "Invalid `trace_id`",
+            ));
+        };
+        Ok(match command_from_log(&self.paths, &trace_id).await? {
+            Some(details) => HttpResponse::json(&details),
+            None => HttpResponse::not_found(),
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct CommandList {
+    active: Vec<ActiveCommandRow>,
+    /// Newest first.
+    recent: Vec<BuildHistoryEntry>,
+}
+
+#[derive(Serialize)]
+struct ActiveCommandRow {
+    trace_id: String,
+    command_line: String,
+    open_spans: u64,
+    finished_spans: u64,
+    pending_spans: u64,
+}
+
+/// Everything the UI shows about a single command. Times are milliseconds since the first
+/// event seen for the command.
+#[derive(Serialize, Default, Debug)]
+struct CommandDetails {
+    command_line: String,
+    live: bool,
+    success: Option<bool>,
+    /// Set when events were dropped, so the timeline is incomplete.
+    truncated: bool,
+    actions: Vec<ActionRow>,
+    tests: Vec<TestRow>,
+    critical_path: Vec<CriticalPathRow>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+struct ActionRow {
+    owner: String,
+    category: String,
+    identifier: String,
+    start_ms: u64,
+    /// `None` while the action is running.
+    end_ms: Option<u64>,
+    execution_kind: String,
+    failed: bool,
+}
+
+#[derive(Serialize, Debug)]
+struct TestRow {
+    target: String,
+    name: String,
+    status: String,
+    duration_ms: u64,
+}
+
+#[derive(Serialize, Debug)]
+struct CriticalPathRow {
+    kind: String,
+    name: String,
+    category: Option<String>,
+    identifier: Option<String>,
+    start_ms: Option<u64>,
+    duration_ms: u64,
+}
+
+/// Builds `CommandDetails` out of `CommandEvent`s, pairing action starts with their ends.
+#[derive(Default)]
+struct DetailsBuilder {
+    details: CommandDetails,
+    /// Indexes of running actions in `details.actions`, by owner, category and identifier.
+    running: HashMap<(String, String, String), Vec<usize>>,
+}
+
+impl DetailsBuilder {
+    fn add(&mut self, at_ms: u64, event: &CommandEvent) {
+        fn key(action: Option<&buck2_subscription_proto::Action>) -> (String, String, String) {
+            let action = action.cloned().unwrap_or_default();
+            (action.owner, action.category, action.identifier)
+        }
+
+        match &event.event {
+            Some(Event::ActionStarted(started)) => {
+                let key = key(started.action.as_ref());
+                self.running
+                    .entry(key.clone())
+                    .or_default()
+                    .push(self.details.actions.len());
+                self.details.actions.push(ActionRow {
+                    owner: key.0,
+                    category: key.1,
+                    identifier: key.2,
+                    start_ms: at_ms,
+                    end_ms: None,
+                    execution_kind: String::new(),
+                    failed: false,
+                });
+            }
+            Some(Event::ActionFinished(finished)) => {
+                let key = key(finished.action.as_ref());
+                let index = self.running.get_mut(&key).and_then(|indexes| indexes.pop());
+                let row = match index {
+                    Some(index) => &mut self.details.actions[index],
+                    None => {
+                        // Started before we were listening.
+                        self.details.actions.push(ActionRow {
+                            owner: key.0,
+                            category: key.1,
+                            identifier: key.2,
+                            start_ms: at_ms.saturating_sub(finished.wall_time_us / 1000),
+                            end_ms: None,
+                            execution_kind: String::new(),
+                            failed: false,
+                        });
+                        self.details.actions.last_mut().unwrap()
+                    }
+                };
+                row.end_ms = Some(at_ms);
+                row.failed = finished.failed;
+                row.execution_kind = finished.execution_kind.clone();
+            }
+            Some(Event::TestResult(result)) => self.details.tests.push(TestRow {
+                target: result.target.clone(),
+                name: result.name.clone(),
+                status: result.status.clone(),
+                duration_ms: result.duration_us / 1000,
+            }),
+            Some(Event::CommandFinished(finished)) => self.details.success = Some(finished.success),
+            Some(Event::BuildProgress(_)) | None => {}
+        }
+    }
+
+    fn add_critical_path(&mut self, entries: &[buck2_data::CriticalPathEntry2]) {
+        self.details.critical_path = entries
+            .iter()
+            .filter_map(|entry| {
+                let display =
+                    CriticalPathEntryDisplay::from_entry(entry, TargetDisplayOptions::for_log())
+                        .ok()??;
+                let duration = entry
+                    .duration
+                    .clone()
+                    .and_then(|d| Duration::try_from(d).ok())
+                    .unwrap_or_default();
+                Some(CriticalPathRow {
+                    kind: display.kind.to_owned(),
+                    name: display.name,
+                    category: display.category.map(ToOwned::to_owned),
+                    identifier: display.identifier.map(ToOwned::to_owned),
+                    start_ms: entry.start_offset_ns.map(|ns| ns / 1_000_000),
+                    duration_ms: duration.as_millis() as u64,
+                })
+            })
+            .collect();
+    }
+}
+
+struct LiveCommand {
+    started: Instant,
+    events: VecDeque<(u64, Arc<CommandEvent>)>,
+    truncated: bool,
+}
+
+/// Events of running commands. These are only recorded while the UI is being polled, see
+/// `LIVE_IDLE_TIMEOUT`.
+#[derive(Default)]
+struct LiveCommands {
+    commands: Mutex<HashMap<String, LiveCommand>>,
+    /// Time of the last API request, `None` when not recording.
+    last_request: Mutex<Option<Instant>>,
+}
+
+impl LiveCommands {
+    /// Starts recording unless already recording, and postpones the idle timeout.
+    fn keep_recording(self: &Arc<Self>) {
+        let mut last_request = self.last_request.lock();
+        if last_request.replace(Instant::now()).is_none() {
+            tokio::task::spawn(self.dupe().record(subscribe_to_command_events()));
+        }
+    }
+
+    async fn record(self: Arc<Self>, mut receiver: broadcast::Receiver<Arc<CommandEvent>>) {
+        loop {
+            let Some(last_request) = *self.last_request.lock() else {
+                return;
+            };
+            let deadline = tokio::time::Instant::from_std(last_request + LIVE_IDLE_TIMEOUT);
+            match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(Ok(event)) => self.add(event),
+                Ok(Err(broadcast::error::RecvError::Lagged(_))) => {
+                    for command in self.commands.lock().values_mut() {
+                        command.truncated = true;
+                    }
+                }
+                Ok(Err(broadcast::error::RecvError::Closed)) => {
+                    self.stop_recording(&mut self.last_request.lock());
+                    return;
+                }
+                Err(_) => {
+                    // A request may have come in since we read `last_request`.
+                    let mut last_request = self.last_request.lock();
+                    if last_request.is_some_and(|t| t.elapsed() >= LIVE_IDLE_TIMEOUT) {
+                        self.stop_recording(&mut last_request);
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Called with `last_request` locked, so that a concurrent `keep_recording` either sees this
+    /// recorder still running or starts a new one.
+    fn stop_recording(&self, last_request: &mut Option<Instant>) {
+        *last_request = None;
+        self.commands.lock().clear();
+    }
+
+    fn add(&self, event: Arc<CommandEvent>) {
+        let mut commands = self.commands.lock();
+        if matches!(event.event, Some(Event::CommandFinished(_))) {
+            // From now on the event log has everything.
+            commands.remove(&event.trace_id);
+            return;
+        }
+        let command = commands
+            .entry(event.trace_id.clone())
+            .or_insert_with(|| LiveCommand {
+                started: Instant::now(),
+                events: VecDeque::new(),
+                truncated: false,
+            });
+        if command.events.len() >= MAX_LIVE_EVENTS {
+            command.truncated = true;
+            return;
+        }
+        let at_ms = command.started.elapsed().as_millis() as u64;
+        command.events.push_back((at_ms, event));
+    }
+
+    /// Drops commands that finished without us seeing their `CommandFinished`.
+    fn retain(&self, is_active: impl Fn(&str) -> bool) {
+        self.commands
+            .lock()
+            .retain(|trace_id, _| is_active(trace_id));
+    }
+
+    fn details(&self, trace_id: &str) -> Option<CommandDetails> {
+        let commands = self.commands.lock();
+        let command = commands.get(trace_id)?;
+        let mut builder = DetailsBuilder::default();
+        for (at_ms, event) in &command.events {
+            builder.add(*at_ms, event);
+        }
+        drop(commands);
+
+        builder.details.command_line = active_commands()
+            .iter()
+            .find(|(id, _)| id.to_string() == trace_id)
+            .map(|(_, command)| command.state().argv.join(" "))
+            .unwrap_or_default();
+        builder.details.live = true;
+        Some(builder.details)
+    }
+}
+
+/// Reads the details of a finished command from its event log.
+async fn command_from_log(
+    paths: &InvocationPaths,
+    trace_id: &TraceId,
+) -> buck2_error::Result<Option<CommandDetails>> {
+    let Some(log) = find_log_by_trace_id(&paths.log_dir(), trace_id)? else {
+        return Ok(None);
+    };
+    let (invocation, mut events) = log.unpack_stream().await?;
+
+    let mut builder = DetailsBuilder::default();
+    builder.details.command_line = invocation.display_command_line();
+    let mut first_timestamp = None;
+    while let Some(value) = events.try_next().await? {
+        let StreamValue::Event(event) = value else {
+            continue;
+        };
+        let event = BuckEvent::try_from(event)?;
+        let first_timestamp = *first_timestamp.get_or_insert(event.timestamp());
+        let at_ms = event
+            .timestamp()
+            .duration_since(first_timestamp)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        if let buck2_data::buck_event::Data::Instant(instant) = event.data() {
+            if let Some(buck2_data::instant_event::Data::BuildGraphInfo(info)) = &instant.data {
+                builder.add_critical_path(&info.critical_path2);
+            }
+        }
+        if let Some(event) = command_event(&event) {
+            builder.add(at_ms, &event);
+        }
+    }
+    Ok(Some(builder.details))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(identifier: &str) -> Option<buck2_subscription_proto::Action> {
+        Some(buck2_subscription_proto::Action {
+            owner: "root//:t".to_owned(),
+            category: "cxx_compile".to_owned(),
+            identifier: identifier.to_owned(),
+        })
+    }
+
+    fn event(event: impl Into<Event>) -> CommandEvent {
+        CommandEvent {
+            trace_id: String::new(),
+            event: Some(event.into()),
+        }
+    }
+
+    #[test]
+    fn test_details_builder_pairs_actions() {
+        let mut builder = DetailsBuilder::default();
+        builder.add(
+            10,
+            &event(buck2_subscription_proto::ActionStarted {
+                action: action("a.cpp"),
+            }),
+        );
+        builder.add(
+            20,
+            &event(buck2_subscription_proto::ActionStarted {
+                action: action("b.cpp"),
+            }),
+        );
+        builder.add(
+            50,
+            &event(buck2_subscription_proto::ActionFinished {
+                action: action("a.cpp"),
+                failed: true,
+                execution_kind: "LOCAL".to_owned(),
+                wall_time_us: 40_000,
+            }),
+        );
+        // Started before the UI was listening.
+        builder.add(
+            60,
+            &event(buck2_subscription_proto::ActionFinished {
+                action: action("c.cpp"),
+                failed: false,
+                execution_kind: "ACTION_CACHE".to_owned(),
+                wall_time_us: 30_000,
+            }),
+        );
+
+        let actions = &builder.details.actions;
+        assert_eq!(3, actions.len());
+        assert_eq!(
+            (10, Some(50), true),
+            (actions[0].start_ms, actions[0].end_ms, actions[0].failed)
+        );
+        assert_eq!((20, None), (actions[1].start_ms, actions[1].end_ms));
+        assert_eq!(
+            (30, Some(60), "ACTION_CACHE"),
+            (
+                actions[2].start_ms,
+                actions[2].end_ms,
+                actions[2].execution_kind.as_str()
+            )
+        );
+    }
+}
@@ -0,0 +1,263 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! `buck2 fetch`: downloads every external input of a set of targets and pins them in
+//! `buck2.lock`.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use buck2_build_api::actions::RegisteredAction;
+use buck2_build_api::actions::artifact::get_artifact_fs::GetArtifactFs;
+use buck2_build_api::analysis::calculation::RuleAnalysisCalculation;
+use buck2_build_api::artifact_groups::ArtifactGroup;
+use buck2_build_api::configure_targets::load_compatible_patterns_with_modifiers;
+use buck2_build_api::materialize::MaterializationAndUploadContext;
+use buck2_build_api::materialize::materialize_and_upload_artifact_group;
+use buck2_cli_proto::new_generic::FetchRequest;
+use buck2_cli_proto::new_generic::FetchResponse;
+use buck2_common::dice::cells::HasCellResolver;
+use buck2_common::external_cells::EXTERNAL_CELLS_IMPL;
+use buck2_common::lockfile::LOCKFILE;
+use buck2_common::lockfile::LockedDownload;
+use buck2_common::lockfile::Lockfile;
+use buck2_common::pattern::parse_from_cli::parse_patterns_with_modifiers_from_cli_args;
+use buck2_core::cells::external::ExternalCellOrigin;
+use buck2_core::configuration::compatibility::MaybeCompatible;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_core::pattern::pattern_type::TargetPatternExtra;
+use buck2_error::BuckErrorContext;
+use buck2_execute::materialize::materializer::CopiedArtifact;
+use buck2_execute::materialize::materializer::HasMaterializer;
+use buck2_fs::fs_util;
+use buck2_fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_node::load_patterns::MissingTargetBehavior;
+use buck2_node::nodes::configured::ConfiguredTargetNode;
+use buck2_node::nodes::unconfigured::RuleKind;
+use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::global_cfg_options::global_cfg_options_from_client_context;
+use buck2_server_ctx::partial_result_dispatcher::NoPartialResult;
+use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
+use buck2_server_ctx::template::ServerCommandTemplate;
+use buck2_server_ctx::template::run_server_command;
+use dice::DiceComputations;
+use dice::DiceTransaction;
+use dupe::Dupe;
+use dupe::IterDupedExt;
+use futures::FutureExt;
+
+pub(crate) async fn fetch_command(
+    ctx: &dyn ServerCommandContextTrait,
+    partial_result_dispatcher: PartialResultDispatcher<NoPartialResult>,
+    req: FetchRequest,
+) -> buck2_error::Result<FetchResponse> {
+    run_server_command(FetchServerCommand { req }, ctx, partial_result_dispatcher).await
+}
+
+struct FetchServerCommand {
+    req: FetchRequest,
+}
+
+#[derive(buck2_error::Error, Debug)]
+#[buck2(tag = Input)]
+enum FetchError {
+    #[error("`{0}` is out of date, run `buck2 fetch` to update it")]
+    LockfileOutOfDate(AbsNormPathBuf),
+}
+
+/// Configured targets reachable from `roots`, including the roots themselves.
+fn transitive_closure(
+    roots: impl IntoIterator<Item = ConfiguredTargetNode>,
+) -> Vec<ConfiguredTargetNode> {
+    let mut seen = HashSet::new();
+    let mut queue: Vec<ConfiguredTargetNode> = roots.into_iter().collect();
+    let mut nodes = Vec::new();
+    while let Some(node) = queue.pop() {
+        if !seen.insert(node.label().dupe()) {
+            continue;
+        }
+        queue.extend(node.deps().filter(|d| !seen.contains(d.label())).duped());
+        nodes.push(node);
+    }
+    nodes
+}
+
+/// `download_file` actions registered by the analysis of `nodes`.
+async fn download_actions(
+    ctx: &mut DiceComputations<'_>,
+    nodes: Vec<ConfiguredTargetNode>,
+) -> buck2_error::Result<Vec<Arc<RegisteredAction>>> {
+    let actions = ctx
+        .try_compute_join(nodes, |ctx, node| {
+            async move {
+                if node.rule_kind() == RuleKind::Configuration {
+                    return Ok(Vec::new());
+                }
+                match ctx.get_analysis_result(node.label()).await? {
+                    MaybeCompatible::Incompatible(_) => Ok(Vec::new()),
+                    MaybeCompatible::Compatible(result) => Ok(result
+                        .analysis_values()
+                        .iter_actions()
+                        .filter(|a| a.action().download().is_some())
+                        .cloned()
+                        .collect()),
+                }
+            }
+            .boxed()
+        })
+        .await?;
+    Ok(actions.into_iter().flatten().collect())
+}
+
+/// Downloads the output of a `download_file` action, and copies it to the offline cache that
+/// `buck2.use_network_action_output_cache` builds read from.
+async fn fetch_download(
+    ctx: &mut DiceComputations<'_>,
+    action: &RegisteredAction,
+) -> buck2_error::Result<()> {
+    let output = action.action().first_output();
+    let values = materialize_and_upload_artifact_group(
+        ctx,
+        &ArtifactGroup::Artifact(output.dupe().into()),
+        &MaterializationAndUploadContext::materialize(),
+    )
+    .await?;
+    let (_, value) = values
+        .iter()
+        .next()
+        .with_internal_error(|| format!("No value for `{output}`"))?;
+
+    let artifact_fs = ctx.get_artifact_fs().await?;
+    let build_path =
+        artifact_fs.resolve_build(output.get_path(), Some(&value.content_based_path_hash()))?;
+    let offline_cache_path = artifact_fs.resolve_offline_output_cache_path(output.get_path())?;
+    let materializer = ctx.per_transaction_data().get_materializer();
+    let entry = value.entry().dupe().map_dir(|d| d.as_immutable());
+    materializer
+        .declare_copy(
+            offline_cache_path.clone(),
+            value.dupe(),
+            vec![CopiedArtifact::new(
+                build_path,
+                offline_cache_path.clone(),
+                entry,
+                None,
+            )],
+        )
+        .await?;
+    materializer
+        .ensure_materialized(vec![offline_cache_path])
+        .await
+}
+
+#[async_trait::async_trait]
+impl ServerCommandTemplate for FetchServerCommand {
+    type StartEvent = buck2_data::FetchCommandStart;
+    type EndEvent = buck2_data::FetchCommandEnd;
+    type Response = FetchResponse;
+    type PartialResult = NoPartialResult;
+
+    fn checks_lockfile(&self) -> bool {
+        false
+    }
+
+    async fn command(
+        &self,
+        server_ctx: &dyn ServerCommandContextTrait,
+        _partial_result_dispatcher: PartialResultDispatcher<Self::PartialResult>,
+        mut ctx: DiceTransaction,
+    ) -> buck2_error::Result<Self::Response> {
+        let mut lockfile = Lockfile::default();
+
+        // Every external cell is pinned, not only those the targets happen to use: a build
+        // may load from any cell in the configuration.
+        let cell_resolver = ctx.get_cell_resolver().await?;
+        for (cell, instance) in cell_resolver.cells() {
+            let Some(origin) = instance.external() else {
+                continue;
+            };
+            lockfile.add_external_cell(cell, origin);
+            if !matches!(origin, ExternalCellOrigin::Bundled(_)) {
+                EXTERNAL_CELLS_IMPL
+                    .get()?
+                    .get_file_ops_delegate(&mut ctx, cell, origin.dupe())
+                    .await
+                    .with_buck_error_context(|| format!("Error fetching external cell `{cell}`"))?;
+            }
+        }
+
+        let parsed_patterns = parse_patterns_with_modifiers_from_cli_args::<TargetPatternExtra>(
+            &mut ctx,
+            &self.req.target_patterns,
+            server_ctx.working_dir(),
+        )
+        .await?;
+        let global_cfg_options =
+            global_cfg_options_from_client_context(&self.req.target_cfg, server_ctx, &mut ctx)
+                .await?;
+        let targets = load_compatible_patterns_with_modifiers(
+            &mut ctx,
+            parsed_patterns,
+            &global_cfg_options,
+            MissingTargetBehavior::Fail,
+            false,
+        )
+        .await?;
+        if let Some(error) = targets.errors.into_iter().next() {
+            return Err(error.error);
+        }
+
+        let nodes = transitive_closure(targets.compatible_targets.iter().duped());
+        let actions = download_actions(&mut ctx, nodes).await?;
+
+        for action in &actions {
+            let download = action
+                .action()
+                .download()
+                .with_internal_error(|| format!("`{}` is not a download", action.key()))?;
+            lockfile.add_download(
+                download.url.to_owned(),
+                LockedDownload::new(download.checksum.sha1(), download.checksum.sha256()),
+            )?;
+        }
+
+        ctx.try_compute_join(actions, |ctx, action| {
+            async move {
+                fetch_download(ctx, &action)
+                    .await
+                    .with_buck_error_context(|| format!("Error fetching `{}`", action.owner()))
+            }
+            .boxed()
+        })
+        .await?;
+
+        let path = server_ctx
+            .project_root()
+            .resolve(ProjectRelativePath::unchecked_new(LOCKFILE));
+        // A lockfile we can't parse is treated as out of date.
+        let existing =
+            fs_util::read_to_string_if_exists(&path)?.and_then(|s| Lockfile::parse(&s).ok());
+        let changed = existing.as_ref() != Some(&lockfile);
+
+        if changed {
+            if self.req.check {
+                return Err(FetchError::LockfileOutOfDate(path).into());
+            }
+            fs_util::write(&path, lockfile.to_json()?)?;
+        }
+
+        Ok(FetchResponse {
+            lockfile: path.to_string(),
+            external_cells: lockfile.external_cells_len() as u64,
+            downloads: lockfile.downloads_len() as u64,
+            changed,
+        })
+    }
+}
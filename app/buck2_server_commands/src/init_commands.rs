@@ -17,6 +17,8 @@ use buck2_cli_proto::new_generic::ExpandExternalCellsRequest;
 use buck2_cli_proto::new_generic::ExpandExternalCellsResponse;
 use buck2_cli_proto::new_generic::ExplainRequest;
 use buck2_cli_proto::new_generic::ExplainResponse;
+use buck2_cli_proto::new_generic::FetchRequest;
+use buck2_cli_proto::new_generic::FetchResponse;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
 use buck2_server_ctx::late_bindings::OTHER_SERVER_COMMANDS;
 use buck2_server_ctx::late_bindings::OtherServerCommands;
@@ -28,6 +30,7 @@ use crate::complete::complete_command;
 use crate::debug_eval::debug_eval_command;
 use crate::expand_external_cells::expand_external_cells_command;
 use crate::explain::explain_command;
+use crate::fetch::fetch_command;
 use crate::install::install_command;
 
 struct OtherServerCommandsInstance;
@@ -84,6 +87,15 @@ impl OtherServerCommands for OtherServerCommandsInstance {
     ) -> buck2_error::Result<ExpandExternalCellsResponse> {
         expand_external_cells_command(ctx, partial_result_dispatcher, req).await
     }
+
+    async fn fetch(
+        &self,
+        ctx: &dyn ServerCommandContextTrait,
+        partial_result_dispatcher: PartialResultDispatcher<NoPartialResult>,
+        req: FetchRequest,
+    ) -> buck2_error::Result<FetchResponse> {
+        fetch_command(ctx, partial_result_dispatcher, req).await
+    }
 }
 
 pub(crate) fn init_other_server_commands() {
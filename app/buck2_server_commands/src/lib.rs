@@ -23,6 +23,7 @@ pub(crate) mod expand_external_cells;
 pub(crate) mod explain;
 #[cfg(fbcode_build)]
 pub(crate) mod explain_code;
+pub(crate) mod fetch;
 pub(crate) mod init_commands;
 pub(crate) mod install;
 
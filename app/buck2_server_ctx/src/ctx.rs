@@ -120,6 +120,7 @@ pub trait ServerCommandContextTrait: Send + Sync {
     async fn dice_accessor<'a>(
         &'a self,
         private: PrivateStruct,
+        checks_lockfile: bool,
     ) -> buck2_error::Result<DiceAccessor<'a>>;
 
     fn events(&self) -> &EventDispatcher;
@@ -181,6 +182,7 @@ pub trait ServerCommandDiceContext {
         &'v self,
         exec: F,
         exclusive_cmd: Option<String>,
+        checks_lockfile: bool,
     ) -> buck2_error::Result<R>
     where
         F: FnOnce(&'v dyn ServerCommandContextTrait, DiceTransaction) -> Fut + Send,
@@ -197,13 +199,14 @@ impl ServerCommandDiceContext for dyn ServerCommandContextTrait + '_ {
         Fut: Future<Output = buck2_error::Result<R>> + Send,
         R: Send,
     {
-        self.with_dice_ctx_maybe_exclusive(exec, None).await
+        self.with_dice_ctx_maybe_exclusive(exec, None, true).await
     }
 
     async fn with_dice_ctx_maybe_exclusive<'v, F, Fut, R>(
         &'v self,
         exec: F,
         exclusive_cmd: Option<String>,
+        checks_lockfile: bool,
     ) -> buck2_error::Result<R>
     where
         F: FnOnce(&'v dyn ServerCommandContextTrait, DiceTransaction) -> Fut + Send,
@@ -218,7 +221,9 @@ impl ServerCommandDiceContext for dyn ServerCommandContextTrait + '_ {
             preemptible,
             build_signals,
             exit_when,
-        } = self.dice_accessor(PrivateStruct(())).await?;
+        } = self
+            .dice_accessor(PrivateStruct(()), checks_lockfile)
+            .await?;
 
         let early_command_timing = EarlyCommandTimingBuilder::new(self.command_start());
 
@@ -262,6 +267,9 @@ impl ServerCommandDiceContext for dyn ServerCommandContextTrait + '_ {
                                                         .to_owned(),
                                                     early_command_timing: early_command_timing
                                                         .finish_early_command_timing(),
+                                                    log_graph: dice
+                                                        .per_transaction_data()
+                                                        .get_log_critical_path_graph(),
                                                 },
                                                 || exec(self, dice),
                                             )
@@ -55,6 +55,12 @@ pub trait ServerCommandTemplate: Send + Sync {
         None
     }
 
+    /// Whether external cells and downloads are checked against `buck2.lock`. False for the
+    /// command that writes it.
+    fn checks_lockfile(&self) -> bool {
+        true
+    }
+
     /// Command implementation.
     async fn command(
         &self,
@@ -87,6 +93,7 @@ pub async fn run_server_command<T: ServerCommandTemplate>(
                     command.command(server_ctx, partial_result_dispatcher, ctx)
                 },
                 command.exclusive_command_name(),
+                command.checks_lockfile(),
             )
             .await
             .map_err(Into::into);
@@ -32,5 +32,9 @@ fn main() -> io::Result<()> {
             "buck.subscription.SubscriptionResponse.response",
             "#[derive(::derive_more::From)]",
         )
+        .type_attribute(
+            "buck.subscription.CommandEvent.event",
+            "#[derive(::derive_more::From)]",
+        )
         .compile(proto_files, &includes)
 }
@@ -51,6 +51,9 @@ use buck2_common::pattern::resolve::ResolvedPattern;
 use buck2_core::cells::CellResolver;
 use buck2_core::cells::name::CellName;
 use buck2_core::configuration::compatibility::MaybeCompatible;
+use buck2_core::fs::artifact_path_resolver::ArtifactFs;
+use buck2_core::fs::buck_out_path::BuckOutTestPath;
+use buck2_core::fs::project::ProjectRoot;
 use buck2_core::global_cfg_options::GlobalCfgOptions;
 use buck2_core::package::PackageLabelWithModifiers;
 use buck2_core::pattern::pattern::Modifiers;
@@ -69,7 +72,10 @@ use buck2_error::ErrorTag;
 use buck2_events::dispatch::console_message;
 use buck2_events::dispatch::with_dispatcher_async;
 use buck2_fs::fs_util;
+use buck2_fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_fs::paths::abs_path::AbsPathBuf;
+use buck2_fs::paths::forward_rel_path::ForwardRelativePath;
+use buck2_fs::paths::forward_rel_path::ForwardRelativePathBuf;
 use buck2_interpreter::extra::InterpreterHostPlatform;
 use buck2_interpreter_for_build::interpreter::context::HasInterpreterContext;
 use buck2_node::load_patterns::MissingTargetBehavior;
@@ -103,6 +109,7 @@ use futures::stream::TryStreamExt;
 use indexmap::IndexSet;
 use itertools::Itertools;
 
+use crate::coverage::merge_lcov;
 use crate::downward_api::BuckTestDownwardApi;
 use crate::executor_launcher::ExecutorLaunch;
 use crate::executor_launcher::ExecutorLauncher;
@@ -136,6 +143,7 @@ struct ExecutorReport {
     exit_code: Option<i32>,
     statuses: TestStatuses,
     info_messages: Vec<String>,
+    coverage_reports: Vec<AbsNormPathBuf>,
 }
 
 impl ExecutorReport {
@@ -143,6 +151,7 @@ impl ExecutorReport {
         match status {
             ExecutorMessage::TestResult(res) => {
                 self.statuses.ingest(res);
+                self.coverage_reports.extend(res.coverage.clone());
             }
             ExecutorMessage::ExitCode(exit_code) => {
                 self.exit_code = Some(*exit_code);
@@ -457,7 +466,9 @@ async fn test(
         allow_re: options.allow_re,
         force_use_project_relative_paths: options.force_use_project_relative_paths,
         force_run_from_project_root: options.force_run_from_project_root,
+        coverage: options.coverage,
     });
+    let session_prefix = session.prefix();
 
     let build_opts = request
         .build_opts
@@ -601,6 +612,19 @@ async fn test(
         None
     };
 
+    let coverage_report = if options.coverage {
+        let artifact_fs = ctx.get_artifact_fs().await?;
+        let path = write_coverage_report(
+            &artifact_fs,
+            server_ctx.project_root(),
+            &session_prefix,
+            &test_outcome.executor_report.coverage_reports,
+        )?;
+        Some(path.to_string())
+    } else {
+        None
+    };
+
     let mut target_rule_type_names: Vec<String> = Vec::new();
     for configured in test_outcome.build_target_result.configured.keys() {
         target_rule_type_names
@@ -629,9 +653,40 @@ async fn test(
         executor_info_messages: test_outcome.executor_report.info_messages,
         serialized_build_report,
         target_rule_type_names,
+        coverage_report,
     })
 }
 
+/// Merges the LCOV reports of the tests into a single report in the test output directory of the
+/// session, and returns its path.
+fn write_coverage_report(
+    artifact_fs: &ArtifactFs,
+    project_root: &ProjectRoot,
+    session_prefix: &ForwardRelativePath,
+    reports: &[AbsNormPathBuf],
+) -> buck2_error::Result<AbsNormPathBuf> {
+    let reports = reports
+        .iter()
+        .map(|report| {
+            fs_util::read_to_string(report)
+                .with_buck_error_context(|| format!("Failed to read coverage report `{report}`"))
+        })
+        .collect::<buck2_error::Result<Vec<_>>>()?;
+    let merged = merge_lcov(reports.iter().map(|report| report.as_str()))?;
+
+    let path = project_root.resolve(artifact_fs.buck_out_path_resolver().resolve_test(
+        &BuckOutTestPath::new(
+            session_prefix.to_buf(),
+            ForwardRelativePathBuf::unchecked_new("coverage.lcov".to_owned()),
+        ),
+    ));
+    if let Some(parent) = path.parent() {
+        fs_util::create_dir_all(parent)?;
+    }
+    fs_util::write(&path, merged).buck_error_context("Failed to write coverage report")?;
+    Ok(path)
+}
+
 async fn test_targets(
     ctx: DiceTransaction,
     pattern: ResolvedPattern<ConfiguredProvidersPatternExtra>,
@@ -1480,7 +1535,12 @@ fn run_tests<'a, 'b>(
 
     match maybe_handle {
         Ok(handle) => {
-            let fut = test_info.dispatch(handle, test_executor, working_dir_cell);
+            let fut = test_info.dispatch(
+                handle,
+                test_executor,
+                working_dir_cell,
+                session.options().coverage,
+            );
 
             (async move {
                 fut.await
@@ -0,0 +1,251 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! Merging of the per-test LCOV reports produced by `buck2 test --coverage`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(tag = Input)]
+enum CoverageError {
+    #[error("Invalid LCOV line: `{0}`")]
+    InvalidLine(String),
+    #[error("LCOV record outside of a source file: `{0}`")]
+    RecordOutsideOfFile(String),
+}
+
+/// The coverage of a single source file, summed over all the reports.
+#[derive(Default)]
+struct FileCoverage {
+    /// Function name to the line it starts on.
+    functions: BTreeMap<String, u64>,
+    /// Function name to the number of times it was called.
+    function_hits: BTreeMap<String, u64>,
+    /// Line number to the number of times it was executed.
+    lines: BTreeMap<u64, u64>,
+    /// `(line, block, branch)` to the number of times the branch was taken, or `None` if the
+    /// block containing it was never executed.
+    branches: BTreeMap<(u64, String, String), Option<u64>>,
+}
+
+struct MergedReport(BTreeMap<String, FileCoverage>);
+
+impl fmt::Display for MergedReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (path, file) in &self.0 {
+            writeln!(f, "TN:")?;
+            writeln!(f, "SF:{path}")?;
+            for (name, start) in &file.functions {
+                writeln!(f, "FN:{start},{name}")?;
+            }
+            for (name, count) in &file.function_hits {
+                writeln!(f, "FNDA:{count},{name}")?;
+            }
+            writeln!(f, "FNF:{}", file.functions.len())?;
+            writeln!(
+                f,
+                "FNH:{}",
+                file.function_hits.values().filter(|c| **c > 0).count()
+            )?;
+            for ((number, block, branch), taken) in &file.branches {
+                match taken {
+                    Some(taken) => writeln!(f, "BRDA:{number},{block},{branch},{taken}")?,
+                    None => writeln!(f, "BRDA:{number},{block},{branch},-")?,
+                }
+            }
+            writeln!(f, "BRF:{}", file.branches.len())?;
+            writeln!(
+                f,
+                "BRH:{}",
+                file.branches
+                    .values()
+                    .filter(|t| t.is_some_and(|t| t > 0))
+                    .count()
+            )?;
+            for (number, count) in &file.lines {
+                writeln!(f, "DA:{number},{count}")?;
+            }
+            writeln!(f, "LF:{}", file.lines.len())?;
+            writeln!(f, "LH:{}", file.lines.values().filter(|c| **c > 0).count())?;
+            writeln!(f, "end_of_record")?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_count(line: &str, count: &str) -> buck2_error::Result<u64> {
+    count
+        .parse()
+        .map_err(|_| CoverageError::InvalidLine(line.to_owned()).into())
+}
+
+fn split2<'a>(line: &str, value: &'a str) -> buck2_error::Result<(&'a str, &'a str)> {
+    value
+        .split_once(',')
+        .ok_or_else(|| CoverageError::InvalidLine(line.to_owned()).into())
+}
+
+/// Merges LCOV tracefiles into one, summing the counts of the files which appear in several.
+/// Summary lines (`LF`, `LH`, ...) are recomputed from the merged counts.
+pub(crate) fn merge_lcov<'a>(
+    reports: impl IntoIterator<Item = &'a str>,
+) -> buck2_error::Result<String> {
+    let mut files = BTreeMap::<String, FileCoverage>::new();
+
+    for report in reports {
+        let mut current: Option<&mut FileCoverage> = None;
+        for line in report.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("TN:") {
+                continue;
+            }
+            if line == "end_of_record" {
+                current = None;
+                continue;
+            }
+            let Some((kind, value)) = line.split_once(':') else {
+                return Err(CoverageError::InvalidLine(line.to_owned()).into());
+            };
+            if kind == "SF" {
+                current = Some(files.entry(value.to_owned()).or_default());
+                continue;
+            }
+            let file = match (kind, &mut current) {
+                ("LF" | "LH" | "FNF" | "FNH" | "BRF" | "BRH", _) => continue,
+                (_, Some(file)) => file,
+                (_, None) => {
+                    return Err(CoverageError::RecordOutsideOfFile(line.to_owned()).into());
+                }
+            };
+            match kind {
+                "FN" => {
+                    let (start, name) = split2(line, value)?;
+                    file.functions
+                        .insert(name.to_owned(), parse_count(line, start)?);
+                }
+                "FNDA" => {
+                    let (count, name) = split2(line, value)?;
+                    *file.function_hits.entry(name.to_owned()).or_default() +=
+                        parse_count(line, count)?;
+                }
+                "DA" => {
+                    let mut parts = value.split(',');
+                    let (Some(number), Some(count)) = (parts.next(), parts.next()) else {
+                        return Err(CoverageError::InvalidLine(line.to_owned()).into());
+                    };
+                    *file.lines.entry(parse_count(line, number)?).or_default() +=
+                        parse_count(line, count)?;
+                }
+                "BRDA" => {
+                    let parts = value.splitn(4, ',').collect::<Vec<_>>();
+                    let &[number, block, branch, taken] = parts.as_slice() else {
+                        return Err(CoverageError::InvalidLine(line.to_owned()).into());
+                    };
+                    let taken = match taken {
+                        "-" => None,
+                        taken => Some(parse_count(line, taken)?),
+                    };
+                    let entry = file
+                        .branches
+                        .entry((
+                            parse_count(line, number)?,
+                            block.to_owned(),
+                            branch.to_owned(),
+                        ))
+                        .or_default();
+                    *entry = match (*entry, taken) {
+                        (Some(a), Some(b)) => Some(a + b),
+                        (a, b) => a.or(b),
+                    };
+                }
+                // Records we don't know how to merge (e.g. `VER`) are dropped.
+                _ => {}
+            }
+        }
+    }
+
+    Ok(MergedReport(files).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_merge_lcov_sums_counts() -> buck2_error::Result<()> {
+        let a = indoc!(
+            "
+            TN:a
+            SF:src/lib.rs
+            FN:1,main
+            FNDA:1,main
+            DA:1,1
+            DA:2,0
+            BRDA:2,0,0,-
+            LF:2
+            LH:1
+            end_of_record
+            "
+        );
+        let b = indoc!(
+            "
+            SF:src/lib.rs
+            FNDA:2,main
+            DA:2,3
+            BRDA:2,0,0,1
+            end_of_record
+            SF:src/other.rs
+            DA:5,0
+            end_of_record
+            "
+        );
+        assert_eq!(
+            indoc!(
+                "
+                TN:
+                SF:src/lib.rs
+                FN:1,main
+                FNDA:3,main
+                FNF:1
+                FNH:1
+                BRDA:2,0,0,1
+                BRF:1
+                BRH:1
+                DA:1,1
+                DA:2,3
+                LF:2
+                LH:2
+                end_of_record
+                TN:
+                SF:src/other.rs
+                FNF:0
+                FNH:0
+                BRF:0
+                BRH:0
+                DA:5,0
+                LF:1
+                LH:0
+                end_of_record
+                "
+            ),
+            merge_lcov([a, b])?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_lcov_invalid() {
+        assert!(merge_lcov(["SF:a\nDA:x,1\nend_of_record\n"]).is_err());
+        assert!(merge_lcov(["DA:1,1\n"]).is_err());
+    }
+}
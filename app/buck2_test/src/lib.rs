@@ -14,6 +14,7 @@
 //! Implementation of test running.
 
 pub mod command;
+pub(crate) mod coverage;
 pub mod downward_api;
 pub mod executor_launcher;
 pub(crate) mod local_resource_api;
@@ -1237,9 +1237,10 @@ impl BuckTestOrchestrator<'_> {
                 };
                 Ok(Cow::Owned(executor_config))
             }
-            Executor::Local(_) | Executor::RemoteEnabled(_) | Executor::None => {
-                Ok(Cow::Borrowed(executor_config))
-            }
+            Executor::Local(_)
+            | Executor::RemoteEnabled(_)
+            | Executor::External(_)
+            | Executor::None => Ok(Cow::Borrowed(executor_config)),
         }
     }
 
@@ -1819,6 +1820,7 @@ impl<'a> Execute2RequestExpander<'a> {
         } = self;
         let cli_args_for_interpolation = test_info
             .command()
+            .chain(test_info.coverage_merger())
             .filter_map(|c| match c {
                 TestCommandMember::Literal(..) => None,
                 TestCommandMember::Arglike(a) => Some(a),
@@ -1868,6 +1870,7 @@ impl<'a> Execute2RequestExpander<'a> {
         } = self;
         let cli_args_for_interpolation = test_info
             .command()
+            .chain(test_info.coverage_merger())
             .filter_map(|c| match c {
                 TestCommandMember::Literal(..) => None,
                 TestCommandMember::Arglike(a) => Some(a),
@@ -2345,6 +2348,7 @@ mod tests {
                     duration: Some(Duration::from_micros(1)),
                     details: "1".to_owned(),
                     max_memory_used_bytes: None,
+                    coverage: None,
                 })
                 .await?;
 
@@ -2357,6 +2361,7 @@ mod tests {
                     duration: Some(Duration::from_micros(2)),
                     details: "2".to_owned(),
                     max_memory_used_bytes: None,
+                    coverage: None,
                 })
                 .await?;
 
@@ -2379,6 +2384,7 @@ mod tests {
                     duration: Some(Duration::from_micros(1)),
                     details: "1".to_owned(),
                     max_memory_used_bytes: None,
+                    coverage: None,
                 }),
                 ExecutorMessage::TestResult(TestResult {
                     target,
@@ -2389,6 +2395,7 @@ mod tests {
                     duration: Some(Duration::from_micros(2)),
                     details: "2".to_owned(),
                     max_memory_used_bytes: None,
+                    coverage: None,
                 }),
                 ExecutorMessage::ExitCode(0),
             ]
@@ -68,6 +68,7 @@ pub(crate) fn convert_test_result(
         details,
         target: test_target,
         max_memory_used_bytes,
+        coverage: _,
     } = test_result;
 
     let test_target = session.get(test_target)?;
@@ -122,6 +122,8 @@ pub struct TestResult {
     pub max_memory_used_bytes: Option<u64>,
     // the output of the test execution (combining stdout and stderr)
     pub details: String,
+    // the LCOV coverage report for the test, when running with coverage
+    pub coverage: Option<AbsNormPathBuf>,
 }
 
 /// different possible test results
@@ -165,6 +167,11 @@ pub struct ExternalRunnerSpec {
     pub oncall: Option<String>,
     /// Cell of current working directory for test command.
     pub working_dir_cell: CellName,
+    /// Whether the test should collect coverage (`buck2 test --coverage`).
+    pub coverage: bool,
+    /// Command which merges the raw coverage data of the test into an LCOV report. Handles in
+    /// it continue the numbering of the handles in `command`. Empty if the rule provides none.
+    pub coverage_merger: Vec<ExternalRunnerSpecValue>,
 }
 
 /// Command line argument or environment variable value
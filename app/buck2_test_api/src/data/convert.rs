@@ -384,6 +384,7 @@ impl TryFrom<buck2_test_proto::TestResult> for TestResult {
             duration,
             details,
             max_memory_used_bytes,
+            coverage,
         } = s;
 
         let duration = duration
@@ -402,6 +403,10 @@ impl TryFrom<buck2_test_proto::TestResult> for TestResult {
             duration,
             max_memory_used_bytes,
             details,
+            coverage: coverage
+                .map(|c| c.try_into())
+                .transpose()
+                .buck_error_context("Invalid `coverage`")?,
         })
     }
 }
@@ -427,6 +432,14 @@ impl TryInto<buck2_test_proto::TestResult> for TestResult {
             msg: self.msg.map(|msg| OptionalMsg { msg }),
             duration: self.duration.try_map(|d| d.try_into())?,
             max_memory_used_bytes: self.max_memory_used_bytes,
+            coverage: self
+                .coverage
+                .map(|c| {
+                    c.to_str()
+                        .buck_error_context("Invalid `coverage`")
+                        .map(|c| c.to_owned())
+                })
+                .transpose()?,
         })
     }
 }
@@ -444,6 +457,8 @@ impl TryFrom<buck2_test_proto::ExternalRunnerSpec> for ExternalRunnerSpec {
             contacts,
             oncall,
             working_dir_cell,
+            coverage,
+            coverage_merger,
         } = s;
 
         Ok(Self {
@@ -463,6 +478,10 @@ impl TryFrom<buck2_test_proto::ExternalRunnerSpec> for ExternalRunnerSpec {
             contacts,
             oncall,
             working_dir_cell: CellName::unchecked_new(&working_dir_cell)?,
+            coverage,
+            coverage_merger: coverage_merger
+                .into_try_map(|x| x.try_into())
+                .buck_error_context("Invalid `coverage_merger`")?,
         })
     }
 }
@@ -480,6 +499,8 @@ impl TryInto<buck2_test_proto::ExternalRunnerSpec> for ExternalRunnerSpec {
             contacts,
             oncall,
             working_dir_cell,
+            coverage,
+            coverage_merger,
         } = self;
         Ok(buck2_test_proto::ExternalRunnerSpec {
             target: Some(target.try_into().buck_error_context("Invalid `target`")?),
@@ -495,6 +516,10 @@ impl TryInto<buck2_test_proto::ExternalRunnerSpec> for ExternalRunnerSpec {
             contacts,
             oncall,
             working_dir_cell: working_dir_cell.as_str().to_owned(),
+            coverage,
+            coverage_merger: coverage_merger
+                .into_try_map(|x| x.try_into())
+                .buck_error_context("Invalid `coverage_merger`")?,
         })
     }
 }
@@ -1227,6 +1252,11 @@ mod tests {
             contacts: vec!["contact1".to_owned(), "contact2".to_owned()],
             oncall: Some("contact1".to_owned()),
             working_dir_cell: CellName::testing_new("qux"),
+            coverage: true,
+            coverage_merger: vec![
+                ExternalRunnerSpecValue::Verbatim("merge".to_owned()),
+                ExternalRunnerSpecValue::ArgHandle(ArgHandle(43)),
+            ],
         };
         assert_roundtrips::<buck2_test_proto::ExternalRunnerSpec, ExternalRunnerSpec>(&test_spec);
     }
@@ -11,14 +11,19 @@
 use std::time::Duration;
 
 use buck2_error::BuckErrorContext;
+use buck2_fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_test_api::data::ArgValue;
 use buck2_test_api::data::ArgValueContent;
 use buck2_test_api::data::ConfiguredTargetHandle;
+use buck2_test_api::data::DeclaredOutput;
 use buck2_test_api::data::ExecuteResponse;
 use buck2_test_api::data::ExecutionResult2;
 use buck2_test_api::data::ExecutionStatus;
 use buck2_test_api::data::ExternalRunnerSpec;
 use buck2_test_api::data::ExternalRunnerSpecValue;
+use buck2_test_api::data::Output;
+use buck2_test_api::data::OutputName;
+use buck2_test_api::data::RemoteStorageConfig;
 use buck2_test_api::data::RequiredLocalResources;
 use buck2_test_api::data::TestResult;
 use buck2_test_api::data::TestStage;
@@ -30,12 +35,18 @@ use futures::TryStreamExt;
 use futures::channel::mpsc::UnboundedReceiver;
 use host_sharing::HostSharingRequirements;
 use parking_lot::Mutex;
+use sorted_vector_map::SortedVectorMap;
 
 use crate::config::Config;
 use crate::config::EnvValue;
 
 pub type SpecReceiver = UnboundedReceiver<ExternalRunnerSpec>;
 
+/// The output directory tests write their raw coverage data to.
+const COVERAGE_OUTPUT: &str = "coverage";
+/// The LCOV report written by the coverage merger of a test.
+const COVERAGE_REPORT: &str = "coverage.lcov";
+
 /// Internal test runner implementation for Buck2.
 ///
 /// This is a basic test runner intended to be used by the open-source Buck2 build
@@ -80,6 +91,8 @@ impl Buck2TestRunner {
                     spec.target.cell, spec.target.package, spec.target.target
                 );
                 let target_handle = spec.target.handle.to_owned();
+                let suite = spec.target.target.clone();
+                let coverage_merger = spec.coverage.then(|| spec.coverage_merger.clone());
 
                 let execution_response = self
                     .execute_test_from_spec(spec)
@@ -91,7 +104,35 @@ impl Buck2TestRunner {
                     ExecuteResponse::Cancelled(_) => return Ok(TestStatus::OMITTED),
                 };
 
-                let test_result = get_test_result(name, target_handle, execution_result);
+                let coverage = match (
+                    coverage_merger,
+                    execution_result
+                        .outputs
+                        .get(&OutputName::unchecked_new(COVERAGE_OUTPUT.to_owned())),
+                ) {
+                    (Some(merger), Some(Output::LocalPath(coverage_dir))) if !merger.is_empty() => {
+                        Some(
+                            self.merge_coverage(suite, target_handle, merger, coverage_dir)
+                                .await,
+                        )
+                    }
+                    _ => None,
+                };
+
+                let mut test_result = get_test_result(name, target_handle, execution_result);
+                match coverage {
+                    Some(Ok(report)) => test_result.coverage = report,
+                    // The test itself ran, so report the failure on its result rather than
+                    // failing the whole run.
+                    Some(Err(message)) => {
+                        let message = format!("Coverage merging failed: {message}");
+                        test_result
+                            .details
+                            .push_str(&format!("---- COVERAGE ----\n{message}\n"));
+                        test_result.msg = Some(message);
+                    }
+                    None => {}
+                }
                 let test_status = test_result.status.clone();
 
                 self.report_test_result(test_result)
@@ -165,6 +206,8 @@ impl Buck2TestRunner {
             )
         });
 
+        let coverage_env = spec.coverage.then(coverage_env).into_iter().flatten();
+
         let env = spec
             .env
             .into_iter()
@@ -178,11 +221,19 @@ impl Buck2TestRunner {
                 )
             })
             .chain(config_env)
+            .chain(coverage_env)
             .collect();
 
         let target_handle = spec.target.handle;
         let host_sharing_requirements = HostSharingRequirements::default();
-        let pre_create_dirs = Vec::new();
+        let pre_create_dirs = if spec.coverage {
+            vec![DeclaredOutput::unchecked_new(
+                COVERAGE_OUTPUT.to_owned(),
+                RemoteStorageConfig::new(false),
+            )]
+        } else {
+            Vec::new()
+        };
         let executor_override = None;
 
         self.orchestrator_client
@@ -200,6 +251,78 @@ impl Buck2TestRunner {
             .await
     }
 
+    /// Runs the coverage merger of a test over the raw coverage data it wrote, returning the path
+    /// of the resulting LCOV report, or `None` if the merger was cancelled. If the merger could not
+    /// run, failed, or did not write a report, returns a message describing why.
+    async fn merge_coverage(
+        &self,
+        suite: String,
+        target_handle: ConfiguredTargetHandle,
+        merger: Vec<ExternalRunnerSpecValue>,
+        coverage_dir: &AbsNormPathBuf,
+    ) -> Result<Option<AbsNormPathBuf>, String> {
+        let stage = TestStage::Testing {
+            suite,
+            testcases: Vec::new(),
+            variant: Some("coverage".to_owned()),
+        };
+
+        let report = OutputName::unchecked_new(COVERAGE_REPORT.to_owned());
+        let command = merger
+            .into_iter()
+            .map(|spec_value| ArgValue {
+                content: ArgValueContent::ExternalRunnerSpecValue(spec_value),
+                format: None,
+            })
+            .chain([
+                ArgValue {
+                    content: ArgValueContent::ExternalRunnerSpecValue(
+                        ExternalRunnerSpecValue::Verbatim(coverage_dir.to_string()),
+                    ),
+                    format: None,
+                },
+                ArgValue {
+                    content: ArgValueContent::DeclaredOutput(report.clone()),
+                    format: None,
+                },
+            ])
+            .collect();
+
+        let response = self
+            .orchestrator_client
+            .execute2(
+                stage,
+                target_handle,
+                command,
+                SortedVectorMap::new(),
+                Duration::from_secs(self.config.timeout),
+                HostSharingRequirements::default(),
+                Vec::new(),
+                None,
+                RequiredLocalResources { resources: vec![] },
+            )
+            .await
+            .map_err(|e| format!("{e:#}"))?;
+
+        let result = match response {
+            ExecuteResponse::Result(r) => r,
+            ExecuteResponse::Cancelled(_) => return Ok(None),
+        };
+        match (&result.status, result.outputs.get(&report)) {
+            (ExecutionStatus::Finished { exitcode: 0 }, Some(Output::LocalPath(path))) => {
+                Ok(Some(path.clone()))
+            }
+            (ExecutionStatus::Finished { exitcode: 0 }, _) => {
+                Err("the merger did not write a report".to_owned())
+            }
+            (ExecutionStatus::Finished { exitcode }, _) => Err(format!(
+                "the merger exited with code {exitcode}\n---- STDOUT ----\n{:?}\n---- STDERR ----\n{:?}",
+                result.stdout, result.stderr
+            )),
+            (ExecutionStatus::TimedOut { .. }, _) => Err("the merger timed out".to_owned()),
+        }
+    }
+
     async fn report_test_result(&self, test_result: TestResult) -> buck2_error::Result<()> {
         self.orchestrator_client
             .report_test_result(test_result)
@@ -230,9 +353,32 @@ fn get_test_result(
             execution_result.stdout, execution_result.stderr
         ),
         max_memory_used_bytes: execution_result.max_memory_used_bytes,
+        coverage: None,
     }
 }
 
+/// Points the coverage runtimes of LLVM, gcc and coverage.py at the coverage output directory.
+fn coverage_env() -> impl Iterator<Item = (String, ArgValue)> {
+    [
+        ("LLVM_PROFILE_FILE", "{}/%p-%m.profraw"),
+        ("GCOV_PREFIX", "{}"),
+        ("COVERAGE_FILE", "{}/.coverage"),
+        ("BUCK_COVERAGE_DIR", "{}"),
+    ]
+    .into_iter()
+    .map(|(name, format)| {
+        (
+            name.to_owned(),
+            ArgValue {
+                content: ArgValueContent::DeclaredOutput(OutputName::unchecked_new(
+                    COVERAGE_OUTPUT.to_owned(),
+                )),
+                format: Some(format.to_owned()),
+            },
+        )
+    })
+}
+
 #[derive(Debug)]
 enum RunVerdict {
     Pass,
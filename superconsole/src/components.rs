@@ -18,8 +18,12 @@ pub use blank::Blank;
 pub use bordering::Bordered;
 pub use bounding::Bounded;
 pub use padding::Padded;
+pub use progress_bar::ProgressBar;
+pub use scroll_view::ScrollView;
+pub use sparkline::Sparkline;
 pub use spinner::Spinner;
 pub use splitting::Split;
+pub use table::Table;
 
 use crate::Dimensions;
 use crate::Lines;
@@ -35,8 +39,12 @@ mod draw_horizontal;
 mod draw_vertical;
 pub(crate) mod echo;
 pub mod padding;
+pub mod progress_bar;
+pub mod scroll_view;
+pub mod sparkline;
 pub mod spinner;
 pub mod splitting;
+pub mod table;
 
 /// Used to mark whether a draw is final.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
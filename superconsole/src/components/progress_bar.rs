@@ -0,0 +1,143 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! A horizontal bar showing how much of some work is done.
+
+use std::convert::Infallible;
+
+use crate::Dimensions;
+use crate::Line;
+use crate::Lines;
+use crate::Span;
+use crate::components::Component;
+use crate::components::DrawMode;
+use crate::style::ContentStyle;
+use crate::style::StyledContent;
+
+/// Partially filled cells, by eighths.
+const PARTIAL_BLOCKS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+const FULL_BLOCK: char = '█';
+
+/// A component that renders `label [█████▍    ]  54%` on a single line, using all the width it is
+/// given.
+///
+/// The bar is drawn with eighth-of-a-cell precision. If there isn't room for the bar, only the
+/// label and percentage are drawn.
+pub struct ProgressBar {
+    done: u64,
+    total: u64,
+    label: Option<Line>,
+    style: ContentStyle,
+}
+
+impl ProgressBar {
+    /// A bar for `done` out of `total` units of work. No work at all counts as not started.
+    pub fn new(done: u64, total: u64) -> Self {
+        Self {
+            done,
+            total,
+            label: None,
+            style: ContentStyle::default(),
+        }
+    }
+
+    /// Text to draw before the bar.
+    pub fn with_label(mut self, label: Line) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    /// The style of the filled part of the bar.
+    pub fn with_style(mut self, style: ContentStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// How much of the work is done, between 0 and 1.
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            (self.done as f64 / self.total as f64).min(1.0)
+        }
+    }
+
+    /// The bar, without brackets, filling `width` cells.
+    fn bar(&self, width: usize) -> (String, String) {
+        let eighths = (self.fraction() * (width * 8) as f64).round() as usize;
+        let mut filled = FULL_BLOCK.to_string().repeat(eighths / 8);
+        let partial = eighths % 8;
+        if partial != 0 {
+            filled.push(PARTIAL_BLOCKS[partial - 1]);
+        }
+        let empty = " ".repeat(width - filled.chars().count());
+        (filled, empty)
+    }
+}
+
+impl Component for ProgressBar {
+    type Error = Infallible;
+
+    fn draw_unchecked(&self, dimensions: Dimensions, _mode: DrawMode) -> Result<Lines, Infallible> {
+        let percentage = format!("{:>4}%", (self.fraction() * 100.0).floor() as u64);
+
+        let mut line = self.label.clone().unwrap_or_default();
+        if !line.is_empty() {
+            line.pad_right(1);
+        }
+
+        // Brackets, then the percentage.
+        let chrome = 2 + percentage.len();
+        let bar_width = dimensions.width.saturating_sub(line.len() + chrome);
+        if bar_width > 0 {
+            let (filled, empty) = self.bar(bar_width);
+            line.push(Span::new_unstyled_lossy("["));
+            line.push(Span::new_styled_lossy(StyledContent::new(
+                self.style, filled,
+            )));
+            line.push(Span::new_unstyled_lossy(empty));
+            line.push(Span::new_unstyled_lossy("]"));
+        }
+        line.push(Span::new_unstyled_lossy(percentage));
+
+        Ok(Lines(vec![line]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(bar: &ProgressBar, width: usize) -> String {
+        let lines = bar
+            .draw(Dimensions::new(width, 1), DrawMode::Normal)
+            .unwrap();
+        assert_eq!(lines.len(), 1);
+        lines.0[0].to_unstyled()
+    }
+
+    #[test]
+    fn test_progress_bar() {
+        assert_eq!(draw(&ProgressBar::new(0, 4), 12), "[     ]   0%");
+        assert_eq!(draw(&ProgressBar::new(1, 2), 12), "[██▌  ]  50%");
+        assert_eq!(draw(&ProgressBar::new(4, 4), 12), "[█████] 100%");
+        // Overshooting is clamped, and no work counts as none done.
+        assert_eq!(draw(&ProgressBar::new(9, 4), 12), "[█████] 100%");
+        assert_eq!(draw(&ProgressBar::new(0, 0), 12), "[     ]   0%");
+    }
+
+    #[test]
+    fn test_label() {
+        let bar = ProgressBar::new(1, 3).with_label(Line::sanitized("Jobs"));
+        assert_eq!(draw(&bar, 18), "Jobs [██    ]  33%");
+        // Not enough room for the bar.
+        assert_eq!(draw(&bar, 12), "Jobs   33%");
+    }
+}
@@ -0,0 +1,195 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! A window over a component that draws more lines than fit on screen.
+
+use crate::Dimensions;
+use crate::Line;
+use crate::Lines;
+use crate::Span;
+use crate::components::Component;
+use crate::components::DrawMode;
+use crate::style::Attribute;
+use crate::style::ContentStyle;
+use crate::style::StyledContent;
+
+/// Which part of the child a [`ScrollView`] shows.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ScrollPosition {
+    /// Start at this line, or as close to it as possible while filling the view.
+    Offset(usize),
+    /// Show the last lines, e.g. to follow the output of a command.
+    Bottom,
+}
+
+/// A component that draws its child with the height of its content, then shows only the part of
+/// it that fits, according to its [`ScrollPosition`].
+///
+/// The child is given `content_height` lines to draw in, so that components which fill their
+/// height (e.g. with alignment or padding) get a sensible size. Lines past it are cut.
+///
+/// With indicators, the first and last lines of the view are replaced by a count of the lines
+/// hidden above and below, when there are any.
+pub struct ScrollView<C> {
+    child: C,
+    content_height: usize,
+    position: ScrollPosition,
+    indicators: bool,
+}
+
+impl<C: Component> ScrollView<C> {
+    /// A view of the start of `child`, which draws `content_height` lines.
+    pub fn new(child: C, content_height: usize) -> Self {
+        Self {
+            child,
+            content_height,
+            position: ScrollPosition::Offset(0),
+            indicators: false,
+        }
+    }
+
+    pub fn with_position(mut self, position: ScrollPosition) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn with_indicators(mut self) -> Self {
+        self.indicators = true;
+        self
+    }
+}
+
+fn indicator(arrow: char, hidden: usize) -> Line {
+    let plural = if hidden == 1 { "" } else { "s" };
+    Line::from_iter([Span::new_styled_lossy(StyledContent::new(
+        ContentStyle {
+            attributes: Attribute::Italic.into(),
+            ..ContentStyle::default()
+        },
+        format!("{arrow} {hidden} more line{plural}"),
+    ))])
+}
+
+impl<C: Component> Component for ScrollView<C> {
+    type Error = C::Error;
+
+    fn draw_unchecked(&self, dimensions: Dimensions, mode: DrawMode) -> Result<Lines, C::Error> {
+        let Lines(mut lines) = self
+            .child
+            .draw(Dimensions::new(dimensions.width, self.content_height), mode)?;
+        let total = lines.len();
+        let height = dimensions.height;
+        if total <= height {
+            return Ok(Lines(lines));
+        }
+
+        let offset = match self.position {
+            ScrollPosition::Offset(offset) => offset.min(total - height),
+            ScrollPosition::Bottom => total - height,
+        };
+        let mut window: Vec<Line> = lines.drain(offset..offset + height).collect();
+
+        // With a single line there's no room to say what's hidden on both sides, so only mention
+        // what's above.
+        if self.indicators && height > 0 {
+            let below = total - offset - height;
+            if below > 0 && height > 1 {
+                *window.last_mut().unwrap() = indicator('↓', below + 1);
+            }
+            if offset > 0 {
+                window[0] = indicator('↑', offset + 1);
+            }
+        }
+        Ok(Lines(window))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use super::*;
+    use crate::components::echo::Echo;
+
+    fn numbers(n: usize) -> ScrollView<Echo> {
+        ScrollView::new(
+            Echo(Lines(
+                (0..n).map(|i| Line::sanitized(&i.to_string())).collect(),
+            )),
+            n,
+        )
+    }
+
+    fn draw<C: Component<Error = Infallible>>(view: &ScrollView<C>, height: usize) -> Vec<String> {
+        view.draw(Dimensions::new(20, height), DrawMode::Normal)
+            .unwrap()
+            .iter()
+            .map(|line| line.to_unstyled())
+            .collect()
+    }
+
+    #[test]
+    fn test_positions() {
+        let view = numbers(10);
+        assert_eq!(draw(&view, 3), vec!["0", "1", "2"]);
+        // Short content is drawn as is.
+        assert_eq!(draw(&view, 20).len(), 10);
+
+        let view = view.with_position(ScrollPosition::Offset(4));
+        assert_eq!(draw(&view, 3), vec!["4", "5", "6"]);
+        // Scrolled past the end, the view stays full.
+        assert_eq!(draw(&view, 8), vec!["2", "3", "4", "5", "6", "7", "8", "9"]);
+
+        let view = view.with_position(ScrollPosition::Bottom);
+        assert_eq!(draw(&view, 3), vec!["7", "8", "9"]);
+    }
+
+    #[test]
+    fn test_indicators() {
+        let view = numbers(10).with_indicators();
+        assert_eq!(draw(&view, 3), vec!["0", "1", "↓ 8 more lines"]);
+
+        let view = view.with_position(ScrollPosition::Offset(4));
+        assert_eq!(
+            draw(&view, 4),
+            vec!["↑ 5 more lines", "5", "6", "↓ 3 more lines"]
+        );
+
+        let view = view.with_position(ScrollPosition::Bottom);
+        assert_eq!(draw(&view, 2), vec!["↑ 9 more lines", "9"]);
+        assert_eq!(draw(&view, 1), vec!["↑ 10 more lines"]);
+    }
+
+    /// Fills whatever height it is given.
+    struct Fill;
+
+    impl Component for Fill {
+        type Error = Infallible;
+
+        fn draw_unchecked(
+            &self,
+            dimensions: Dimensions,
+            _mode: DrawMode,
+        ) -> Result<Lines, Infallible> {
+            Ok(Lines(
+                (0..dimensions.height)
+                    .map(|i| Line::sanitized(&i.to_string()))
+                    .collect(),
+            ))
+        }
+    }
+
+    #[test]
+    fn test_child_gets_content_height() {
+        let view = ScrollView::new(Fill, 6).with_position(ScrollPosition::Bottom);
+        assert_eq!(draw(&view, 2), vec!["4", "5"]);
+        assert_eq!(draw(&view, 10).len(), 6);
+    }
+}
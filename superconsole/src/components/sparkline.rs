@@ -0,0 +1,107 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! A one line chart of recent values, e.g. the number of running jobs over time.
+
+use std::convert::Infallible;
+
+use crate::Dimensions;
+use crate::Line;
+use crate::Lines;
+use crate::Span;
+use crate::components::Component;
+use crate::components::DrawMode;
+use crate::style::ContentStyle;
+use crate::style::StyledContent;
+
+const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// A component that draws one bar per value, oldest first, scaled so that the largest value fills
+/// the cell.
+///
+/// When there are more values than the width allows, only the most recent ones are drawn. Zero is
+/// drawn as a blank, so that it can be told apart from small values.
+pub struct Sparkline {
+    values: Vec<u64>,
+    max: Option<u64>,
+    style: ContentStyle,
+}
+
+impl Sparkline {
+    pub fn new(values: Vec<u64>) -> Self {
+        Self {
+            values,
+            max: None,
+            style: ContentStyle::default(),
+        }
+    }
+
+    /// Scale to this value instead of the largest one drawn. Larger values are drawn full.
+    pub fn with_max(mut self, max: u64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    pub fn with_style(mut self, style: ContentStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    fn level(value: u64, max: u64) -> char {
+        if value == 0 || max == 0 {
+            return ' ';
+        }
+        let level = (value.min(max) as u128 * LEVELS.len() as u128).div_ceil(max as u128);
+        LEVELS[level as usize - 1]
+    }
+}
+
+impl Component for Sparkline {
+    type Error = Infallible;
+
+    fn draw_unchecked(&self, dimensions: Dimensions, _mode: DrawMode) -> Result<Lines, Infallible> {
+        let values = &self.values[self.values.len().saturating_sub(dimensions.width)..];
+        let max = self
+            .max
+            .unwrap_or_else(|| values.iter().copied().max().unwrap_or(0));
+        let chart: String = values.iter().map(|v| Self::level(*v, max)).collect();
+        Ok(Lines(vec![Line::from_iter([Span::new_styled_lossy(
+            StyledContent::new(self.style, chart),
+        )])]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(sparkline: &Sparkline, width: usize) -> String {
+        sparkline
+            .draw(Dimensions::new(width, 1), DrawMode::Normal)
+            .unwrap()
+            .0[0]
+            .to_unstyled()
+    }
+
+    #[test]
+    fn test_sparkline() {
+        let sparkline = Sparkline::new(vec![0, 1, 2, 4, 8]);
+        assert_eq!(draw(&sparkline, 10), " ▁▂▄█");
+        // Only the latest values are drawn, and rescaled.
+        assert_eq!(draw(&sparkline, 2), "▄█");
+    }
+
+    #[test]
+    fn test_with_max() {
+        let sparkline = Sparkline::new(vec![1, 8, 16]).with_max(8);
+        assert_eq!(draw(&sparkline, 10), "▁██");
+        assert_eq!(draw(&Sparkline::new(vec![0, 0]), 10), "  ");
+    }
+}
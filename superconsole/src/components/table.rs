@@ -0,0 +1,303 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! A table of styled cells, whose columns are sized to their content and the available width.
+
+use std::convert::Infallible;
+
+use crate::Component;
+use crate::Dimensions;
+use crate::DrawMode;
+use crate::Line;
+use crate::Lines;
+use crate::Span;
+use crate::style::StyledContent;
+
+const ELLIPSIS: &str = "...";
+
+/// How wide a [`Column`] is.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ColumnWidth {
+    /// Exactly this many cells.
+    Fixed(usize),
+    /// As wide as the widest cell in the column. When the table doesn't fit, the widest of these
+    /// columns shrink first.
+    Fit,
+    /// Whatever width the other columns leave over, shared equally with other `Fill` columns.
+    Fill,
+}
+
+/// Which side of a column its cells stick to.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ColumnAlignment {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Column {
+    pub width: ColumnWidth,
+    pub alignment: ColumnAlignment,
+}
+
+impl Column {
+    /// A left-aligned column.
+    pub fn new(width: ColumnWidth) -> Self {
+        Self {
+            width,
+            alignment: ColumnAlignment::Left,
+        }
+    }
+
+    pub fn right_aligned(mut self) -> Self {
+        self.alignment = ColumnAlignment::Right;
+        self
+    }
+}
+
+/// The [`Table`] [`Component`] lays out rows of cells in columns.
+///
+/// Each column is sized according to its [`ColumnWidth`], and cells that don't fit in their
+/// column are truncated with an ellipsis, keeping the style of their last span. Rows may have
+/// fewer cells than there are columns: the rest are left blank.
+#[derive(Debug, Clone)]
+pub struct Table {
+    columns: Vec<Column>,
+    header: Option<Vec<Line>>,
+    rows: Vec<Vec<Line>>,
+    gap: usize,
+}
+
+impl Table {
+    /// A table with the given columns, separated by one space.
+    pub fn new(columns: Vec<Column>) -> Self {
+        Self {
+            columns,
+            header: None,
+            rows: Vec::new(),
+            gap: 1,
+        }
+    }
+
+    /// A row drawn above the others. It is sized like any other row, so style it to stand out.
+    pub fn with_header(mut self, header: Vec<Line>) -> Self {
+        self.header = Some(header);
+        self
+    }
+
+    /// How many spaces to put between columns.
+    pub fn with_gap(mut self, gap: usize) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    pub fn push_row(&mut self, row: Vec<Line>) {
+        self.rows.push(row);
+    }
+
+    /// The number of rows, not counting the header.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    fn all_rows(&self) -> impl Iterator<Item = &Vec<Line>> {
+        self.header.iter().chain(self.rows.iter())
+    }
+
+    /// The width of each column when drawing in `width` cells.
+    fn column_widths(&self, width: usize) -> Vec<usize> {
+        let mut content = vec![0; self.columns.len()];
+        for row in self.all_rows() {
+            for (max, cell) in content.iter_mut().zip(row) {
+                *max = (*max).max(cell.len());
+            }
+        }
+
+        let mut widths: Vec<usize> = self
+            .columns
+            .iter()
+            .zip(&content)
+            .map(|(column, content)| match column.width {
+                ColumnWidth::Fixed(width) => width,
+                ColumnWidth::Fit => *content,
+                ColumnWidth::Fill => 0,
+            })
+            .collect();
+
+        let gaps = self.gap * self.columns.len().saturating_sub(1);
+        let available = width.saturating_sub(gaps);
+        let used: usize = widths.iter().sum();
+
+        if used <= available {
+            let fill: Vec<usize> = (0..self.columns.len())
+                .filter(|i| self.columns[*i].width == ColumnWidth::Fill)
+                .collect();
+            if !fill.is_empty() {
+                let spare = available - used;
+                for (n, i) in fill.iter().enumerate() {
+                    widths[*i] = spare / fill.len() + usize::from(n < spare % fill.len());
+                }
+            }
+            return widths;
+        }
+
+        // Too wide: cap the `Fit` columns at the largest width that makes everything fit. If even
+        // that's not enough, the rows are truncated when drawn.
+        let fit: Vec<usize> = (0..self.columns.len())
+            .filter(|i| self.columns[*i].width == ColumnWidth::Fit)
+            .collect();
+        let fixed = used - fit.iter().map(|i| widths[*i]).sum::<usize>();
+        let budget = available.saturating_sub(fixed);
+        let total_at = |cap: usize| fit.iter().map(|i| widths[*i].min(cap)).sum::<usize>();
+
+        let (mut low, mut high) = (0, fit.iter().map(|i| widths[*i]).max().unwrap_or(0));
+        while low < high {
+            let mid = (low + high).div_ceil(2);
+            if total_at(mid) <= budget {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        let cap = low;
+
+        // Hand out what is left below the cap, left to right.
+        let mut spare = budget - total_at(cap);
+        for i in &fit {
+            let mut w = widths[*i].min(cap);
+            if w < widths[*i] && spare > 0 {
+                w += 1;
+                spare -= 1;
+            }
+            widths[*i] = w;
+        }
+        widths
+    }
+
+    fn draw_row(&self, row: &[Line], widths: &[usize]) -> Line {
+        let mut line = Line::default();
+        for (i, (column, width)) in self.columns.iter().zip(widths).enumerate() {
+            if i > 0 {
+                line.pad_right(self.gap);
+            }
+            let mut cell = row.get(i).cloned().unwrap_or_default();
+            truncate_cell(&mut cell, *width);
+            let padding = width - cell.len();
+            match column.alignment {
+                ColumnAlignment::Left => cell.pad_right(padding),
+                ColumnAlignment::Right => cell.pad_left(padding),
+            }
+            line.extend(cell);
+        }
+        line
+    }
+}
+
+fn truncate_cell(cell: &mut Line, width: usize) {
+    if cell.len() <= width {
+        return;
+    }
+    if width <= ELLIPSIS.len() {
+        cell.truncate_line(width);
+        return;
+    }
+
+    let style = cell
+        .iter()
+        .last()
+        .map(|span| span.style)
+        .unwrap_or_default();
+    cell.truncate_line(width - ELLIPSIS.len());
+    cell.push(Span::new_styled_lossy(StyledContent::new(
+        style,
+        ELLIPSIS.to_owned(),
+    )));
+}
+
+impl Component for Table {
+    type Error = Infallible;
+
+    fn draw_unchecked(&self, dimensions: Dimensions, _mode: DrawMode) -> Result<Lines, Infallible> {
+        let widths = self.column_widths(dimensions.width);
+        Ok(Lines(
+            self.all_rows()
+                .take(dimensions.height)
+                .map(|row| self.draw_row(row, &widths))
+                .collect(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(cells: &[&str]) -> Vec<Line> {
+        cells.iter().map(|c| Line::sanitized(c)).collect()
+    }
+
+    fn draw(table: &Table, width: usize) -> Vec<String> {
+        table
+            .draw(Dimensions::new(width, 10), DrawMode::Normal)
+            .unwrap()
+            .iter()
+            .map(|line| line.to_unstyled())
+            .collect()
+    }
+
+    #[test]
+    fn test_fill_and_fit() {
+        let mut table = Table::new(vec![
+            Column::new(ColumnWidth::Fill),
+            Column::new(ColumnWidth::Fit).right_aligned(),
+        ]);
+        table.push_row(row(&["compile", "1.2s"]));
+        table.push_row(row(&["link", "10.0s"]));
+
+        assert_eq!(
+            draw(&table, 16),
+            vec!["compile     1.2s", "link       10.0s"]
+        );
+        // The label is truncated to make room for the time.
+        assert_eq!(draw(&table, 10), vec!["c...  1.2s", "link 10.0s"]);
+    }
+
+    #[test]
+    fn test_shrink_widest_fit_column() {
+        let mut table = Table::new(vec![
+            Column::new(ColumnWidth::Fit),
+            Column::new(ColumnWidth::Fit),
+            Column::new(ColumnWidth::Fixed(2)),
+        ])
+        .with_header(row(&["name", "description", "ok"]));
+        table.push_row(row(&["a", "a long description", "y"]));
+
+        assert_eq!(
+            draw(&table, 18),
+            vec!["name descrip... ok", "a    a long ... y "]
+        );
+    }
+
+    #[test]
+    fn test_missing_cells() {
+        let mut table = Table::new(vec![
+            Column::new(ColumnWidth::Fixed(3)),
+            Column::new(ColumnWidth::Fixed(3)),
+        ])
+        .with_gap(2);
+        table.push_row(row(&["abcdef"]));
+
+        assert_eq!(draw(&table, 20), vec!["abc     "]);
+    }
+}
@@ -0,0 +1,236 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! Keyboard input for interactive components.
+//!
+//! Superconsole does not read from the terminal itself: the caller reads raw bytes (with the
+//! terminal in non-canonical mode), feeds them through a [`KeyDecoder`], and hands the resulting
+//! [`Key`]s to whichever component has focus, via [`Interactive::handle_key`].
+
+/// A key press, decoded from the bytes a terminal sends.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Enter,
+    Escape,
+    Backspace,
+    Tab,
+    Char(char),
+}
+
+/// A component (or the state behind one) which responds to keyboard input while it has focus.
+pub trait Interactive {
+    /// Returns whether the key was used. Unused keys can be passed on to another component.
+    fn handle_key(&mut self, key: Key) -> bool;
+}
+
+const ESC: u8 = 0x1b;
+
+/// Longest escape sequence we try to decode. Anything longer is dropped.
+const MAX_SEQUENCE_LEN: usize = 16;
+
+/// Turns the bytes a terminal sends into [`Key`]s, one byte at a time.
+///
+/// A lone `Esc` can't be told apart from the start of an escape sequence until the next byte
+/// arrives, so callers should [`flush`](KeyDecoder::flush) the decoder if no further byte arrives
+/// shortly after one for which [`is_pending`](KeyDecoder::is_pending) is true.
+#[derive(Debug, Default)]
+pub struct KeyDecoder {
+    pending: Vec<u8>,
+}
+
+impl KeyDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether some bytes have been received that don't form a complete key yet.
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Decode the bytes received so far as best we can, and reset. Only a lone `Esc` decodes to a
+    /// key; incomplete sequences are dropped.
+    pub fn flush(&mut self) -> Option<Key> {
+        let key = match self.pending.as_slice() {
+            [ESC] => Some(Key::Escape),
+            _ => None,
+        };
+        self.pending.clear();
+        key
+    }
+
+    /// Feed one byte to the decoder, returning a key if that completes one.
+    pub fn push(&mut self, byte: u8) -> Option<Key> {
+        match self.pending.as_slice() {
+            [] => self.push_first(byte),
+            [ESC] => match byte {
+                b'[' | b'O' => {
+                    self.pending.push(byte);
+                    None
+                }
+                // A second `Esc` is a new sequence: the first one was a lone `Esc`.
+                ESC => Some(Key::Escape),
+                // `Esc` followed by anything else is how terminals send `Alt` chords, which we
+                // don't support.
+                _ => {
+                    self.pending.clear();
+                    Some(Key::Escape)
+                }
+            },
+            [ESC, ..] => self.push_escape_sequence(byte),
+            _ => self.push_utf8(byte),
+        }
+    }
+
+    fn push_first(&mut self, byte: u8) -> Option<Key> {
+        match byte {
+            ESC => {
+                self.pending.push(byte);
+                None
+            }
+            b'\r' | b'\n' => Some(Key::Enter),
+            b'\t' => Some(Key::Tab),
+            0x7f | 0x08 => Some(Key::Backspace),
+            0x20..=0x7e => Some(Key::Char(byte as char)),
+            // Start of a multi-byte UTF-8 character.
+            0xc0..=0xf7 => {
+                self.pending.push(byte);
+                None
+            }
+            // Other control characters, or stray continuation bytes.
+            _ => None,
+        }
+    }
+
+    fn push_escape_sequence(&mut self, byte: u8) -> Option<Key> {
+        self.pending.push(byte);
+
+        // Parameter and intermediate bytes, which precede the final byte.
+        if (0x20..=0x3f).contains(&byte) {
+            if self.pending.len() >= MAX_SEQUENCE_LEN {
+                self.pending.clear();
+            }
+            return None;
+        }
+
+        let sequence = std::mem::take(&mut self.pending);
+        let (introducer, params) = (sequence[1], &sequence[2..sequence.len() - 1]);
+        match (introducer, params, byte) {
+            (_, [], b'A') => Some(Key::Up),
+            (_, [], b'B') => Some(Key::Down),
+            (_, [], b'C') => Some(Key::Right),
+            (_, [], b'D') => Some(Key::Left),
+            (_, [], b'H') => Some(Key::Home),
+            (_, [], b'F') => Some(Key::End),
+            (b'[', b"1" | b"7", b'~') => Some(Key::Home),
+            (b'[', b"4" | b"8", b'~') => Some(Key::End),
+            (b'[', b"5", b'~') => Some(Key::PageUp),
+            (b'[', b"6", b'~') => Some(Key::PageDown),
+            _ => None,
+        }
+    }
+
+    fn push_utf8(&mut self, byte: u8) -> Option<Key> {
+        if byte & 0xc0 != 0x80 {
+            // Not a continuation byte: the character was truncated. Start over with this byte.
+            self.pending.clear();
+            return self.push_first(byte);
+        }
+
+        self.pending.push(byte);
+        let expected_len = match self.pending[0] {
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            _ => 4,
+        };
+        if self.pending.len() < expected_len {
+            return None;
+        }
+
+        let bytes = std::mem::take(&mut self.pending);
+        std::str::from_utf8(&bytes)
+            .ok()
+            .and_then(|s| s.chars().next())
+            .map(Key::Char)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8]) -> Vec<Key> {
+        let mut decoder = KeyDecoder::new();
+        let mut keys: Vec<Key> = bytes.iter().filter_map(|b| decoder.push(*b)).collect();
+        keys.extend(decoder.flush());
+        keys
+    }
+
+    #[test]
+    fn test_plain_keys() {
+        assert_eq!(
+            decode(b"a?\r\t\x7f"),
+            vec![
+                Key::Char('a'),
+                Key::Char('?'),
+                Key::Enter,
+                Key::Tab,
+                Key::Backspace
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escape_sequences() {
+        assert_eq!(
+            decode(b"\x1b[A\x1b[B\x1bOC\x1b[5~\x1b[6~\x1b[H\x1b[4~"),
+            vec![
+                Key::Up,
+                Key::Down,
+                Key::Right,
+                Key::PageUp,
+                Key::PageDown,
+                Key::Home,
+                Key::End
+            ]
+        );
+        // Unknown sequences are dropped in their entirety.
+        assert_eq!(decode(b"\x1b[1;5Ax"), vec![Key::Char('x')]);
+    }
+
+    #[test]
+    fn test_lone_escape() {
+        let mut decoder = KeyDecoder::new();
+        assert_eq!(decoder.push(ESC), None);
+        assert!(decoder.is_pending());
+        assert_eq!(decoder.flush(), Some(Key::Escape));
+        assert!(!decoder.is_pending());
+
+        assert_eq!(decode(b"\x1b\x1b[A"), vec![Key::Escape, Key::Up]);
+    }
+
+    #[test]
+    fn test_utf8() {
+        assert_eq!(
+            decode("é↓".as_bytes()),
+            vec![Key::Char('é'), Key::Char('↓')]
+        );
+        // A truncated character is dropped.
+        assert_eq!(decode(b"\xe2\x86a"), vec![Key::Char('a')]);
+    }
+}
@@ -24,6 +24,9 @@
 //! Components live in the scratch area.
 //!
 //! A set of pre-baked composition and testing oriented components are provided in the [`components`] module.
+//!
+//! Rendering is one-way: keyboard input is decoded by the caller with [`input::KeyDecoder`] and
+//! routed to the state behind a component, such as a [`selection::Selection`].
 
 pub use components::Component;
 pub use components::DrawMode;
@@ -47,7 +50,9 @@ pub mod components;
 pub mod content;
 mod dimensions;
 pub mod error;
+pub mod input;
 pub mod output;
+pub mod selection;
 pub mod style;
 mod superconsole;
 pub mod testing;
@@ -0,0 +1,175 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is dual-licensed under either the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree or the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree. You may select, at your option, one of the
+ * above-listed licenses.
+ */
+
+//! Keyboard focus within a list, and scrolling to keep it in view.
+
+use std::cell::Cell;
+use std::ops::Range;
+
+use crate::input::Interactive;
+use crate::input::Key;
+
+/// Tracks which item of a list is selected, and which part of the list is scrolled into view.
+///
+/// The owner of the list keeps this up to date with [`set_len`](Selection::set_len) as the list
+/// changes, and forwards key presses to it. When drawing, it asks for the
+/// [`visible_range`](Selection::visible_range) of items: that scrolls as little as possible to keep
+/// the selected item in view.
+#[derive(Debug, Default)]
+pub struct Selection {
+    len: usize,
+    selected: Option<usize>,
+    /// The first visible item. Drawing only has `&self`, so this is a `Cell`.
+    offset: Cell<usize>,
+    /// How many items were visible last time this was drawn, used to page through the list.
+    page: Cell<usize>,
+}
+
+impl Selection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Update the length of the list. The selection moves to the last item if it's now past the
+    /// end, and is cleared if the list is empty.
+    pub fn set_len(&mut self, len: usize) {
+        self.len = len;
+        self.selected = match self.selected {
+            Some(_) if len == 0 => None,
+            selected => selected.map(|i| i.min(len - 1)),
+        };
+    }
+
+    /// Select an item (or nothing). Out of range indices select the last item.
+    pub fn select(&mut self, index: Option<usize>) {
+        self.selected = index;
+        self.set_len(self.len);
+    }
+
+    pub fn select_previous(&mut self) {
+        self.move_by(-1);
+    }
+
+    pub fn select_next(&mut self) {
+        self.move_by(1);
+    }
+
+    /// Move the selection by `delta` items, stopping at either end of the list. If nothing is
+    /// selected, moving selects the first item.
+    fn move_by(&mut self, delta: isize) {
+        if self.len == 0 {
+            return;
+        }
+        let selected = match self.selected {
+            Some(i) => i.saturating_add_signed(delta).min(self.len - 1),
+            None => 0,
+        };
+        self.selected = Some(selected);
+    }
+
+    /// The range of items to draw in `height` lines, scrolled so that the selected item is
+    /// visible.
+    pub fn visible_range(&self, height: usize) -> Range<usize> {
+        let height = height.min(self.len);
+        self.page.set(height);
+        if height == 0 {
+            return 0..0;
+        }
+
+        let mut offset = self.offset.get().min(self.len - height);
+        if let Some(selected) = self.selected {
+            if selected < offset {
+                offset = selected;
+            } else if selected >= offset + height {
+                offset = selected + 1 - height;
+            }
+        }
+        self.offset.set(offset);
+        offset..offset + height
+    }
+}
+
+impl Interactive for Selection {
+    fn handle_key(&mut self, key: Key) -> bool {
+        let page = self.page.get().max(1) as isize;
+        match key {
+            Key::Up => self.move_by(-1),
+            Key::Down => self.move_by(1),
+            Key::PageUp => self.move_by(-page),
+            Key::PageDown => self.move_by(page),
+            Key::Home if !self.is_empty() => self.selected = Some(0),
+            Key::End if !self.is_empty() => self.selected = Some(self.len - 1),
+            _ => return false,
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_navigation() {
+        let mut selection = Selection::new();
+        assert!(!selection.handle_key(Key::Enter));
+        selection.handle_key(Key::Down);
+        assert_eq!(selection.selected(), None);
+
+        selection.set_len(3);
+        selection.handle_key(Key::Down);
+        assert_eq!(selection.selected(), Some(0));
+        selection.handle_key(Key::Up);
+        assert_eq!(selection.selected(), Some(0));
+        selection.handle_key(Key::End);
+        assert_eq!(selection.selected(), Some(2));
+        selection.handle_key(Key::Down);
+        assert_eq!(selection.selected(), Some(2));
+
+        selection.set_len(2);
+        assert_eq!(selection.selected(), Some(1));
+        selection.set_len(0);
+        assert_eq!(selection.selected(), None);
+    }
+
+    #[test]
+    fn test_scrolling() {
+        let mut selection = Selection::new();
+        selection.set_len(10);
+        assert_eq!(selection.visible_range(4), 0..4);
+
+        selection.select(Some(5));
+        assert_eq!(selection.visible_range(4), 2..6);
+        // Moving within the visible range doesn't scroll.
+        selection.select_previous();
+        assert_eq!(selection.visible_range(4), 2..6);
+
+        selection.handle_key(Key::PageUp);
+        assert_eq!(selection.selected(), Some(0));
+        assert_eq!(selection.visible_range(4), 0..4);
+
+        selection.handle_key(Key::End);
+        assert_eq!(selection.visible_range(4), 6..10);
+        // Growing the viewport scrolls back so that it stays full.
+        assert_eq!(selection.visible_range(20), 0..10);
+    }
+}
@@ -0,0 +1,73 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+# pyre-strict
+
+
+import json
+
+from buck2.tests.e2e_util.api.buck import Buck
+from buck2.tests.e2e_util.asserts import expect_failure
+from buck2.tests.e2e_util.buck_workspace import buck_test
+
+
+@buck_test()
+async def test_aspect_propagates_along_attr_aspects(buck: Buck) -> None:
+    result = await buck.build("//:licenses")
+    output = result.get_build_report().output_for_target("root//:licenses")
+    assert json.loads(output.read_text()) == {
+        "b": ["b", "a"],
+        "c": ["c", "b", "a"],
+    }
+
+
+@buck_test()
+async def test_aspect_sees_subtargets_of_deps(buck: Buck) -> None:
+    result = await buck.build("//:dep_names")
+    output = result.get_build_report().output_for_target("root//:dep_names")
+    assert json.loads(output.read_text()) == {"d": ["a[sub]"]}
+
+
+@buck_test()
+async def test_aspect_in_audit_providers(buck: Buck) -> None:
+    result = await buck.audit(
+        "providers", "//:c", "--aspect", "//:defs.bzl%licenses_aspect"
+    )
+    assert "NameInfo" in result.stdout
+    assert "LicensesInfo" in result.stdout
+
+    await expect_failure(
+        buck.audit(
+            "providers", "//:c[sub]", "--aspect", "//:defs.bzl%licenses_aspect"
+        ),
+        stderr_regex="Aspects can only be applied to a whole target",
+    )
+
+
+@buck_test()
+async def test_aspect_rejects_subtargets(buck: Buck) -> None:
+    await expect_failure(
+        buck.build("//:licenses_of_subtarget"),
+        stderr_regex="cannot be applied to subtarget `root//:b\\[sub\\]",
+    )
+
+
+@buck_test()
+async def test_aspect_rejects_transitions(buck: Buck) -> None:
+    await expect_failure(
+        buck.build("//:licenses_through_tool"),
+        stderr_regex="cannot propagate along attribute `tool` to `root//:a",
+    )
+
+
+@buck_test()
+async def test_aspect_rejects_existing_providers(buck: Buck) -> None:
+    await expect_failure(
+        buck.build("//:duplicate"),
+        stderr_regex="aspect returned provider of type `NameInfo`, which the target already has",
+    )
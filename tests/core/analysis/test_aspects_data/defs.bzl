@@ -0,0 +1,73 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+
+NameInfo = provider(fields = ["name"])
+LicensesInfo = provider(fields = ["licenses"])
+
+def _licenses_impl(target, deps):
+    licenses = [target[NameInfo].name]
+    for dep in deps:
+        licenses.extend(dep[LicensesInfo].licenses)
+    return [LicensesInfo(licenses = licenses)]
+
+licenses_aspect = aspect(impl = _licenses_impl, attr_aspects = ["deps"])
+
+licenses_through_tool_aspect = aspect(impl = _licenses_impl, attr_aspects = ["tool"])
+
+def _duplicate_impl(_target, _deps):
+    return [NameInfo(name = "duplicate")]
+
+duplicate_aspect = aspect(impl = _duplicate_impl)
+
+def _dep_names_impl(_target, deps):
+    return [LicensesInfo(licenses = [dep[NameInfo].name for dep in deps])]
+
+dep_names_aspect = aspect(impl = _dep_names_impl, attr_aspects = ["deps"])
+
+def _library_impl(ctx):
+    return [
+        DefaultInfo(sub_targets = {"sub": [DefaultInfo(), NameInfo(name = ctx.label.name + "[sub]")]}),
+        NameInfo(name = ctx.label.name),
+    ]
+
+library = rule(
+    impl = _library_impl,
+    attrs = {
+        "deps": attrs.list(attrs.dep(), default = []),
+        "tool": attrs.option(attrs.exec_dep(), default = None),
+    },
+)
+
+def _collect(ctx):
+    out = ctx.actions.write_json(ctx.label.name + ".json", {
+        # The dep keeps its own providers alongside those added by the aspect.
+        dep[NameInfo].name: dep[LicensesInfo].licenses
+        for dep in ctx.attrs.deps
+    })
+    return [DefaultInfo(default_output = out)]
+
+collect_licenses = rule(
+    impl = _collect,
+    attrs = {"deps": attrs.list(attrs.dep(aspects = [licenses_aspect]))},
+)
+
+collect_licenses_through_tool = rule(
+    impl = _collect,
+    attrs = {"deps": attrs.list(attrs.dep(aspects = [licenses_through_tool_aspect]))},
+)
+
+collect_dep_names = rule(
+    impl = _collect,
+    attrs = {"deps": attrs.list(attrs.dep(aspects = [dep_names_aspect]))},
+)
+
+collect_duplicate = rule(
+    impl = _collect,
+    attrs = {"deps": attrs.list(attrs.dep(aspects = [duplicate_aspect]))},
+)
@@ -0,0 +1,48 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+# pyre-strict
+
+
+import json
+
+from buck2.tests.e2e_util.api.buck import Buck
+from buck2.tests.e2e_util.asserts import expect_failure
+from buck2.tests.e2e_util.buck_workspace import buck_test
+
+NO_MATCHES = r'`glob\(\["missing/\*.txt"\], exclude = \[\]\)` in `.*TARGETS.fixture` matched no files'
+
+
+@buck_test()
+async def test_empty_glob_warns(buck: Buck) -> None:
+    result = await buck.targets("//:")
+    assert result.stdout.splitlines() == ["root//:found", "root//:missing"]
+    assert "matched no files" in result.stderr
+
+
+@buck_test()
+async def test_strict_globs(buck: Buck) -> None:
+    await expect_failure(
+        buck.targets("//:", "--strict-globs"),
+        stderr_regex=NO_MATCHES,
+    )
+
+
+@buck_test()
+async def test_audit_globs(buck: Buck) -> None:
+    result = await buck.audit("globs", "//")
+    assert result.stdout.splitlines() == [
+        "root//:",
+        '  glob(["src/*.txt"], exclude = ["src/b.txt"]): 1 matches',
+        '  glob(["missing/*.txt"], exclude = []): 0 matches',
+    ]
+
+    result = await buck.audit("globs", "--empty", "--json", "//")
+    assert json.loads(result.stdout) == {
+        "root//": [{"include": ["missing/*.txt"], "exclude": [], "matches": 0}],
+    }
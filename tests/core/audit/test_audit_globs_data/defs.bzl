@@ -0,0 +1,16 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+
+def _stub_impl(_ctx):
+    return [DefaultInfo()]
+
+stub = rule(
+    impl = _stub_impl,
+    attrs = {"srcs": attrs.list(attrs.source(), default = [])},
+)
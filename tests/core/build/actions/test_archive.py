@@ -0,0 +1,46 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+# pyre-strict
+
+
+from buck2.tests.e2e_util.api.buck import Buck
+from buck2.tests.e2e_util.asserts import expect_failure
+from buck2.tests.e2e_util.buck_workspace import buck_test
+
+
+@buck_test()
+async def test_archive_then_extract(buck: Buck) -> None:
+    result = await buck.build("//:extract_tar", "//:extract_zip")
+    report = result.get_build_report()
+
+    tar = report.output_for_target("root//:extract_tar")
+    assert (tar / "a.txt").read_text() == "a"
+    assert (tar / "sub" / "b.txt").read_text() == "b"
+    assert not (tar / "pkg-1.0").exists()
+
+    unzipped = report.output_for_target("root//:extract_zip")
+    assert (unzipped / "pkg-1.0" / "a.txt").read_text() == "a"
+    assert (unzipped / "pkg-1.0" / "sub" / "b.txt").read_text() == "b"
+
+
+@buck_test()
+async def test_archive_is_deterministic(buck: Buck) -> None:
+    result = await buck.build("//:tar", "//:tar_again")
+    report = result.get_build_report()
+    first = report.output_for_target("root//:tar").read_bytes()
+    second = report.output_for_target("root//:tar_again").read_bytes()
+    assert first == second
+
+
+@buck_test()
+async def test_extract_missing_strip_prefix(buck: Buck) -> None:
+    await expect_failure(
+        buck.build("//:extract_missing_prefix"),
+        stderr_regex="No entry in archive `.*` is under `strip_prefix` `missing`",
+    )
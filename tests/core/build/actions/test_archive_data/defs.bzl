@@ -0,0 +1,41 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+
+def _archive_impl(ctx):
+    srcs = {
+        "{}/{}".format(ctx.attrs.prefix, path): ctx.actions.write(path, content)
+        for path, content in ctx.attrs.files.items()
+    }
+    out = ctx.actions.archive(ctx.attrs.out, srcs)
+    return [DefaultInfo(default_output = out)]
+
+archive = rule(
+    impl = _archive_impl,
+    attrs = {
+        "files": attrs.dict(attrs.string(), attrs.string()),
+        "out": attrs.string(),
+        "prefix": attrs.string(),
+    },
+)
+
+def _extract_impl(ctx):
+    out = ctx.actions.extract(
+        ctx.label.name,
+        ctx.attrs.archive[DefaultInfo].default_outputs[0],
+        strip_prefix = ctx.attrs.strip_prefix,
+    )
+    return [DefaultInfo(default_output = out)]
+
+extract = rule(
+    impl = _extract_impl,
+    attrs = {
+        "archive": attrs.dep(),
+        "strip_prefix": attrs.option(attrs.string(), default = None),
+    },
+)
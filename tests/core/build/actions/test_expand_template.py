@@ -0,0 +1,41 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+# pyre-strict
+
+
+import os
+
+from buck2.tests.e2e_util.api.buck import Buck
+from buck2.tests.e2e_util.buck_workspace import buck_test
+
+
+@buck_test()
+async def test_expand_template(buck: Buck) -> None:
+    result = await buck.build("//:expanded")
+    output = result.get_build_report().output_for_target("root//:expanded")
+    lines = output.read_text().splitlines()
+
+    # Replaced text is not expanded again.
+    assert lines[0] == "hello: {GREETING}, world"
+
+    # Artifacts are replaced by their path relative to the project root.
+    dep = lines[1].removeprefix("dep: ")
+    assert dep.startswith("buck-out/"), dep
+    assert dep.endswith("/dep.txt"), dep
+    assert (buck.cwd / dep).read_text() == "dep"
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_expand_template_executable(buck: Buck) -> None:
+    result = await buck.build("//:executable", "//:expanded")
+    report = result.get_build_report()
+    executable = report.output_for_target("root//:executable")
+    assert executable.read_text() == "#!/bin/sh\necho hello\n"
+    assert os.access(executable, os.X_OK)
+    assert not os.access(report.output_for_target("root//:expanded"), os.X_OK)
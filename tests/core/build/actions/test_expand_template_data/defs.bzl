@@ -0,0 +1,31 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+
+def _expand_impl(ctx):
+    template = ctx.actions.write("template.txt", ctx.attrs.template)
+    dep = ctx.actions.write("dep.txt", "dep")
+    out = ctx.actions.expand_template(
+        ctx.label.name,
+        template,
+        {
+            "{DEP}": dep,
+            "{GREETING}": "hello",
+            "{NAME}": "{GREETING}, world",
+        },
+        is_executable = ctx.attrs.is_executable,
+    )
+    return [DefaultInfo(default_output = out, other_outputs = [dep])]
+
+expand = rule(
+    impl = _expand_impl,
+    attrs = {
+        "is_executable": attrs.bool(default = False),
+        "template": attrs.string(),
+    },
+)
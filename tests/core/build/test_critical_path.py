@@ -10,10 +10,12 @@
 
 
 import json
+import re
 import typing
 from dataclasses import dataclass
 
 from buck2.tests.e2e_util.api.buck import Buck
+from buck2.tests.e2e_util.asserts import expect_failure
 from buck2.tests.e2e_util.buck_workspace import buck_test
 from buck2.tests.e2e_util.helper.golden import golden
 from buck2.tests.e2e_util.helper.utils import filter_events
@@ -400,3 +402,37 @@ async def test_critical_path_test_entries(buck: Buck) -> None:
         == "root//:long_running_test"
     )
     assert test_execution_action["duration_us"] > 100000  # 100ms
+
+
+@buck_test()
+async def test_critical_path_what_if_cache_hits(buck: Buck) -> None:
+    await buck.build(
+        "//:step_3",
+        "--no-remote-cache",
+        "-c",
+        "buck2.log_critical_path_graph=true",
+    )
+
+    result = await buck.log(
+        "critical-path", "--what-if", "cache-hits", "--format", "json"
+    )
+    m = re.search(r"Critical path: ([\d.]+)s, estimated: ([\d.]+)s", result.stderr)
+    assert m, result.stderr
+    baseline, estimate = float(m.group(1)), float(m.group(2))
+    # `step_3` sleeps for 5 seconds, none of which a cache hit would take.
+    assert baseline >= 5
+    assert estimate < baseline - 4
+
+    critical_path = [json.loads(e) for e in result.stdout.splitlines()]
+    assert len(critical_path) > 0
+    assert all("kind" in e for e in critical_path)
+
+
+@buck_test()
+async def test_critical_path_what_if_requires_graph(buck: Buck) -> None:
+    await buck.build("//:step_0", "--no-remote-cache")
+
+    await expect_failure(
+        buck.log("critical-path", "--what-if", "cache-hits"),
+        stderr_regex="buck2.log_critical_path_graph=true",
+    )
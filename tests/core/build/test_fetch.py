@@ -0,0 +1,118 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+# pyre-strict
+
+
+import hashlib
+import json
+import tempfile
+import threading
+from contextlib import contextmanager
+from functools import partial
+from http.server import SimpleHTTPRequestHandler, ThreadingHTTPServer
+from pathlib import Path
+from typing import Iterator, List
+
+from buck2.tests.e2e_util.api.buck import Buck
+from buck2.tests.e2e_util.asserts import expect_failure
+from buck2.tests.e2e_util.buck_workspace import buck_test
+
+CONTENT = b"downloaded\n"
+SHA1: str = hashlib.sha1(CONTENT).hexdigest()
+
+
+@contextmanager
+def serve() -> Iterator[str]:
+    """Serves `CONTENT` over HTTP, and yields its URL."""
+    with tempfile.TemporaryDirectory() as directory:
+        (Path(directory) / "file.txt").write_bytes(CONTENT)
+        handler = partial(SimpleHTTPRequestHandler, directory=directory)
+        server = ThreadingHTTPServer(("127.0.0.1", 0), handler)
+        thread = threading.Thread(target=server.serve_forever, daemon=True)
+        thread.start()
+        try:
+            yield f"http://127.0.0.1:{server.server_address[1]}/file.txt"
+        finally:
+            server.shutdown()
+            server.server_close()
+
+
+def config(url: str, sha1: str = SHA1) -> List[str]:
+    return ["-c", f"test.url={url}", "-c", f"test.sha1={sha1}"]
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_fetch_then_build_offline(buck: Buck) -> None:
+    with serve() as url:
+        await buck.run_buck_command("fetch", "//:dl", *config(url))
+
+    lockfile = json.loads((buck.cwd / "buck2.lock").read_text())
+    assert lockfile["downloads"] == {url: {"sha1": SHA1}}
+
+    # Nothing is served anymore, and a new daemon has no state from the fetch, so the output
+    # can only come from the offline cache.
+    await buck.kill()
+    result = await buck.build(
+        "//:dl",
+        *config(url),
+        "-c",
+        "buck2.use_network_action_output_cache=true",
+    )
+    output = result.get_build_report().output_for_target("root//:dl")
+    assert output.read_bytes() == CONTENT
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_fetch_is_up_to_date(buck: Buck) -> None:
+    with serve() as url:
+        await buck.run_buck_command("fetch", "//:dl", *config(url))
+        await buck.run_buck_command("fetch", "--check", "//:dl", *config(url))
+
+        await expect_failure(
+            buck.run_buck_command(
+                "fetch", "--check", "//:dl", *config(url, sha1="0" * 40)
+            ),
+            stderr_regex="is out of date, run `buck2 fetch` to update it",
+        )
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_build_checks_lockfile(buck: Buck) -> None:
+    with serve() as url:
+        await buck.run_buck_command("fetch", "//:dl", *config(url))
+
+        await buck.build("//:dl", *config(url))
+
+        await expect_failure(
+            buck.build("//:dl", *config(url, sha1="0" * 40)),
+            stderr_regex=r"Download `.*` is pinned to `sha1:.*` in `buck2.lock`, but the build uses `sha1:0+`",
+        )
+
+        await expect_failure(
+            buck.build("//:dl", *config(url + "?unpinned")),
+            stderr_regex=r"Download `.*\?unpinned` is not pinned in `buck2.lock`",
+        )
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_build_rechecks_edited_lockfile(buck: Buck) -> None:
+    with serve() as url:
+        await buck.run_buck_command("fetch", "//:dl", *config(url))
+        await buck.build("//:dl", *config(url))
+
+        # Nothing but the lockfile changes, so the check must not come from a cached build.
+        lockfile_path = buck.cwd / "buck2.lock"
+        lockfile = json.loads(lockfile_path.read_text())
+        lockfile["downloads"][url]["sha1"] = "0" * 40
+        lockfile_path.write_text(json.dumps(lockfile))
+
+        await expect_failure(
+            buck.build("//:dl", *config(url)),
+            stderr_regex=r"Download `.*` is pinned to `sha1:0+` in `buck2.lock`, but the build uses `sha1:",
+        )
@@ -0,0 +1,20 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+def _download_impl(ctx: AnalysisContext) -> list[Provider]:
+    out = ctx.actions.declare_output("out.txt")
+    ctx.actions.download_file(out, ctx.attrs.url, sha1 = ctx.attrs.sha1)
+    return [DefaultInfo(default_output = out)]
+
+download = rule(
+    impl = _download_impl,
+    attrs = {
+        "sha1": attrs.string(),
+        "url": attrs.string(),
+    },
+)
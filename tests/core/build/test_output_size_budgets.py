@@ -0,0 +1,75 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+# pyre-strict
+
+
+import json
+import re
+
+from buck2.tests.e2e_util.api.buck import Buck
+from buck2.tests.e2e_util.asserts import expect_failure
+from buck2.tests.e2e_util.buck_workspace import buck_test
+
+EXCEEDED = "produced .* of outputs, which exceeds its output size budget of"
+
+
+@buck_test()
+async def test_budget_exceeded_is_warning(buck: Buck) -> None:
+    result = await buck.build("root//:big", "-c", "output_size_budgets.zeros=1K")
+    assert re.search(EXCEEDED, result.stderr)
+
+
+@buck_test()
+async def test_enforced_budget_fails_action(buck: Buck) -> None:
+    await expect_failure(
+        buck.build(
+            "root//:big",
+            "-c",
+            "output_size_budgets.zeros=1K",
+            "-c",
+            "buck2.enforce_output_size_budgets=true",
+        ),
+        stderr_regex=EXCEEDED,
+    )
+
+
+@buck_test()
+async def test_within_budget(buck: Buck) -> None:
+    await buck.build(
+        "root//:big",
+        "-c",
+        "output_size_budgets.default=1M",
+        "-c",
+        "buck2.enforce_output_size_budgets=true",
+    )
+
+
+@buck_test()
+async def test_action_budget_overrides_config(buck: Buck) -> None:
+    await expect_failure(
+        buck.build(
+            "root//:big_with_budget",
+            "-c",
+            "output_size_budgets.default=1M",
+            "-c",
+            "buck2.enforce_output_size_budgets=true",
+        ),
+        stderr_regex=EXCEEDED,
+    )
+
+
+@buck_test()
+async def test_log_output_sizes(buck: Buck) -> None:
+    await buck.build("root//:big", "root//:small")
+    out = await buck.log("output-sizes", "--format", "json")
+    records = [json.loads(line) for line in out.stdout.splitlines()]
+    assert [(r["target"].split(" ")[0], r["bytes"]) for r in records] == [
+        ("root//:big", 4096),
+        ("root//:small", 16),
+    ]
@@ -0,0 +1,30 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+def _zeros_impl(ctx: AnalysisContext) -> list[Provider]:
+    out = ctx.actions.declare_output("zeros.bin")
+    ctx.actions.run(
+        [
+            "sh",
+            "-c",
+            'head -c {} /dev/zero > "$1"'.format(ctx.attrs.size),
+            "--",
+            out.as_output(),
+        ],
+        category = "zeros",
+        output_size_budget = ctx.attrs.budget,
+    )
+    return [DefaultInfo(default_output = out)]
+
+zeros = rule(
+    impl = _zeros_impl,
+    attrs = {
+        "budget": attrs.option(attrs.int(), default = None),
+        "size": attrs.int(),
+    },
+)
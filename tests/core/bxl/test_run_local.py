@@ -0,0 +1,52 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+# pyre-strict
+
+
+from buck2.tests.e2e_util.api.buck import Buck
+from buck2.tests.e2e_util.asserts import expect_failure
+from buck2.tests.e2e_util.buck_workspace import buck_test
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_run_local_stdout(buck: Buck) -> None:
+    result = await buck.bxl("//run_local.bxl:echo")
+    assert result.stdout.strip() == "hello"
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_run_local_input(buck: Buck) -> None:
+    result = await buck.bxl("//run_local.bxl:cat_input")
+    assert result.stdout.strip() == "file contents"
+
+    (buck.cwd / "input.txt").write_text("new contents\n")
+    result = await buck.bxl("//run_local.bxl:cat_input")
+    assert result.stdout.strip() == "new contents"
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_run_local_env(buck: Buck) -> None:
+    result = await buck.bxl("//run_local.bxl:env")
+    assert result.stdout.strip() == "from_env"
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_run_local_failure(buck: Buck) -> None:
+    await expect_failure(
+        buck.bxl("//run_local.bxl:failure"),
+        stderr_regex="failed with exit code 3(.|\n)*oops",
+    )
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_run_local_output(buck: Buck) -> None:
+    await expect_failure(
+        buck.bxl("//run_local.bxl:output"),
+        stderr_regex="`run_local` commands can't have outputs",
+    )
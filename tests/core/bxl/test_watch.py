@@ -0,0 +1,95 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+# pyre-strict
+
+
+import asyncio
+from typing import List
+
+from buck2.tests.e2e_util.api.buck import Buck
+from buck2.tests.e2e_util.buck_workspace import buck_test
+
+# We don't expect a change to take anywhere near this long to be picked up, but on CI on a busy
+# host this could take a while.
+TIMEOUT_S = 60
+
+
+async def read_until(stream: asyncio.StreamReader, line: str) -> List[str]:
+    """Reads lines from `stream` until one containing `line`, and returns the lines before it."""
+    lines = []
+    while True:
+        read = await asyncio.wait_for(stream.readline(), timeout=TIMEOUT_S)
+        assert read, f"Stream closed before `{line}`, read: {lines}"
+        read = read.decode("utf-8").rstrip()
+        if line in read:
+            return lines
+        lines.append(read)
+
+
+async def start_watch(buck: Buck) -> asyncio.subprocess.Process:
+    return await asyncio.create_subprocess_exec(
+        *buck.construct_buck_command(
+            "bxl", "--watch", "--console=simple", "//watch.bxl:targets"
+        ),
+        stdout=asyncio.subprocess.PIPE,
+        stderr=asyncio.subprocess.PIPE,
+        cwd=buck.cwd,
+        env=buck._env,
+    )
+
+
+async def stop_watch(proc: asyncio.subprocess.Process) -> None:
+    proc.kill()
+    await proc.wait()
+
+
+@buck_test()
+async def test_watch_reruns_on_change(buck: Buck) -> None:
+    proc = await start_watch(buck)
+    try:
+        assert proc.stdout is not None
+        assert proc.stderr is not None
+
+        await read_until(proc.stderr, "BXL SUCCEEDED")
+        assert await read_until(proc.stdout, "target: a") == []
+
+        with open(buck.cwd / "TARGETS.fixture", "a") as f:
+            f.write('stub(name = "b")\n')
+
+        await read_until(proc.stderr, "Files changed, BXL evaluated again")
+        await read_until(proc.stderr, "BXL SUCCEEDED")
+        assert await read_until(proc.stdout, "target: b") == ["target: a"]
+    finally:
+        await stop_watch(proc)
+
+
+@buck_test()
+async def test_watch_recovers_from_error(buck: Buck) -> None:
+    proc = await start_watch(buck)
+    try:
+        assert proc.stdout is not None
+        assert proc.stderr is not None
+
+        await read_until(proc.stderr, "BXL SUCCEEDED")
+        await read_until(proc.stdout, "target: a")
+
+        with open(buck.cwd / "TARGETS.fixture", "a") as f:
+            f.write("this is not starlark\n")
+
+        # The error is printed, and the command keeps watching.
+        await read_until(proc.stderr, "BXL FAILED")
+        assert proc.returncode is None
+
+        with open(buck.cwd / "TARGETS.fixture", "w") as f:
+            f.write('load(":defs.bzl", "stub")\n\nstub(name = "c")\n')
+
+        await read_until(proc.stderr, "BXL SUCCEEDED")
+        await read_until(proc.stdout, "target: c")
+    finally:
+        await stop_watch(proc)
@@ -0,0 +1,4 @@
+def _stub_impl(_ctx):
+    return [DefaultInfo()]
+
+stub = rule(impl = _stub_impl, attrs = {})
@@ -0,0 +1,85 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+# pyre-strict
+
+
+import json
+import threading
+from contextlib import contextmanager
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+from typing import Any, Dict, Iterator, List, Tuple
+
+from buck2.tests.e2e_util.api.buck import Buck
+from buck2.tests.e2e_util.buck_workspace import buck_test
+
+
+@contextmanager
+def collector(status: int = 200) -> Iterator[Tuple[str, List[Dict[str, Any]]]]:
+    """Runs a stub OTLP/HTTP collector, and yields its URL and the spans it received."""
+    spans: List[Dict[str, Any]] = []
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self) -> None:
+            body = self.rfile.read(int(self.headers["Content-Length"]))
+            if self.path == "/v1/traces":
+                for resource in json.loads(body)["resourceSpans"]:
+                    for scope in resource["scopeSpans"]:
+                        spans.extend(scope["spans"])
+            self.send_response(status)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", "2")
+            self.end_headers()
+            self.wfile.write(b"{}")
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    thread = threading.Thread(target=server.serve_forever, daemon=True)
+    thread.start()
+    try:
+        yield f"http://127.0.0.1:{server.server_address[1]}", spans
+    finally:
+        server.shutdown()
+        server.server_close()
+
+
+def attributes(span: Dict[str, Any]) -> Dict[str, Any]:
+    return {
+        attribute["key"]: next(iter(attribute["value"].values()))
+        for attribute in span["attributes"]
+    }
+
+
+@buck_test()
+async def test_otlp_exports_spans(buck: Buck) -> None:
+    with collector() as (url, spans):
+        await buck.build("//:write", "--otlp-endpoint", url)
+
+    [command] = [span for span in spans if span["name"] == "buck2 build"]
+    assert attributes(command)["buck2.command"] == "build"
+    assert "parentSpanId" not in command
+
+    [action] = [
+        span
+        for span in spans
+        if span["name"] == "action" and attributes(span)["buck2.action.kind"] == "WRITE"
+    ]
+    assert attributes(action)["buck2.action.cache_hit"] is False
+    assert {span["traceId"] for span in spans} == {command["traceId"]}
+
+    # Every exported parent is itself exported.
+    ids = {span["spanId"] for span in spans}
+    for span in spans:
+        assert span.get("parentSpanId", command["spanId"]) in ids
+
+
+@buck_test()
+async def test_otlp_export_failure_does_not_fail_command(buck: Buck) -> None:
+    with collector(status=500) as (url, spans):
+        await buck.build("//:write", "--otlp-endpoint", url)
+
+    assert any(span["name"] == "buck2 build" for span in spans)
@@ -0,0 +1,66 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+# pyre-strict
+
+import urllib.error
+import urllib.parse
+import urllib.request
+
+import pytest
+
+from buck2.tests.e2e_util.api.buck import Buck
+from buck2.tests.e2e_util.asserts import expect_failure
+from buck2.tests.e2e_util.buck_workspace import buck_test
+
+
+async def start_ui(buck: Buck) -> str:
+    return await start_ui_on(buck, 0)
+
+
+async def start_ui_on(buck: Buck, port: int) -> str:
+    result = await buck.run_buck_command("ui", "--port", str(port))
+    url = result.stdout.strip()
+    assert url.startswith("http://")
+    return url
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_ui_with_token(buck: Buck) -> None:
+    url = await start_ui(buck)
+    with urllib.request.urlopen(url, timeout=30) as response:
+        assert response.status == 200
+        assert response.headers["Content-Type"].startswith("text/html")
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_ui_without_token(buck: Buck) -> None:
+    url = urllib.parse.urlsplit(await start_ui(buck))
+    for query in ["", "token=wrong"]:
+        with pytest.raises(urllib.error.HTTPError) as e:
+            urllib.request.urlopen(
+                urllib.parse.urlunsplit(url._replace(query=query)), timeout=30
+            )
+        assert e.value.code == 403
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_ui_url_is_stable(buck: Buck) -> None:
+    assert await start_ui(buck) == await start_ui(buck)
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_ui_already_running_on_other_port(buck: Buck) -> None:
+    url = await start_ui(buck)
+    port = urllib.parse.urlsplit(url).port
+    assert port is not None
+    assert await start_ui_on(buck, port) == url
+    await expect_failure(
+        buck.run_buck_command("ui", "--port", str(port + 1)),
+        stderr_regex=f"already running on port {port}",
+    )
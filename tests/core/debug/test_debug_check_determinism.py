@@ -0,0 +1,49 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+# pyre-strict
+
+
+from buck2.tests.e2e_util.api.buck import Buck
+from buck2.tests.e2e_util.asserts import expect_failure
+from buck2.tests.e2e_util.buck_workspace import buck_test
+
+
+def _determinism_buck_outs(buck: Buck) -> list[str]:
+    return [
+        p.name for p in (buck.cwd / "buck-out").iterdir() if "-determinism-" in p.name
+    ]
+
+
+@buck_test()
+async def test_check_determinism_deterministic(buck: Buck) -> None:
+    result = await buck.debug("check-determinism", "root//:constant")
+    assert "No non-deterministic actions found." in result.stdout
+    assert _determinism_buck_outs(buck) == []
+
+
+@buck_test()
+async def test_check_determinism_non_deterministic(buck: Buck) -> None:
+    failure = await expect_failure(
+        buck.debug("check-determinism", "root//:constant", "root//:timestamp"),
+        stderr_regex="Found 1 non-deterministic action",
+    )
+    assert "root//:timestamp" in failure.stdout
+    assert "__timestamp__/timestamp.txt" in failure.stdout
+    assert "constant" not in failure.stdout
+    assert _determinism_buck_outs(buck) == []
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_check_determinism_umask(buck: Buck) -> None:
+    failure = await expect_failure(
+        buck.debug("check-determinism", "root//:umask"),
+        stderr_regex="Found 1 non-deterministic action",
+    )
+    assert "__umask__/umask.txt" in failure.stdout
+    assert _determinism_buck_outs(buck) == []
@@ -0,0 +1,23 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+def _shell_impl(ctx: AnalysisContext) -> list[Provider]:
+    out = ctx.actions.declare_output(ctx.attrs.out)
+    ctx.actions.run(
+        ["sh", "-c", ctx.attrs.script + ' > "$1"', "--", out.as_output()],
+        category = "shell",
+    )
+    return [DefaultInfo(default_output = out)]
+
+shell = rule(
+    impl = _shell_impl,
+    attrs = {
+        "out": attrs.string(),
+        "script": attrs.string(),
+    },
+)
@@ -0,0 +1,117 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+# pyre-unsafe
+
+# A minimal external executor for `test_external_executor.py`: it runs commands
+# on this host and appends a JSON line to a log file for every request it
+# starts, finishes, times out or has cancelled.
+
+import argparse
+import json
+import os
+import signal
+import subprocess
+import sys
+import threading
+from concurrent import futures
+
+import grpc
+from buck2.app.buck2_external_executor_proto import (
+    external_executor_pb2,
+    external_executor_pb2_grpc,
+)
+
+
+class ExternalExecutor(external_executor_pb2_grpc.ExternalExecutorServicer):
+    def __init__(self, log_path):
+        self.log_path = log_path
+        self.log_lock = threading.Lock()
+
+    def log(self, event, request, **kwargs):
+        entry = {"event": event, "action_digest": request.action_digest, **kwargs}
+        with self.log_lock, open(self.log_path, "a") as f:
+            f.write(json.dumps(entry) + "\n")
+
+    def Execute(self, request, context):
+        self.log(
+            "started",
+            request,
+            argv=[os.fsdecode(arg) for arg in request.argv],
+            working_directory=request.working_directory,
+            output_paths=list(request.output_paths),
+        )
+        process = subprocess.Popen(
+            list(request.argv),
+            cwd=os.path.join(request.project_root, request.working_directory),
+            env={entry.key: entry.value for entry in request.env},
+            stdout=subprocess.PIPE,
+            stderr=subprocess.PIPE,
+            # So that killing the command also kills anything it spawned.
+            start_new_session=True,
+        )
+
+        # Buck2 cancels the RPC when the build is cancelled, and expects the
+        # command to be killed.
+        def on_rpc_done():
+            if process.poll() is None:
+                os.killpg(process.pid, signal.SIGKILL)
+                self.log("cancelled", request)
+
+        context.add_callback(on_rpc_done)
+
+        timeout = request.timeout_s if request.HasField("timeout_s") else None
+        try:
+            stdout, stderr = process.communicate(timeout=timeout)
+        except subprocess.TimeoutExpired:
+            os.killpg(process.pid, signal.SIGKILL)
+            stdout, stderr = process.communicate()
+            self.log("timed_out", request)
+            return external_executor_pb2.ExecuteResponse(
+                exit_code=process.returncode,
+                stdout=stdout,
+                stderr=stderr,
+                timed_out_after_s=timeout,
+            )
+
+        self.log("finished", request, exit_code=process.returncode)
+        return external_executor_pb2.ExecuteResponse(
+            exit_code=process.returncode, stdout=stdout, stderr=stderr
+        )
+
+
+def serve(args):
+    server = grpc.server(futures.ThreadPoolExecutor(max_workers=10))
+    external_executor_pb2_grpc.add_ExternalExecutorServicer_to_server(
+        ExternalExecutor(args.log), server
+    )
+    server.add_insecure_port(f"unix:{args.socket}")
+
+    stop_event = threading.Event()
+    signal.signal(signal.SIGTERM, lambda x, y: stop_event.set())
+    signal.signal(signal.SIGINT, lambda x, y: stop_event.set())
+
+    server.start()
+    stop_event.wait()
+    server.stop(1).wait()
+
+
+def parse_args(args):
+    parser = argparse.ArgumentParser(description="Run a test external executor")
+    parser.add_argument("--socket", required=True, help="Unix socket to listen on")
+    parser.add_argument("--log", required=True, help="File to record requests in")
+    return parser.parse_args(args)
+
+
+def main() -> None:
+    serve(parse_args(sys.argv[1:]))
+
+
+if __name__ == "__main__":
+    # Do not add code here, it won't be run. Add them to the function called below.
+    main()  # pragma: no cover
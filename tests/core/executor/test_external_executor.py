@@ -0,0 +1,164 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+# pyre-strict
+
+import asyncio
+import json
+import os
+import signal
+from collections.abc import AsyncIterator
+from contextlib import asynccontextmanager
+from pathlib import Path
+from typing import Any
+
+from buck2.tests.e2e_util.api.buck import Buck
+from buck2.tests.e2e_util.asserts import expect_failure
+from buck2.tests.e2e_util.buck_workspace import buck_test, env
+
+
+@asynccontextmanager
+async def _external_executor(tmp_path: Path) -> AsyncIterator[tuple[Path, Path]]:
+    """
+    Starts `external_executor_server.py`, yielding the socket it listens on and
+    the log it records requests in.
+    """
+    socket = tmp_path / "executor.sock"
+    log = tmp_path / "executor.log"
+    log.touch()
+    process = await asyncio.create_subprocess_exec(
+        os.environ["EXTERNAL_EXECUTOR_SERVER"],
+        "--socket",
+        str(socket),
+        "--log",
+        str(log),
+    )
+    try:
+        for _i in range(300):
+            if socket.exists():
+                break
+            await asyncio.sleep(0.1)
+        else:
+            raise Exception("External executor never started listening")
+        yield socket, log
+    finally:
+        process.terminate()
+        await process.wait()
+
+
+def _read_log(log: Path) -> list[dict[str, Any]]:
+    return [json.loads(line) for line in log.read_text().splitlines()]
+
+
+async def _wait_for_event(log: Path, event: str) -> dict[str, Any]:
+    for _i in range(30):
+        for entry in _read_log(log):
+            if entry["event"] == event:
+                return entry
+        await asyncio.sleep(1)
+    raise Exception(f"External executor never logged `{event}`")
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_external_executor_not_listening(buck: Buck, tmp_path: Path) -> None:
+    socket = tmp_path / "executor.sock"
+    await expect_failure(
+        buck.build("root//:run_action", "-c", f"test.socket={socket}"),
+        stderr_regex="Error connecting to external executor",
+    )
+
+
+@buck_test()
+async def test_external_executor_with_local(buck: Buck, tmp_path: Path) -> None:
+    socket = tmp_path / "executor.sock"
+    await expect_failure(
+        buck.build(
+            "root//:run_action",
+            "-c",
+            f"test.socket={socket}",
+            "-c",
+            "test.local_enabled=true",
+        ),
+        stderr_regex="`external_executor` cannot be used with `local_enabled` or `remote_enabled`",
+    )
+
+
+@buck_test()
+async def test_external_executor_remote_only(buck: Buck, tmp_path: Path) -> None:
+    socket = tmp_path / "executor.sock"
+    await expect_failure(
+        buck.build(
+            "root//:run_action", "-c", f"test.socket={socket}", "--remote-only"
+        ),
+        stderr_regex="incompatible with the external executor at .*, which counts as local execution",
+    )
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_external_executor_runs_command(buck: Buck, tmp_path: Path) -> None:
+    async with _external_executor(tmp_path) as (socket, log):
+        result = await buck.build("root//:hello", "-c", f"test.socket={socket}")
+
+        output = result.get_build_report().output_for_target("root//:hello")
+        assert output.read_text() == "hello\n"
+
+        [started, finished] = _read_log(log)
+        assert started["event"] == "started"
+        assert started["argv"][:3] == ["sh", "-c", 'echo hello > "$1"']
+        assert len(started["output_paths"]) == 1
+        assert started["output_paths"][0].endswith("/hello.txt")
+        assert started["argv"][-1] == started["output_paths"][0]
+        assert finished == {
+            "event": "finished",
+            "action_digest": started["action_digest"],
+            "exit_code": 0,
+        }
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_external_executor_exit_code(buck: Buck, tmp_path: Path) -> None:
+    async with _external_executor(tmp_path) as (socket, log):
+        failure = await expect_failure(
+            buck.build("root//:fail", "-c", f"test.socket={socket}"),
+            stderr_regex="exit code 3",
+        )
+        assert "oops" in failure.stderr
+        assert _read_log(log)[-1]["exit_code"] == 3
+
+
+@buck_test(skip_for_os=["windows"])
+@env("BUCK2_ALLOW_INTERNAL_TEST_RUNNER_DO_NOT_USE", "1")
+async def test_external_executor_timeout(buck: Buck, tmp_path: Path) -> None:
+    async with _external_executor(tmp_path) as (socket, log):
+        await expect_failure(
+            buck.test(
+                "root//:timeout",
+                "-c",
+                f"test.socket={socket}",
+                "--",
+                "--timeout",
+                "1",
+                # Empty test executor forces the internal test executor to be used.
+                test_executor="",
+            ),
+            stderr_regex="Timeout: ",
+        )
+        await _wait_for_event(log, "timed_out")
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_external_executor_cancellation(buck: Buck, tmp_path: Path) -> None:
+    async with _external_executor(tmp_path) as (socket, log):
+        command = await buck.build("root//:slow", "-c", f"test.socket={socket}").start()
+        started = await _wait_for_event(log, "started")
+
+        command.send_signal(signal.SIGINT)
+        await command.communicate()
+
+        cancelled = await _wait_for_event(log, "cancelled")
+        assert cancelled["action_digest"] == started["action_digest"]
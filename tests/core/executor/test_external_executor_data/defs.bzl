@@ -0,0 +1,69 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+def _run_action(ctx):
+    out = ctx.actions.declare_output("out")
+    ctx.actions.run(cmd_args("touch", out.as_output()), category = "test")
+    return [DefaultInfo(out)]
+
+run_action = rule(
+    impl = _run_action,
+    attrs = {},
+)
+
+def _shell(ctx):
+    out = ctx.actions.declare_output(ctx.attrs.out)
+    ctx.actions.run(
+        ["sh", "-c", ctx.attrs.script, "--", out.as_output()],
+        category = "shell",
+    )
+    return [DefaultInfo(out)]
+
+shell = rule(
+    impl = _shell,
+    attrs = {
+        "out": attrs.string(),
+        "script": attrs.string(),
+    },
+)
+
+def _shell_test(ctx):
+    return [
+        DefaultInfo(),
+        ExternalRunnerTestInfo(
+            command = ["sh", "-c", ctx.attrs.script],
+            type = "custom",
+        ),
+    ]
+
+shell_test = rule(
+    impl = _shell_test,
+    attrs = {
+        "script": attrs.string(),
+    },
+)
+
+def _execution_platforms(ctx):
+    return [
+        DefaultInfo(),
+        ExecutionPlatformRegistrationInfo(platforms = [ExecutionPlatformInfo(
+            label = ctx.label.raw_target(),
+            configuration = ConfigurationInfo(constraints = {}, values = {}),
+            executor_config = CommandExecutorConfig(
+                local_enabled = read_config("test", "local_enabled", "false") == "true",
+                remote_enabled = False,
+                remote_cache_enabled = False,
+                external_executor = read_config("test", "socket"),
+            ),
+        )]),
+    ]
+
+execution_platforms = rule(
+    impl = _execution_platforms,
+    attrs = {},
+)
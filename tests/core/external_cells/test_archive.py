@@ -0,0 +1,141 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+# pyre-strict
+
+import hashlib
+import io
+import re
+import tarfile
+import tempfile
+import threading
+from contextlib import contextmanager
+from functools import partial
+from http.server import SimpleHTTPRequestHandler, ThreadingHTTPServer
+from pathlib import Path
+from typing import Iterator, Optional
+
+from buck2.tests.e2e_util.api.buck import Buck
+from buck2.tests.e2e_util.asserts import expect_failure
+from buck2.tests.e2e_util.buck_workspace import buck_test
+
+PREFIX = "libfoo-1.0"
+
+FILES = {
+    f"{PREFIX}/.buckconfig": "[buildfile]\n  name = TARGETS.fixture\n",
+    f"{PREFIX}/TARGETS.fixture": 'load("@root//:defs.bzl", "copy_src")\n\n'
+    'copy_src(\n    name = "t",\n    src = "src.txt",\n)\n',
+    f"{PREFIX}/src.txt": "from the archive\n",
+    # Outside of `strip_prefix`, so not part of the cell.
+    "other/ignored.txt": "ignored\n",
+}
+
+
+def _tarball() -> bytes:
+    buf = io.BytesIO()
+    with tarfile.open(fileobj=buf, mode="w:gz") as tar:
+        for name, content in FILES.items():
+            data = content.encode()
+            info = tarfile.TarInfo(name)
+            info.size = len(data)
+            tar.addfile(info, io.BytesIO(data))
+    return buf.getvalue()
+
+
+TARBALL: bytes = _tarball()
+SHA256: str = hashlib.sha256(TARBALL).hexdigest()
+
+
+@contextmanager
+def serve() -> Iterator[str]:
+    """Serves `TARBALL` over HTTP, and yields its URL."""
+    with tempfile.TemporaryDirectory() as directory:
+        (Path(directory) / "libfoo.tar.gz").write_bytes(TARBALL)
+        handler = partial(SimpleHTTPRequestHandler, directory=directory)
+        server = ThreadingHTTPServer(("127.0.0.1", 0), handler)
+        thread = threading.Thread(target=server.serve_forever, daemon=True)
+        thread.start()
+        try:
+            yield f"http://127.0.0.1:{server.server_address[1]}/libfoo.tar.gz"
+        finally:
+            server.shutdown()
+            server.server_close()
+
+
+def _configure(buck: Buck, url: str, strip_prefix: Optional[str] = PREFIX) -> None:
+    lines = [
+        "",
+        "[external_cell_libfoo]",
+        f"  urls = {url}",
+        f"  sha256 = {SHA256}",
+    ]
+    if strip_prefix is not None:
+        lines.append(f"  strip_prefix = {strip_prefix}")
+    with open(buck.cwd / ".buckconfig", "a") as f:
+        f.write("\n".join(lines) + "\n")
+
+
+async def _build(buck: Buck, *args: str) -> str:
+    res = await buck.build_without_report(
+        "libfoo//:t", "--show-full-simple-output", *args
+    )
+    return Path(res.stdout.strip()).read_text()
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_archive_fetch(buck: Buck) -> None:
+    with serve() as url:
+        _configure(buck, url)
+        assert await _build(buck) == "from the archive\n"
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_archive_expand(buck: Buck) -> None:
+    with serve() as url:
+        _configure(buck, url)
+        await buck.expand_external_cell("libfoo")
+    assert (buck.cwd / "libfoo" / "src.txt").read_text() == "from the archive\n"
+    assert not (buck.cwd / "libfoo" / "other").exists()
+    assert not (buck.cwd / "libfoo" / PREFIX).exists()
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_archive_strip_prefix_not_found(buck: Buck) -> None:
+    with serve() as url:
+        _configure(buck, url, strip_prefix="missing")
+        await expect_failure(
+            buck.build("libfoo//:t"),
+            stderr_regex="No entry in archive .* is under `strip_prefix` `missing`",
+        )
+
+
+@buck_test(skip_for_os=["windows"])
+async def test_archive_offline_cache(buck: Buck) -> None:
+    offline = ["-c", "buck2.use_network_action_output_cache=true"]
+    # Nothing is served, so the archive has to come from the cache.
+    _configure(buck, "http://127.0.0.1:1/libfoo.tar.gz")
+
+    failure = await expect_failure(
+        buck.build("libfoo//:t", *offline),
+        stderr_regex="is not in the cache and network access is disabled",
+    )
+    match = re.search(r"Place the archive at\s+`([^`]+)`", failure.stderr)
+    assert match is not None, failure.stderr
+    cached = buck.cwd / match.group(1)
+    cached.parent.mkdir(parents=True, exist_ok=True)
+
+    # Archives placed by hand are checked, and removed if they don't match.
+    cached.write_bytes(b"not the archive")
+    await expect_failure(
+        buck.build("libfoo//:t", *offline),
+        stderr_regex=f"but the external cell expects `{SHA256}`",
+    )
+    assert not cached.exists()
+
+    cached.write_bytes(TARBALL)
+    assert await _build(buck, *offline) == "from the archive\n"
@@ -67,6 +67,30 @@ async def test_active_commands(buck: Buck) -> None:
         assert "subscribe" in commands[0]["argv"]
 
 
+@buck_test()
+async def test_command_events(buck: Buck) -> None:
+    async with await buck.subscribe("--command-events") as subscribe:
+        # The subscribe command reports its own progress, so once we see that the
+        # subscription is in place.
+        msg = await subscribe.read_message()
+        assert "BuildProgress" in msg["response"]["CommandEvent"]["event"]
+
+        await buck.build("//:stage1")
+
+        finished_actions = []
+        while True:
+            msg = await asyncio.wait_for(subscribe.read_message(), timeout=20)
+            event = msg["response"]["CommandEvent"]["event"]
+            if "ActionFinished" in event:
+                finished_actions.append(event["ActionFinished"])
+            if "CommandFinished" in event and finished_actions:
+                assert event["CommandFinished"]["success"]
+                break
+
+        assert finished_actions[0]["action"]["category"] == "cp"
+        assert not finished_actions[0]["failed"]
+
+
 @buck_test()
 async def test_disconnect_eof(buck: Buck) -> None:
     async with await buck.subscribe() as subscribe:
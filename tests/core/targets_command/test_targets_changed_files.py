@@ -0,0 +1,82 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+# pyre-strict
+
+import json
+from typing import Dict, List
+
+from buck2.tests.e2e_util.api.buck import Buck
+from buck2.tests.e2e_util.buck_workspace import buck_test
+
+
+async def changed(
+    buck: Buck, *files: str, graph: str = "configured"
+) -> Dict[str, List[str]]:
+    result = await buck.targets(
+        "//...",
+        "--json",
+        "--changed-files-graph",
+        graph,
+        "--changed-files",
+        *files,
+    )
+    return {
+        target["buck.package"] + ":" + target["name"]: [
+            reason["kind"] + " " + reason.get("path", reason.get("target", ""))
+            for reason in target["reasons"]
+        ]
+        for target in json.loads(result.stdout)
+    }
+
+
+@buck_test()
+async def test_changed_input(buck: Buck) -> None:
+    assert await changed(buck, "a.txt") == {
+        "root//:a": ["input root//a.txt"],
+        "root//:b": ["dep root//:a"],
+        "root//other:d": ["dep root//:b"],
+    }
+    assert await changed(buck, "c.txt") == {"root//:c": ["input root//c.txt"]}
+    assert await changed(buck, "other/d.txt") == {
+        "root//other:d": ["input root//other/d.txt"],
+    }
+
+
+@buck_test()
+async def test_changed_input_unconfigured(buck: Buck) -> None:
+    assert await changed(buck, "b.txt", graph="unconfigured") == {
+        "root//:b": ["input root//b.txt"],
+        "root//other:d": ["dep root//:b"],
+    }
+
+
+@buck_test()
+async def test_changed_build_files(buck: Buck) -> None:
+    assert await changed(buck, "other/helpers.bzl") == {
+        "root//other:d": ["build_file root//other/helpers.bzl"],
+    }
+    # Loaded transitively by `other`.
+    assert set(await changed(buck, "defs.bzl")) == {
+        "root//:a",
+        "root//:b",
+        "root//:c",
+        "root//other:d",
+    }
+
+
+@buck_test()
+async def test_changed_buckconfig(buck: Buck) -> None:
+    result = await changed(buck, ".buckconfig")
+    assert set(result) == {"root//:a", "root//:b", "root//:c", "root//other:d"}
+    assert result["root//:c"] == ["config root//.buckconfig"]
+
+
+@buck_test()
+async def test_unrelated_change(buck: Buck) -> None:
+    assert await changed(buck, "README.md") == {}
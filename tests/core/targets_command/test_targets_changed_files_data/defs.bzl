@@ -0,0 +1,10 @@
+def _lib_impl(_ctx):
+    return [DefaultInfo()]
+
+lib = rule(
+    impl = _lib_impl,
+    attrs = {
+        "deps": attrs.list(attrs.dep(), default = []),
+        "srcs": attrs.list(attrs.source(), default = []),
+    },
+)
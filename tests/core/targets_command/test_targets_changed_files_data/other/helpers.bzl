@@ -0,0 +1,4 @@
+load("//:defs.bzl", "lib")
+
+def other_lib(name):
+    lib(name = name, srcs = [name + ".txt"], deps = ["//:b"])
@@ -0,0 +1,57 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+# pyre-strict
+
+
+import re
+from pathlib import Path
+
+from buck2.tests.e2e_util.api.buck import Buck
+from buck2.tests.e2e_util.buck_workspace import buck_test, env
+
+# Empty test executor forces internal test executor to be used.
+INTERNAL_TEST_EXECUTOR = ""
+
+
+def coverage_report(stderr: str) -> str:
+    match = re.search(r"Coverage report: (.*)", stderr)
+    assert match is not None, stderr
+    return Path(match.group(1).strip()).read_text()
+
+
+@buck_test(skip_for_os=["windows"])
+@env("BUCK2_ALLOW_INTERNAL_TEST_RUNNER_DO_NOT_USE", "1")
+async def test_coverage_merges_reports(buck: Buck) -> None:
+    result = await buck.test(
+        "--coverage",
+        ":first",
+        ":second",
+        test_executor=INTERNAL_TEST_EXECUTOR,
+    )
+
+    report = coverage_report(result.stderr)
+    assert "SF:src.py" in report
+    assert "DA:1,1\nDA:2,2\nDA:3,1\n" in report
+    assert "LF:3\nLH:3\n" in report
+
+
+@buck_test(skip_for_os=["windows"])
+@env("BUCK2_ALLOW_INTERNAL_TEST_RUNNER_DO_NOT_USE", "1")
+async def test_coverage_merger_failure(buck: Buck) -> None:
+    # The failing merger only loses the coverage of its own test.
+    result = await buck.test(
+        "--coverage",
+        ":first",
+        ":broken_merger",
+        test_executor=INTERNAL_TEST_EXECUTOR,
+    )
+
+    report = coverage_report(result.stderr)
+    assert "DA:1,1\nDA:2,1\n" in report
+    assert "DA:4" not in report
@@ -0,0 +1,30 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is dual-licensed under either the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree or the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree. You may select, at your option, one of the
+# above-listed licenses.
+
+# The test writes the lines it "covers" to the coverage directory, and the merger (which is passed
+# that directory and the report path) turns them into an LCOV report for `src.py`.
+_TEST = 'for line in {lines}; do echo "DA:$line,1" >> "$BUCK_COVERAGE_DIR/lines"; done'
+_MERGER = '{{ echo "SF:src.py"; cat "$1/lines"; echo end_of_record; }} > "$2"'
+
+def _covered_test_impl(ctx):
+    return [
+        DefaultInfo(),
+        ExternalRunnerTestInfo(
+            command = ["sh", "-c", _TEST.format(lines = " ".join([str(line) for line in ctx.attrs.lines]))],
+            coverage_merger = ["sh", "-c", ctx.attrs.merger or _MERGER, "--"],
+            type = "custom",
+        ),
+    ]
+
+covered_test = rule(
+    impl = _covered_test_impl,
+    attrs = {
+        "lines": attrs.list(attrs.int()),
+        "merger": attrs.option(attrs.string(), default = None),
+    },
+)